@@ -0,0 +1,28 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let observations = |value: fn(usize) -> String| {
+        let segments: Vec<String> = (1..=1000)
+            .map(|i| format!("OBX|{i}|NM|{}||{}", i % 7, value(i)))
+            .collect();
+        format!("MSH|^~\\&|\r{}", segments.join("\r"))
+    };
+    let old = observations(|i| i.to_string());
+    let old = hl7_parser::Message::parse(&old).expect("Can parse message");
+
+    c.bench_function("diff 1000 mostly unchanged segments", |b| {
+        let mut new = observations(|i| i.to_string());
+        new.push_str("\rNTE|1||note");
+        let new = hl7_parser::Message::parse(&new).expect("Can parse message");
+        b.iter(|| hl7_parser::diff::diff(black_box(&old), black_box(&new)))
+    });
+
+    c.bench_function("diff 1000 changed segments", |b| {
+        let new = observations(|i| format!("{i}.0"));
+        let new = hl7_parser::Message::parse(&new).expect("Can parse message");
+        b.iter(|| hl7_parser::diff::diff(black_box(&old), black_box(&new)))
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -10,6 +10,48 @@ fn criterion_benchmark(c: &mut Criterion) {
             Message::parse(black_box(message.as_str())).expect("can parse message");
         })
     });
+
+    c.bench_function("lazy parse adt^a01", |b| {
+        let message = include_str!("../test_assets/sample_adt_a01.hl7")
+            .replace("\r\n", "\r")
+            .replace('\n', "\r");
+        b.iter(|| {
+            LazyMessage::parse(black_box(message.as_str())).expect("can parse message");
+        })
+    });
+
+    c.bench_function("route adt^a01", |b| {
+        let message = include_str!("../test_assets/sample_adt_a01.hl7")
+            .replace("\r\n", "\r")
+            .replace('\n', "\r");
+        b.iter(|| {
+            let message = Message::parse(black_box(message.as_str())).expect("can parse message");
+            black_box(message.query("MSH.9").expect("has message type"));
+            black_box(message.query("PID.3").expect("has patient identifier"));
+        })
+    });
+
+    c.bench_function("lazy route adt^a01", |b| {
+        let message = include_str!("../test_assets/sample_adt_a01.hl7")
+            .replace("\r\n", "\r")
+            .replace('\n', "\r");
+        b.iter(|| {
+            let message =
+                LazyMessage::parse(black_box(message.as_str())).expect("can parse message");
+            black_box(
+                message
+                    .query("MSH.9")
+                    .expect("can parse MSH")
+                    .expect("has message type"),
+            );
+            black_box(
+                message
+                    .query("PID.3")
+                    .expect("can parse PID")
+                    .expect("has patient identifier"),
+            );
+        })
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
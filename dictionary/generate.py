@@ -0,0 +1,267 @@
+#!/usr/bin/env python3
+"""Generate src/dictionary/segments.rs and src/dictionary/data_types.rs from the tables in this
+directory.
+
+The tables are tab-separated, with a header row:
+
+- segments.tsv: segment, description
+- fields.tsv: segment, position, name, data_type, optionality, repeatable (Y/N), table, since,
+  until
+- data_types.tsv: data_type, description
+- components.tsv: data_type, position, name, component_data_type, optionality, table, since,
+  until
+
+`since` and `until` are the first and last HL7 versions (i.e. `2.3.1`) that a row applies to; an
+empty `until` means the row still applies in the latest version. An empty `table` means the
+element isn't drawn from an HL7 table. Rows are emitted in the order they appear in each table.
+
+Usage: python3 dictionary/generate.py [DATA_DIR]
+"""
+
+import csv
+import subprocess
+import sys
+from collections import defaultdict
+from pathlib import Path
+
+ROOT = Path(__file__).resolve().parent.parent
+OUT = ROOT / "src" / "dictionary"
+
+VERSIONS = {
+    "2.1": "V2_1",
+    "2.2": "V2_2",
+    "2.3": "V2_3",
+    "2.3.1": "V2_3_1",
+    "2.4": "V2_4",
+    "2.5": "V2_5",
+    "2.5.1": "V2_5_1",
+    "2.6": "V2_6",
+    "2.7": "V2_7",
+    "2.7.1": "V2_7_1",
+    "2.8": "V2_8",
+}
+
+OPTIONALITIES = {
+    "R": "Required",
+    "O": "Optional",
+    "C": "Conditional",
+    "B": "BackwardCompatible",
+    "X": "NotUsed",
+    "W": "Withdrawn",
+}
+
+HEADER = """\
+// This file is generated by `dictionary/generate.py` from the tables in `dictionary/`. Don't edit
+// it by hand: change the tables and re-run the script instead.
+"""
+
+SEGMENTS_PRELUDE = """\
+use super::{FieldDefinition, Optionality, SegmentDefinition, Version, Version::*};
+
+{optionalities}
+/// Define a field which applies to all versions. A `table` of `0` means the field has no
+/// associated table.
+const fn f(
+    position: usize,
+    name: &'static str,
+    data_type: &'static str,
+    optionality: Optionality,
+    repeatable: bool,
+    table: u16,
+) -> FieldDefinition {
+    FieldDefinition {
+        position,
+        name,
+        data_type,
+        optionality,
+        repeatable,
+        table: if table == 0 { None } else { Some(table) },
+        since: V2_1,
+        until: None,
+    }
+}
+
+impl FieldDefinition {
+    const fn since(mut self, version: Version) -> Self {
+        self.since = version;
+        self
+    }
+
+    const fn until(mut self, version: Version) -> Self {
+        self.until = Some(version);
+        self
+    }
+}
+"""
+
+DATA_TYPES_PRELUDE = """\
+use super::{ComponentDefinition, DataTypeDefinition, Optionality, Version, Version::*};
+
+{optionalities}
+/// Define a component which applies to all versions. A `table` of `0` means the component has
+/// no associated table.
+const fn c(
+    position: usize,
+    name: &'static str,
+    data_type: &'static str,
+    optionality: Optionality,
+    table: u16,
+) -> ComponentDefinition {
+    ComponentDefinition {
+        position,
+        name,
+        data_type,
+        optionality,
+        table: if table == 0 { None } else { Some(table) },
+        since: V2_1,
+        until: None,
+    }
+}
+
+impl ComponentDefinition {
+    const fn since(mut self, version: Version) -> Self {
+        self.since = version;
+        self
+    }
+
+    const fn until(mut self, version: Version) -> Self {
+        self.until = Some(version);
+        self
+    }
+}
+
+const fn primitive(name: &'static str, description: &'static str) -> DataTypeDefinition {
+    DataTypeDefinition {
+        name,
+        description,
+        components: &[],
+    }
+}
+"""
+
+
+def optionalities(rows):
+    """Constants for each optionality used by the rows, in the order of `OPTIONALITIES`"""
+    used = {row["optionality"] for row in rows}
+    return "".join(
+        f"const {code}: Optionality = Optionality::{name};\n"
+        for code, name in OPTIONALITIES.items()
+        if code in used
+    )
+
+
+def read(path):
+    with open(path, newline="", encoding="utf-8") as f:
+        return list(csv.DictReader(f, delimiter="\t", quoting=csv.QUOTE_NONE))
+
+
+def fail(path, line, message):
+    sys.exit(f"{path}:{line}: {message}")
+
+
+def string(value):
+    return '"%s"' % value.replace("\\", "\\\\").replace('"', '\\"')
+
+
+def versions(path, line, row):
+    since = row["since"] or "2.1"
+    until = row["until"]
+    for version in (since, until):
+        if version and version not in VERSIONS:
+            fail(path, line, f"unknown version `{version}`")
+    suffix = ""
+    if since != "2.1":
+        suffix += f".since({VERSIONS[since]})"
+    if until:
+        suffix += f".until({VERSIONS[until]})"
+    return suffix
+
+
+def common(path, line, row):
+    if not row["position"].isdigit():
+        fail(path, line, f"invalid position `{row['position']}`")
+    if row["optionality"] not in OPTIONALITIES:
+        fail(path, line, f"invalid optionality `{row['optionality']}`")
+    table = row["table"] or "0"
+    if not table.isdigit():
+        fail(path, line, f"invalid table `{table}`")
+    return int(row["position"]), table
+
+
+def generate_segments(data):
+    path = data / "fields.tsv"
+    rows = read(path)
+    fields = defaultdict(list)
+    for line, row in enumerate(rows, start=2):
+        position, table = common(path, line, row)
+        if row["repeatable"] not in ("Y", "N"):
+            fail(path, line, f"invalid repeatable `{row['repeatable']}`")
+        repeatable = "true" if row["repeatable"] == "Y" else "false"
+        fields[row["segment"]].append(
+            f"f({position}, {string(row['name'])}, {string(row['data_type'])}, "
+            f"{row['optionality']}, {repeatable}, {table}){versions(path, line, row)},"
+        )
+
+    prelude = SEGMENTS_PRELUDE.replace("{optionalities}", optionalities(rows))
+    out = [HEADER, prelude, "pub(super) static SEGMENTS: &[SegmentDefinition] = &["]
+    for segment in read(data / "segments.tsv"):
+        name = segment["segment"]
+        out.append("SegmentDefinition {")
+        out.append(f"name: {string(name)},")
+        out.append(f"description: {string(segment['description'])},")
+        out.append("fields: &[")
+        out.extend(fields.pop(name, []))
+        out.append("],")
+        out.append("},")
+    out.append("];")
+    if fields:
+        sys.exit(f"{path}: fields for unknown segments: {', '.join(sorted(fields))}")
+    return "\n".join(out) + "\n"
+
+
+def generate_data_types(data):
+    path = data / "components.tsv"
+    rows = read(path)
+    components = defaultdict(list)
+    for line, row in enumerate(rows, start=2):
+        position, table = common(path, line, row)
+        components[row["data_type"]].append(
+            f"c({position}, {string(row['name'])}, {string(row['component_data_type'])}, "
+            f"{row['optionality']}, {table}){versions(path, line, row)},"
+        )
+
+    prelude = DATA_TYPES_PRELUDE.replace("{optionalities}", optionalities(rows))
+    out = [HEADER, prelude, "pub(super) static DATA_TYPES: &[DataTypeDefinition] = &["]
+    for data_type in read(data / "data_types.tsv"):
+        name = data_type["data_type"]
+        description = string(data_type["description"])
+        if name not in components:
+            out.append(f"primitive({string(name)}, {description}),")
+            continue
+        out.append("DataTypeDefinition {")
+        out.append(f"name: {string(name)},")
+        out.append(f"description: {description},")
+        out.append("components: &[")
+        out.extend(components.pop(name))
+        out.append("],")
+        out.append("},")
+    out.append("];")
+    if components:
+        sys.exit(f"{path}: components for unknown data types: {', '.join(sorted(components))}")
+    return "\n".join(out) + "\n"
+
+
+def main():
+    data = Path(sys.argv[1]) if len(sys.argv) > 1 else ROOT / "dictionary"
+    outputs = {
+        OUT / "segments.rs": generate_segments(data),
+        OUT / "data_types.rs": generate_data_types(data),
+    }
+    for path, source in outputs.items():
+        path.write_text(source, encoding="utf-8")
+        subprocess.run(["rustfmt", "--edition", "2021", str(path)], check=True)
+        print(f"wrote {path.relative_to(ROOT)}")
+
+
+if __name__ == "__main__":
+    main()
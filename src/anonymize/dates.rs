@@ -0,0 +1,107 @@
+//! Date shifting and truncation which keeps the precision of the original timestamp.
+
+use crate::datetime::{
+    calendar::{civil_from_days, days_from_civil, days_in_month},
+    parse_timestamp, TimeStamp,
+};
+
+/// Parse a timestamp, returning it along with the length of its date portion
+fn parse(value: &str) -> Option<(TimeStamp, usize)> {
+    let timestamp = parse_timestamp(value, false).ok()?;
+    if let Some(month) = timestamp.month {
+        let days_in_month = days_in_month(timestamp.year, month)?;
+        if timestamp
+            .day
+            .is_some_and(|day| day == 0 || day > days_in_month)
+        {
+            return None;
+        }
+    }
+    let length = match (timestamp.month, timestamp.day) {
+        (None, _) => 4,
+        (Some(_), None) => 6,
+        (Some(_), Some(_)) => 8,
+    };
+    Some((timestamp, length))
+}
+
+/// Shift the date of a timestamp by a number of days, keeping its precision and any time and
+/// offset as they are. Timestamps without a day or month are shifted from the first day of
+/// their month or year. Returns `None` if the value isn't a valid timestamp.
+pub(super) fn shift(value: &str, days: i64) -> Option<String> {
+    let (timestamp, length) = parse(value)?;
+    let start = days_from_civil(
+        timestamp.year as i64,
+        timestamp.month.unwrap_or(1),
+        timestamp.day.unwrap_or(1),
+    );
+    let (year, month, day) = civil_from_days(start + days);
+    if !(0..=9999).contains(&year) {
+        return None;
+    }
+    let date = format!("{year:04}{month:02}{day:02}");
+    Some(format!("{}{}", &date[..length], &value[length..]))
+}
+
+/// The year of a timestamp. Returns `None` if the value isn't a valid timestamp.
+pub(super) fn year(value: &str) -> Option<String> {
+    let (timestamp, _) = parse(value)?;
+    Some(format!("{:04}", timestamp.year))
+}
+
+/// The year of a date of birth, except for people aged over 89 on the reference date, who are
+/// given the year which makes them 90 so that they fall into a single category of 90 or older.
+/// Missing months and days are assumed to make the person older. Returns `None` if either value
+/// isn't a valid timestamp.
+pub(super) fn birth_year(value: &str, reference: &str) -> Option<String> {
+    let (birth, _) = parse(value)?;
+    let (reference, _) = parse(reference)?;
+    let birthday = (birth.month.unwrap_or(1), birth.day.unwrap_or(1));
+    let reference_day = (reference.month.unwrap_or(12), reference.day.unwrap_or(31));
+    let age = reference.year as i64 - birth.year as i64 - i64::from(reference_day < birthday);
+    if age > 89 {
+        Some(format!("{:04}", (reference.year as i64 - 90).max(0)))
+    } else {
+        Some(format!("{:04}", birth.year))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    #[test]
+    fn shifts_dates_keeping_precision() {
+        assert_eq!(shift("20240228", 2).as_deref(), Some("20240301"));
+        assert_eq!(
+            shift("20231231235959.1-0600", 1).as_deref(),
+            Some("20240101235959.1-0600")
+        );
+        assert_eq!(shift("202401", -1).as_deref(), Some("202312"));
+        assert_eq!(shift("2024", 400).as_deref(), Some("2025"));
+        assert_eq!(shift("20240231", 1), None);
+        assert_eq!(shift("not a date", 1), None);
+    }
+
+    #[test]
+    fn keeps_only_the_year() {
+        assert_eq!(year("19800614").as_deref(), Some("1980"));
+        assert_eq!(year("1980").as_deref(), Some("1980"));
+        assert_eq!(year("June 1980"), None);
+    }
+
+    #[test]
+    fn aggregates_birth_years_over_89() {
+        assert_eq!(birth_year("19800614", "20240102").as_deref(), Some("1980"));
+        assert_eq!(birth_year("19340103", "20240102").as_deref(), Some("1934"));
+        assert_eq!(birth_year("19340102", "20240102").as_deref(), Some("1934"));
+        assert_eq!(birth_year("19331231", "20240102").as_deref(), Some("1934"));
+        assert_eq!(birth_year("19100101", "20240102").as_deref(), Some("1934"));
+        // a birth year alone could be January 1st, and a reference year alone December 31st
+        assert_eq!(birth_year("1934", "20240102").as_deref(), Some("1934"));
+        assert_eq!(birth_year("1933", "20240102").as_deref(), Some("1934"));
+        assert_eq!(birth_year("19340601", "2024").as_deref(), Some("1934"));
+        assert_eq!(birth_year("19800614", "not a date"), None);
+    }
+}
@@ -0,0 +1,129 @@
+//! Consistent fake values, chosen from fixed lists using a salted hash of the original value.
+
+use super::Fake;
+
+const FAMILY_NAMES: &[&str] = &[
+    "Abbott",
+    "Barker",
+    "Castillo",
+    "Dalton",
+    "Ellison",
+    "Fraser",
+    "Garrison",
+    "Hale",
+    "Ibarra",
+    "Jennings",
+    "Kearney",
+    "Lindqvist",
+    "Mercer",
+    "Nakamura",
+    "Okafor",
+    "Pemberton",
+    "Quinlan",
+    "Rasmussen",
+    "Sandoval",
+    "Thorne",
+    "Underwood",
+    "Valdez",
+    "Whitaker",
+    "Xiong",
+    "Yardley",
+    "Zimmerman",
+];
+
+const GIVEN_NAMES: &[&str] = &[
+    "Alex", "Blair", "Casey", "Dana", "Emery", "Finley", "Gray", "Harper", "Indigo", "Jordan",
+    "Kai", "Logan", "Morgan", "Noel", "Oakley", "Parker", "Quinn", "Riley", "Sage", "Taylor",
+    "Umber", "Val", "Wren", "Xen", "Yael", "Zion",
+];
+
+const STREETS: &[&str] = &[
+    "Birch", "Cedar", "Elm", "Hawthorn", "Juniper", "Linden", "Maple", "Oak", "Pine", "Rowan",
+    "Spruce", "Willow",
+];
+
+const STREET_SUFFIXES: &[&str] = &["St", "Ave", "Rd", "Ln", "Dr", "Way", "Ct", "Pl"];
+
+const CITIES: &[&str] = &[
+    "Ashford",
+    "Brookhaven",
+    "Clearwater",
+    "Dunmore",
+    "Eastwick",
+    "Fairview",
+    "Glenwood",
+    "Harborview",
+    "Ironwood",
+    "Lakeside",
+    "Millbrook",
+    "Northfield",
+    "Oakridge",
+    "Pinecrest",
+    "Riverton",
+    "Springdale",
+];
+
+/// Pick an element of a list using a hash
+fn pick<'a>(list: &[&'a str], hash: u64) -> &'a str {
+    list[(hash % list.len() as u64) as usize]
+}
+
+/// Generate a fake value of the given kind for an original value, using a hash of the original
+pub(super) fn fake(kind: Fake, original: &str, hash: u64) -> String {
+    match kind {
+        Fake::FamilyName => pick(FAMILY_NAMES, hash).to_string(),
+        Fake::GivenName => pick(GIVEN_NAMES, hash).to_string(),
+        Fake::Street => format!(
+            "{} {} {}",
+            hash % 9900 + 100,
+            pick(STREETS, hash / 9900),
+            pick(STREET_SUFFIXES, hash / 9900 / STREETS.len() as u64),
+        ),
+        Fake::City => pick(CITIES, hash).to_string(),
+        // 555-0100 through 555-0199 are reserved for fictional use
+        Fake::Phone => format!("(555)555-01{:02}", hash % 100),
+        Fake::Identifier => identifier(original, hash),
+    }
+}
+
+/// Replace each digit and letter of an identifier, keeping its length and format
+fn identifier(original: &str, hash: u64) -> String {
+    let mut state = hash;
+    original
+        .chars()
+        .map(|c| {
+            // a simple LCG, seeded by the hash, to vary each character
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let n = (state >> 33) as u32;
+            if c.is_ascii_digit() {
+                char::from_digit(n % 10, 10).unwrap_or('0')
+            } else if c.is_ascii_uppercase() {
+                (b'A' + (n % 26) as u8) as char
+            } else if c.is_ascii_lowercase() {
+                (b'a' + (n % 26) as u8) as char
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    #[test]
+    fn identifiers_keep_their_format() {
+        let fake = identifier("AB-1234x", 42);
+        assert_eq!(fake.len(), 8);
+        assert_eq!(&fake[2..3], "-");
+        assert!(fake[..2].chars().all(|c| c.is_ascii_uppercase()));
+        assert!(fake[3..7].chars().all(|c| c.is_ascii_digit()));
+        assert!(fake[7..].chars().all(|c| c.is_ascii_lowercase()));
+        assert_eq!(fake, identifier("AB-1234x", 42));
+        assert_ne!(fake, identifier("AB-1234x", 43));
+    }
+}
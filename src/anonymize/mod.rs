@@ -0,0 +1,732 @@
+//! # De-identifying HL7 messages
+//!
+//! An [`Anonymizer`] applies a [`Profile`] of [`Rule`]s to a message, producing a
+//! [`MessageBuilder`] with the same separators as the original message. Each rule selects
+//! values with a [`LocationQuery`] and applies an [`Action`] to them:
+//!
+//! - [`Action::Redact`] removes the value
+//! - [`Action::Hash`] replaces the value with a salted hash of it
+//! - [`Action::Fake`] replaces the value with a fake value of the same kind, such as a name
+//! - [`Action::ShiftDate`] shifts a date by a per-patient number of days, keeping the precision
+//!   of the original timestamp
+//! - [`Action::Keep`] keeps only part of a value, such as the year of a date or the first three
+//!   digits of a zip code, optionally following the Safe Harbor rules for sparsely populated
+//!   zip codes and people over 89
+//!
+//! Hashes, fake values, and date shifts are derived from a salted hash of the original value
+//! (or of the patient's identifier, for date shifts), so that they are consistent across
+//! messages anonymized with the same salt. The salt should be kept secret, otherwise the
+//! original values could be recovered by hashing candidate values.
+//!
+//! Queries without a segment index apply to every segment with that name, and queries without
+//! a repeat apply to every repeat of the field. A rule for a whole segment (e.g. `NTE`) can only
+//! redact, which removes the segment. Rules are applied in order, so a later rule for the same
+//! value replaces an earlier one.
+//!
+//! [`Profile::safe_harbor`] is a default profile based on the HIPAA Safe Harbor method, covering
+//! names, addresses, dates, contact details, and identifiers in the `PID`, `NK1`, `PV1`, `GT1`,
+//! and `IN1` segments, and free text `OBX` values. It is a starting point rather than a
+//! guarantee of de-identification: messages can contain PHI in other segments, in `Z` segments,
+//! or in unexpected fields, and should be reviewed.
+//!
+//! ## Examples
+//!
+//! ```
+//! use hl7_parser::{anonymize::{Anonymizer, Profile}, Message};
+//!
+//! let message = Message::parse(
+//!     "MSH|^~\\&|||||20240102\rPID|1||12345^^^MRN||Doe^John^Q||19800614|M|||123 Main St^^Springfield^IL^62704",
+//! ).unwrap();
+//! let anonymizer = Anonymizer::new("secret salt", Profile::safe_harbor());
+//! let anonymized = anonymizer.anonymize(&message).unwrap().to_string();
+//!
+//! assert!(!anonymized.contains("Doe"));
+//! assert!(!anonymized.contains("12345"));
+//! assert!(anonymized.contains("|1980|M|"));
+//! assert!(anonymized.contains("^IL^627"));
+//!
+//! // the same salt always gives the same result
+//! assert_eq!(anonymizer.anonymize(&message).unwrap().to_string(), anonymized);
+//! ```
+
+mod dates;
+mod fake;
+
+use crate::{
+    builder::{MessageBuilder, Patch, PatchError},
+    query::LocationQuery,
+    Message,
+};
+use sha2::{Digest, Sha256};
+
+/// What to do with a value selected by a [`Rule`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Action {
+    /// Remove the value
+    Redact,
+    /// Replace the value with the first 16 hexadecimal digits of a salted SHA-256 hash of it
+    Hash,
+    /// Replace the value with a consistent fake value of the given kind
+    Fake(Fake),
+    /// Shift a timestamp by a per-patient number of days, keeping its precision. Values which
+    /// aren't valid timestamps are removed.
+    ShiftDate,
+    /// Keep only part of the value
+    Keep(Keep),
+}
+
+/// The kinds of fake value which can replace an original value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Fake {
+    FamilyName,
+    GivenName,
+    /// A street address, such as `123 Maple St`
+    Street,
+    City,
+    /// A phone number in the range reserved for fictional use, such as `(555)555-0123`
+    Phone,
+    /// The original value with each digit and letter replaced, keeping its length and format
+    Identifier,
+}
+
+/// Which part of a value to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Keep {
+    /// The year of a timestamp. Values which aren't valid timestamps are removed.
+    Year,
+    /// The first characters of a value, such as the first three digits of a zip code
+    Prefix(usize),
+    /// The first three digits of a zip code, or `000` for the three-digit areas with 20,000 or
+    /// fewer people, as Safe Harbor requires
+    Zip3,
+    /// The year of a date of birth, except for people aged over 89 at the time of the message
+    /// (`MSH-7`), who are given the year which makes them 90, as Safe Harbor requires. Values are
+    /// removed if the message has no valid `MSH-7`.
+    BirthYear,
+}
+
+/// The three-digit zip codes with 20,000 or fewer people, which Safe Harbor requires to be
+/// replaced with `000` (from the HHS de-identification guidance, using 2000 census data)
+const RESTRICTED_ZIP3: [&str; 17] = [
+    "036", "059", "063", "102", "203", "556", "692", "790", "821", "823", "830", "831", "878",
+    "879", "884", "890", "893",
+];
+
+/// A condition on another field of the same segment, which must have one of the given values
+/// for a rule to apply
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Condition {
+    /// The field number (1-based)
+    pub field: usize,
+    /// The decoded values the field can have
+    pub any_of: Vec<String>,
+}
+
+/// An action to apply to the values selected by a query
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rule {
+    #[cfg_attr(feature = "serde", serde(with = "crate::query::serde_string"))]
+    pub query: LocationQuery,
+    pub action: Action,
+    /// Only apply the rule to segments matching the condition
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub when: Option<Condition>,
+}
+
+impl Rule {
+    /// Create a rule which applies to every value selected by the query
+    pub fn new(query: LocationQuery, action: Action) -> Self {
+        Rule {
+            query,
+            action,
+            when: None,
+        }
+    }
+
+    /// Only apply the rule to segments where the given field (1-based) has one of the values
+    pub fn when<S: ToString>(mut self, field: usize, any_of: impl IntoIterator<Item = S>) -> Self {
+        self.when = Some(Condition {
+            field,
+            any_of: any_of.into_iter().map(|v| v.to_string()).collect(),
+        });
+        self
+    }
+}
+
+/// A list of rules to apply to a message, in order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Profile {
+    pub rules: Vec<Rule>,
+}
+
+impl Profile {
+    /// Create a profile with no rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule to the end of the profile
+    pub fn push(&mut self, rule: Rule) {
+        self.rules.push(rule);
+    }
+
+    /// Add a rule to the end of the profile
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        self.push(rule);
+        self
+    }
+
+    /// A profile based on the HIPAA Safe Harbor method: names are replaced with fake names,
+    /// street addresses and cities with fake ones, zip codes are truncated to their first three
+    /// digits (or `000` in sparsely populated areas), dates are truncated to their year, with
+    /// people over 89 grouped together by their year of birth, phone numbers are replaced with
+    /// fictional ones, record and account identifiers are hashed, other identifiers such as
+    /// social security numbers are removed, and free text (`TX`, `FT`, and `ST`) observation
+    /// values are removed. Ages recorded as values, such as in `OBX` segments, aren't covered.
+    pub fn safe_harbor() -> Self {
+        use Action::*;
+        const YEAR: Action = Keep(self::Keep::Year);
+        const BIRTH_YEAR: Action = Keep(self::Keep::BirthYear);
+        const ZIP3: Action = Keep(self::Keep::Zip3);
+        const FAMILY: Action = Fake(self::Fake::FamilyName);
+        const GIVEN: Action = Fake(self::Fake::GivenName);
+        const STREET: Action = Fake(self::Fake::Street);
+        const CITY: Action = Fake(self::Fake::City);
+        const PHONE: Action = Fake(self::Fake::Phone);
+
+        /// Rules for an extended person name (XPN): family, given, and middle names, suffix,
+        /// prefix, and degree
+        fn name(field: &str) -> Vec<(String, Action)> {
+            vec![
+                (format!("{field}.1"), FAMILY),
+                (format!("{field}.2"), GIVEN),
+                (format!("{field}.3"), Redact),
+                (format!("{field}.4"), Redact),
+                (format!("{field}.5"), Redact),
+                (format!("{field}.6"), Redact),
+            ]
+        }
+
+        /// Rules for an extended address (XAD): street, other designation, city, zip, and
+        /// county
+        fn address(field: &str) -> Vec<(String, Action)> {
+            vec![
+                (format!("{field}.1"), STREET),
+                (format!("{field}.2"), Redact),
+                (format!("{field}.3"), CITY),
+                (format!("{field}.5"), ZIP3),
+                (format!("{field}.9"), Redact),
+            ]
+        }
+
+        fn rules(rules: &[(&str, Action)]) -> Vec<(String, Action)> {
+            rules
+                .iter()
+                .map(|(query, action)| (query.to_string(), action.clone()))
+                .collect()
+        }
+
+        let rules = [
+            // patient identification
+            rules(&[("PID.2", Hash), ("PID.3.1", Hash), ("PID.4", Hash)]),
+            name("PID.5"),
+            rules(&[("PID.6", Redact), ("PID.7", BIRTH_YEAR), ("PID.9", Redact)]),
+            address("PID.11"),
+            rules(&[
+                ("PID.12", Redact),
+                ("PID.13", PHONE),
+                ("PID.14", PHONE),
+                ("PID.18", Hash),
+                ("PID.19", Redact),
+                ("PID.20", Redact),
+                ("PID.21", Hash),
+                ("PID.23", Redact),
+                ("PID.29", YEAR),
+            ]),
+            // next of kin
+            name("NK1.2"),
+            address("NK1.4"),
+            rules(&[
+                ("NK1.5", PHONE),
+                ("NK1.6", PHONE),
+                ("NK1.12", Hash),
+                ("NK1.16", BIRTH_YEAR),
+                ("NK1.30", Redact),
+                ("NK1.31", PHONE),
+                ("NK1.32", Redact),
+                ("NK1.33", Hash),
+                ("NK1.37", Redact),
+            ]),
+            // patient visit
+            rules(&[
+                ("PV1.19", Hash),
+                ("PV1.44", YEAR),
+                ("PV1.45", YEAR),
+                ("PV1.50", Hash),
+            ]),
+            // guarantor
+            rules(&[("GT1.2", Hash)]),
+            name("GT1.3"),
+            name("GT1.4"),
+            address("GT1.5"),
+            rules(&[
+                ("GT1.6", PHONE),
+                ("GT1.7", PHONE),
+                ("GT1.8", BIRTH_YEAR),
+                ("GT1.12", Redact),
+                ("GT1.16", Redact),
+                ("GT1.17", Redact),
+                ("GT1.18", Redact),
+                ("GT1.19", Redact),
+            ]),
+            // insurance
+            name("IN1.16"),
+            rules(&[("IN1.18", BIRTH_YEAR)]),
+            address("IN1.19"),
+            rules(&[("IN1.36", Hash), ("IN1.49", Hash)]),
+        ]
+        .concat();
+
+        let mut profile: Profile = rules
+            .into_iter()
+            .map(|(query, action)| {
+                let query = LocationQuery::parse(&query).expect("Safe Harbor queries are valid");
+                Rule::new(query, action)
+            })
+            .collect();
+        profile.push(
+            Rule::new(
+                LocationQuery::parse("OBX.5").expect("Safe Harbor queries are valid"),
+                Redact,
+            )
+            .when(2, ["TX", "FT", "ST"]),
+        );
+        profile
+    }
+}
+
+impl FromIterator<Rule> for Profile {
+    fn from_iter<T: IntoIterator<Item = Rule>>(iter: T) -> Self {
+        Profile {
+            rules: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Errors that can occur when anonymizing a message
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+pub enum AnonymizeError {
+    /// The rule can't be applied: it is for a whole segment but doesn't redact it, or it is
+    /// for the encoding characters in `MSH.1` or `MSH.2`
+    #[error("Invalid rule for `{0}`")]
+    InvalidRule(String),
+
+    /// The anonymized values couldn't be applied to the message
+    #[error(transparent)]
+    Patch(#[from] PatchError),
+}
+
+/// Applies a [`Profile`] to messages, using a secret salt for hashes, fake values, and date
+/// shifts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Anonymizer {
+    salt: String,
+    profile: Profile,
+    patient_id: LocationQuery,
+    max_date_shift: u32,
+}
+
+impl Anonymizer {
+    /// Create an anonymizer with the given salt and profile. Dates are shifted by up to 365
+    /// days, using the first patient identifier (`PID.3.1`) to choose the shift.
+    pub fn new<S: ToString>(salt: S, profile: Profile) -> Self {
+        Anonymizer {
+            salt: salt.to_string(),
+            profile,
+            patient_id: LocationQuery::parse("PID.3[1].1").expect("Query is valid"),
+            max_date_shift: 365,
+        }
+    }
+
+    /// Use the value at the given query to identify the patient when choosing how far to shift
+    /// dates, so that each patient's dates are shifted by the same number of days
+    pub fn with_patient_id(mut self, query: LocationQuery) -> Self {
+        self.patient_id = query;
+        self
+    }
+
+    /// Shift dates by at most the given number of days, forwards or backwards
+    pub fn with_max_date_shift(mut self, days: u32) -> Self {
+        self.max_date_shift = days;
+        self
+    }
+
+    /// The profile of rules applied to messages
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    /// Anonymize a message, returning a builder for the anonymized message which uses the
+    /// original message's separators
+    pub fn anonymize(&self, message: &Message) -> Result<MessageBuilder, AnonymizeError> {
+        let separators = &message.separators;
+        let date_shift = self.date_shift(message);
+        let message_date = message.query("MSH.7.1").map(|date| date.raw_value());
+        let mut patch = Patch::new();
+        let mut removed_segments = Vec::new();
+
+        for rule in &self.profile.rules {
+            let query = &rule.query;
+            let is_encoding = query.segment == "MSH" && query.field.is_none_or(|f| f <= 2);
+            if is_encoding || (query.field.is_none() && rule.action != Action::Redact) {
+                return Err(AnonymizeError::InvalidRule(query.to_string()));
+            }
+
+            let segments = message
+                .segments()
+                .filter(|s| s.name == query.segment)
+                .enumerate()
+                .map(|(i, s)| (i + 1, s))
+                .filter(|(i, _)| query.segment_index.is_none_or(|n| n == *i));
+            for (segment_index, segment) in segments {
+                if let Some(condition) = &rule.when {
+                    let value = segment
+                        .field(condition.field)
+                        .map(|f| separators.decode(f.raw_value()).to_string())
+                        .unwrap_or_default();
+                    if !condition.any_of.contains(&value) {
+                        continue;
+                    }
+                }
+                let location = LocationQuery {
+                    segment_index: Some(segment_index),
+                    ..query.clone()
+                };
+
+                let Some(field_number) = query.field else {
+                    removed_segments.push(location);
+                    continue;
+                };
+                let Some(field) = segment.field(field_number).filter(|f| !f.is_empty()) else {
+                    continue;
+                };
+                let whole_field = query.repeat.is_none() && query.component.is_none();
+                if rule.action == Action::Redact && whole_field {
+                    patch = patch.with_delete(location);
+                    continue;
+                }
+
+                for (repeat_index, repeat) in field.repeats().enumerate() {
+                    let repeat_index = repeat_index + 1;
+                    if query.repeat.is_some_and(|r| r != repeat_index) {
+                        continue;
+                    }
+                    let raw = match (query.component, query.subcomponent) {
+                        (None, _) => Some(repeat.raw_value()),
+                        (Some(c), None) => repeat.component(c).map(|c| c.raw_value()),
+                        (Some(c), Some(s)) => repeat
+                            .component(c)
+                            .and_then(|c| c.subcomponent(s))
+                            .map(|s| s.raw_value()),
+                    };
+                    let Some(raw) = raw.filter(|raw| !raw.is_empty()) else {
+                        continue;
+                    };
+                    let value = separators.decode(raw).to_string();
+                    let anonymized = self.apply(&rule.action, &value, date_shift, message_date);
+                    let anonymized = anonymized.unwrap_or_default();
+                    patch = patch.with_set(
+                        LocationQuery {
+                            repeat: Some(repeat_index),
+                            ..location.clone()
+                        },
+                        separators.encode(&anonymized),
+                    );
+                }
+            }
+        }
+
+        // remove segments last, and from the end, so that the indices of the other operations
+        // still refer to the original segments
+        removed_segments.sort_by_key(|q| std::cmp::Reverse(q.segment_index));
+        removed_segments.dedup();
+        for query in removed_segments {
+            patch = patch.with_delete(query);
+        }
+
+        let mut builder = MessageBuilder::from(message);
+        patch.apply(&mut builder)?;
+        Ok(builder)
+    }
+
+    /// Apply an action to a decoded value, returning `None` if the value should be removed
+    fn apply(
+        &self,
+        action: &Action,
+        value: &str,
+        date_shift: i64,
+        message_date: Option<&str>,
+    ) -> Option<String> {
+        match action {
+            Action::Redact => None,
+            Action::Hash => {
+                let digest = self.digest("hash", value);
+                Some(digest[..8].iter().map(|b| format!("{b:02x}")).collect())
+            }
+            Action::Fake(kind) => Some(fake::fake(*kind, value, self.hash("fake", value))),
+            Action::ShiftDate => dates::shift(value, date_shift),
+            Action::Keep(Keep::Year) => dates::year(value),
+            Action::Keep(Keep::Prefix(length)) => Some(value.chars().take(*length).collect()),
+            Action::Keep(Keep::Zip3) => {
+                let zip3: String = value.chars().take(3).collect();
+                if RESTRICTED_ZIP3.contains(&zip3.as_str()) {
+                    Some("000".to_string())
+                } else {
+                    Some(zip3)
+                }
+            }
+            Action::Keep(Keep::BirthYear) => dates::birth_year(value, message_date?),
+        }
+    }
+
+    /// The number of days to shift dates by for the patient in the message, which is never zero
+    fn date_shift(&self, message: &Message) -> i64 {
+        let max = self.max_date_shift as i64;
+        if max == 0 {
+            return 0;
+        }
+        let patient = message
+            .query(self.patient_id.clone())
+            .map(|result| message.separators.decode(result.raw_value()).to_string())
+            .unwrap_or_default();
+        let shift = (self.hash("date shift", &patient) % (2 * max as u64)) as i64 - max;
+        if shift >= 0 {
+            shift + 1
+        } else {
+            shift
+        }
+    }
+
+    /// A salted SHA-256 digest of a value, for a given purpose
+    fn digest(&self, purpose: &str, value: &str) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        for part in [self.salt.as_str(), purpose, value] {
+            hasher.update((part.len() as u64).to_le_bytes());
+            hasher.update(part.as_bytes());
+        }
+        hasher.finalize().into()
+    }
+
+    /// A salted hash of a value as a number, for a given purpose
+    fn hash(&self, purpose: &str, value: &str) -> u64 {
+        let digest = self.digest(purpose, value);
+        u64::from_le_bytes(digest[..8].try_into().expect("Digest is 32 bytes"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    const MESSAGE: &str = "MSH|^~\\&|APP|FAC|||20240102030405||ADT^A01|1|P|2.5.1\r\
+        PID|1||12345^^^MRN~999-99-9999^^^SSN||Doe^John^Q^Jr||19800614|M|||123 Main St^Apt 4^Springfield^IL^62704^USA||(217)555-1234|||||ACCT1|999-99-9999\r\
+        NK1|1|Doe^Jane|SPO|123 Main St^^Springfield^IL^62704\r\
+        PV1|1|I|W^101^A||||1234^Welby^Marcus||||||||||||V123|||||||||||||||||||||||||20240101120000|20240103\r\
+        OBX|1|TX|NOTE||Patient John Doe reports feeling well||||||F\r\
+        OBX|2|NM|HR||72|/min|||||F\r\
+        NTE|1||Called back at home";
+
+    fn query(query: &str) -> LocationQuery {
+        LocationQuery::parse(query).unwrap()
+    }
+
+    fn anonymize(anonymizer: &Anonymizer, message: &str) -> Message<'static> {
+        let message = Message::parse(message).unwrap();
+        let anonymized = anonymizer.anonymize(&message).unwrap().to_string();
+        Message::parse(Box::leak(anonymized.into_boxed_str())).unwrap()
+    }
+
+    fn value<'m>(message: &'m Message<'m>, query: &str) -> String {
+        message
+            .query(query)
+            .map(|r| r.raw_value().to_string())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn safe_harbor_removes_phi() {
+        let anonymizer = Anonymizer::new("salt", Profile::safe_harbor());
+        let message = anonymize(&anonymizer, MESSAGE);
+        let text = message.raw_value();
+        for phi in [
+            "12345",
+            "999-99-9999",
+            "Doe",
+            "John",
+            "Jane",
+            "Main St",
+            "Springfield",
+            "62704",
+            "555-1234",
+            "ACCT1",
+            "V123",
+            "0614",
+            "feeling well",
+        ] {
+            assert!(!text.contains(phi), "{phi} is in {text}");
+        }
+
+        assert_eq!(value(&message, "PID.3[1].4"), "MRN");
+        assert_eq!(value(&message, "PID.3[1].1").len(), 16);
+        assert_eq!(value(&message, "PID.5.3"), "");
+        assert_eq!(value(&message, "PID.7"), "1980");
+        assert_eq!(value(&message, "PID.8"), "M");
+        assert_eq!(value(&message, "PID.11.4"), "IL");
+        assert_eq!(value(&message, "PID.11.5"), "627");
+        assert_eq!(value(&message, "PID.11.6"), "USA");
+        assert!(value(&message, "PID.13").starts_with("(555)555-01"));
+        assert_eq!(value(&message, "PID.19"), "");
+        assert_eq!(value(&message, "PV1.3"), "W^101^A");
+        assert_eq!(value(&message, "PV1.44"), "2024");
+        assert_eq!(value(&message, "OBX[1].5"), "");
+        assert_eq!(value(&message, "OBX[2].5"), "72");
+        // NTE segments aren't part of the profile
+        assert_eq!(value(&message, "NTE.3"), "Called back at home");
+    }
+
+    #[test]
+    fn safe_harbor_groups_small_zip_codes_and_people_over_89() {
+        let anonymizer = Anonymizer::new("salt", Profile::safe_harbor());
+        let source = MESSAGE
+            .replace("19800614", "19300614")
+            .replace("62704", "05901");
+        let message = anonymize(&anonymizer, &source);
+        assert_eq!(value(&message, "PID.7"), "1934");
+        assert_eq!(value(&message, "PID.11.5"), "000");
+        assert_eq!(value(&message, "NK1.4.5"), "000");
+
+        // without a message date, the age can't be known
+        let source = source.replace("20240102030405", "");
+        let message = anonymize(&anonymizer, &source);
+        assert_eq!(value(&message, "PID.7"), "");
+    }
+
+    #[test]
+    fn values_are_consistent() {
+        let anonymizer = Anonymizer::new("salt", Profile::safe_harbor());
+        let message = anonymize(&anonymizer, MESSAGE);
+        // the spouse shares the patient's family name and address
+        assert_eq!(value(&message, "PID.5.1"), value(&message, "NK1.2.1"));
+        assert_eq!(value(&message, "PID.11.1"), value(&message, "NK1.4.1"));
+        assert_eq!(
+            anonymize(&anonymizer, MESSAGE).raw_value(),
+            message.raw_value()
+        );
+
+        let other = anonymize(&Anonymizer::new("pepper", Profile::safe_harbor()), MESSAGE);
+        assert_ne!(value(&message, "PID.3[1].1"), value(&other, "PID.3[1].1"));
+    }
+
+    #[test]
+    fn dates_are_shifted_per_patient() {
+        let profile = Profile::new()
+            .with_rule(Rule::new(query("PID.7"), Action::ShiftDate))
+            .with_rule(Rule::new(query("PV1.44"), Action::ShiftDate))
+            .with_rule(Rule::new(query("PV1.45"), Action::ShiftDate));
+        let anonymizer = Anonymizer::new("salt", profile).with_max_date_shift(30);
+        let message = anonymize(&anonymizer, MESSAGE);
+
+        let shift = anonymizer.date_shift(&Message::parse(MESSAGE).unwrap());
+        assert!(shift != 0 && (-30..=30).contains(&shift), "{shift}");
+        assert_eq!(
+            value(&message, "PID.7"),
+            dates::shift("19800614", shift).unwrap()
+        );
+        // precision and times are kept
+        let admit = value(&message, "PV1.44");
+        assert_eq!(admit.len(), 14);
+        assert!(admit.ends_with("120000"));
+        assert_eq!(value(&message, "PV1.45").len(), 8);
+
+        // another patient is shifted differently
+        let other = MESSAGE.replace("12345", "54321");
+        let other_shift = anonymizer.date_shift(&Message::parse(&other).unwrap());
+        assert_ne!(shift, other_shift);
+    }
+
+    #[test]
+    fn keeps_parts_of_values_and_removes_segments() {
+        let profile = Profile::new()
+            .with_rule(Rule::new(query("PID.11.5"), Action::Keep(Keep::Prefix(3))))
+            .with_rule(Rule::new(query("PID.3[2]"), Action::Redact))
+            .with_rule(Rule::new(query("OBX"), Action::Redact).when(2, ["TX"]))
+            .with_rule(Rule::new(query("NTE"), Action::Redact));
+        let message = anonymize(&Anonymizer::new("salt", profile), MESSAGE);
+        assert_eq!(value(&message, "PID.11.5"), "627");
+        assert_eq!(value(&message, "PID.3"), "12345^^^MRN~");
+        let segments: Vec<&str> = message.segments().map(|s| s.name).collect();
+        assert_eq!(segments, vec!["MSH", "PID", "NK1", "PV1", "OBX"]);
+        assert_eq!(value(&message, "OBX.2"), "NM");
+    }
+
+    #[test]
+    fn keeps_escape_sequences_and_separators() {
+        let message = "MSH|*~\\&|\rPID|1||A\\S\\B||Doe*John\rNTE|1||a\\F\\b";
+        let profile = Profile::new().with_rule(Rule::new(query("PID.5.1"), Action::Hash));
+        let anonymized = Anonymizer::new("salt", profile)
+            .anonymize(&Message::parse(message).unwrap())
+            .unwrap();
+        assert_eq!(anonymized.separators().component, '*');
+        let anonymized = anonymized.to_string();
+        assert!(anonymized.starts_with("MSH|*~\\&|\rPID|1||A\\S\\B||"));
+        assert!(anonymized.ends_with("*John\rNTE|1||a\\F\\b"));
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        let message = Message::parse(MESSAGE).unwrap();
+        for rule in [
+            Rule::new(query("PID"), Action::Hash),
+            Rule::new(query("MSH.2"), Action::Redact),
+        ] {
+            let error = Anonymizer::new("salt", Profile::new().with_rule(rule.clone()))
+                .anonymize(&message)
+                .unwrap_err();
+            assert_eq!(error, AnonymizeError::InvalidRule(rule.query.to_string()));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn profiles_can_be_deserialized() {
+        let profile: Profile = serde_json::from_value(serde_json::json!([
+            { "query": "PID.5.1", "action": { "fake": "family_name" } },
+            { "query": "PID.7", "action": "shift_date" },
+            { "query": "PID.11.5", "action": { "keep": { "prefix": 3 } } },
+            { "query": "OBX.5", "action": "redact", "when": { "field": 2, "any_of": ["TX"] } },
+        ]))
+        .unwrap();
+        assert_eq!(
+            profile,
+            Profile::new()
+                .with_rule(Rule::new(query("PID.5.1"), Action::Fake(Fake::FamilyName)))
+                .with_rule(Rule::new(query("PID.7"), Action::ShiftDate))
+                .with_rule(Rule::new(query("PID.11.5"), Action::Keep(Keep::Prefix(3))))
+                .with_rule(Rule::new(query("OBX.5"), Action::Redact).when(2, ["TX"]))
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use hl7_parser::{
+    locate::{LineCol, Offset, Positions},
+    parser::ParseError,
+    Message,
+};
+use std::ops::Range;
+
+/// An open `.hl7` document, which may contain any number of messages
+pub struct Document {
+    pub text: String,
+}
+
+/// A message within a document, along with the byte offset of its start within the document
+pub struct DocumentMessage<'d> {
+    pub offset: usize,
+    pub message: Message<'d>,
+}
+
+impl DocumentMessage<'_> {
+    /// The range of the message within the document
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.message.raw_value().len()
+    }
+}
+
+/// Content of a document which couldn't be parsed as part of a message
+pub struct DocumentError {
+    pub range: Range<usize>,
+    pub message: String,
+}
+
+/// The parsed form of a document
+pub struct ParsedDocument<'d> {
+    pub positions: Positions<'d>,
+    pub messages: Vec<DocumentMessage<'d>>,
+    pub errors: Vec<DocumentError>,
+}
+
+impl Document {
+    /// Parse the document into its messages. Each message starts with an `MSH` segment at the
+    /// start of a line, and continues for as long as the following lines look like segments;
+    /// any other content is reported as an error.
+    pub fn parse(&self) -> ParsedDocument<'_> {
+        let text = self.text.as_str();
+        let mut messages = Vec::new();
+        let mut errors = Vec::new();
+        let mut error = |start: usize, end: usize, message: &str| {
+            let line_end = text[start..end]
+                .find(['\r', '\n'])
+                .map(|i| start + i)
+                .unwrap_or(end);
+            errors.push(DocumentError {
+                range: start..line_end,
+                message: message.to_string(),
+            });
+        };
+
+        let mut offset = skip_whitespace(text, 0);
+        while offset < text.len() {
+            let end = next_message(text, offset);
+            let chunk = &text[offset..end];
+            if !chunk.starts_with("MSH") {
+                error(
+                    offset,
+                    end,
+                    "Expected a message starting with an MSH segment",
+                );
+            } else {
+                match Message::parse_with_lenient_newlines(chunk, true) {
+                    Ok(message) => {
+                        let message_end = offset + message.raw_value().len();
+                        messages.push(DocumentMessage { offset, message });
+                        let trailing = skip_whitespace(text, message_end);
+                        if trailing < end {
+                            error(
+                                trailing,
+                                end,
+                                "Expected a segment: a three character segment name followed by the field separator",
+                            );
+                        }
+                    }
+                    Err(ParseError::FailedToParse { position, .. }) => error(
+                        offset + position,
+                        end,
+                        "Invalid MSH segment: expected the field separator and encoding characters",
+                    ),
+                    Err(e) => error(offset, end, &e.to_string()),
+                }
+            }
+            offset = skip_whitespace(text, end);
+        }
+
+        ParsedDocument {
+            positions: Positions::new(text),
+            messages,
+            errors,
+        }
+    }
+}
+
+/// The offset of the first non-whitespace character at or after `from`
+fn skip_whitespace(text: &str, from: usize) -> usize {
+    text.len() - text[from..].trim_start().len()
+}
+
+/// The offset of the next line starting with `MSH` after the line containing `from`, or the end
+/// of the text
+fn next_message(text: &str, from: usize) -> usize {
+    let mut offset = from;
+    while let Some(i) = text[offset..].find(['\r', '\n']) {
+        offset += i + 1;
+        if text[offset..].starts_with("MSH") {
+            return offset;
+        }
+    }
+    text.len()
+}
+
+impl<'d> ParsedDocument<'d> {
+    /// The message containing the given byte offset, including the position just past its end
+    pub fn message_at(&self, offset: usize) -> Option<&DocumentMessage<'d>> {
+        self.messages.iter().find(|m| {
+            let range = m.range();
+            offset >= range.start && offset <= range.end
+        })
+    }
+
+    /// Convert an LSP position into a byte offset within the document
+    pub fn offset(&self, position: lsp_types::Position) -> Option<usize> {
+        self.positions.to_byte(Offset::LineColUtf16(LineCol::new(
+            position.line as usize,
+            position.character as usize,
+        )))
+    }
+
+    /// Convert a byte offset within the document into an LSP position
+    pub fn position(&self, offset: usize) -> lsp_types::Position {
+        let line_col = self
+            .positions
+            .at_byte(offset)
+            .map(|p| p.line_col_utf16)
+            .unwrap_or_default();
+        lsp_types::Position::new(line_col.line as u32, line_col.column as u32)
+    }
+
+    /// Convert a byte range within the document into an LSP range
+    pub fn range(&self, range: Range<usize>) -> lsp_types::Range {
+        lsp_types::Range::new(self.position(range.start), self.position(range.end))
+    }
+}
@@ -0,0 +1,284 @@
+use crate::document::{DocumentMessage, ParsedDocument};
+use hl7_parser::{
+    dictionary::{self, Severity, Version},
+    Message,
+};
+use lsp_types::{
+    Diagnostic, DiagnosticSeverity, DocumentSymbol, GotoDefinitionResponse, Hover, HoverContents,
+    InlayHint, InlayHintKind, InlayHintLabel, InlayHintTooltip, Location, MarkupContent,
+    MarkupKind, SemanticToken, SemanticTokenType, SemanticTokens, SemanticTokensLegend, SymbolKind,
+    Uri,
+};
+use std::ops::Range;
+
+/// The semantic token types used for highlighting, in legend order
+const TOKEN_TYPES: [SemanticTokenType; 2] =
+    [SemanticTokenType::KEYWORD, SemanticTokenType::OPERATOR];
+const SEGMENT_NAME_TOKEN: u32 = 0;
+const DELIMITER_TOKEN: u32 = 1;
+
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: Vec::new(),
+    }
+}
+
+/// The HL7 version used to look up definitions for a message
+fn version(message: &Message) -> Version {
+    message.version().unwrap_or(Version::LATEST)
+}
+
+/// Show the path, definition, and decoded value of the location under the cursor
+pub fn hover(document: &ParsedDocument, offset: usize) -> Option<Hover> {
+    let DocumentMessage {
+        offset: start,
+        message,
+    } = document.message_at(offset)?;
+    let cursor = message.locate_cursor(offset - start)?;
+    let (_, _, segment) = cursor.segment?;
+
+    let mut contents = format!("**{cursor}**");
+    if let Some(definition) = cursor.describe() {
+        contents.push_str(&format!(" — {definition}"));
+        if let Some(data_type) = definition.data_type() {
+            contents.push_str(&format!("\n\nData type: `{data_type}`"));
+        }
+        if let Some(optionality) = definition.optionality() {
+            contents.push_str(&format!(", optionality: `{optionality}`"));
+        }
+        if let Some(table) = definition.table() {
+            contents.push_str(&format!(", table: `{table:04}`"));
+        }
+    }
+    if let Some(value) = cursor.value(&message.separators) {
+        contents.push_str(&format!("\n\n```\n{value}\n```"));
+    }
+
+    let range = cursor.range().unwrap_or(&segment.range);
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: contents,
+        }),
+        range: Some(document.range(start + range.start..start + range.end)),
+    })
+}
+
+/// Jump from an acknowledgement's `MSA.2` to the `MSH.10` of the message it acknowledges
+pub fn definition(
+    uri: &Uri,
+    document: &ParsedDocument,
+    offset: usize,
+) -> Option<GotoDefinitionResponse> {
+    let source = document.message_at(offset)?;
+    let cursor = source.message.locate_cursor(offset - source.offset)?;
+    let ((name, _, _), (field_index, field)) = cursor.segment.zip(cursor.field)?;
+    if name != "MSA" || field_index != 2 || field.is_empty() {
+        return None;
+    }
+
+    let control_id = field.raw_value();
+    document
+        .messages
+        .iter()
+        .filter(|m| m.offset != source.offset)
+        .find_map(|m| {
+            let msh_10 = m.message.segment("MSH")?.field(10)?;
+            (msh_10.raw_value() == control_id).then(|| {
+                GotoDefinitionResponse::Scalar(Location {
+                    uri: uri.clone(),
+                    range: document
+                        .range(m.offset + msh_10.range.start..m.offset + msh_10.range.end),
+                })
+            })
+        })
+}
+
+/// List each message, identified by its type and control ID, with its segments as children
+#[allow(deprecated)] // `DocumentSymbol::deprecated` must still be provided
+pub fn document_symbols(document: &ParsedDocument) -> Vec<DocumentSymbol> {
+    document
+        .messages
+        .iter()
+        .map(|m| {
+            let version = version(&m.message);
+            let msh_field = |n| {
+                m.message
+                    .segment("MSH")
+                    .and_then(|msh| msh.field(n))
+                    .map(|f| f.raw_value())
+                    .filter(|v| !v.is_empty())
+            };
+            let segments = m
+                .message
+                .segments()
+                .map(|segment| {
+                    let range = m.offset + segment.range.start..m.offset + segment.range.end;
+                    let name_range = range.start..range.start + segment.name.len();
+                    DocumentSymbol {
+                        name: segment.name.to_string(),
+                        detail: dictionary::segment(version, segment.name)
+                            .map(|s| s.description.to_string()),
+                        kind: SymbolKind::STRUCT,
+                        tags: None,
+                        deprecated: None,
+                        range: document.range(range),
+                        selection_range: document.range(name_range),
+                        children: None,
+                    }
+                })
+                .collect();
+
+            let range = m.range();
+            DocumentSymbol {
+                name: msh_field(9).unwrap_or("Message").to_string(),
+                detail: msh_field(10).map(|id| format!("Control ID {id}")),
+                kind: SymbolKind::MODULE,
+                tags: None,
+                deprecated: None,
+                range: document.range(range.clone()),
+                selection_range: document.range(range.start..range.start + 3),
+                children: Some(segments),
+            }
+        })
+        .collect()
+}
+
+/// Show the field number before each non-empty field within the given byte range
+pub fn inlay_hints(document: &ParsedDocument, range: Range<usize>) -> Vec<InlayHint> {
+    let mut hints = Vec::new();
+    for m in &document.messages {
+        let version = version(&m.message);
+        for segment in m.message.segments() {
+            let segment_range = m.offset + segment.range.start..m.offset + segment.range.end;
+            if segment_range.end < range.start || segment_range.start > range.end {
+                continue;
+            }
+            // MSH.1 and MSH.2 are the separators themselves
+            let skip = if segment.name == "MSH" { 2 } else { 0 };
+            for (i, field) in segment.fields().enumerate().skip(skip) {
+                if field.is_empty() {
+                    continue;
+                }
+                let number = i + 1;
+                hints.push(InlayHint {
+                    position: document.position(m.offset + field.range.start),
+                    label: InlayHintLabel::String(format!("{number}:")),
+                    kind: Some(InlayHintKind::PARAMETER),
+                    text_edits: None,
+                    tooltip: dictionary::field(version, segment.name, number).map(|f| {
+                        InlayHintTooltip::String(format!("{}.{number}: {}", segment.name, f.name))
+                    }),
+                    padding_left: None,
+                    padding_right: None,
+                    data: None,
+                });
+            }
+        }
+    }
+    hints
+}
+
+/// Highlight segment names and delimiters
+pub fn semantic_tokens(document: &ParsedDocument) -> SemanticTokens {
+    // (byte offset, length in UTF-16 code units, token type)
+    let mut tokens: Vec<(usize, u32, u32)> = Vec::new();
+    for m in &document.messages {
+        let separators = &m.message.separators;
+        let delimiters = [
+            separators.field,
+            separators.component,
+            separators.repetition,
+            separators.subcomponent,
+        ];
+        for segment in m.message.segments() {
+            let start = m.offset + segment.range.start;
+            tokens.push((
+                start,
+                segment.name.encode_utf16().count() as u32,
+                SEGMENT_NAME_TOKEN,
+            ));
+            for (i, c) in segment.raw_value().char_indices().skip(3) {
+                // the encoding characters of MSH.2 are delimiters too, including the escape
+                // character, which is otherwise only part of escape sequences
+                let is_encoding_character = segment.name == "MSH" && i < 8;
+                if delimiters.contains(&c) || is_encoding_character {
+                    tokens.push((start + i, c.len_utf16() as u32, DELIMITER_TOKEN));
+                }
+            }
+        }
+    }
+
+    let mut data = Vec::with_capacity(tokens.len());
+    let mut previous = lsp_types::Position::new(0, 0);
+    for (offset, length, token_type) in tokens {
+        let position = document.position(offset);
+        let delta_line = position.line - previous.line;
+        let delta_start = if delta_line == 0 {
+            position.character - previous.character
+        } else {
+            position.character
+        };
+        data.push(SemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type,
+            token_modifiers_bitset: 0,
+        });
+        previous = position;
+    }
+
+    SemanticTokens {
+        result_id: None,
+        data,
+    }
+}
+
+/// Report content that couldn't be parsed, and validate each message against the dictionary
+pub fn diagnostics(document: &ParsedDocument) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = document
+        .errors
+        .iter()
+        .map(|e| {
+            diagnostic(
+                document,
+                e.range.clone(),
+                DiagnosticSeverity::ERROR,
+                &e.message,
+            )
+        })
+        .collect();
+
+    for m in &document.messages {
+        for problem in dictionary::validate(&m.message) {
+            let severity = match problem.severity {
+                Severity::Information => DiagnosticSeverity::INFORMATION,
+                Severity::Warning => DiagnosticSeverity::WARNING,
+            };
+            diagnostics.push(diagnostic(
+                document,
+                m.offset + problem.range.start..m.offset + problem.range.end,
+                severity,
+                &problem.message,
+            ));
+        }
+    }
+    diagnostics
+}
+
+fn diagnostic(
+    document: &ParsedDocument,
+    range: Range<usize>,
+    severity: DiagnosticSeverity,
+    message: &str,
+) -> Diagnostic {
+    Diagnostic {
+        range: document.range(range),
+        severity: Some(severity),
+        source: Some("hl7".to_string()),
+        message: message.to_string(),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,200 @@
+//! A Language Server Protocol server for `.hl7` files, communicating over stdio.
+//!
+//! Provides hovers with the path, definition and decoded value of the location under the
+//! cursor, go-to-definition from an acknowledgement's `MSA.2` to the acknowledged message's
+//! control ID, document symbols for messages and segments, inlay hints with field numbers,
+//! diagnostics from parsing and dictionary validation, and semantic tokens for segment names
+//! and delimiters.
+
+use document::Document;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification as NotificationTrait, PublishDiagnostics,
+    },
+    request::{
+        DocumentSymbolRequest, GotoDefinition, HoverRequest, InlayHintRequest,
+        Request as RequestTrait, SemanticTokensFullRequest,
+    },
+    DocumentSymbolResponse, HoverProviderCapability, OneOf, PublishDiagnosticsParams,
+    SemanticTokensFullOptions, SemanticTokensOptions, SemanticTokensResult, ServerCapabilities,
+    ServerInfo, TextDocumentSyncCapability, TextDocumentSyncKind, Uri,
+};
+use std::{collections::HashMap, error::Error};
+
+mod document;
+mod features;
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Sync + Send>>;
+
+fn main() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        inlay_hint_provider: Some(OneOf::Left(true)),
+        semantic_tokens_provider: Some(
+            SemanticTokensOptions {
+                legend: features::semantic_tokens_legend(),
+                full: Some(SemanticTokensFullOptions::Bool(true)),
+                ..Default::default()
+            }
+            .into(),
+        ),
+        ..Default::default()
+    };
+    let initialize = serde_json::json!({
+        "capabilities": capabilities,
+        "serverInfo": ServerInfo {
+            name: "hl7-lsp".to_string(),
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        },
+    });
+    connection.initialize_finish(connection.initialize_start()?.0, initialize)?;
+
+    Server::default().run(&connection)?;
+    // the writer thread only finishes once the connection has been dropped
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+#[derive(Default)]
+struct Server {
+    documents: HashMap<Uri, Document>,
+}
+
+impl Server {
+    fn run(&mut self, connection: &Connection) -> Result<()> {
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    connection.sender.send(self.request(request).into())?;
+                }
+                Message::Notification(notification) => {
+                    if let Some(diagnostics) = self.notification(notification) {
+                        connection.sender.send(diagnostics.into())?;
+                    }
+                }
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn request(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            HoverRequest::METHOD => respond::<HoverRequest>(request, |params| {
+                let position = params.text_document_position_params;
+                let document = self.documents.get(&position.text_document.uri)?.parse();
+                features::hover(&document, document.offset(position.position)?)
+            }),
+            GotoDefinition::METHOD => respond::<GotoDefinition>(request, |params| {
+                let position = params.text_document_position_params;
+                let uri = position.text_document.uri;
+                let document = self.documents.get(&uri)?.parse();
+                features::definition(&uri, &document, document.offset(position.position)?)
+            }),
+            DocumentSymbolRequest::METHOD => respond::<DocumentSymbolRequest>(request, |params| {
+                let document = self.documents.get(&params.text_document.uri)?.parse();
+                Some(DocumentSymbolResponse::Nested(features::document_symbols(
+                    &document,
+                )))
+            }),
+            InlayHintRequest::METHOD => respond::<InlayHintRequest>(request, |params| {
+                let document = self.documents.get(&params.text_document.uri)?.parse();
+                let start = document.offset(params.range.start).unwrap_or(0);
+                let end = document
+                    .offset(params.range.end)
+                    .unwrap_or(document.positions.source().len());
+                Some(features::inlay_hints(&document, start..end))
+            }),
+            SemanticTokensFullRequest::METHOD => {
+                respond::<SemanticTokensFullRequest>(request, |params| {
+                    let document = self.documents.get(&params.text_document.uri)?.parse();
+                    Some(SemanticTokensResult::Tokens(features::semantic_tokens(
+                        &document,
+                    )))
+                })
+            }
+            method => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("Unsupported request: {method}"),
+            ),
+        }
+    }
+
+    /// Handle a notification, returning the diagnostics to publish for the affected document
+    fn notification(&mut self, notification: Notification) -> Option<Notification> {
+        let (uri, version) = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params = params::<DidOpenTextDocument>(notification)?;
+                let document = params.text_document;
+                self.documents.insert(
+                    document.uri.clone(),
+                    Document {
+                        text: document.text,
+                    },
+                );
+                (document.uri, Some(document.version))
+            }
+            DidChangeTextDocument::METHOD => {
+                let params = params::<DidChangeTextDocument>(notification)?;
+                // documents are synchronized in full, so the last change is the whole document
+                let text = params.content_changes.into_iter().last()?.text;
+                let uri = params.text_document.uri;
+                self.documents.insert(uri.clone(), Document { text });
+                (uri, Some(params.text_document.version))
+            }
+            DidCloseTextDocument::METHOD => {
+                let uri = params::<DidCloseTextDocument>(notification)?
+                    .text_document
+                    .uri;
+                self.documents.remove(&uri);
+                return Some(publish_diagnostics(uri, Vec::new(), None));
+            }
+            _ => return None,
+        };
+
+        let diagnostics = features::diagnostics(&self.documents.get(&uri)?.parse());
+        Some(publish_diagnostics(uri, diagnostics, version))
+    }
+}
+
+/// Respond to a request using a handler for its parameters
+fn respond<R: RequestTrait>(
+    request: Request,
+    handler: impl FnOnce(R::Params) -> R::Result,
+) -> Response {
+    match serde_json::from_value::<R::Params>(request.params) {
+        Ok(params) => Response::new_ok(request.id, handler(params)),
+        Err(e) => Response::new_err(request.id, ErrorCode::InvalidParams as i32, e.to_string()),
+    }
+}
+
+/// Extract the parameters of a notification, ignoring notifications with invalid parameters
+fn params<N: NotificationTrait>(notification: Notification) -> Option<N::Params> {
+    notification.extract(N::METHOD).ok()
+}
+
+fn publish_diagnostics(
+    uri: Uri,
+    diagnostics: Vec<lsp_types::Diagnostic>,
+    version: Option<i32>,
+) -> Notification {
+    Notification::new(
+        PublishDiagnostics::METHOD.to_string(),
+        PublishDiagnosticsParams {
+            uri,
+            diagnostics,
+            version,
+        },
+    )
+}
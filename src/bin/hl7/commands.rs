@@ -0,0 +1,446 @@
+use crate::{
+    input::{self, Chunk, Input},
+    Result,
+};
+use hl7_parser::{
+    anonymize::{Anonymizer, Profile},
+    dictionary::{self, Severity, Version},
+    display::{Layout, Rendered},
+    json,
+    locate::Positions,
+    parser::ParseError,
+    query::LocationQuery,
+    Message,
+};
+use std::{collections::HashMap, fmt::Write as _, io::Write as _, path::Path, process::ExitCode};
+
+/// The exit code used when a command ran successfully but found problems, differences, or
+/// nothing at all
+const NOT_OK: u8 = 1;
+/// The exit code used when a command couldn't process some of its input
+const FAILED: u8 = 2;
+
+const NOT_A_MESSAGE: &str = "Expected a message starting with an MSH segment";
+
+/// Options shared by every command
+pub struct Options {
+    /// Only accept `\r` as a segment separator
+    pub strict: bool,
+}
+
+impl Options {
+    fn parse<'i>(&self, chunk: &Chunk<'i>) -> std::result::Result<Message<'i>, ParseError> {
+        Message::parse_with_lenient_newlines(chunk.text, !self.strict)
+    }
+
+    /// The segment separator used for messages written by the tool: the standard `\r` in strict
+    /// mode, otherwise a newline so that the output is readable in a terminal
+    fn segment_separator(&self) -> &'static str {
+        if self.strict {
+            "\r"
+        } else {
+            "\n"
+        }
+    }
+}
+
+/// Call `f` with the label of each message in the inputs and the parsed message, reporting
+/// parse errors and errors from `f` on stderr. Returns whether every message was handled.
+fn each_message(
+    inputs: &[Input],
+    options: &Options,
+    mut f: impl FnMut(&str, &Message) -> Result<()>,
+) -> bool {
+    let mut ok = true;
+    for input in inputs {
+        for chunk in input.messages() {
+            let label = input.label(&chunk);
+            if !chunk.text.starts_with("MSH") {
+                eprintln!("{label}: {NOT_A_MESSAGE}");
+                ok = false;
+                continue;
+            }
+            let result = options
+                .parse(&chunk)
+                .map_err(Into::into)
+                .and_then(|message| f(&label, &message));
+            if let Err(e) = result {
+                eprintln!("{label}: {e}");
+                ok = false;
+            }
+        }
+    }
+    ok
+}
+
+fn message_count(inputs: &[Input]) -> usize {
+    inputs.iter().map(|input| input.messages().len()).sum()
+}
+
+fn exit_code(ok: bool) -> ExitCode {
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(FAILED)
+    }
+}
+
+/// How to print messages with [`pretty`]
+pub struct Pretty {
+    /// Print a table rather than a tree
+    pub table: bool,
+    /// Show the dictionary name of each location
+    pub names: bool,
+    /// Colour the output with ANSI escape codes
+    pub color: bool,
+    /// Truncate lines to this many characters
+    pub width: Option<usize>,
+    /// Show empty fields
+    pub empty: bool,
+    /// Show the component of fields with a single component
+    pub expand: bool,
+}
+
+/// Print each message as a tree or table of its segments, fields, repeats, components, and
+/// subcomponents
+pub fn pretty(inputs: &[Input], options: &Options, pretty: &Pretty) -> Result<ExitCode> {
+    let layout = if pretty.table {
+        Layout::Table
+    } else {
+        Layout::Tree
+    };
+    let multiple = message_count(inputs) > 1;
+    let mut first = true;
+    let ok = each_message(inputs, options, |label, message| {
+        if multiple {
+            if !first {
+                println!();
+            }
+            println!("==> {label} <==");
+        }
+        first = false;
+        let mut rendered = Rendered::new(message, layout)
+            .with_names(pretty.names)
+            .with_color(pretty.color)
+            .with_empty_fields(pretty.empty)
+            .with_collapsed_components(!pretty.expand);
+        if let Some(width) = pretty.width {
+            rendered = rendered.with_width(width);
+        }
+        print!("{rendered}");
+        Ok(())
+    });
+    Ok(exit_code(ok))
+}
+
+/// Print the value at a location in each message, decoded unless `raw` is set. Symbolic names
+/// are resolved for each message's own version.
+pub fn query(inputs: &[Input], options: &Options, path: &str, raw: bool) -> Result<ExitCode> {
+    // report a malformed query once, rather than once per message
+    LocationQuery::parse_with_version(path, Version::LATEST)?;
+
+    let multiple = message_count(inputs) > 1;
+    let mut found = false;
+    let ok = each_message(inputs, options, |label, message| {
+        let version = message.version().unwrap_or(Version::LATEST);
+        let query = LocationQuery::parse_with_version(path, version)?;
+        let Some(result) = message.query(query) else {
+            return Ok(());
+        };
+        found = true;
+        let value = if raw {
+            result.raw_value().to_string()
+        } else {
+            result.display(&message.separators).to_string()
+        };
+        if multiple {
+            println!("{label}: {value}");
+        } else {
+            println!("{value}");
+        }
+        Ok(())
+    });
+
+    if !ok {
+        Ok(ExitCode::from(FAILED))
+    } else if !found {
+        Ok(ExitCode::from(NOT_OK))
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// Print parse errors and dictionary validation problems for each message, as
+/// `name:line:column: severity: message`, in the order they appear in the input
+pub fn validate(inputs: &[Input], options: &Options) -> Result<ExitCode> {
+    let mut ok = true;
+    for input in inputs {
+        let mut reports = Vec::new();
+        let mut report = |offset: usize, severity: &str, message: &str| {
+            reports.push((offset, severity.to_string(), message.to_string()));
+        };
+
+        for chunk in input.messages() {
+            if !chunk.text.starts_with("MSH") {
+                report(chunk.offset, "error", NOT_A_MESSAGE);
+                ok = false;
+                continue;
+            }
+            let message = match options.parse(&chunk) {
+                Ok(message) => message,
+                Err(e) => {
+                    let position = match e {
+                        ParseError::FailedToParse { position, .. } => position,
+                        ParseError::IncompleteInput(_) => 0,
+                    };
+                    report(chunk.offset + position, "error", &e.to_string());
+                    ok = false;
+                    continue;
+                }
+            };
+
+            let rest = &chunk.text[message.raw_value().len()..];
+            if !rest.trim().is_empty() {
+                report(
+                    chunk.offset + chunk.text.len() - rest.trim_start().len(),
+                    "error",
+                    "Expected a segment: a three character segment name followed by the field separator",
+                );
+                ok = false;
+            }
+
+            for problem in dictionary::validate(&message) {
+                report(
+                    chunk.offset + problem.range.start,
+                    &problem.severity.to_string(),
+                    &problem.message,
+                );
+                ok &= problem.severity < Severity::Warning;
+            }
+        }
+
+        let positions = Positions::new(&input.text);
+        reports.sort_by_key(|(offset, ..)| *offset);
+        for (offset, severity, message) in reports {
+            let line_col = positions
+                .at_byte(offset)
+                .map(|p| p.line_col)
+                .unwrap_or_default();
+            println!(
+                "{}:{}:{}: {severity}: {message}",
+                input.name,
+                line_col.line + 1,
+                line_col.column + 1
+            );
+        }
+    }
+
+    Ok(if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(NOT_OK)
+    })
+}
+
+/// Print each message as canonical JSON, one message per line unless `pretty` is set
+pub fn to_json(inputs: &[Input], options: &Options, pretty: bool) -> Result<ExitCode> {
+    let ok = each_message(inputs, options, |_, message| {
+        let value = json::to_json(message);
+        if pretty {
+            println!("{}", serde_json::to_string_pretty(&value)?);
+        } else {
+            println!("{value}");
+        }
+        Ok(())
+    });
+    Ok(exit_code(ok))
+}
+
+/// Print the message encoded by each canonical JSON value in the inputs. The messages are
+/// written exactly as they were encoded, followed by a newline.
+pub fn from_json(inputs: &[Input]) -> Result<ExitCode> {
+    let mut ok = true;
+    for input in inputs {
+        let values = serde_json::Deserializer::from_str(&input.text).into_iter();
+        for (i, value) in values.enumerate() {
+            let result: Result<String> = value
+                .map_err(Into::into)
+                .and_then(|value| json::from_json(&value).map_err(Into::into));
+            match result {
+                Ok(message) => println!("{message}"),
+                Err(e) => {
+                    eprintln!("{}: value {}: {e}", input.name, i + 1);
+                    ok = false;
+                    // a syntax error leaves the rest of the stream unreadable
+                    if e.is::<serde_json::Error>() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    Ok(exit_code(ok))
+}
+
+/// Print the differences between two messages, one per line
+pub fn diff(old: &str, new: &str, options: &Options) -> Result<ExitCode> {
+    let old = only_input(old)?;
+    let new = only_input(new)?;
+    let old_chunks = old.messages();
+    let new_chunks = new.messages();
+    let old_message = only_message(&old, &old_chunks, options)?;
+    let new_message = only_message(&new, &new_chunks, options)?;
+
+    let diff = hl7_parser::diff::diff(&old_message, &new_message);
+    print!("{diff}");
+    Ok(if diff.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(NOT_OK)
+    })
+}
+
+fn only_input(name: &str) -> Result<Input> {
+    let mut inputs = input::read(&[name.to_string()])?;
+    if inputs.len() != 1 {
+        return Err(format!("{name}: expected a single file, found {}", inputs.len()).into());
+    }
+    Ok(inputs.remove(0))
+}
+
+fn only_message<'i>(input: &Input, chunks: &[Chunk<'i>], options: &Options) -> Result<Message<'i>> {
+    let [chunk] = chunks else {
+        return Err(format!(
+            "{}: expected a single message, found {}",
+            input.name,
+            chunks.len()
+        )
+        .into());
+    };
+    options
+        .parse(chunk)
+        .map_err(|e| format!("{}: {e}", input.label(chunk)).into())
+}
+
+/// Print each message with the safe harbor identifiers removed or replaced
+pub fn anonymize(inputs: &[Input], options: &Options, salt: &str) -> Result<ExitCode> {
+    let anonymizer = Anonymizer::new(salt, Profile::safe_harbor());
+    let separator = options.segment_separator();
+    let ok = each_message(inputs, options, |_, message| {
+        let anonymized = anonymizer.anonymize(message)?;
+        println!("{}", anonymized.render_with_segment_separators(separator));
+        Ok(())
+    });
+    Ok(exit_code(ok))
+}
+
+/// Write each message to its own file in `out_dir`, named after its input and its position
+/// within the input, printing the path of each file written. Inputs with the same file name
+/// are numbered (`a-0001.hl7`, then `a-2-0001.hl7`), and existing files are never overwritten.
+pub fn split(inputs: &[Input], out_dir: &Path) -> Result<ExitCode> {
+    std::fs::create_dir_all(out_dir)?;
+    let mut ok = true;
+    let mut stems = HashMap::new();
+    for input in inputs {
+        let stem = input
+            .path
+            .as_ref()
+            .and_then(|path| path.file_stem())
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "stdin".to_string());
+        // inputs with the same name in different directories are numbered in order
+        let count = stems.entry(stem.clone()).or_insert(0);
+        *count += 1;
+        let stem = match *count {
+            1 => stem,
+            n => format!("{stem}-{n}"),
+        };
+        for (i, chunk) in input.messages().iter().enumerate() {
+            if !chunk.text.starts_with("MSH") {
+                eprintln!("{}: {NOT_A_MESSAGE}", input.label(chunk));
+                ok = false;
+                continue;
+            }
+            let path = out_dir.join(format!("{stem}-{:04}.hl7", i + 1));
+            let file = std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path);
+            match file {
+                Ok(mut file) => file.write_all(chunk.text.as_bytes())?,
+                Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => {
+                    eprintln!("{}: already exists, not overwriting it", path.display());
+                    ok = false;
+                    continue;
+                }
+                Err(error) => return Err(error.into()),
+            }
+            println!("{}", path.display());
+        }
+    }
+    Ok(exit_code(ok))
+}
+
+/// How to acknowledge messages
+pub struct Acknowledgment<'a> {
+    /// The acknowledgment code for `MSA.1`, such as `AA`
+    pub code: &'a str,
+    /// The text message for `MSA.3`
+    pub text: Option<&'a str>,
+    /// The control ID for the acknowledgment's `MSH.10`, rather than the acknowledged message's
+    pub control_id: Option<&'a str>,
+}
+
+/// Print an acknowledgment for each message
+pub fn ack(inputs: &[Input], options: &Options, ack: &Acknowledgment) -> Result<ExitCode> {
+    let now = jiff::Zoned::now().strftime("%Y%m%d%H%M%S%z").to_string();
+    let separator = options.segment_separator();
+    let ok = each_message(inputs, options, |_, message| {
+        println!("{}", acknowledge(message, ack, &now, separator)?);
+        Ok(())
+    });
+    Ok(exit_code(ok))
+}
+
+/// Build an acknowledgment for a message, addressed back to its sender
+fn acknowledge(
+    message: &Message,
+    ack: &Acknowledgment,
+    now: &str,
+    segment_separator: &str,
+) -> Result<String> {
+    let msh = message.segment("MSH").ok_or("Message has no MSH segment")?;
+    let field = |n: usize| msh.field(n).map(|f| f.raw_value()).unwrap_or_default();
+    let component = |n: usize| msh.field(9).and_then(|f| f.component(n));
+    let separators = &message.separators;
+    let (f, c) = (separators.field, separators.component);
+
+    let event = component(2).map(|c| c.raw_value()).unwrap_or_default();
+    let mut message_type = format!("ACK{c}{event}");
+    if component(3).is_some() {
+        write!(message_type, "{c}ACK")?;
+    }
+    let control_id = field(10);
+    let ack_control_id = match ack.control_id {
+        Some(id) => separators.encode(id).to_string(),
+        None => control_id.to_string(),
+    };
+
+    let mut out = format!(
+        "MSH{f}{encoding}{f}{receiving_app}{f}{receiving_facility}{f}{sending_app}{f}\
+        {sending_facility}{f}{now}{f}{f}{message_type}{f}{ack_control_id}{f}{processing_id}{f}\
+        {version}{segment_separator}MSA{f}{code}{f}{control_id}",
+        encoding = field(2),
+        receiving_app = field(5),
+        receiving_facility = field(6),
+        sending_app = field(3),
+        sending_facility = field(4),
+        processing_id = field(11),
+        version = field(12),
+        code = ack.code,
+    );
+    if let Some(text) = ack.text {
+        write!(out, "{f}{}", separators.encode(text))?;
+    }
+    Ok(out)
+}
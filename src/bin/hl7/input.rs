@@ -0,0 +1,140 @@
+use crate::Result;
+use hl7_parser::locate::Positions;
+use std::{
+    io::Read,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// The contents of a file, or of stdin
+pub struct Input {
+    /// The name to use when reporting on the input: its path, or `<stdin>`
+    pub name: String,
+    pub path: Option<PathBuf>,
+    pub text: String,
+}
+
+/// A message within an input, along with where it starts within the input
+pub struct Chunk<'i> {
+    /// The byte offset of the start of the message
+    pub offset: usize,
+    /// The line the message starts on, counting from 1
+    pub line: usize,
+    pub text: &'i str,
+}
+
+impl Input {
+    /// A label for a chunk of this input, of the form `name:line`
+    pub fn label(&self, chunk: &Chunk) -> String {
+        format!("{}:{}", self.name, chunk.line)
+    }
+
+    /// Split the input into its messages. Each message starts with an `MSH` segment at the start
+    /// of a line; batch header and trailer segments (`FHS`, `BHS`, `BTS`, and `FTS`) and blank
+    /// lines are skipped. Any content before the first `MSH` segment is returned as a chunk of its
+    /// own, so that it gets reported when it fails to parse.
+    pub fn messages(&self) -> Vec<Chunk<'_>> {
+        let text = self.text.as_str();
+        let positions = Positions::new(text);
+        let mut chunks = Vec::new();
+        let mut current: Option<Range<usize>> = None;
+        let mut finish = |current: &mut Option<Range<usize>>| {
+            if let Some(range) = current.take() {
+                let line = positions
+                    .at_byte(range.start)
+                    .map(|p| p.line_col.line)
+                    .unwrap_or_default();
+                chunks.push(Chunk {
+                    offset: range.start,
+                    line: line + 1,
+                    text: &text[range],
+                });
+            }
+        };
+
+        for line in lines(text) {
+            let content = &text[line.clone()];
+            if content.trim().is_empty() {
+                continue;
+            }
+            if ["FHS", "BHS", "BTS", "FTS"]
+                .iter()
+                .any(|name| content.starts_with(name))
+            {
+                finish(&mut current);
+            } else if content.starts_with("MSH") {
+                finish(&mut current);
+                current = Some(line);
+            } else if let Some(range) = current.as_mut() {
+                range.end = line.end;
+            } else {
+                current = Some(line);
+            }
+        }
+        finish(&mut current);
+        chunks
+    }
+}
+
+/// The byte ranges of the lines in the text, excluding their line endings
+fn lines(text: &str) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        if c == '\r' || c == '\n' {
+            lines.push(start..i);
+            start = i + 1;
+        }
+    }
+    lines.push(start..text.len());
+    lines
+}
+
+/// Read the inputs named on the command line. Each name is a path, a glob pattern, or `-` for
+/// stdin; no names at all also means stdin.
+pub fn read(names: &[String]) -> Result<Vec<Input>> {
+    if names.is_empty() {
+        return Ok(vec![stdin()?]);
+    }
+
+    let mut inputs = Vec::new();
+    for name in names {
+        if name == "-" {
+            inputs.push(stdin()?);
+        } else if Path::new(name).exists() {
+            inputs.push(file(PathBuf::from(name))?);
+        } else {
+            let before = inputs.len();
+            for path in glob::glob(name)? {
+                let path = path?;
+                if !path.is_dir() {
+                    inputs.push(file(path)?);
+                }
+            }
+            if inputs.len() == before {
+                return Err(format!("{name}: no such file").into());
+            }
+        }
+    }
+    Ok(inputs)
+}
+
+fn stdin() -> Result<Input> {
+    let mut text = String::new();
+    std::io::stdin().read_to_string(&mut text)?;
+    Ok(Input {
+        name: "<stdin>".to_string(),
+        path: None,
+        text,
+    })
+}
+
+fn file(path: PathBuf) -> Result<Input> {
+    let name = path.display().to_string();
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("{name}: {e}"))?;
+    Ok(Input {
+        name,
+        path: Some(path),
+        text,
+    })
+}
@@ -0,0 +1,195 @@
+//! A command-line tool for working with HL7v2 messages.
+//!
+//! Messages are read from files, glob patterns, or stdin (when no files are given, or for `-`),
+//! and each input may contain any number of messages, optionally wrapped in batch headers and
+//! trailers. Segments may be separated by `\r`, `\n`, or `\r\n` unless `--strict` is given.
+//!
+//! Exit codes are 0 on success, 1 when a command found problems, differences, or nothing at all,
+//! and 2 when some of the input couldn't be processed.
+
+use clap::{Parser, Subcommand, ValueEnum};
+use commands::{Acknowledgment, Options, Pretty};
+use std::{error::Error, io::IsTerminal, path::PathBuf, process::ExitCode};
+
+mod commands;
+mod input;
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Sync + Send>>;
+
+#[derive(Parser)]
+#[command(name = "hl7", version, about = "Work with HL7v2 messages")]
+struct Cli {
+    /// Only accept `\r` as a segment separator, and use it when writing messages
+    #[arg(long, global = true)]
+    strict: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print messages as a tree of their segments, fields, components, and subcomponents
+    Pretty {
+        /// Print a table with a row for each location rather than a tree
+        #[arg(long)]
+        table: bool,
+        /// Show the dictionary name of each segment, field, component, and subcomponent
+        #[arg(long)]
+        names: bool,
+        /// When to colour the output
+        #[arg(long, value_enum, default_value_t = Color::Auto)]
+        color: Color,
+        /// Truncate lines to this many characters
+        #[arg(long)]
+        width: Option<usize>,
+        /// Show empty fields
+        #[arg(long)]
+        empty: bool,
+        /// Show the component of fields which only have one, rather than showing them on one line
+        #[arg(long)]
+        expand: bool,
+        /// Files or glob patterns to read, or `-` for stdin
+        files: Vec<String>,
+    },
+    /// Print the value at a location in each message, such as `PID.5.1` or
+    /// `PID.PatientName.FamilyName`
+    Query {
+        /// The location to print
+        path: String,
+        /// Print the value as it appears in the message, without decoding escape sequences
+        #[arg(long)]
+        raw: bool,
+        /// Files or glob patterns to read, or `-` for stdin
+        files: Vec<String>,
+    },
+    /// Check messages against the dictionary definitions for their version
+    Validate {
+        /// Files or glob patterns to read, or `-` for stdin
+        files: Vec<String>,
+    },
+    /// Convert messages to canonical JSON, one message per line
+    ToJson {
+        /// Pretty-print the JSON
+        #[arg(long)]
+        pretty: bool,
+        /// Files or glob patterns to read, or `-` for stdin
+        files: Vec<String>,
+    },
+    /// Convert a stream of canonical JSON values back into messages
+    FromJson {
+        /// Files or glob patterns to read, or `-` for stdin
+        files: Vec<String>,
+    },
+    /// Print the differences between two messages
+    Diff {
+        /// The original message, or `-` for stdin
+        old: String,
+        /// The changed message, or `-` for stdin
+        new: String,
+    },
+    /// Remove or replace the HIPAA safe harbor identifiers in messages
+    Anonymize {
+        /// The secret used to derive hashes, fake values, and date shifts
+        #[arg(long, env = "HL7_ANONYMIZE_SALT", hide_env_values = true)]
+        salt: String,
+        /// Files or glob patterns to read, or `-` for stdin
+        files: Vec<String>,
+    },
+    /// Write each message to its own file, without overwriting existing files
+    Split {
+        /// The directory to write the messages to
+        #[arg(long, default_value = ".")]
+        out_dir: PathBuf,
+        /// Files or glob patterns to read, or `-` for stdin
+        files: Vec<String>,
+    },
+    /// Generate an acknowledgment for each message
+    Ack {
+        /// The acknowledgment code
+        #[arg(long, default_value = "AA", value_parser = ["AA", "AE", "AR", "CA", "CE", "CR"])]
+        code: String,
+        /// A text message to include in `MSA.3`
+        #[arg(long)]
+        text: Option<String>,
+        /// The control ID of the acknowledgment, instead of the acknowledged message's
+        #[arg(long)]
+        control_id: Option<String>,
+        /// Files or glob patterns to read, or `-` for stdin
+        files: Vec<String>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Color {
+    /// Colour the output when writing to a terminal
+    Auto,
+    Always,
+    Never,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("hl7: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<ExitCode> {
+    let options = Options { strict: cli.strict };
+    match cli.command {
+        Command::Pretty {
+            table,
+            names,
+            color,
+            width,
+            empty,
+            expand,
+            files,
+        } => {
+            let pretty = Pretty {
+                table,
+                names,
+                color: match color {
+                    Color::Auto => std::io::stdout().is_terminal(),
+                    Color::Always => true,
+                    Color::Never => false,
+                },
+                width,
+                empty,
+                expand,
+            };
+            commands::pretty(&input::read(&files)?, &options, &pretty)
+        }
+        Command::Query { path, raw, files } => {
+            commands::query(&input::read(&files)?, &options, &path, raw)
+        }
+        Command::Validate { files } => commands::validate(&input::read(&files)?, &options),
+        Command::ToJson { pretty, files } => {
+            commands::to_json(&input::read(&files)?, &options, pretty)
+        }
+        Command::FromJson { files } => commands::from_json(&input::read(&files)?),
+        Command::Diff { old, new } => commands::diff(&old, &new, &options),
+        Command::Anonymize { salt, files } => {
+            commands::anonymize(&input::read(&files)?, &options, &salt)
+        }
+        Command::Split { out_dir, files } => commands::split(&input::read(&files)?, &out_dir),
+        Command::Ack {
+            code,
+            text,
+            control_id,
+            files,
+        } => {
+            let ack = Acknowledgment {
+                code: &code,
+                text: text.as_deref(),
+                control_id: control_id.as_deref(),
+            };
+            commands::ack(&input::read(&files)?, &options, &ack)
+        }
+    }
+}
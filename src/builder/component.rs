@@ -171,7 +171,9 @@ mod display {
     impl Display for ComponentBuilderDisplay<'_> {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             match self.component {
-                ComponentBuilder::Value(value) => self.separators.encode(value).fmt(f),
+                ComponentBuilder::Value(value) => {
+                    self.separators.encode_keeping_escapes(value).fmt(f)
+                }
                 ComponentBuilder::Subcomponents(subcomponents) => {
                     if subcomponents.is_empty() {
                         return Ok(());
@@ -179,7 +181,7 @@ mod display {
                     let max_index = subcomponents.keys().max().unwrap();
                     for i in 1..=*max_index {
                         if let Some(value) = subcomponents.get(&i) {
-                            self.separators.encode(value).fmt(f)?;
+                            self.separators.encode_keeping_escapes(value).fmt(f)?;
                         }
                         if i < *max_index {
                             write!(f, "{}", self.separators.subcomponent)?;
@@ -198,22 +200,39 @@ impl<S: ToString> From<S> for ComponentBuilder {
     }
 }
 
-impl<'m> From<&'m Component<'m>> for ComponentBuilder {
-    fn from(component: &'m Component<'m>) -> Self {
+impl ComponentBuilder {
+    /// Convert a parsed component into a builder, decoding the escape sequences for separators
+    /// with the separators of the message it came from so that rendering the builder encodes
+    /// them again. Other escape sequences, such as `\H\` or `\X0D\`, are kept as they are.
+    pub fn from_component(component: &Component, separators: &Separators) -> Self {
+        ComponentBuilder::from_parsed(component, &|value| separators.decode_separators(value))
+    }
+
+    /// Convert a parsed component into a builder, using `value` to convert each value
+    pub(super) fn from_parsed(component: &Component, value: &dyn Fn(&str) -> String) -> Self {
         if component.subcomponents.len() <= 1 {
-            ComponentBuilder::Value(component.source.to_string())
+            ComponentBuilder::Value(value(component.source))
         } else {
             let subcomponents = component
                 .subcomponents
                 .iter()
                 .enumerate()
-                .map(|(i, subcomponent)| (i + 1, subcomponent.value.to_string()))
+                .map(|(i, subcomponent)| (i + 1, value(subcomponent.value)))
                 .collect();
             ComponentBuilder::Subcomponents(subcomponents)
         }
     }
 }
 
+/// Convert a parsed component into a builder, keeping its values as they appear in the message,
+/// escape sequences included. Use [`ComponentBuilder::from_component`] to decode them, so that
+/// rendering the builder doesn't encode them a second time.
+impl<'m> From<&'m Component<'m>> for ComponentBuilder {
+    fn from(component: &'m Component<'m>) -> Self {
+        ComponentBuilder::from_parsed(component, &str::to_string)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
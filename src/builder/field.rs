@@ -200,7 +200,7 @@ mod display {
     impl<'a> Display for FieldBuilderDisplay<'a> {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             match self.field {
-                FieldBuilder::Value(value) => self.separators.encode(value).fmt(f),
+                FieldBuilder::Value(value) => self.separators.encode_keeping_escapes(value).fmt(f),
                 FieldBuilder::Repeats(repeats) => {
                     let mut first = true;
                     for repeat in repeats {
@@ -224,21 +224,43 @@ impl<S: ToString> From<S> for FieldBuilder {
     }
 }
 
-impl<'m> From<&'m Field<'m>> for FieldBuilder {
-    fn from(field: &'m Field) -> Self {
+impl FieldBuilder {
+    /// Convert a parsed field into a builder, decoding the escape sequences for separators
+    /// with the separators of the message it came from so that rendering the builder encodes
+    /// them again. Other escape sequences, such as `\H\` or `\X0D\`, are kept as they are.
+    pub fn from_field(field: &Field, separators: &Separators) -> Self {
+        FieldBuilder::from_parsed(field, &|value| separators.decode_separators(value))
+    }
+
+    /// Convert a parsed field into a builder, using `value` to convert each value
+    pub(super) fn from_parsed(field: &Field, value: &dyn Fn(&str) -> String) -> Self {
         if field.has_repeats()
             || (!field.repeats.is_empty()
                 && (field.repeats[0].has_components()
                     || (!field.repeats[0].components.is_empty()
                         && field.repeats[0].components[0].has_subcomponents())))
         {
-            FieldBuilder::Repeats(field.repeats().map(RepeatBuilder::from).collect())
+            FieldBuilder::Repeats(
+                field
+                    .repeats()
+                    .map(|repeat| RepeatBuilder::from_parsed(repeat, value))
+                    .collect(),
+            )
         } else {
-            FieldBuilder::Value(field.raw_value().to_string())
+            FieldBuilder::Value(value(field.raw_value()))
         }
     }
 }
 
+/// Convert a parsed field into a builder, keeping its values as they appear in the message,
+/// escape sequences included. Use [`FieldBuilder::from_field`] to decode them, so that
+/// rendering the builder doesn't encode them a second time.
+impl<'m> From<&'m Field<'m>> for FieldBuilder {
+    fn from(field: &'m Field) -> Self {
+        FieldBuilder::from_parsed(field, &str::to_string)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -252,4 +274,33 @@ mod tests {
         let display = field.display(&separators).to_string();
         assert_eq!(display, "foo~bar");
     }
+
+    #[test]
+    fn decodes_escape_sequences_when_converting() {
+        let separators = Separators::default();
+        let source = r"A\T\B~C^D\S\E&F\E\";
+        let field = crate::parser::parse_field(source).unwrap();
+        let builder = FieldBuilder::from(&field);
+        assert_eq!(builder.repeat(0).unwrap().value().unwrap(), r"A\T\B");
+        let builder = FieldBuilder::from_field(&field, &separators);
+        assert_eq!(builder.repeat(0).unwrap().value().unwrap(), "A&B");
+        assert_eq!(
+            builder.repeat(1).unwrap().component(2).unwrap(),
+            &ComponentBuilder::with_subcomponents(HashMap::from([
+                (1, "D^E".to_string()),
+                (2, "F\\".to_string()),
+            ]))
+        );
+        assert_eq!(builder.display(&separators).to_string(), source);
+
+        let separators = Separators {
+            escape: '#',
+            ..Separators::default()
+        };
+        let source = "A#T#B#E#";
+        let field = crate::parser::parse_field_with_separators(source, separators).unwrap();
+        let builder = FieldBuilder::from_field(&field, &separators);
+        assert_eq!(builder.value().unwrap(), "A&B#");
+        assert_eq!(builder.display(&separators).to_string(), source);
+    }
 }
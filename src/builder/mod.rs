@@ -49,6 +49,11 @@ pub use repeat::*;
 mod component;
 pub use component::*;
 
+mod patch;
+pub use patch::*;
+
+mod raw;
+
 use crate::{message::Separators, Message};
 
 /// Prelude for building HL7 messages.
@@ -260,11 +265,16 @@ mod display {
     }
 }
 
-/// Convert a message into a message builder.
+/// Convert a message into a message builder. Escape sequences for separators in the message are
+/// decoded using the message's separators, so that rendering the builder encodes them again;
+/// other escape sequences, such as `\H\` or `\X0D\`, are kept as they are.
 impl<'m> From<&'m Message<'m>> for MessageBuilder {
     fn from(message: &'m Message) -> Self {
         let mut builder = MessageBuilder::new(message.separators);
-        builder.segments = message.segments().map(SegmentBuilder::from).collect();
+        builder.segments = message
+            .segments()
+            .map(|segment| SegmentBuilder::from_segment(segment, &message.separators))
+            .collect();
         builder
     }
 }
@@ -284,4 +294,36 @@ mod tests {
         let display = builder.render_with_newlines().to_string();
         assert_eq!(message_src.trim(), display);
     }
+
+    #[test]
+    fn escape_sequences_survive_roundtrips() {
+        let message_src = "MSH|^~\\&|\rZZZ|A\\T\\B|C^D\\S\\E~F&G\\E\\";
+        let message = Message::parse(message_src).expect("Can parse message");
+        let builder = MessageBuilder::from(&message);
+        assert_eq!(
+            builder
+                .segment_named("ZZZ")
+                .unwrap()
+                .field(1)
+                .unwrap()
+                .value(),
+            Some(&"A&B".to_string())
+        );
+        assert_eq!(builder.to_string(), message_src);
+    }
+
+    #[test]
+    fn formatting_and_other_escape_sequences_survive_roundtrips() {
+        let message_src = r"MSH|^~\&|
+ZZZ|\H\bold\N\ line\.sp\x \X41\|\Zxx\^\.br\\X0D\&\E\ \T\|C:\E\temp\E\";
+        let message_src = message_src.replace('\n', "\r");
+        let message = Message::parse(&message_src).expect("Can parse message");
+        let builder = MessageBuilder::from(&message);
+        let zzz = builder.segment_named("ZZZ").unwrap();
+        assert_eq!(
+            zzz.field(1).unwrap().value().unwrap(),
+            r"\H\bold\N\ line\.sp\x \X41\"
+        );
+        assert_eq!(builder.to_string(), message_src);
+    }
 }
@@ -0,0 +1,993 @@
+//! Edit scripts for message builders: a list of set, insert, delete, and move operations keyed
+//! by [`LocationQuery`], which can be applied to a [`MessageBuilder`], serialized, and inverted.
+
+use super::{
+    raw::{component_builder, field_builder, repeat_builder, segment_builder},
+    ComponentBuilder, FieldBuilder, MessageBuilder, RepeatBuilder, SegmentBuilder,
+};
+use crate::{message::Separators, query::LocationQuery};
+use std::collections::HashMap;
+
+/// A list of operations to apply to a message, in order.
+///
+/// Values in a patch are raw HL7 values, as they would appear in a message encoded with the
+/// builder's separators. This means that a value can contain structure, such as `Doe^John`
+/// to set both the family and given names of `PID.5`, and that separators which are part of a
+/// value must be escaped (e.g. `Doe\S\John`).
+///
+/// The level of each operation is determined by the most specific part of its query: `PID` is a
+/// segment, `PID.3` a field, `PID.3[2]` a repeat, `PID.5.1` a component (of the first repeat),
+/// and `PID.3.4.2` a subcomponent. Segments without an index refer to the first segment with
+/// that name.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::{builder::{MessageBuilder, Patch}, query::LocationQuery, Message};
+///
+/// let message = Message::parse("MSH|^~\\&|\rPID|1||123||Doe^John||||||||||||||555-12-3456\rPV1|1|I|W^101^A").unwrap();
+/// let mut builder = MessageBuilder::from(&message);
+///
+/// let patch = Patch::new()
+///     .with_delete(LocationQuery::parse("PID.19").unwrap())
+///     .with_move(
+///         LocationQuery::parse("PV1.3.1").unwrap(),
+///         LocationQuery::parse("PV1.3.4").unwrap(),
+///     )
+///     .with_insert(LocationQuery::parse("NTE").unwrap(), "NTE|1||Patched");
+///
+/// let undo = patch.apply(&mut builder).unwrap();
+/// assert_eq!(
+///     builder.to_string(),
+///     "MSH|^~\\&|\rPID|1||123||Doe^John\rPV1|1|I|^101^A^W\rNTE|1||Patched"
+/// );
+///
+/// undo.apply(&mut builder).unwrap();
+/// assert_eq!(builder.to_string(), message.raw_value());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Patch {
+    pub operations: Vec<Operation>,
+}
+
+/// A single operation within a [`Patch`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "op", rename_all = "snake_case"))]
+pub enum Operation {
+    /// Set the value at the query, replacing any existing value. Fields, components, and
+    /// subcomponents are created if they don't exist, as is the repeat just past the last
+    /// repeat of a field. Segments must already exist.
+    Set {
+        #[cfg_attr(feature = "serde", serde(with = "crate::query::serde_string"))]
+        query: LocationQuery,
+        value: String,
+    },
+    /// Insert a segment or repeat, so that it is found at the query afterwards. Existing
+    /// segments or repeats at and after the query are shifted along. Segments are inserted
+    /// relative to the segment given by `position` if there is one, in which case the segment
+    /// may end up at a different index than the query's, and otherwise before the segment
+    /// currently at the query or after the last segment with the same name. If there are no
+    /// segments with the same name, the segment is added to the end of the message.
+    Insert {
+        #[cfg_attr(feature = "serde", serde(with = "crate::query::serde_string"))]
+        query: LocationQuery,
+        value: String,
+        #[cfg_attr(
+            feature = "serde",
+            serde(default, skip_serializing_if = "Option::is_none")
+        )]
+        position: Option<SegmentPosition>,
+    },
+    /// Delete the value at the query. Segments and repeats are removed, shifting any following
+    /// segments or repeats along; fields, components, and subcomponents are cleared, keeping
+    /// the positions of the values after them.
+    Delete {
+        #[cfg_attr(feature = "serde", serde(with = "crate::query::serde_string"))]
+        query: LocationQuery,
+    },
+    /// Set the value at `to` to the value at `from`, then delete the value at `from`
+    Move {
+        #[cfg_attr(feature = "serde", serde(with = "crate::query::serde_string"))]
+        from: LocationQuery,
+        #[cfg_attr(feature = "serde", serde(with = "crate::query::serde_string"))]
+        to: LocationQuery,
+    },
+}
+
+/// Where to insert a segment, relative to another segment
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SegmentPosition {
+    Before(
+        #[cfg_attr(feature = "serde", serde(with = "crate::query::serde_string"))] LocationQuery,
+    ),
+    After(#[cfg_attr(feature = "serde", serde(with = "crate::query::serde_string"))] LocationQuery),
+}
+
+/// Errors that can occur when applying a patch
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PatchError {
+    /// The segment or repeat at the query doesn't exist
+    #[error("Nothing found at `{0}`")]
+    NotFound(String),
+
+    /// Only segments and repeats can be inserted
+    #[error("Cannot insert at `{0}`: only segments and repeats can be inserted")]
+    NotInsertable(String),
+
+    /// A position was given for inserting something other than a segment
+    #[error("Cannot insert at `{0}` relative to a segment: only segments can be positioned")]
+    NotPositionable(String),
+
+    /// The value contains separators which can't appear at the query's level, or is a segment
+    /// with a different name
+    #[error("Invalid value for `{query}`: `{value}`")]
+    InvalidValue { query: String, value: String },
+
+    /// The query refers to the MSH segment itself or its encoding characters, which define the
+    /// message's separators
+    #[error("Cannot patch `{0}`: it contains the message's separators")]
+    EncodingCharacters(String),
+
+    /// The query has an index of 0; indices start at 1
+    #[error("Invalid index in `{0}`: indices start at 1")]
+    InvalidIndex(String),
+}
+
+impl Patch {
+    /// Create an empty patch
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an operation to the end of the patch
+    pub fn push(&mut self, operation: Operation) {
+        self.operations.push(operation);
+    }
+
+    /// Check if the patch has no operations
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Add an operation to the end of the patch
+    pub fn with_operation(mut self, operation: Operation) -> Self {
+        self.push(operation);
+        self
+    }
+
+    /// Add a [`Operation::Set`] operation to the end of the patch
+    pub fn with_set<S: ToString>(self, query: LocationQuery, value: S) -> Self {
+        self.with_operation(Operation::Set {
+            query,
+            value: value.to_string(),
+        })
+    }
+
+    /// Add an [`Operation::Insert`] operation to the end of the patch
+    pub fn with_insert<S: ToString>(self, query: LocationQuery, value: S) -> Self {
+        self.with_operation(Operation::Insert {
+            query,
+            value: value.to_string(),
+            position: None,
+        })
+    }
+
+    /// Add an [`Operation::Insert`] operation for a segment at a position relative to another
+    /// segment to the end of the patch
+    pub fn with_insert_at<S: ToString>(
+        self,
+        query: LocationQuery,
+        value: S,
+        position: SegmentPosition,
+    ) -> Self {
+        self.with_operation(Operation::Insert {
+            query,
+            value: value.to_string(),
+            position: Some(position),
+        })
+    }
+
+    /// Add a [`Operation::Delete`] operation to the end of the patch
+    pub fn with_delete(self, query: LocationQuery) -> Self {
+        self.with_operation(Operation::Delete { query })
+    }
+
+    /// Add a [`Operation::Move`] operation to the end of the patch
+    pub fn with_move(self, from: LocationQuery, to: LocationQuery) -> Self {
+        self.with_operation(Operation::Move { from, to })
+    }
+
+    /// Apply the patch to a message builder, returning the inverse patch which undoes the
+    /// changes. If any operation fails, the builder is left unchanged.
+    pub fn apply(&self, builder: &mut MessageBuilder) -> Result<Patch, PatchError> {
+        let mut patched = builder.clone();
+        let mut inverse = Vec::new();
+        for operation in &self.operations {
+            match operation {
+                Operation::Set { query, value } => {
+                    inverse.push(set(&mut patched, query, value)?);
+                }
+                Operation::Insert {
+                    query,
+                    value,
+                    position,
+                } => {
+                    inverse.push(Some(insert(&mut patched, query, value, position.as_ref())?));
+                }
+                Operation::Delete { query } => {
+                    inverse.push(delete(&mut patched, query)?);
+                }
+                Operation::Move { from, to } => {
+                    let value = get(&patched, from)?
+                        .ok_or_else(|| PatchError::NotFound(from.to_string()))?;
+                    inverse.push(set(&mut patched, to, &value)?);
+                    inverse.push(delete(&mut patched, from)?);
+                }
+            }
+        }
+        *builder = patched;
+        Ok(Patch {
+            operations: inverse.into_iter().rev().flatten().collect(),
+        })
+    }
+
+    /// Get the patch which undoes the changes made by applying this patch to the given
+    /// builder, without modifying the builder.
+    pub fn inverse(&self, builder: &MessageBuilder) -> Result<Patch, PatchError> {
+        self.apply(&mut builder.clone())
+    }
+}
+
+impl FromIterator<Operation> for Patch {
+    fn from_iter<T: IntoIterator<Item = Operation>>(iter: T) -> Self {
+        Patch {
+            operations: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl MessageBuilder {
+    /// Apply a patch to the message, returning the inverse patch which undoes the changes.
+    /// Equivalent to calling `patch.apply(builder)`.
+    pub fn apply_patch(&mut self, patch: &Patch) -> Result<Patch, PatchError> {
+        patch.apply(self)
+    }
+}
+
+/// The part of a message which a query refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Segment,
+    Field(usize),
+    Repeat(usize, usize),
+    Component(usize, usize, usize),
+    Subcomponent(usize, usize, usize, usize),
+}
+
+impl Level {
+    fn of(query: &LocationQuery) -> Result<Self, PatchError> {
+        let indices = [
+            query.segment_index,
+            query.field,
+            query.repeat,
+            query.component,
+            query.subcomponent,
+        ];
+        if indices.contains(&Some(0)) {
+            return Err(PatchError::InvalidIndex(query.to_string()));
+        }
+        if query.segment == "MSH" && query.field.is_none_or(|field| field <= 2) {
+            return Err(PatchError::EncodingCharacters(query.to_string()));
+        }
+        let Some(field) = query.field else {
+            return Ok(Level::Segment);
+        };
+        let repeat = query.repeat.unwrap_or(1);
+        Ok(match (query.component, query.subcomponent) {
+            (Some(component), Some(subcomponent)) => {
+                Level::Subcomponent(field, repeat, component, subcomponent)
+            }
+            (Some(component), None) => Level::Component(field, repeat, component),
+            (None, _) if query.repeat.is_some() => Level::Repeat(field, repeat),
+            (None, _) => Level::Field(field),
+        })
+    }
+
+    /// The separators which can't appear within a value at this level
+    fn forbidden(&self, separators: &Separators) -> Vec<char> {
+        let mut forbidden = vec!['\r', '\n'];
+        if *self == Level::Segment {
+            return forbidden;
+        }
+        forbidden.push(separators.field);
+        if matches!(self, Level::Field(_)) {
+            return forbidden;
+        }
+        forbidden.push(separators.repetition);
+        if matches!(self, Level::Repeat(..)) {
+            return forbidden;
+        }
+        forbidden.push(separators.component);
+        if matches!(self, Level::Component(..)) {
+            return forbidden;
+        }
+        forbidden.push(separators.subcomponent);
+        forbidden
+    }
+}
+
+/// The index of the segment the query refers to within the message
+fn segment_index(builder: &MessageBuilder, query: &LocationQuery) -> Option<usize> {
+    let n = query.segment_index.unwrap_or(1);
+    builder
+        .segments
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.name == query.segment)
+        .nth(n.checked_sub(1)?)
+        .map(|(i, _)| i)
+}
+
+/// The query for the segment at the given index within the message
+fn segment_query(builder: &MessageBuilder, index: usize) -> LocationQuery {
+    let name = &builder.segments[index].name;
+    let n = builder.segments[..=index]
+        .iter()
+        .filter(|s| &s.name == name)
+        .count();
+    LocationQuery {
+        segment: name.clone(),
+        segment_index: Some(n),
+        field: None,
+        repeat: None,
+        component: None,
+        subcomponent: None,
+    }
+}
+
+fn segment_mut<'b>(
+    builder: &'b mut MessageBuilder,
+    query: &LocationQuery,
+) -> Result<&'b mut SegmentBuilder, PatchError> {
+    let index =
+        segment_index(builder, query).ok_or_else(|| PatchError::NotFound(query.to_string()))?;
+    Ok(&mut builder.segments[index])
+}
+
+/// Get the raw value at the query, or `None` if there is no value there
+fn get(builder: &MessageBuilder, query: &LocationQuery) -> Result<Option<String>, PatchError> {
+    let level = Level::of(query)?;
+    let separators = &builder.separators;
+    let index =
+        segment_index(builder, query).ok_or_else(|| PatchError::NotFound(query.to_string()))?;
+    let segment = &builder.segments[index];
+    if level == Level::Segment {
+        return Ok(Some(segment.display(separators).to_string()));
+    }
+
+    let (field, rest) = match level {
+        Level::Field(f) => (f, None),
+        Level::Repeat(f, r) => (f, Some((r, None))),
+        Level::Component(f, r, c) => (f, Some((r, Some((c, None))))),
+        Level::Subcomponent(f, r, c, s) => (f, Some((r, Some((c, Some(s)))))),
+        Level::Segment => unreachable!(),
+    };
+    let Some(field) = segment.fields.get(&field) else {
+        return Ok(None);
+    };
+    let Some((repeat, rest)) = rest else {
+        return Ok(Some(field.display(separators).to_string()));
+    };
+    let repeat = match field {
+        FieldBuilder::Value(value) if repeat == 1 => &RepeatBuilder::Value(value.clone()),
+        FieldBuilder::Value(_) => return Ok(None),
+        FieldBuilder::Repeats(repeats) => match repeats.get(repeat - 1) {
+            Some(repeat) => repeat,
+            None => return Ok(None),
+        },
+    };
+    let Some((component, subcomponent)) = rest else {
+        return Ok(Some(repeat.display(separators).to_string()));
+    };
+    let component = match repeat {
+        RepeatBuilder::Value(value) if component == 1 => &ComponentBuilder::Value(value.clone()),
+        RepeatBuilder::Value(_) => return Ok(None),
+        RepeatBuilder::Components(components) => match components.get(&component) {
+            Some(component) => component,
+            None => return Ok(None),
+        },
+    };
+    let Some(subcomponent) = subcomponent else {
+        return Ok(Some(component.display(separators).to_string()));
+    };
+    let value = match component {
+        ComponentBuilder::Value(value) if subcomponent == 1 => value,
+        ComponentBuilder::Value(_) => return Ok(None),
+        ComponentBuilder::Subcomponents(subcomponents) => match subcomponents.get(&subcomponent) {
+            Some(value) => value,
+            None => return Ok(None),
+        },
+    };
+    Ok(Some(separators.encode_keeping_escapes(value).to_string()))
+}
+
+/// Set the raw value at the query, returning the operation which undoes it
+fn set(
+    builder: &mut MessageBuilder,
+    query: &LocationQuery,
+    value: &str,
+) -> Result<Option<Operation>, PatchError> {
+    let level = Level::of(query)?;
+    let separators = builder.separators;
+    validate(query, level, value, &separators)?;
+    let old = get(builder, query)?;
+
+    let segment = segment_mut(builder, query)?;
+    match level {
+        Level::Segment => *segment = segment_builder(value, &separators),
+        Level::Field(f) => {
+            segment.fields.insert(f, field_builder(value, &separators));
+        }
+        Level::Repeat(f, r) => {
+            *repeat_mut(segment, f, r, query)? = repeat_builder(value, &separators);
+        }
+        Level::Component(f, r, c) => {
+            *component_mut(segment, f, r, c, query)? = component_builder(value, &separators);
+        }
+        Level::Subcomponent(f, r, c, s) => {
+            let component = component_mut(segment, f, r, c, query)?;
+            subcomponents_mut(component).insert(s, separators.decode_separators(value));
+        }
+    }
+
+    Ok(Some(match old {
+        Some(value) => Operation::Set {
+            query: query.clone(),
+            value,
+        },
+        None => Operation::Delete {
+            query: query.clone(),
+        },
+    }))
+}
+
+/// Insert a segment or repeat at the query, returning the operation which undoes it
+fn insert(
+    builder: &mut MessageBuilder,
+    query: &LocationQuery,
+    value: &str,
+    position: Option<&SegmentPosition>,
+) -> Result<Operation, PatchError> {
+    let level = Level::of(query)?;
+    let separators = builder.separators;
+    validate(query, level, value, &separators)?;
+    if position.is_some() && level != Level::Segment {
+        return Err(PatchError::NotPositionable(query.to_string()));
+    }
+
+    match level {
+        Level::Segment => {
+            let n = query.segment_index.unwrap_or(1);
+            let count = builder
+                .segments
+                .iter()
+                .filter(|s| s.name == query.segment)
+                .count();
+            if n == 0 || n > count + 1 {
+                return Err(PatchError::NotFound(query.to_string()));
+            }
+            let index = match position {
+                Some(SegmentPosition::Before(anchor)) => segment_index(builder, anchor)
+                    .ok_or_else(|| PatchError::NotFound(anchor.to_string()))?,
+                Some(SegmentPosition::After(anchor)) => {
+                    segment_index(builder, anchor)
+                        .ok_or_else(|| PatchError::NotFound(anchor.to_string()))?
+                        + 1
+                }
+                None => match segment_index(builder, query) {
+                    Some(index) => index,
+                    None if count > 0 => {
+                        let last = LocationQuery {
+                            segment_index: Some(count),
+                            ..query.clone()
+                        };
+                        segment_index(builder, &last).unwrap_or_default() + 1
+                    }
+                    None => builder.segments.len(),
+                },
+            };
+            builder
+                .segments
+                .insert(index, segment_builder(value, &separators));
+            // the segment lands next to its anchor, which may not be where the query points
+            return Ok(Operation::Delete {
+                query: segment_query(builder, index),
+            });
+        }
+        Level::Repeat(f, r) => {
+            let segment = segment_mut(builder, query)?;
+            let repeats = repeats_mut(segment.fields.entry(f).or_default());
+            if r == 0 || r > repeats.len() + 1 {
+                return Err(PatchError::NotFound(query.to_string()));
+            }
+            repeats.insert(r - 1, repeat_builder(value, &separators));
+        }
+        _ => return Err(PatchError::NotInsertable(query.to_string())),
+    }
+    Ok(Operation::Delete {
+        query: query.clone(),
+    })
+}
+
+/// Delete the value at the query, returning the operation which undoes it
+fn delete(
+    builder: &mut MessageBuilder,
+    query: &LocationQuery,
+) -> Result<Option<Operation>, PatchError> {
+    let level = Level::of(query)?;
+    let Some(old) = get(builder, query)? else {
+        return match level {
+            Level::Repeat(..) => Err(PatchError::NotFound(query.to_string())),
+            _ => Ok(None),
+        };
+    };
+    let separators = builder.separators;
+
+    if level == Level::Segment {
+        let index =
+            segment_index(builder, query).ok_or_else(|| PatchError::NotFound(query.to_string()))?;
+        builder.segments.remove(index);
+        let position = if index < builder.segments.len() {
+            Some(SegmentPosition::Before(segment_query(builder, index)))
+        } else if index > 0 {
+            Some(SegmentPosition::After(segment_query(builder, index - 1)))
+        } else {
+            None
+        };
+        return Ok(Some(Operation::Insert {
+            query: query.clone(),
+            value: old,
+            position,
+        }));
+    }
+
+    let segment = segment_mut(builder, query)?;
+    let inverse = match level {
+        Level::Field(f) => {
+            segment.fields.remove(&f);
+            Operation::Set {
+                query: query.clone(),
+                value: old,
+            }
+        }
+        Level::Repeat(f, r) => {
+            repeats_mut(segment.fields.entry(f).or_default()).remove(r - 1);
+            Operation::Insert {
+                query: query.clone(),
+                value: old,
+                position: None,
+            }
+        }
+        Level::Component(f, r, c) => {
+            components_mut(repeat_mut(segment, f, r, query)?).remove(&c);
+            Operation::Set {
+                query: query.clone(),
+                value: old,
+            }
+        }
+        Level::Subcomponent(f, r, c, s) => {
+            subcomponents_mut(component_mut(segment, f, r, c, query)?).remove(&s);
+            Operation::Set {
+                query: query.clone(),
+                value: old,
+            }
+        }
+        Level::Segment => unreachable!(),
+    };
+
+    // clearing the last value within a field removes the field, along with any empty fields at
+    // the end of the segment, so that deleting doesn't leave trailing field separators behind
+    let is_blank = |f: &FieldBuilder| f.display(&separators).to_string().is_empty();
+    let field = query.field.unwrap_or_default();
+    if segment.fields.get(&field).is_some_and(is_blank) {
+        segment.fields.remove(&field);
+    }
+    while let Some(last) = segment.fields.keys().max().copied() {
+        if !segment.fields.get(&last).is_some_and(is_blank) {
+            break;
+        }
+        segment.fields.remove(&last);
+    }
+    Ok(Some(inverse))
+}
+
+/// Check that a value can be used at the query's level
+fn validate(
+    query: &LocationQuery,
+    level: Level,
+    value: &str,
+    separators: &Separators,
+) -> Result<(), PatchError> {
+    let invalid = || PatchError::InvalidValue {
+        query: query.to_string(),
+        value: value.to_string(),
+    };
+    if level == Level::Segment {
+        let name = value.split(separators.field).next().unwrap_or_default();
+        if name != query.segment {
+            return Err(invalid());
+        }
+    }
+    if value.contains(level.forbidden(separators).as_slice()) {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+fn repeats_mut(field: &mut FieldBuilder) -> &mut Vec<RepeatBuilder> {
+    if let FieldBuilder::Value(value) = field {
+        let repeats = if value.is_empty() {
+            Vec::new()
+        } else {
+            vec![RepeatBuilder::Value(std::mem::take(value))]
+        };
+        *field = FieldBuilder::Repeats(repeats);
+    }
+    match field {
+        FieldBuilder::Repeats(repeats) => repeats,
+        FieldBuilder::Value(_) => unreachable!(),
+    }
+}
+
+fn components_mut(repeat: &mut RepeatBuilder) -> &mut HashMap<usize, ComponentBuilder> {
+    if let RepeatBuilder::Value(value) = repeat {
+        let mut components = HashMap::new();
+        if !value.is_empty() {
+            components.insert(1, ComponentBuilder::Value(std::mem::take(value)));
+        }
+        *repeat = RepeatBuilder::Components(components);
+    }
+    match repeat {
+        RepeatBuilder::Components(components) => components,
+        RepeatBuilder::Value(_) => unreachable!(),
+    }
+}
+
+fn subcomponents_mut(component: &mut ComponentBuilder) -> &mut HashMap<usize, String> {
+    if let ComponentBuilder::Value(value) = component {
+        let mut subcomponents = HashMap::new();
+        if !value.is_empty() {
+            subcomponents.insert(1, std::mem::take(value));
+        }
+        *component = ComponentBuilder::Subcomponents(subcomponents);
+    }
+    match component {
+        ComponentBuilder::Subcomponents(subcomponents) => subcomponents,
+        ComponentBuilder::Value(_) => unreachable!(),
+    }
+}
+
+/// Get the repeat at the given field and repeat numbers, creating it if it is just past the
+/// end of the field
+fn repeat_mut<'s>(
+    segment: &'s mut SegmentBuilder,
+    field: usize,
+    repeat: usize,
+    query: &LocationQuery,
+) -> Result<&'s mut RepeatBuilder, PatchError> {
+    let repeats = repeats_mut(segment.fields.entry(field).or_default());
+    if repeat == 0 || repeat > repeats.len() + 1 {
+        return Err(PatchError::NotFound(query.to_string()));
+    }
+    if repeat > repeats.len() {
+        repeats.push(RepeatBuilder::default());
+    }
+    Ok(&mut repeats[repeat - 1])
+}
+
+fn component_mut<'s>(
+    segment: &'s mut SegmentBuilder,
+    field: usize,
+    repeat: usize,
+    component: usize,
+    query: &LocationQuery,
+) -> Result<&'s mut ComponentBuilder, PatchError> {
+    let repeat = repeat_mut(segment, field, repeat, query)?;
+    Ok(components_mut(repeat).entry(component).or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Message;
+    use pretty_assertions_sorted::assert_eq;
+
+    const MESSAGE: &str = "MSH|^~\\&|\rPID|1||123^^^MRN||Doe^John\rNTE|1||first\rPV1|1|I|W^101^A";
+
+    fn query(query: &str) -> LocationQuery {
+        LocationQuery::parse(query).unwrap()
+    }
+
+    /// Apply a patch, checking the result and that the inverse restores the message
+    fn patched(patch: Patch) -> String {
+        let message = Message::parse(MESSAGE).unwrap();
+        let mut builder = MessageBuilder::from(&message);
+        let inverse = patch.apply(&mut builder).expect("Can apply the patch");
+        let result = builder.to_string();
+        inverse
+            .apply(&mut builder)
+            .expect("Can apply the inverse patch");
+        assert_eq!(builder.to_string(), MESSAGE, "inverse of {patch:?}");
+        result
+    }
+
+    #[test]
+    fn sets_values_at_each_level() {
+        assert_eq!(
+            patched(Patch::new().with_set(query("PID.5"), "Smith^Jane")),
+            "MSH|^~\\&|\rPID|1||123^^^MRN||Smith^Jane\rNTE|1||first\rPV1|1|I|W^101^A"
+        );
+        assert_eq!(
+            patched(Patch::new().with_set(query("PID.3[2]"), "456^^^SSN")),
+            "MSH|^~\\&|\rPID|1||123^^^MRN~456^^^SSN||Doe^John\rNTE|1||first\rPV1|1|I|W^101^A"
+        );
+        assert_eq!(
+            patched(Patch::new().with_set(query("PID.5.3"), "Q")),
+            "MSH|^~\\&|\rPID|1||123^^^MRN||Doe^John^Q\rNTE|1||first\rPV1|1|I|W^101^A"
+        );
+        assert_eq!(
+            patched(Patch::new().with_set(query("PID.3.4.2"), "1.2.3")),
+            "MSH|^~\\&|\rPID|1||123^^^MRN&1.2.3||Doe^John\rNTE|1||first\rPV1|1|I|W^101^A"
+        );
+        assert_eq!(
+            patched(Patch::new().with_set(query("PID.19"), "555-12-3456")),
+            "MSH|^~\\&|\rPID|1||123^^^MRN||Doe^John||||||||||||||555-12-3456\rNTE|1||first\rPV1|1|I|W^101^A"
+        );
+        assert_eq!(
+            patched(Patch::new().with_set(query("NTE"), "NTE|1||replaced")),
+            "MSH|^~\\&|\rPID|1||123^^^MRN||Doe^John\rNTE|1||replaced\rPV1|1|I|W^101^A"
+        );
+    }
+
+    #[test]
+    fn escaped_values_are_decoded() {
+        let message = Message::parse(MESSAGE).unwrap();
+        let mut builder = MessageBuilder::from(&message);
+        Patch::new()
+            .with_set(query("NTE.3"), "A\\T\\B")
+            .apply(&mut builder)
+            .unwrap();
+        assert_eq!(
+            builder
+                .segment_named("NTE")
+                .unwrap()
+                .field(3)
+                .unwrap()
+                .value(),
+            Some(&"A&B".to_string())
+        );
+        assert_eq!(get(&builder, &query("NTE.3")).unwrap().unwrap(), "A\\T\\B");
+    }
+
+    #[test]
+    fn inserts_and_deletes_segments_and_repeats() {
+        assert_eq!(
+            patched(Patch::new().with_insert(query("NTE[2]"), "NTE|2||second")),
+            "MSH|^~\\&|\rPID|1||123^^^MRN||Doe^John\rNTE|1||first\rNTE|2||second\rPV1|1|I|W^101^A"
+        );
+        assert_eq!(
+            patched(Patch::new().with_insert(query("NTE"), "NTE|0||zeroth")),
+            "MSH|^~\\&|\rPID|1||123^^^MRN||Doe^John\rNTE|0||zeroth\rNTE|1||first\rPV1|1|I|W^101^A"
+        );
+        assert_eq!(
+            patched(Patch::new().with_insert_at(
+                query("PD1"),
+                "PD1|||Clinic",
+                SegmentPosition::After(query("PID"))
+            )),
+            "MSH|^~\\&|\rPID|1||123^^^MRN||Doe^John\rPD1|||Clinic\rNTE|1||first\rPV1|1|I|W^101^A"
+        );
+        assert_eq!(
+            patched(Patch::new().with_insert_at(
+                query("NTE"),
+                "NTE|9||new",
+                SegmentPosition::After(query("PV1"))
+            )),
+            "MSH|^~\\&|\rPID|1||123^^^MRN||Doe^John\rNTE|1||first\rPV1|1|I|W^101^A\rNTE|9||new"
+        );
+        assert_eq!(
+            patched(Patch::new().with_insert(query("ZZZ"), "ZZZ|1")),
+            "MSH|^~\\&|\rPID|1||123^^^MRN||Doe^John\rNTE|1||first\rPV1|1|I|W^101^A\rZZZ|1"
+        );
+        assert_eq!(
+            patched(Patch::new().with_insert(query("PID.3[1]"), "456^^^SSN")),
+            "MSH|^~\\&|\rPID|1||456^^^SSN~123^^^MRN||Doe^John\rNTE|1||first\rPV1|1|I|W^101^A"
+        );
+        assert_eq!(
+            patched(Patch::new().with_delete(query("NTE"))),
+            "MSH|^~\\&|\rPID|1||123^^^MRN||Doe^John\rPV1|1|I|W^101^A"
+        );
+        assert_eq!(
+            patched(Patch::new().with_delete(query("PV1"))),
+            "MSH|^~\\&|\rPID|1||123^^^MRN||Doe^John\rNTE|1||first"
+        );
+        assert_eq!(
+            patched(Patch::new().with_delete(query("PID.3[1]"))),
+            "MSH|^~\\&|\rPID|1||||Doe^John\rNTE|1||first\rPV1|1|I|W^101^A"
+        );
+    }
+
+    #[test]
+    fn undoes_anchored_inserts() {
+        let source = "MSH|^~\\&|\rNTE|1|first\rPID|1\rNTE|2|second";
+        let message = Message::parse(source).unwrap();
+        let mut builder = MessageBuilder::from(&message);
+        let undo = Patch::new()
+            .with_insert_at(
+                query("NTE"),
+                "NTE|9|new",
+                SegmentPosition::After(query("PID")),
+            )
+            .apply(&mut builder)
+            .unwrap();
+        assert_eq!(
+            builder.to_string(),
+            "MSH|^~\\&|\rNTE|1|first\rPID|1\rNTE|9|new\rNTE|2|second"
+        );
+        assert_eq!(undo, Patch::new().with_delete(query("NTE[2]")));
+        undo.apply(&mut builder).unwrap();
+        assert_eq!(builder.to_string(), source);
+    }
+
+    #[test]
+    fn deletes_clear_fields_and_components() {
+        assert_eq!(
+            patched(Patch::new().with_delete(query("PID.3"))),
+            "MSH|^~\\&|\rPID|1||||Doe^John\rNTE|1||first\rPV1|1|I|W^101^A"
+        );
+        assert_eq!(
+            patched(Patch::new().with_delete(query("PID.5.1"))),
+            "MSH|^~\\&|\rPID|1||123^^^MRN||^John\rNTE|1||first\rPV1|1|I|W^101^A"
+        );
+        assert_eq!(patched(Patch::new().with_delete(query("PID.19"))), MESSAGE);
+    }
+
+    #[test]
+    fn moves_values() {
+        assert_eq!(
+            patched(Patch::new().with_move(query("PV1.3.1"), query("PV1.3.4"))),
+            "MSH|^~\\&|\rPID|1||123^^^MRN||Doe^John\rNTE|1||first\rPV1|1|I|^101^A^W"
+        );
+        assert_eq!(
+            patched(Patch::new().with_move(query("PID.5"), query("NTE.3"))),
+            "MSH|^~\\&|\rPID|1||123^^^MRN\rNTE|1||Doe^John\rPV1|1|I|W^101^A"
+        );
+    }
+
+    #[test]
+    fn applies_operations_in_order() {
+        assert_eq!(
+            patched(
+                Patch::new()
+                    .with_insert(query("NTE[2]"), "NTE|2||second")
+                    .with_set(query("NTE[2].3"), "changed")
+                    .with_delete(query("NTE[1]"))
+            ),
+            "MSH|^~\\&|\rPID|1||123^^^MRN||Doe^John\rNTE|2||changed\rPV1|1|I|W^101^A"
+        );
+    }
+
+    #[test]
+    fn failed_patches_leave_the_builder_unchanged() {
+        let message = Message::parse(MESSAGE).unwrap();
+        let mut builder = MessageBuilder::from(&message);
+        let patch = Patch::new()
+            .with_set(query("PID.5"), "Smith")
+            .with_delete(query("OBX"));
+        assert_eq!(
+            patch.apply(&mut builder),
+            Err(PatchError::NotFound("OBX".to_string()))
+        );
+        assert_eq!(builder.to_string(), MESSAGE);
+    }
+
+    #[test]
+    fn rejects_invalid_operations() {
+        let message = Message::parse(MESSAGE).unwrap();
+        let mut builder = MessageBuilder::from(&message);
+        let mut apply = |patch: Patch| patch.apply(&mut builder).unwrap_err();
+        assert_eq!(
+            apply(Patch::new().with_set(query("PID.5.1"), "Doe^John")),
+            PatchError::InvalidValue {
+                query: "PID.5.1".to_string(),
+                value: "Doe^John".to_string()
+            }
+        );
+        assert_eq!(
+            apply(Patch::new().with_insert(query("NTE"), "OBX|1")),
+            PatchError::InvalidValue {
+                query: "NTE".to_string(),
+                value: "OBX|1".to_string()
+            }
+        );
+        assert_eq!(
+            apply(Patch::new().with_insert(query("PID.5"), "Doe")),
+            PatchError::NotInsertable("PID.5".to_string())
+        );
+        assert_eq!(
+            apply(Patch::new().with_insert(query("NTE[3]"), "NTE|3")),
+            PatchError::NotFound("NTE[3]".to_string())
+        );
+        assert_eq!(
+            apply(Patch::new().with_set(query("MSH.2"), "^~\\&#")),
+            PatchError::EncodingCharacters("MSH.2".to_string())
+        );
+        assert_eq!(
+            apply(Patch::new().with_set(query("PID.3[3]"), "789")),
+            PatchError::NotFound("PID.3[3]".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_zero_indices() {
+        let message = Message::parse(MESSAGE).unwrap();
+        let mut builder = MessageBuilder::from(&message);
+        let zero = |query: LocationQuery| {
+            let expected = PatchError::InvalidIndex(query.to_string());
+            (query, expected)
+        };
+        for (query, expected) in [
+            zero(LocationQuery {
+                repeat: Some(0),
+                ..query("PID.3")
+            }),
+            zero(LocationQuery {
+                component: Some(0),
+                ..query("PID.5")
+            }),
+            zero(LocationQuery {
+                field: Some(0),
+                ..query("PID")
+            }),
+        ] {
+            for patch in [
+                Patch::new().with_set(query.clone(), "x"),
+                Patch::new().with_insert(query.clone(), "x"),
+                Patch::new().with_delete(query.clone()),
+                Patch::new().with_move(query.clone(), self::query("NTE.3")),
+                Patch::new().with_move(self::query("NTE.3"), query.clone()),
+            ] {
+                assert_eq!(patch.apply(&mut builder), Err(expected.clone()));
+            }
+        }
+        assert_eq!(builder.to_string(), MESSAGE);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_patches() {
+        let patch = Patch::new()
+            .with_set(query("PID.19"), "")
+            .with_insert_at(
+                query("PD1"),
+                "PD1|||Clinic",
+                SegmentPosition::After(query("PID")),
+            )
+            .with_delete(query("NTE[2]"))
+            .with_move(query("PV1.3.1"), query("PV1.3.4"));
+        let json = serde_json::json!([
+            { "op": "set", "query": "PID.19", "value": "" },
+            { "op": "insert", "query": "PD1", "value": "PD1|||Clinic", "position": { "after": "PID" } },
+            { "op": "delete", "query": "NTE[2]" },
+            { "op": "move", "from": "PV1.3.1", "to": "PV1.3.4" },
+        ]);
+        assert_eq!(serde_json::to_value(&patch).unwrap(), json);
+        assert_eq!(serde_json::from_value::<Patch>(json).unwrap(), patch);
+    }
+}
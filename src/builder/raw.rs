@@ -0,0 +1,64 @@
+//! Conversions from raw HL7 values, encoded with a message's separators, into builders holding
+//! values with their escaped separators decoded.
+
+use super::{ComponentBuilder, FieldBuilder, RepeatBuilder, SegmentBuilder};
+use crate::message::Separators;
+
+/// Build a segment from its raw value, including the segment name
+pub(crate) fn segment_builder(value: &str, separators: &Separators) -> SegmentBuilder {
+    let mut parts = value.split(separators.field);
+    let mut segment = SegmentBuilder::new(parts.next().unwrap_or_default());
+    segment.fields = parts
+        .enumerate()
+        .map(|(i, field)| (i + 1, field_builder(field, separators)))
+        .collect();
+    segment
+}
+
+/// Build a field from its raw value
+pub(crate) fn field_builder(value: &str, separators: &Separators) -> FieldBuilder {
+    if value.contains([
+        separators.repetition,
+        separators.component,
+        separators.subcomponent,
+    ]) {
+        FieldBuilder::Repeats(
+            value
+                .split(separators.repetition)
+                .map(|repeat| repeat_builder(repeat, separators))
+                .collect(),
+        )
+    } else {
+        FieldBuilder::Value(separators.decode_separators(value))
+    }
+}
+
+/// Build a repeat from its raw value
+pub(crate) fn repeat_builder(value: &str, separators: &Separators) -> RepeatBuilder {
+    if value.contains([separators.component, separators.subcomponent]) {
+        RepeatBuilder::Components(
+            value
+                .split(separators.component)
+                .enumerate()
+                .map(|(i, component)| (i + 1, component_builder(component, separators)))
+                .collect(),
+        )
+    } else {
+        RepeatBuilder::Value(separators.decode_separators(value))
+    }
+}
+
+/// Build a component from its raw value
+pub(crate) fn component_builder(value: &str, separators: &Separators) -> ComponentBuilder {
+    if value.contains(separators.subcomponent) {
+        ComponentBuilder::Subcomponents(
+            value
+                .split(separators.subcomponent)
+                .enumerate()
+                .map(|(i, subcomponent)| (i + 1, separators.decode_separators(subcomponent)))
+                .collect(),
+        )
+    } else {
+        ComponentBuilder::Value(separators.decode_separators(value))
+    }
+}
@@ -182,7 +182,7 @@ mod display {
     impl<'a> Display for RepeatBuilderDisplay<'a> {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             match self.repeat {
-                RepeatBuilder::Value(value) => self.separators.encode(value).fmt(f),
+                RepeatBuilder::Value(value) => self.separators.encode_keeping_escapes(value).fmt(f),
                 RepeatBuilder::Components(components) => {
                     if components.is_empty() {
                         return Ok(());
@@ -209,22 +209,41 @@ impl<S: ToString> From<S> for RepeatBuilder {
     }
 }
 
-impl<'m> From<&'m Repeat<'m>> for RepeatBuilder {
-    fn from(repeat: &'m Repeat<'m>) -> Self {
-        if repeat.has_components() {
+impl RepeatBuilder {
+    /// Convert a parsed repeat into a builder, decoding the escape sequences for separators
+    /// with the separators of the message it came from so that rendering the builder encodes
+    /// them again. Other escape sequences, such as `\H\` or `\X0D\`, are kept as they are.
+    pub fn from_repeat(repeat: &Repeat, separators: &Separators) -> Self {
+        RepeatBuilder::from_parsed(repeat, &|value| separators.decode_separators(value))
+    }
+
+    /// Convert a parsed repeat into a builder, using `value` to convert each value
+    pub(super) fn from_parsed(repeat: &Repeat, value: &dyn Fn(&str) -> String) -> Self {
+        if repeat.has_components()
+            || (!repeat.components.is_empty() && repeat.components[0].has_subcomponents())
+        {
             let components = repeat
                 .components
                 .iter()
                 .enumerate()
-                .map(|(i, c)| (i + 1, c.into()))
+                .map(|(i, c)| (i + 1, ComponentBuilder::from_parsed(c, value)))
                 .collect();
             RepeatBuilder::Components(components)
         } else {
-            RepeatBuilder::Value(repeat.raw_value().to_string())
+            RepeatBuilder::Value(value(repeat.raw_value()))
         }
     }
 }
 
+/// Convert a parsed repeat into a builder, keeping its values as they appear in the message,
+/// escape sequences included. Use [`RepeatBuilder::from_repeat`] to decode them, so that
+/// rendering the builder doesn't encode them a second time.
+impl<'m> From<&'m Repeat<'m>> for RepeatBuilder {
+    fn from(repeat: &'m Repeat<'m>) -> Self {
+        RepeatBuilder::from_parsed(repeat, &str::to_string)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
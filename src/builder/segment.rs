@@ -1,6 +1,6 @@
 use display::SegmentBuilderDisplay;
 
-use crate::message::Segment;
+use crate::message::{Segment, Separators};
 use std::{collections::HashMap, fmt::Display};
 
 use super::FieldBuilder;
@@ -156,23 +156,47 @@ mod display {
     }
 }
 
-impl<'m> From<&'m Segment<'m>> for SegmentBuilder {
-    fn from(segment: &'m Segment) -> Self {
+impl SegmentBuilder {
+    /// Convert a parsed segment into a builder, decoding the escape sequences for separators
+    /// with the separators of the message it came from so that rendering the builder encodes
+    /// them again. Other escape sequences, such as `\H\` or `\X0D\`, are kept as they are.
+    /// `MSH.1` and `MSH.2` are the separators themselves, and aren't decoded.
+    pub fn from_segment(segment: &Segment, separators: &Separators) -> Self {
+        SegmentBuilder::from_parsed(segment, &|value| separators.decode_separators(value))
+    }
+
+    /// Convert a parsed segment into a builder, using `value` to convert each value other than
+    /// `MSH.1` and `MSH.2`
+    fn from_parsed(segment: &Segment, value: &dyn Fn(&str) -> String) -> Self {
         let mut builder = SegmentBuilder::new(segment.name);
         builder.fields = segment
             .fields
             .iter()
             .enumerate()
-            .map(|(index, field)| (index + 1, field.into()))
+            .map(|(index, field)| {
+                let field = if segment.name == "MSH" && index < 2 {
+                    FieldBuilder::Value(field.raw_value().to_string())
+                } else {
+                    FieldBuilder::from_parsed(field, value)
+                };
+                (index + 1, field)
+            })
             .collect();
         builder
     }
 }
 
+/// Convert a parsed segment into a builder, keeping its values as they appear in the message,
+/// escape sequences included. Use [`SegmentBuilder::from_segment`] to decode them, so that
+/// rendering the builder doesn't encode them a second time.
+impl<'m> From<&'m Segment<'m>> for SegmentBuilder {
+    fn from(segment: &'m Segment) -> Self {
+        SegmentBuilder::from_parsed(segment, &str::to_string)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::message::Separators;
-
     use super::*;
     use pretty_assertions_sorted::assert_eq;
 
@@ -195,4 +219,22 @@ mod tests {
         let display = builder.display(&separators).to_string();
         assert_eq!(display, r#"PID|1|2|3"#);
     }
+
+    #[test]
+    fn decodes_escape_sequences_when_converting() {
+        let message = crate::Message::parse("MSH#^~!&#A!T!B\rZZZ#C!F!D").unwrap();
+        let msh =
+            SegmentBuilder::from_segment(message.segment("MSH").unwrap(), &message.separators);
+        assert_eq!(msh.field(1).unwrap().value().unwrap(), "#");
+        assert_eq!(msh.field(2).unwrap().value().unwrap(), "^~!&");
+        assert_eq!(msh.field(3).unwrap().value().unwrap(), "A&B");
+        let zzz =
+            SegmentBuilder::from_segment(message.segment("ZZZ").unwrap(), &message.separators);
+        assert_eq!(zzz.field(1).unwrap().value().unwrap(), "C#D");
+        assert_eq!(zzz.display(&message.separators).to_string(), "ZZZ#C!F!D");
+
+        let segment = crate::parser::parse_segment(r"ZZZ|A\F\B").unwrap();
+        let builder = SegmentBuilder::from(&segment);
+        assert_eq!(builder.field(1).unwrap().value().unwrap(), r"A\F\B");
+    }
 }
@@ -0,0 +1,428 @@
+use super::{
+    parse_timestamp, Date, DateTimeParseError, ErroredDateTimeComponent, Time, TimeStamp,
+    TimeStampOffset,
+};
+
+/// Whether a year is a leap year in the proleptic Gregorian calendar
+fn is_leap_year(year: u16) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+/// The number of days in a month of a year, or `None` if the month is out of range
+pub(crate) fn days_in_month(year: u16, month: u8) -> Option<u8> {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => Some(31),
+        4 | 6 | 9 | 11 => Some(30),
+        2 if is_leap_year(year) => Some(29),
+        2 => Some(28),
+        _ => None,
+    }
+}
+
+/// The number of days from 1970-01-01 to a date in the proleptic Gregorian calendar. Out of
+/// range months and days aren't rejected, they just give meaningless results.
+pub(crate) fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month as i64 + 9) % 12) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The date in the proleptic Gregorian calendar which is a number of days from 1970-01-01, as
+/// a year, month, and day
+#[cfg(feature = "anonymize")]
+pub(crate) fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn check_range(
+    component: ErroredDateTimeComponent,
+    value: Option<u32>,
+    min: u32,
+    max: u32,
+) -> Result<(), DateTimeParseError> {
+    match value {
+        Some(value) if value < min || value > max => Err(DateTimeParseError::OutOfRange {
+            component,
+            value,
+            min,
+            max,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Check that no part is set after a missing one, i.e. a day without a month
+fn check_sequence(parts: &[(ErroredDateTimeComponent, bool)]) -> Result<(), DateTimeParseError> {
+    let mut missing = None;
+    for (component, is_set) in parts {
+        match (is_set, missing) {
+            (false, None) => missing = Some(*component),
+            (true, Some(missing)) => return Err(DateTimeParseError::MissingComponent(missing)),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn validate_date(year: u16, month: Option<u8>, day: Option<u8>) -> Result<(), DateTimeParseError> {
+    check_range(ErroredDateTimeComponent::Year, Some(year as u32), 0, 9999)?;
+    check_range(ErroredDateTimeComponent::Month, month.map(u32::from), 1, 12)?;
+    if let (Some(month), Some(day)) = (month, day) {
+        let days_in_month = days_in_month(year, month).unwrap_or_default();
+        if day == 0 || day > days_in_month {
+            return Err(DateTimeParseError::DayOutOfRange {
+                year,
+                month,
+                day,
+                days_in_month,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn validate_time(
+    hour: Option<u8>,
+    minute: Option<u8>,
+    second: Option<u8>,
+    microsecond: Option<u32>,
+    offset: Option<TimeStampOffset>,
+) -> Result<(), DateTimeParseError> {
+    check_range(ErroredDateTimeComponent::Hour, hour.map(u32::from), 0, 23)?;
+    check_range(
+        ErroredDateTimeComponent::Minute,
+        minute.map(u32::from),
+        0,
+        59,
+    )?;
+    // 60 is a leap second
+    check_range(
+        ErroredDateTimeComponent::Second,
+        second.map(u32::from),
+        0,
+        60,
+    )?;
+    check_range(
+        ErroredDateTimeComponent::Microsecond,
+        microsecond,
+        0,
+        999_999,
+    )?;
+    if let Some(offset) = offset {
+        let minutes = offset.hours.unsigned_abs() as u32 * 60 + offset.minutes as u32;
+        let valid = offset.minutes < 60
+            && if offset.hours < 0 {
+                minutes <= 12 * 60
+            } else {
+                minutes <= 14 * 60
+            };
+        if !valid {
+            return Err(DateTimeParseError::OffsetOutOfRange(offset));
+        }
+    }
+    Ok(())
+}
+
+impl TimeStamp {
+    /// Check that the timestamp is a real date and time: the month is 1-12, the day exists in
+    /// that month (accounting for leap years), the hour is 0-23, the minute is 0-59, the second
+    /// is 0-60 (allowing for leap seconds), and the offset is between `-1200` and `+1400`. Parts
+    /// set after a missing part, such as a day without a month, are also rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hl7_parser::datetime::{DateTimeParseError, TimeStamp};
+    ///
+    /// let ts: TimeStamp = "20240229".parse().unwrap();
+    /// assert!(ts.validate().is_ok());
+    ///
+    /// let ts: TimeStamp = "20230229".parse().unwrap();
+    /// assert!(matches!(
+    ///     ts.validate(),
+    ///     Err(DateTimeParseError::DayOutOfRange { day: 29, days_in_month: 28, .. })
+    /// ));
+    /// ```
+    pub fn validate(&self) -> Result<(), DateTimeParseError> {
+        use ErroredDateTimeComponent as C;
+        check_sequence(&[
+            (C::Month, self.month.is_some()),
+            (C::Day, self.day.is_some()),
+            (C::Hour, self.hour.is_some()),
+            (C::Minute, self.minute.is_some()),
+            (C::Second, self.second.is_some()),
+            (C::Microsecond, self.microsecond.is_some()),
+        ])?;
+        validate_date(self.year, self.month, self.day)?;
+        validate_time(
+            self.hour,
+            self.minute,
+            self.second,
+            self.microsecond,
+            self.offset,
+        )
+    }
+
+    /// Fix a time of `24:00`, which some senders use for midnight at the end of a day, by
+    /// moving it to `00:00` on the next day. Other timestamps are returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hl7_parser::datetime::TimeStamp;
+    ///
+    /// let ts: TimeStamp = "202312312400".parse().unwrap();
+    /// assert_eq!(ts.normalize().to_string(), "202401010000");
+    /// ```
+    pub fn normalize(self) -> TimeStamp {
+        let is_midnight = self.hour == Some(24)
+            && self.minute.unwrap_or_default() == 0
+            && self.second.unwrap_or_default() == 0
+            && self.microsecond.unwrap_or_default() == 0;
+        let (Some(month), Some(day), true) = (self.month, self.day, is_midnight) else {
+            return self;
+        };
+        let Some(days_in_month) = days_in_month(self.year, month).filter(|d| day <= *d) else {
+            return self;
+        };
+        let (year, month, day) = if day < days_in_month {
+            (self.year, month, day + 1)
+        } else if month < 12 {
+            (self.year, month + 1, 1)
+        } else {
+            (self.year + 1, 1, 1)
+        };
+        TimeStamp {
+            year,
+            month: Some(month),
+            day: Some(day),
+            hour: Some(0),
+            ..self
+        }
+    }
+}
+
+impl Date {
+    /// Check that the date is a real date: the month is 1-12 and the day exists in that month,
+    /// accounting for leap years
+    pub fn validate(&self) -> Result<(), DateTimeParseError> {
+        check_sequence(&[
+            (ErroredDateTimeComponent::Month, self.month.is_some()),
+            (ErroredDateTimeComponent::Day, self.day.is_some()),
+        ])?;
+        validate_date(self.year, self.month, self.day)
+    }
+}
+
+impl Time {
+    /// Check that the time is a real time: the hour is 0-23, the minute is 0-59, the second is
+    /// 0-60 (allowing for leap seconds), and the offset is between `-1200` and `+1400`
+    pub fn validate(&self) -> Result<(), DateTimeParseError> {
+        use ErroredDateTimeComponent as C;
+        check_sequence(&[
+            (C::Minute, self.minute.is_some()),
+            (C::Second, self.second.is_some()),
+            (C::Microsecond, self.microsecond.is_some()),
+        ])?;
+        validate_time(
+            Some(self.hour),
+            self.minute,
+            self.second,
+            self.microsecond,
+            self.offset,
+        )
+    }
+}
+
+/// Parse an HL7 timestamp strictly, without allowing trailing characters, and check that it is
+/// a real date and time with [`TimeStamp::validate`]
+///
+/// # Example
+///
+/// ```
+/// use hl7_parser::datetime::{parse_timestamp_validated, DateTimeParseError};
+///
+/// assert!(parse_timestamp_validated("20230312195905-0700").is_ok());
+/// assert!(matches!(
+///     parse_timestamp_validated("2023031225"),
+///     Err(DateTimeParseError::OutOfRange { value: 25, .. })
+/// ));
+/// ```
+pub fn parse_timestamp_validated(s: &str) -> Result<TimeStamp, DateTimeParseError> {
+    let timestamp = parse_timestamp(s, false)?;
+    timestamp.validate()?;
+    Ok(timestamp)
+}
+
+/// Parse an HL7 timestamp, fixing common mistakes made by senders, and check that it is a real
+/// date and time. The following are accepted:
+///
+/// * surrounding whitespace
+/// * a trailing `Z` for UTC, which becomes `+0000`
+/// * offsets with only hours (`-07`) or with a colon (`-07:00`)
+/// * more than four digits of fractional seconds, which are truncated
+/// * a time of `24:00`, which becomes `00:00` on the next day (see [`TimeStamp::normalize`])
+///
+/// # Example
+///
+/// ```
+/// use hl7_parser::datetime::parse_timestamp_normalized;
+///
+/// let ts = parse_timestamp_normalized(" 20231231240000Z ").unwrap();
+/// assert_eq!(ts.to_string(), "20240101000000+0000");
+/// let ts = parse_timestamp_normalized("20230312195905.123456").unwrap();
+/// assert_eq!(ts.to_string(), "20230312195905.1234");
+/// let ts = parse_timestamp_normalized("202303121959-07:00").unwrap();
+/// assert_eq!(ts.to_string(), "202303121959-0700");
+/// ```
+pub fn parse_timestamp_normalized(s: &str) -> Result<TimeStamp, DateTimeParseError> {
+    let s = s.trim();
+    let (s, offset) = match s.strip_suffix(['Z', 'z']) {
+        Some(s) => (s, "+0000".to_string()),
+        None => match s.rfind(['+', '-']) {
+            Some(at) => {
+                let offset = s[at..].replacen(':', "", 1);
+                let offset = if offset.len() == 3 {
+                    offset + "00"
+                } else {
+                    offset
+                };
+                (&s[..at], offset)
+            }
+            None => (s, String::new()),
+        },
+    };
+    let s = match s.split_once('.') {
+        Some((whole, fraction)) if fraction.len() > 4 && fraction.is_char_boundary(4) => {
+            format!("{whole}.{}", &fraction[..4])
+        }
+        _ => s.to_string(),
+    };
+    let timestamp = parse_timestamp(&format!("{s}{offset}"), false)?.normalize();
+    timestamp.validate()?;
+    Ok(timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    fn ts(s: &str) -> TimeStamp {
+        s.parse().expect("can parse timestamp")
+    }
+
+    #[cfg(feature = "anonymize")]
+    #[test]
+    fn converts_between_days_and_dates() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(civil_from_days(11017), (2000, 3, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        for days in -800_000..800_000 {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn validates_calendar_dates() {
+        assert!(ts("20000229").validate().is_ok());
+        assert!(ts("19000229").validate().is_err());
+        assert!(ts("20230431").validate().is_err());
+        assert!(ts("20231231").validate().is_ok());
+        assert!(matches!(
+            ts("202313").validate(),
+            Err(DateTimeParseError::OutOfRange {
+                component: ErroredDateTimeComponent::Month,
+                value: 13,
+                ..
+            })
+        ));
+        assert!(matches!(
+            ts("20230100").validate(),
+            Err(DateTimeParseError::DayOutOfRange { day: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn validates_times() {
+        assert!(ts("20161231235960").validate().is_ok());
+        assert!(ts("20230312235961").validate().is_err());
+        assert!(ts("202303122360").validate().is_err());
+        assert!(ts("20230312+1400").validate().is_ok());
+        assert!(ts("20230312-1200").validate().is_ok());
+        assert!(matches!(
+            ts("20230312-1300").validate(),
+            Err(DateTimeParseError::OffsetOutOfRange(_))
+        ));
+        assert!(ts("20230312+0575").validate().is_err());
+        assert_eq!(
+            ts("2023031224").validate().unwrap_err().to_string(),
+            "Invalid hour 24, expected 0 to 23"
+        );
+    }
+
+    #[test]
+    fn validates_structure() {
+        let ts = TimeStamp {
+            year: 2023,
+            day: Some(3),
+            ..Default::default()
+        };
+        assert!(matches!(
+            ts.validate(),
+            Err(DateTimeParseError::MissingComponent(
+                ErroredDateTimeComponent::Month
+            ))
+        ));
+    }
+
+    #[test]
+    fn validates_dates_and_times() {
+        assert!("20230229".parse::<Date>().unwrap().validate().is_err());
+        assert!("202302".parse::<Date>().unwrap().validate().is_ok());
+        assert!("2400".parse::<Time>().unwrap().validate().is_err());
+        assert!("235960.9999".parse::<Time>().unwrap().validate().is_ok());
+    }
+
+    #[test]
+    fn normalizes_midnight() {
+        assert_eq!(ts("2023022824").normalize(), ts("2023030100"));
+        assert_eq!(ts("20240228240000").normalize(), ts("20240229000000"));
+        assert_eq!(ts("202303122401").normalize(), ts("202303122401"));
+        assert_eq!(ts("20230312").normalize(), ts("20230312"));
+    }
+
+    #[test]
+    fn parses_with_normalization() {
+        assert_eq!(
+            parse_timestamp_normalized("20230312195905z").unwrap(),
+            ts("20230312195905+0000")
+        );
+        assert_eq!(
+            parse_timestamp_normalized("20230312195905+05").unwrap(),
+            ts("20230312195905+0500")
+        );
+        assert_eq!(
+            parse_timestamp_normalized("20230312195905.12").unwrap(),
+            ts("20230312195905.12")
+        );
+        assert!(parse_timestamp_normalized("20230230").is_err());
+        assert!(parse_timestamp_normalized("2023-03-12").is_err());
+        assert!(parse_timestamp_validated("20230312 ").is_err());
+    }
+}
@@ -63,10 +63,12 @@
 //! }));
 //! ```
 
-use super::{Date, DateTimeParseError, ErroredDateTimeComponent, Time, TimeStamp, TimeStampOffset};
+use super::{
+    Date, DateTimeParseError, ErroredDateTimeComponent, Period, Time, TimeStamp, TimeStampOffset,
+};
 use chrono::{
     offset::LocalResult, DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime,
-    TimeZone, Timelike,
+    TimeDelta, TimeZone, Timelike,
 };
 
 /// Attempt to convert a `TimeStamp` into a `NaiveDate`. If the `TimeStamp` is
@@ -351,6 +353,18 @@ where
     }
 }
 
+/// Attempt to convert a `Period` into a `TimeDelta`. Periods of months or years can't be
+/// converted, since they don't have a fixed length.
+impl TryFrom<Period> for TimeDelta {
+    type Error = DateTimeParseError;
+
+    fn try_from(value: Period) -> Result<Self, Self::Error> {
+        value.as_seconds().and_then(TimeDelta::try_seconds).ok_or(
+            DateTimeParseError::InvalidComponentRange(ErroredDateTimeComponent::Duration),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::datetime::TimeStampOffset;
@@ -453,4 +467,15 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn can_convert_periods_to_time_deltas() {
+        use crate::datetime::TimeUnit;
+
+        let delta: TimeDelta = Period::new(6, TimeUnit::Hour).try_into().unwrap();
+        assert_eq!(delta, TimeDelta::hours(6));
+        let delta: TimeDelta = Period::new(-30, TimeUnit::Minute).try_into().unwrap();
+        assert_eq!(delta, TimeDelta::minutes(-30));
+        assert!(TimeDelta::try_from(Period::new(1, TimeUnit::Month)).is_err());
+    }
 }
@@ -30,10 +30,13 @@
 //! ```
 use jiff::{
     civil::{date, Date, DateTime, Time},
-    Zoned,
+    SignedDuration, Span, Zoned,
 };
 
-use super::{DateTimeParseError, TimeStamp as HL7TimeStamp, Time as HL7Time, Date as HL7Date};
+use super::{
+    Date as HL7Date, DateTimeParseError, ErroredDateTimeComponent, Period, Time as HL7Time,
+    TimeStamp as HL7TimeStamp, TimeUnit,
+};
 
 impl TryFrom<HL7TimeStamp> for Date {
     type Error = DateTimeParseError;
@@ -269,6 +272,39 @@ impl TryFrom<HL7TimeStamp> for Zoned {
     }
 }
 
+/// Convert a `Period` into a `Span`. Unlike the other duration conversions, this works for
+/// periods of months and years, which are calendar units in a `Span`.
+impl TryFrom<Period> for Span {
+    type Error = DateTimeParseError;
+
+    fn try_from(value: Period) -> Result<Self, Self::Error> {
+        let span = Span::new();
+        let count = value.count;
+        match value.unit {
+            TimeUnit::Second => span.try_seconds(count),
+            TimeUnit::Minute => span.try_minutes(count),
+            TimeUnit::Hour => span.try_hours(count),
+            TimeUnit::Day => span.try_days(count),
+            TimeUnit::Week => span.try_weeks(count),
+            TimeUnit::Month => span.try_months(count),
+            TimeUnit::Year => span.try_years(count),
+        }
+        .map_err(|_| DateTimeParseError::InvalidComponentRange(ErroredDateTimeComponent::Duration))
+    }
+}
+
+/// Attempt to convert a `Period` into a `SignedDuration`. Periods of months or years can't be
+/// converted, since they don't have a fixed length.
+impl TryFrom<Period> for SignedDuration {
+    type Error = DateTimeParseError;
+
+    fn try_from(value: Period) -> Result<Self, Self::Error> {
+        value.as_seconds().map(SignedDuration::from_secs).ok_or(
+            DateTimeParseError::InvalidComponentRange(ErroredDateTimeComponent::Duration),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -321,4 +357,13 @@ mod tests {
         assert_eq!(hl7_timestamp.microsecond, Some(0));
         assert_eq!(hl7_timestamp.offset, Some(crate::datetime::TimeStampOffset { hours: 0, minutes: 0 }));
     }
+
+    #[test]
+    fn can_convert_periods() {
+        let span: Span = Period::new(3, TimeUnit::Month).try_into().unwrap();
+        assert_eq!(span.fieldwise(), Span::new().months(3).fieldwise());
+        let duration: SignedDuration = Period::new(-90, TimeUnit::Minute).try_into().unwrap();
+        assert_eq!(duration, SignedDuration::from_mins(-90));
+        assert!(SignedDuration::try_from(Period::new(1, TimeUnit::Month)).is_err());
+    }
 }
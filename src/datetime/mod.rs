@@ -6,6 +6,14 @@ mod time;
 pub use time::*;
 mod date;
 pub use date::*;
+mod period;
+pub use period::*;
+mod range;
+pub use range::*;
+mod timing;
+pub use timing::*;
+pub(crate) mod calendar;
+pub use calendar::*;
 
 /// Utilies to convert back and forth between chrono's data structures and the hl7-parser ones
 #[cfg(feature = "chrono")]
@@ -19,6 +27,12 @@ pub mod time_crate;
 #[cfg(feature = "jiff")]
 pub mod jiff;
 
+#[cfg(feature = "jiff")]
+mod zone;
+/// Resolution of timestamps without offsets to instants, using a time zone or default offset
+#[cfg(feature = "jiff")]
+pub use zone::*;
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ErroredDateTimeComponent {
     Year,
@@ -32,6 +46,7 @@ pub enum ErroredDateTimeComponent {
     Date,
     Time,
     DateTime,
+    Duration,
 }
 
 impl Display for ErroredDateTimeComponent {
@@ -48,6 +63,7 @@ impl Display for ErroredDateTimeComponent {
             ErroredDateTimeComponent::Date => write!(f, "date"),
             ErroredDateTimeComponent::Time => write!(f, "time"),
             ErroredDateTimeComponent::DateTime => write!(f, "date and time"),
+            ErroredDateTimeComponent::Duration => write!(f, "duration"),
         }
     }
 }
@@ -65,6 +81,34 @@ pub enum DateTimeParseError {
     AmbiguousTime(String, String),
     #[error("Missing component: {0:}")]
     MissingComponent(ErroredDateTimeComponent),
+    #[error("Invalid {component} {value}, expected {min} to {max}")]
+    OutOfRange {
+        component: ErroredDateTimeComponent,
+        value: u32,
+        min: u32,
+        max: u32,
+    },
+    #[error("Invalid day {day} for {year:04}-{month:02}, which has {days_in_month} days")]
+    DayOutOfRange {
+        year: u16,
+        month: u8,
+        day: u8,
+        days_in_month: u8,
+    },
+    #[error("Invalid offset {0}, expected -1200 to +1400")]
+    OffsetOutOfRange(TimeStampOffset),
+}
+
+/// Errors that can result from parsing HL7 date ranges, durations, repeat patterns, and
+/// timing/quantity values
+#[derive(thiserror::Error, Debug)]
+pub enum TimingParseError {
+    #[error("Invalid {0}: {1}")]
+    InvalidDateTime(&'static str, #[source] DateTimeParseError),
+    #[error("Invalid {0} '{1}'")]
+    InvalidValue(&'static str, String),
+    #[error(transparent)]
+    Parse(#[from] crate::parser::ParseError),
 }
 
 /// Trait for parsing HL7 date and time strings into `Date`, `Time`, and `TimeStamp` structs
@@ -0,0 +1,291 @@
+use super::TimingParseError;
+use crate::message::{Repeat, Separators};
+use std::{fmt::Display, str::FromStr};
+
+/// A unit of time used in HL7 durations, intervals, and repeat patterns
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimeUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    /// A calendar month, which doesn't have a fixed length
+    Month,
+    /// A calendar year, which doesn't have a fixed length
+    Year,
+}
+
+impl TimeUnit {
+    /// The single letter code for the unit used by HL7 interval and duration codes (table 0335),
+    /// i.e. `S`, `M`, `H`, `D`, `W`, and `L` for months. Years don't have a code.
+    pub fn code(&self) -> Option<char> {
+        match self {
+            TimeUnit::Second => Some('S'),
+            TimeUnit::Minute => Some('M'),
+            TimeUnit::Hour => Some('H'),
+            TimeUnit::Day => Some('D'),
+            TimeUnit::Week => Some('W'),
+            TimeUnit::Month => Some('L'),
+            TimeUnit::Year => None,
+        }
+    }
+
+    /// Look up a unit by its single letter HL7 code, ignoring case
+    pub fn from_code(code: char) -> Option<TimeUnit> {
+        match code.to_ascii_uppercase() {
+            'S' => Some(TimeUnit::Second),
+            'M' => Some(TimeUnit::Minute),
+            'H' => Some(TimeUnit::Hour),
+            'D' => Some(TimeUnit::Day),
+            'W' => Some(TimeUnit::Week),
+            'L' => Some(TimeUnit::Month),
+            _ => None,
+        }
+    }
+
+    /// Look up a unit by its UCUM or ANSI unit code, as used in the units of `CQ` quantities
+    /// (i.e. `s`, `min`, `h`, `d`, `wk`, `mo`, and `a`), ignoring case
+    pub fn from_unit(unit: &str) -> Option<TimeUnit> {
+        match unit.to_ascii_lowercase().as_str() {
+            "s" | "sec" => Some(TimeUnit::Second),
+            "min" => Some(TimeUnit::Minute),
+            "h" | "hr" => Some(TimeUnit::Hour),
+            "d" => Some(TimeUnit::Day),
+            "wk" => Some(TimeUnit::Week),
+            "mo" => Some(TimeUnit::Month),
+            "a" | "yr" => Some(TimeUnit::Year),
+            _ => None,
+        }
+    }
+
+    /// The UCUM unit code for the unit
+    pub fn unit(&self) -> &'static str {
+        match self {
+            TimeUnit::Second => "s",
+            TimeUnit::Minute => "min",
+            TimeUnit::Hour => "h",
+            TimeUnit::Day => "d",
+            TimeUnit::Week => "wk",
+            TimeUnit::Month => "mo",
+            TimeUnit::Year => "a",
+        }
+    }
+}
+
+/// A length of time in a single unit, such as the 6 hours of `Q6H`. The count may be negative
+/// when the period is an offset, such as the event offset of a repeat pattern.
+///
+/// Periods can be converted to `chrono`, `time`, and `jiff` durations when the respective
+/// features are enabled. Months and years only convert to `jiff::Span`, since they don't have a
+/// fixed length.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::datetime::{Period, TimeUnit};
+///
+/// let period = Period::new(6, TimeUnit::Hour);
+/// assert_eq!(period.as_seconds(), Some(21_600));
+/// assert_eq!(Period::new(1, TimeUnit::Month).as_seconds(), None);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Period {
+    /// The number of units
+    pub count: i64,
+    /// The unit of time
+    pub unit: TimeUnit,
+}
+
+impl Period {
+    /// Create a period of `count` units
+    pub const fn new(count: i64, unit: TimeUnit) -> Self {
+        Period { count, unit }
+    }
+
+    /// The length of the period in seconds, or `None` for months and years or if the length
+    /// overflows
+    pub fn as_seconds(&self) -> Option<i64> {
+        let seconds = match self.unit {
+            TimeUnit::Second => 1,
+            TimeUnit::Minute => 60,
+            TimeUnit::Hour => 3_600,
+            TimeUnit::Day => 86_400,
+            TimeUnit::Week => 604_800,
+            TimeUnit::Month | TimeUnit::Year => return None,
+        };
+        self.count.checked_mul(seconds)
+    }
+
+    /// Parse a period from a quantity and its UCUM or ANSI units, such as the components of a
+    /// `CQ` value
+    pub fn from_quantity(quantity: &str, units: &str) -> Result<Period, TimingParseError> {
+        let count = quantity
+            .parse()
+            .map_err(|_| TimingParseError::InvalidValue("quantity", quantity.to_string()))?;
+        let unit = TimeUnit::from_unit(units)
+            .ok_or_else(|| TimingParseError::InvalidValue("time unit", units.to_string()))?;
+        Ok(Period { count, unit })
+    }
+
+    /// The period in the most natural unit which divides it evenly, e.g. 120 minutes is 2
+    /// hours. Months and years are left alone.
+    pub(crate) fn normalized(self) -> Period {
+        let Some(seconds) = self.as_seconds() else {
+            return self;
+        };
+        [
+            (604_800, TimeUnit::Week),
+            (86_400, TimeUnit::Day),
+            (3_600, TimeUnit::Hour),
+            (60, TimeUnit::Minute),
+        ]
+        .into_iter()
+        .find(|(length, _)| seconds % length == 0)
+        .map(|(length, unit)| Period::new(seconds / length, unit))
+        .unwrap_or(Period::new(seconds, TimeUnit::Second))
+    }
+}
+
+/// The day type and number of days, as found in HL7 `DTN` values, such as the days approved
+/// for an insurance authorization. The day type is a code from HL7 table 0149, i.e. `AP`
+/// (approved), `DE` (denied), or `PE` (pending).
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::datetime::{DayTypeAndNumber, Period, TimeUnit};
+///
+/// let dtn: DayTypeAndNumber = "AP^5".parse().unwrap();
+/// assert_eq!(dtn.day_type, "AP");
+/// assert_eq!(dtn.period(), Some(Period::new(5, TimeUnit::Day)));
+/// assert_eq!(dtn.to_string(), "AP^5");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DayTypeAndNumber {
+    /// The day type code (`DTN.1`)
+    pub day_type: String,
+    /// The number of days (`DTN.2`)
+    pub number_of_days: Option<u32>,
+}
+
+impl DayTypeAndNumber {
+    /// Parse a `DTN` value from its components, decoded with the given separators
+    pub fn from_repeat(
+        repeat: &Repeat,
+        separators: &Separators,
+    ) -> Result<DayTypeAndNumber, TimingParseError> {
+        let component = |n| {
+            repeat
+                .component(n)
+                .map(|c| separators.decode(c.raw_value()).to_string())
+                .unwrap_or_default()
+        };
+        let number = component(2);
+        let number_of_days =
+            if number.is_empty() {
+                None
+            } else {
+                Some(number.parse().map_err(|_| {
+                    TimingParseError::InvalidValue("number of days", number.clone())
+                })?)
+            };
+        Ok(DayTypeAndNumber {
+            day_type: component(1),
+            number_of_days,
+        })
+    }
+
+    /// The number of days as a period
+    pub fn period(&self) -> Option<Period> {
+        self.number_of_days
+            .map(|days| Period::new(days as i64, TimeUnit::Day))
+    }
+}
+
+/// Parse a `DTN` value using the default separators
+impl FromStr for DayTypeAndNumber {
+    type Err = TimingParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DayTypeAndNumber::from_repeat(&crate::parser::parse_repeat(s)?, &Separators::default())
+    }
+}
+
+/// Format a `DTN` value using the default separators
+impl Display for DayTypeAndNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Separators::default().encode(&self.day_type))?;
+        if let Some(number) = self.number_of_days {
+            write!(f, "^{number}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    #[test]
+    fn can_parse_day_types() {
+        let message = crate::Message::parse("MSH|^~#&|\rZZZ|A#S#B^3").unwrap();
+        let repeat = message
+            .segment("ZZZ")
+            .unwrap()
+            .field(1)
+            .unwrap()
+            .repeat(1)
+            .unwrap();
+        let dtn = DayTypeAndNumber::from_repeat(repeat, &message.separators).unwrap();
+        assert_eq!(dtn.day_type, "A^B");
+        assert_eq!(dtn.number_of_days, Some(3));
+        assert_eq!(dtn.to_string(), "A\\S\\B^3");
+    }
+
+    #[test]
+    fn can_normalize_periods() {
+        assert_eq!(
+            Period::new(120, TimeUnit::Minute).normalized(),
+            Period::new(2, TimeUnit::Hour)
+        );
+        assert_eq!(
+            Period::new(14, TimeUnit::Day).normalized(),
+            Period::new(2, TimeUnit::Week)
+        );
+        assert_eq!(
+            Period::new(90, TimeUnit::Second).normalized(),
+            Period::new(90, TimeUnit::Second)
+        );
+        assert_eq!(
+            Period::new(3, TimeUnit::Month).normalized(),
+            Period::new(3, TimeUnit::Month)
+        );
+    }
+
+    #[test]
+    fn can_parse_quantities() {
+        assert_eq!(
+            Period::from_quantity("30", "min").unwrap(),
+            Period::new(30, TimeUnit::Minute)
+        );
+        assert_eq!(
+            Period::from_quantity("2", "WK").unwrap(),
+            Period::new(2, TimeUnit::Week)
+        );
+        assert!(Period::from_quantity("2", "furlong").is_err());
+        assert!(Period::from_quantity("two", "d").is_err());
+    }
+
+    #[test]
+    fn can_parse_day_type_and_number() {
+        let dtn: DayTypeAndNumber = "DE".parse().unwrap();
+        assert_eq!(dtn.number_of_days, None);
+        assert_eq!(dtn.to_string(), "DE");
+        assert!("AP^x".parse::<DayTypeAndNumber>().is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use super::{TimeStamp, TimingParseError};
+use crate::message::{Component, Repeat, Separators};
+use std::{cmp::Ordering, fmt::Display, str::FromStr};
+
+/// A range of dates and times, as found in HL7 `DR` values. Either end may be missing for
+/// ranges which are open-ended.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::datetime::{DateRange, TimeStamp};
+///
+/// let range: DateRange = "20230101^20230331".parse().unwrap();
+/// assert!(range.contains(&"20230315".parse().unwrap()));
+/// assert!(range.contains(&"2023033123".parse().unwrap()));
+/// assert!(!range.contains(&"20230401".parse().unwrap()));
+///
+/// let open: DateRange = "20230101".parse().unwrap();
+/// assert_eq!(open.end, None);
+/// assert!(open.contains(&"2099".parse().unwrap()));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DateRange {
+    /// The start of the range (`DR.1`)
+    pub start: Option<TimeStamp>,
+    /// The end of the range (`DR.2`)
+    pub end: Option<TimeStamp>,
+}
+
+impl DateRange {
+    /// Parse a `DR` value from its start and end timestamps, either of which may be empty
+    pub fn from_components(start: &str, end: &str) -> Result<DateRange, TimingParseError> {
+        let parse = |name, value: &str| {
+            if value.is_empty() {
+                Ok(None)
+            } else {
+                value
+                    .parse()
+                    .map(Some)
+                    .map_err(|e| TimingParseError::InvalidDateTime(name, e))
+            }
+        };
+        Ok(DateRange {
+            start: parse("range start", start)?,
+            end: parse("range end", end)?,
+        })
+    }
+
+    /// Parse a `DR` field repeat from its first and second components, decoded with the given
+    /// separators. In HL7 versions before v2.6 each end is a `TS`, and its degree of precision
+    /// subcomponent is used if present.
+    pub fn from_repeat(
+        repeat: &Repeat,
+        separators: &Separators,
+    ) -> Result<DateRange, TimingParseError> {
+        let end = |n, name| match repeat.component(n).filter(|c| !c.is_empty()) {
+            Some(component) => TimeStamp::from_component(component, separators)
+                .map(Some)
+                .map_err(|e| TimingParseError::InvalidDateTime(name, e)),
+            None => Ok(None),
+        };
+        Ok(DateRange {
+            start: end(1, "range start")?,
+            end: end(2, "range end")?,
+        })
+    }
+
+    /// Parse a `DR` component of a composite field from its first and second subcomponents,
+    /// decoded with the given separators
+    pub fn from_component(
+        component: &Component,
+        separators: &Separators,
+    ) -> Result<DateRange, TimingParseError> {
+        let subcomponent = |n| {
+            component
+                .subcomponent(n)
+                .map(|s| separators.decode(s.raw_value()).to_string())
+                .unwrap_or_default()
+        };
+        DateRange::from_components(&subcomponent(1), &subcomponent(2))
+    }
+
+    /// Whether the timestamp falls within the range, including at either end. The comparison
+    /// is precision-aware, so a timestamp overlapping either end is in the range.
+    pub fn contains(&self, timestamp: &TimeStamp) -> bool {
+        let after_start = self
+            .start
+            .as_ref()
+            .is_none_or(|start| timestamp.partial_cmp(start) != Some(Ordering::Less));
+        let before_end = self
+            .end
+            .as_ref()
+            .is_none_or(|end| timestamp.partial_cmp(end) != Some(Ordering::Greater));
+        after_start && before_end
+    }
+}
+
+/// Parse a `DR` value using the default separators
+impl FromStr for DateRange {
+    type Err = TimingParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DateRange::from_repeat(&crate::parser::parse_repeat(s)?, &Separators::default())
+    }
+}
+
+/// Format a `DR` value using the default separators
+impl Display for DateRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(start) = &self.start {
+            write!(f, "{start}")?;
+        }
+        if let Some(end) = &self.end {
+            write!(f, "^{end}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datetime::Precision;
+    use pretty_assertions_sorted::assert_eq;
+
+    #[test]
+    fn can_parse_date_ranges() {
+        let range: DateRange = "^202303121200".parse().unwrap();
+        assert_eq!(range.start, None);
+        assert_eq!(range.end, Some("202303121200".parse().unwrap()));
+        assert_eq!(range.to_string(), "^202303121200");
+
+        let range: DateRange = "200301011030&D^20031231".parse().unwrap();
+        assert_eq!(range.start.unwrap().precision(), Precision::Day);
+        assert_eq!(
+            DateRange::from_components("2023", "").unwrap().to_string(),
+            "2023"
+        );
+        assert!("2023^tomorrow".parse::<DateRange>().is_err());
+    }
+
+    #[test]
+    fn can_check_containment() {
+        let range: DateRange = "^20230331".parse().unwrap();
+        assert!(range.contains(&"1900".parse().unwrap()));
+        assert!(range.contains(&"202303".parse().unwrap()));
+        assert!(!range.contains(&"202304010000".parse().unwrap()));
+        assert!(DateRange::default().contains(&"2023".parse().unwrap()));
+    }
+}
@@ -39,9 +39,9 @@
 //! assert_eq!(datetime.offset().whole_hours(), -7);
 //! ```
 
-use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+use time::{Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
 
-use super::{DateTimeParseError, ErroredDateTimeComponent, TimeStamp, TimeStampOffset};
+use super::{DateTimeParseError, ErroredDateTimeComponent, Period, TimeStamp, TimeStampOffset};
 
 impl TryFrom<TimeStamp> for Date {
     type Error = DateTimeParseError;
@@ -280,6 +280,21 @@ impl From<OffsetDateTime> for TimeStamp {
     }
 }
 
+/// Attempt to convert a `Period` into a `Duration`. Periods of months or years can't be
+/// converted, since they don't have a fixed length.
+impl TryFrom<Period> for Duration {
+    type Error = DateTimeParseError;
+
+    fn try_from(value: Period) -> Result<Self, Self::Error> {
+        value
+            .as_seconds()
+            .map(Duration::seconds)
+            .ok_or(DateTimeParseError::InvalidComponentRange(
+                ErroredDateTimeComponent::Duration,
+            ))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -328,4 +343,13 @@ mod tests {
         assert_eq!(actual.microsecond(), 1234);
         assert_eq!(actual.offset(), UtcOffset::from_hms(-7, 0, 0).unwrap());
     }
+
+    #[test]
+    fn can_convert_periods_to_durations() {
+        use crate::datetime::TimeUnit;
+
+        let duration: Duration = Period::new(2, TimeUnit::Week).try_into().unwrap();
+        assert_eq!(duration, Duration::weeks(2));
+        assert!(Duration::try_from(Period::new(1, TimeUnit::Year)).is_err());
+    }
 }
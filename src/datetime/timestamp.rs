@@ -1,4 +1,7 @@
-use crate::parser::Span;
+use crate::{
+    message::{Component, Field, Repeat, Separators},
+    parser::Span,
+};
 use nom::{
     bytes::complete::{tag, take_while_m_n},
     character::complete::one_of,
@@ -6,9 +9,9 @@ use nom::{
     sequence::preceded,
     IResult,
 };
-use std::{fmt::Display, str::FromStr};
+use std::{cmp::Ordering, fmt::Display, str::FromStr};
 
-use super::DateTimeParseError;
+use super::{calendar::days_from_civil, DateTimeParseError};
 
 /// A parsed timezone offset in hours and minutes
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
@@ -28,7 +31,8 @@ impl Display for TimeStampOffset {
 }
 
 /// The results of parsing a timestamp. Note that the timestamp is not validated,
-/// i.e. it may not be a valid date or time.
+/// i.e. it may not be a valid date or time; use [`TimeStamp::validate`] or
+/// [`parse_timestamp_validated`] to check it against the calendar.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TimeStamp {
@@ -50,6 +54,340 @@ pub struct TimeStamp {
     pub offset: Option<TimeStampOffset>,
 }
 
+/// How precisely a [`TimeStamp`] is specified, ordered from the coarsest to the finest
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Precision {
+    /// `YYYY`
+    Year,
+    /// `YYYYMM`
+    Month,
+    /// `YYYYMMDD`
+    Day,
+    /// `YYYYMMDDHH`
+    Hour,
+    /// `YYYYMMDDHHMM`
+    Minute,
+    /// `YYYYMMDDHHMMSS`
+    Second,
+    /// `YYYYMMDDHHMMSS.S[S[S[S]]]`
+    FractionalSecond,
+}
+
+impl Precision {
+    /// The degree of precision code used in `TS.2` in HL7 v2.3 through v2.5: `Y`, `L`, `D`, `H`,
+    /// `M`, or `S`. Fractional seconds don't have a code of their own.
+    pub fn code(&self) -> Option<&'static str> {
+        match self {
+            Precision::Year => Some("Y"),
+            Precision::Month => Some("L"),
+            Precision::Day => Some("D"),
+            Precision::Hour => Some("H"),
+            Precision::Minute => Some("M"),
+            Precision::Second => Some("S"),
+            Precision::FractionalSecond => None,
+        }
+    }
+
+    /// The precision for a `TS.2` degree of precision code, if it is one
+    pub fn from_code(code: &str) -> Option<Precision> {
+        match code {
+            "Y" => Some(Precision::Year),
+            "L" => Some(Precision::Month),
+            "D" => Some(Precision::Day),
+            "H" => Some(Precision::Hour),
+            "M" => Some(Precision::Minute),
+            "S" => Some(Precision::Second),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Precision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Precision::Year => write!(f, "year"),
+            Precision::Month => write!(f, "month"),
+            Precision::Day => write!(f, "day"),
+            Precision::Hour => write!(f, "hour"),
+            Precision::Minute => write!(f, "minute"),
+            Precision::Second => write!(f, "second"),
+            Precision::FractionalSecond => write!(f, "fractional second"),
+        }
+    }
+}
+
+const MICROS_PER_SECOND: i64 = 1_000_000;
+const MICROS_PER_MINUTE: i64 = 60 * MICROS_PER_SECOND;
+const MICROS_PER_HOUR: i64 = 60 * MICROS_PER_MINUTE;
+const MICROS_PER_DAY: i64 = 24 * MICROS_PER_HOUR;
+
+impl TimeStamp {
+    /// Parse a `TS` value from its time (`TS.1`) and degree of precision (`TS.2`) components.
+    /// The degree of precision was used in HL7 v2.3 through v2.5 to say that a timestamp is only
+    /// precise to a given unit; the timestamp is truncated to that precision.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hl7_parser::datetime::{Precision, TimeStamp};
+    ///
+    /// let ts = TimeStamp::from_components("200305141030", Some("D")).unwrap();
+    /// assert_eq!(ts.precision(), Precision::Day);
+    /// assert_eq!(ts.to_string(), "20030514");
+    /// ```
+    pub fn from_components(
+        time: &str,
+        degree_of_precision: Option<&str>,
+    ) -> Result<TimeStamp, DateTimeParseError> {
+        let timestamp = parse_timestamp(time, false)?;
+        match degree_of_precision.filter(|code| !code.is_empty()) {
+            Some(code) => Precision::from_code(code)
+                .map(|precision| timestamp.with_precision(precision))
+                .ok_or(DateTimeParseError::ParsingFailed("degree of precision")),
+            None => Ok(timestamp),
+        }
+    }
+
+    /// Parse a `TS` field, using the first repeat's first and second components as the time and
+    /// degree of precision, decoded with the given separators
+    pub fn from_field(
+        field: &Field,
+        separators: &Separators,
+    ) -> Result<TimeStamp, DateTimeParseError> {
+        match field.repeat(1) {
+            Some(repeat) => Self::from_repeat(repeat, separators),
+            None => Err(DateTimeParseError::ParsingFailed("year")),
+        }
+    }
+
+    /// Parse a `TS` repeat, using its first and second components as the time and degree of
+    /// precision, decoded with the given separators
+    pub fn from_repeat(
+        repeat: &Repeat,
+        separators: &Separators,
+    ) -> Result<TimeStamp, DateTimeParseError> {
+        let component = |n| {
+            repeat
+                .component(n)
+                .map(|c| separators.decode(c.raw_value()).to_string())
+        };
+        let time = component(1).unwrap_or_default();
+        let degree = component(2);
+        Self::from_components(&time, degree.as_deref())
+    }
+
+    /// Parse a `TS` component of a composite field, using its first and second subcomponents as
+    /// the time and degree of precision, decoded with the given separators
+    pub fn from_component(
+        component: &Component,
+        separators: &Separators,
+    ) -> Result<TimeStamp, DateTimeParseError> {
+        let subcomponent = |n| {
+            component
+                .subcomponent(n)
+                .map(|s| separators.decode(s.raw_value()).to_string())
+        };
+        let time = subcomponent(1).unwrap_or_default();
+        let degree = subcomponent(2);
+        Self::from_components(&time, degree.as_deref())
+    }
+
+    /// The precision of the timestamp: the finest unit which is set. Parts which are set after a
+    /// missing part (such as a day without a month) are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hl7_parser::datetime::{Precision, TimeStamp};
+    ///
+    /// let ts: TimeStamp = "2023031219".parse().unwrap();
+    /// assert_eq!(ts.precision(), Precision::Hour);
+    /// ```
+    pub fn precision(&self) -> Precision {
+        let parts = [
+            self.month.is_some(),
+            self.day.is_some(),
+            self.hour.is_some(),
+            self.minute.is_some(),
+            self.second.is_some(),
+            self.microsecond.is_some(),
+        ];
+        match parts.iter().take_while(|&&set| set).count() {
+            0 => Precision::Year,
+            1 => Precision::Month,
+            2 => Precision::Day,
+            3 => Precision::Hour,
+            4 => Precision::Minute,
+            5 => Precision::Second,
+            _ => Precision::FractionalSecond,
+        }
+    }
+
+    /// Truncate the timestamp to the given precision, clearing any finer parts. Timestamps which
+    /// are already at or coarser than the precision are returned unchanged, apart from parts set
+    /// after a missing part, which are cleared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hl7_parser::datetime::{Precision, TimeStamp};
+    ///
+    /// let ts: TimeStamp = "20230312195905-0700".parse().unwrap();
+    /// assert_eq!(ts.with_precision(Precision::Day).to_string(), "20230312-0700");
+    /// ```
+    pub fn with_precision(self, precision: Precision) -> TimeStamp {
+        let precision = precision.min(self.precision());
+        let keep = |p: Precision| precision >= p;
+        TimeStamp {
+            year: self.year,
+            month: self.month.filter(|_| keep(Precision::Month)),
+            day: self.day.filter(|_| keep(Precision::Day)),
+            hour: self.hour.filter(|_| keep(Precision::Hour)),
+            minute: self.minute.filter(|_| keep(Precision::Minute)),
+            second: self.second.filter(|_| keep(Precision::Second)),
+            microsecond: self
+                .microsecond
+                .filter(|_| keep(Precision::FractionalSecond)),
+            offset: self.offset,
+        }
+    }
+
+    /// The span of time covered by the timestamp at its precision, as a half-open range of
+    /// microseconds since 1970-01-01. The offset is applied when `with_offset` is set; otherwise
+    /// the range is in the timestamp's local time.
+    fn span(&self, with_offset: bool) -> (i64, i64) {
+        let ts = self.with_precision(self.precision());
+        let year = ts.year as i64;
+        let month = ts.month.unwrap_or(1);
+        let day = days_from_civil(year, month, ts.day.unwrap_or(1));
+        let start = day * MICROS_PER_DAY
+            + ts.hour.unwrap_or_default() as i64 * MICROS_PER_HOUR
+            + ts.minute.unwrap_or_default() as i64 * MICROS_PER_MINUTE
+            + ts.second.unwrap_or_default() as i64 * MICROS_PER_SECOND
+            + ts.microsecond.unwrap_or_default() as i64;
+        let end = match ts.precision() {
+            Precision::Year => days_from_civil(year + 1, 1, 1) * MICROS_PER_DAY,
+            Precision::Month if month >= 12 => days_from_civil(year + 1, 1, 1) * MICROS_PER_DAY,
+            Precision::Month => days_from_civil(year, month + 1, 1) * MICROS_PER_DAY,
+            Precision::Day => start + MICROS_PER_DAY,
+            Precision::Hour => start + MICROS_PER_HOUR,
+            Precision::Minute => start + MICROS_PER_MINUTE,
+            Precision::Second => start + MICROS_PER_SECOND,
+            // fractional seconds are precise to at most four digits
+            Precision::FractionalSecond => start + 100,
+        };
+
+        let offset = match ts.offset.filter(|_| with_offset) {
+            Some(offset) => {
+                let minutes = offset.hours as i64 * 60
+                    + offset.minutes as i64 * if offset.hours < 0 { -1 } else { 1 };
+                minutes * MICROS_PER_MINUTE
+            }
+            None => 0,
+        };
+        (start - offset, end - offset)
+    }
+
+    /// The spans of two timestamps, comparing them in UTC if both have offsets, or in local time
+    /// otherwise
+    fn spans(&self, other: &TimeStamp) -> ((i64, i64), (i64, i64)) {
+        let with_offset = self.offset.is_some() && other.offset.is_some();
+        (self.span(with_offset), other.span(with_offset))
+    }
+
+    /// Whether the spans of time covered by two timestamps overlap, such as `2023` and
+    /// `20230101`, which covers part of 2023. Timestamps are compared in UTC if both have
+    /// offsets, and in local time otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hl7_parser::datetime::TimeStamp;
+    ///
+    /// let year: TimeStamp = "2023".parse().unwrap();
+    /// let day: TimeStamp = "20230101".parse().unwrap();
+    /// let next_year: TimeStamp = "20240101".parse().unwrap();
+    /// assert!(year.overlaps(&day));
+    /// assert!(!year.overlaps(&next_year));
+    /// ```
+    pub fn overlaps(&self, other: &TimeStamp) -> bool {
+        let ((start, end), (other_start, other_end)) = self.spans(other);
+        start < other_end && other_start < end
+    }
+
+    /// A total order for placing timestamps of mixed precision on a timeline: by the start of the
+    /// span of time they cover, then with coarser timestamps before the finer timestamps they
+    /// contain. Use this to sort timestamps, as [`PartialOrd`] doesn't order overlapping
+    /// timestamps.
+    ///
+    /// Timestamps with offsets are placed in UTC. To keep the order consistent, timestamps
+    /// without offsets are placed as though they were in UTC too, so a list which mixes the two
+    /// is only in true time order if the local time is UTC. Give every timestamp an offset
+    /// first, such as by resolving it with a `ZoneContext` (with the `jiff` feature), when sorting
+    /// timestamps from different sources.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hl7_parser::datetime::TimeStamp;
+    ///
+    /// let mut timestamps: Vec<TimeStamp> = ["20230102", "2023", "202301011200", "20230101"]
+    ///     .iter()
+    ///     .map(|ts| ts.parse().unwrap())
+    ///     .collect();
+    /// timestamps.sort_by(TimeStamp::timeline_cmp);
+    /// let sorted: Vec<String> = timestamps.iter().map(|ts| ts.to_string()).collect();
+    /// assert_eq!(sorted, ["2023", "20230101", "202301011200", "20230102"]);
+    /// ```
+    pub fn timeline_cmp(&self, other: &TimeStamp) -> Ordering {
+        let ((start, end), (other_start, other_end)) = (self.span(true), other.span(true));
+        start
+            .cmp(&other_start)
+            .then(other_end.cmp(&end))
+            .then_with(|| self.to_string().cmp(&other.to_string()))
+    }
+}
+
+/// Timestamps are ordered by the span of time they cover at their precision, so `2023` is
+/// before `2024` and after `2022`, but is neither before nor after `20230101`, which overlaps it.
+/// Timestamps are compared in UTC if both have offsets, and in local time if neither does. A
+/// timestamp with an offset and one without are unordered, as the local time of the one without
+/// isn't known. Use [`TimeStamp::timeline_cmp`] to sort timestamps of mixed precision.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::datetime::TimeStamp;
+///
+/// let year: TimeStamp = "2023".parse().unwrap();
+/// let day: TimeStamp = "20230101".parse().unwrap();
+/// let next_year: TimeStamp = "2024".parse().unwrap();
+/// assert!(year < next_year);
+/// assert!(day < next_year);
+/// assert_eq!(year.partial_cmp(&day), None);
+/// ```
+impl PartialOrd for TimeStamp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self == other {
+            return Some(Ordering::Equal);
+        }
+        // ordering a timestamp with an offset against one without would mix frames, making the
+        // order intransitive
+        if self.offset.is_some() != other.offset.is_some() {
+            return None;
+        }
+        let ((start, end), (other_start, other_end)) = self.spans(other);
+        if end <= other_start {
+            Some(Ordering::Less)
+        } else if other_end <= start {
+            Some(Ordering::Greater)
+        } else {
+            None
+        }
+    }
+}
+
 /// Parse an HL7 timestamp in the format: `YYYY[MM[DD[HH[MM[SS[.S[S[S[S]]]]]]]]][+/-ZZZZ]`
 ///
 /// # Arguments
@@ -333,6 +671,137 @@ mod test {
         );
     }
 
+    fn ts(s: &str) -> TimeStamp {
+        s.parse().expect("can parse timestamp")
+    }
+
+    #[test]
+    fn can_get_precision() {
+        assert_eq!(ts("2023").precision(), Precision::Year);
+        assert_eq!(ts("202303").precision(), Precision::Month);
+        assert_eq!(ts("20230312").precision(), Precision::Day);
+        assert_eq!(ts("2023031219").precision(), Precision::Hour);
+        assert_eq!(ts("202303121959").precision(), Precision::Minute);
+        assert_eq!(ts("20230312195905-0700").precision(), Precision::Second);
+        assert_eq!(
+            ts("20230312195905.1").precision(),
+            Precision::FractionalSecond
+        );
+        let gap = TimeStamp {
+            year: 2023,
+            day: Some(12),
+            ..Default::default()
+        };
+        assert_eq!(gap.precision(), Precision::Year);
+        assert_eq!(gap.with_precision(Precision::Day).to_string(), "2023");
+    }
+
+    #[test]
+    fn can_parse_degree_of_precision() {
+        let parse = |time, degree| {
+            TimeStamp::from_components(time, degree)
+                .expect("can parse timestamp")
+                .to_string()
+        };
+        assert_eq!(parse("20230312195905", Some("Y")), "2023");
+        assert_eq!(parse("20230312195905", Some("L")), "202303");
+        assert_eq!(parse("20230312195905", Some("H")), "2023031219");
+        assert_eq!(parse("20230312195905.12", Some("S")), "20230312195905");
+        assert_eq!(parse("2023", Some("S")), "2023");
+        assert_eq!(parse("20230312", Some("")), "20230312");
+        assert_eq!(parse("20230312", None), "20230312");
+        assert!(TimeStamp::from_components("20230312", Some("X")).is_err());
+    }
+
+    #[test]
+    fn decodes_timestamps_with_the_message_separators() {
+        // escape sequences which aren't recognised decode to their contents
+        let message = crate::Message::parse("MSH|^~#&|\rZZZ|20230312#19#0000^H").unwrap();
+        let field = message.segment("ZZZ").unwrap().field(1).unwrap();
+        let ts = TimeStamp::from_field(field, &message.separators).expect("can parse timestamp");
+        assert_eq!(ts.to_string(), "2023031219");
+        assert!(TimeStamp::from_field(field, &Separators::default()).is_err());
+    }
+
+    #[test]
+    fn compares_timestamps_by_span() {
+        assert!(ts("2023") < ts("2024"));
+        assert!(ts("2023") > ts("202212312359"));
+        assert!(ts("202312") < ts("20240101"));
+        assert!(ts("20230312") < ts("2023031300"));
+        assert_eq!(ts("2023").partial_cmp(&ts("2023")), Some(Ordering::Equal));
+        assert_eq!(ts("2023").partial_cmp(&ts("20230101")), None);
+        assert_eq!(ts("20230101").partial_cmp(&ts("2023")), None);
+        assert_eq!(ts("202302").partial_cmp(&ts("20230228")), None);
+        assert!(ts("20230228") < ts("202303"));
+    }
+
+    #[test]
+    fn compares_timestamps_with_offsets_in_utc() {
+        assert!(ts("202303121200-0700") > ts("202303121800+0000"));
+        assert!(ts("202303121200-0700").overlaps(&ts("2023031219+0000")));
+        // the same instant in different offsets overlaps rather than being equal
+        assert_eq!(
+            ts("202303121200-0700").partial_cmp(&ts("202303121900+0000")),
+            None
+        );
+        // a timestamp with an offset and one without are unordered
+        assert_eq!(
+            ts("202303121200-0700").partial_cmp(&ts("202303121800")),
+            None
+        );
+        assert!(ts("202303121200") < ts("202303121800"));
+    }
+
+    #[test]
+    fn comparisons_are_transitive() {
+        let a = ts("202303121200-0700");
+        let b = ts("202303121800");
+        let c = ts("202303121830+0000");
+        let ordered = |x: &TimeStamp, y: &TimeStamp, z: &TimeStamp| !(x < y && y < z) || x < z;
+        for (x, y, z) in [
+            (&a, &b, &c),
+            (&a, &c, &b),
+            (&b, &a, &c),
+            (&b, &c, &a),
+            (&c, &a, &b),
+            (&c, &b, &a),
+        ] {
+            assert!(ordered(x, y, z), "{x} < {y} < {z}");
+            let timeline = |x: &TimeStamp, y: &TimeStamp| x.timeline_cmp(y);
+            if timeline(x, y).is_lt() && timeline(y, z).is_lt() {
+                assert!(timeline(x, z).is_lt(), "{x} < {y} < {z} on the timeline");
+            }
+        }
+    }
+
+    #[test]
+    fn can_sort_timestamps_on_a_timeline() {
+        let mut timestamps = [
+            "20230312195905",
+            "2023031219",
+            "202303",
+            "20230312",
+            "2022",
+            "20230312195905.5",
+            "20230311",
+        ]
+        .map(ts);
+        timestamps.sort_by(TimeStamp::timeline_cmp);
+        assert_eq!(
+            timestamps.map(|ts| ts.to_string()),
+            [
+                "2022",
+                "202303",
+                "20230311",
+                "20230312",
+                "2023031219",
+                "20230312195905",
+                "20230312195905.5000",
+            ]
+        );
+    }
+
     #[test]
     fn can_format_timestamp() {
         let ts = TimeStamp {
@@ -0,0 +1,868 @@
+use super::{parse_time, Period, Time, TimeStamp, TimeUnit, TimingParseError};
+use crate::{
+    message::{Field, Repeat, Segment, Separators},
+    types::Decimal,
+};
+use std::{fmt::Display, str::FromStr};
+
+/// How often something is repeated, as given by an HL7 repeat pattern code (table 0335), such
+/// as `Q6H` or `BID`. Codes which aren't recognized are kept as [`Frequency::Other`].
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::datetime::{Frequency, Period, TimeUnit};
+///
+/// let frequency = Frequency::from_code("Q6H");
+/// assert_eq!(frequency, Frequency::Every(Period::new(6, TimeUnit::Hour)));
+///
+/// let frequency = Frequency::from_code("PRNTID");
+/// assert_eq!(frequency.interval(), Some(Period::new(8, TimeUnit::Hour)));
+/// assert_eq!(frequency.to_string(), "PRNTID");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Frequency {
+    /// Every period, i.e. `Q<n>S`, `Q<n>M`, `Q<n>H`, `Q<n>D`, `Q<n>W`, or `Q<n>L` (months)
+    Every(Period),
+    /// A number of times a day, i.e. `BID`, `TID`, `QID`, or `<n>ID`
+    TimesPerDay(u32),
+    /// On the given days of the week, every given number of weeks, i.e. `Q<n>J<days>`. Days are
+    /// numbered from 1 (Monday) to 7 (Sunday).
+    Weekly {
+        /// The number of weeks between repeats
+        every: u32,
+        /// The days of the week
+        days: Vec<u8>,
+    },
+    /// Every other day (`QOD`)
+    EveryOtherDay,
+    /// Every morning (`QAM`)
+    Morning,
+    /// Every evening (`QPM`)
+    Evening,
+    /// Every night at bedtime (`QHS`)
+    Bedtime,
+    /// Every shift (`QSHIFT`)
+    EveryShift,
+    /// One time only (`ONCE`)
+    Once,
+    /// As needed, optionally no more often than the given frequency, i.e. `PRN` or
+    /// `PRN<frequency>`
+    AsNeeded(Option<Box<Frequency>>),
+    /// A code which isn't recognized, such as an institution-specific or meal-related code
+    Other(String),
+}
+
+impl Frequency {
+    /// Parse a repeat pattern code. This never fails; unrecognized codes are kept as
+    /// [`Frequency::Other`].
+    pub fn from_code(code: &str) -> Frequency {
+        let upper = code.to_ascii_uppercase();
+        match upper.as_str() {
+            "BID" => return Frequency::TimesPerDay(2),
+            "TID" => return Frequency::TimesPerDay(3),
+            "QID" => return Frequency::TimesPerDay(4),
+            "QOD" => return Frequency::EveryOtherDay,
+            "QAM" => return Frequency::Morning,
+            "QPM" => return Frequency::Evening,
+            "QHS" => return Frequency::Bedtime,
+            "QSHIFT" => return Frequency::EveryShift,
+            "ONCE" => return Frequency::Once,
+            "PRN" => return Frequency::AsNeeded(None),
+            _ => {}
+        }
+        if let Some(rest) = upper.strip_prefix("PRN") {
+            return Frequency::AsNeeded(Some(Box::new(Frequency::from_code(rest))));
+        }
+        if let Some(times) = upper.strip_suffix("ID").and_then(positive) {
+            return Frequency::TimesPerDay(times);
+        }
+        if let Some(rest) = upper.strip_prefix('Q') {
+            if let Some((every, days)) = rest.split_once('J') {
+                let every = if every.is_empty() {
+                    Some(1)
+                } else {
+                    positive(every)
+                };
+                let valid = !days.is_empty() && days.bytes().all(|d| (b'1'..=b'7').contains(&d));
+                if let (Some(every), true) = (every, valid) {
+                    let days = days.bytes().map(|d| d - b'0').collect();
+                    return Frequency::Weekly { every, days };
+                }
+            } else if let Some(unit) = rest.chars().last().and_then(TimeUnit::from_code) {
+                if let Some(count) = positive(&rest[..rest.len() - 1]) {
+                    return Frequency::Every(Period::new(count as i64, unit));
+                }
+            }
+        }
+        Frequency::Other(code.to_string())
+    }
+
+    /// The nominal time between repeats, if there is one, e.g. 12 hours for `BID` and 1 day for
+    /// `QAM`. For as needed frequencies, this is the shortest time between repeats.
+    pub fn interval(&self) -> Option<Period> {
+        match self {
+            Frequency::Every(period) => Some(*period),
+            Frequency::TimesPerDay(times) if *times > 0 && 86_400 % times == 0 => {
+                Some(Period::new((86_400 / times) as i64, TimeUnit::Second).normalized())
+            }
+            Frequency::Weekly { every, days } if days.len() == 1 => {
+                Some(Period::new(*every as i64, TimeUnit::Week))
+            }
+            Frequency::EveryOtherDay => Some(Period::new(2, TimeUnit::Day)),
+            Frequency::Morning | Frequency::Evening | Frequency::Bedtime => {
+                Some(Period::new(1, TimeUnit::Day))
+            }
+            Frequency::AsNeeded(Some(frequency)) => frequency.interval(),
+            _ => None,
+        }
+    }
+}
+
+fn positive(s: &str) -> Option<u32> {
+    s.parse().ok().filter(|n| *n > 0)
+}
+
+/// Format the frequency as its repeat pattern code
+impl Display for Frequency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Frequency::Every(period) => match period.unit.code() {
+                Some(code) => write!(f, "Q{}{code}", period.count),
+                None => write!(f, "Q{}L", period.count * 12),
+            },
+            Frequency::TimesPerDay(2) => write!(f, "BID"),
+            Frequency::TimesPerDay(3) => write!(f, "TID"),
+            Frequency::TimesPerDay(4) => write!(f, "QID"),
+            Frequency::TimesPerDay(times) => write!(f, "{times}ID"),
+            Frequency::Weekly { every, days } => {
+                write!(f, "Q{every}J")?;
+                days.iter().try_for_each(|day| write!(f, "{day}"))
+            }
+            Frequency::EveryOtherDay => write!(f, "QOD"),
+            Frequency::Morning => write!(f, "QAM"),
+            Frequency::Evening => write!(f, "QPM"),
+            Frequency::Bedtime => write!(f, "QHS"),
+            Frequency::EveryShift => write!(f, "QSHIFT"),
+            Frequency::Once => write!(f, "ONCE"),
+            Frequency::AsNeeded(None) => write!(f, "PRN"),
+            Frequency::AsNeeded(Some(frequency)) => write!(f, "PRN{frequency}"),
+            Frequency::Other(code) => write!(f, "{code}"),
+        }
+    }
+}
+
+/// How long a service should continue, as given by the duration of a `TQ` value (`TQ.3`) or
+/// the service duration of a `TQ1` segment (`TQ1.6`)
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::datetime::{Period, ServiceDuration, TimeUnit};
+///
+/// let duration: ServiceDuration = "M30".parse().unwrap();
+/// assert_eq!(duration, ServiceDuration::Period(Period::new(30, TimeUnit::Minute)));
+/// assert_eq!("X5".parse::<ServiceDuration>().unwrap(), ServiceDuration::Occurrences(5));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ServiceDuration {
+    /// For a length of time, i.e. `S<n>`, `M<n>`, `H<n>`, `D<n>`, `W<n>`, or `L<n>` (months)
+    Period(Period),
+    /// For a number of times (`X<n>`)
+    Occurrences(u32),
+    /// Until a total amount of the quantity has been given (`T<n>`)
+    Total(u32),
+    /// Until cancelled (`INDEF`)
+    Indefinite,
+}
+
+impl ServiceDuration {
+    /// The length of the service, if it is given as a length of time
+    pub fn period(&self) -> Option<Period> {
+        match self {
+            ServiceDuration::Period(period) => Some(*period),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a `TQ` duration code
+impl FromStr for ServiceDuration {
+    type Err = TimingParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || TimingParseError::InvalidValue("service duration", s.to_string());
+        if s.eq_ignore_ascii_case("INDEF") {
+            return Ok(ServiceDuration::Indefinite);
+        }
+        let mut chars = s.chars();
+        let code = chars.next().ok_or_else(invalid)?;
+        let count = positive(chars.as_str()).ok_or_else(invalid)?;
+        match code.to_ascii_uppercase() {
+            'X' => Ok(ServiceDuration::Occurrences(count)),
+            'T' => Ok(ServiceDuration::Total(count)),
+            code => TimeUnit::from_code(code)
+                .map(|unit| ServiceDuration::Period(Period::new(count as i64, unit)))
+                .ok_or_else(invalid),
+        }
+    }
+}
+
+/// Format the duration as a `TQ` duration code
+impl Display for ServiceDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServiceDuration::Period(period) => match period.unit.code() {
+                Some(code) => write!(f, "{code}{}", period.count),
+                None => write!(f, "L{}", period.count * 12),
+            },
+            ServiceDuration::Occurrences(count) => write!(f, "X{count}"),
+            ServiceDuration::Total(count) => write!(f, "T{count}"),
+            ServiceDuration::Indefinite => write!(f, "INDEF"),
+        }
+    }
+}
+
+/// A repeat pattern, as found in HL7 `RPT` values (`TQ1.3`) and in the interval of `TQ` values
+/// (`TQ.2`), where only the frequency is given.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::datetime::{Frequency, Period, RepeatPattern, TimeUnit};
+///
+/// let pattern: RepeatPattern = "QAM^^^^^^^ACM^-30^M".parse().unwrap();
+/// assert_eq!(pattern.frequency, Frequency::Morning);
+/// assert_eq!(pattern.event.as_deref(), Some("ACM"));
+/// assert_eq!(pattern.event_offset, Some(Period::new(-30, TimeUnit::Minute)));
+/// assert_eq!(pattern.to_string(), "QAM^^^^^^^ACM^-30^M");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RepeatPattern {
+    /// The repeat pattern code (`RPT.1`)
+    pub frequency: Frequency,
+    /// The calendar alignment (`RPT.2`), e.g. `DW` for day of the week
+    pub calendar_alignment: Option<String>,
+    /// The start of the phase range (`RPT.3`)
+    pub phase_range_begin: Option<u32>,
+    /// The end of the phase range (`RPT.4`)
+    pub phase_range_end: Option<u32>,
+    /// The period of the repeat (`RPT.5` and `RPT.6`)
+    pub period: Option<Period>,
+    /// Whether the institution specifies the times (`RPT.7`)
+    pub institution_specified_time: Option<bool>,
+    /// The event the repeat is relative to (`RPT.8`), such as `ACM` (before breakfast)
+    pub event: Option<String>,
+    /// The offset from the event (`RPT.9` and `RPT.10`), which may be negative
+    pub event_offset: Option<Period>,
+    /// The general timing specification (`RPT.11`)
+    pub general_timing_specification: Option<String>,
+}
+
+impl From<Frequency> for RepeatPattern {
+    fn from(frequency: Frequency) -> Self {
+        RepeatPattern {
+            frequency,
+            calendar_alignment: None,
+            phase_range_begin: None,
+            phase_range_end: None,
+            period: None,
+            institution_specified_time: None,
+            event: None,
+            event_offset: None,
+            general_timing_specification: None,
+        }
+    }
+}
+
+impl RepeatPattern {
+    /// Parse an `RPT` value from the components of a field repeat, decoded with the given
+    /// separators. The repeat pattern code is the identifier of `RPT.1`.
+    pub fn from_repeat(
+        repeat: &Repeat,
+        separators: &Separators,
+    ) -> Result<RepeatPattern, TimingParseError> {
+        let component = |n| {
+            repeat
+                .component(n)
+                .and_then(|c| c.subcomponent(1))
+                .map(|s| separators.decode(s.raw_value()).to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let phase = |n, name| {
+            component(n)
+                .map(|v: String| {
+                    v.parse()
+                        .map_err(|_| TimingParseError::InvalidValue(name, v.to_string()))
+                })
+                .transpose()
+        };
+        let period = |count, unit, name| -> Result<Option<Period>, TimingParseError> {
+            let count = component(count)
+                .map(|v: String| {
+                    v.parse()
+                        .map_err(|_| TimingParseError::InvalidValue(name, v.to_string()))
+                })
+                .transpose()?;
+            match (count, component(unit)) {
+                (Some(count), Some(unit)) => {
+                    let unit = unit
+                        .chars()
+                        .next()
+                        .filter(|_| unit.len() == 1)
+                        .and_then(TimeUnit::from_code)
+                        .ok_or_else(|| TimingParseError::InvalidValue("time unit", unit.clone()))?;
+                    Ok(Some(Period::new(count, unit)))
+                }
+                _ => Ok(None),
+            }
+        };
+        let institution_specified_time = match component(7).as_deref() {
+            Some("Y") => Some(true),
+            Some("N") => Some(false),
+            Some(v) => {
+                return Err(TimingParseError::InvalidValue(
+                    "institution specified time",
+                    v.to_string(),
+                ))
+            }
+            None => None,
+        };
+
+        Ok(RepeatPattern {
+            frequency: Frequency::from_code(&component(1).unwrap_or_default()),
+            calendar_alignment: component(2),
+            phase_range_begin: phase(3, "phase range begin")?,
+            phase_range_end: phase(4, "phase range end")?,
+            period: period(5, 6, "period quantity")?,
+            institution_specified_time,
+            event: component(8),
+            event_offset: period(9, 10, "event offset quantity")?,
+            general_timing_specification: component(11),
+        })
+    }
+}
+
+/// Parse an `RPT` value using the default separators
+impl FromStr for RepeatPattern {
+    type Err = TimingParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RepeatPattern::from_repeat(&crate::parser::parse_repeat(s)?, &Separators::default())
+    }
+}
+
+/// Format an `RPT` value using the default separators
+impl Display for RepeatPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let separators = Separators::default();
+        let encode = |v: &Option<String>| {
+            v.as_deref()
+                .map(|v| separators.encode(v).to_string())
+                .unwrap_or_default()
+        };
+        let code = |period: Option<Period>| {
+            period
+                .and_then(|p| p.unit.code())
+                .map(String::from)
+                .unwrap_or_default()
+        };
+        let components = [
+            self.frequency.to_string(),
+            encode(&self.calendar_alignment),
+            display(self.phase_range_begin),
+            display(self.phase_range_end),
+            display(self.period.map(|p| p.count)),
+            code(self.period),
+            display(self.institution_specified_time.map(yes_no)),
+            encode(&self.event),
+            display(self.event_offset.map(|p| p.count)),
+            code(self.event_offset),
+            encode(&self.general_timing_specification),
+        ];
+        write_components(f, &components, separators.component)
+    }
+}
+
+fn display<T: Display>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "Y"
+    } else {
+        "N"
+    }
+}
+
+/// Write the components joined by the separator, leaving off trailing empty components
+fn write_components(
+    f: &mut std::fmt::Formatter<'_>,
+    components: &[String],
+    separator: char,
+) -> std::fmt::Result {
+    let len = components
+        .iter()
+        .rposition(|c| !c.is_empty())
+        .map_or(0, |i| i + 1);
+    for (i, component) in components[..len].iter().enumerate() {
+        if i > 0 {
+            write!(f, "{separator}")?;
+        }
+        write!(f, "{component}")?;
+    }
+    Ok(())
+}
+
+/// The timing and quantity of a service, such as a medication order, as found in the HL7 `TQ`
+/// data type (`ORC-7`, `OBR-27`, and `RXE-1` before HL7 v2.5) and in the `TQ1` segment which
+/// replaced it.
+///
+/// The order sequencing and occurrence duration of `TQ` values, and the relative times and
+/// occurrence duration of `TQ1` segments, aren't parsed.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::datetime::{Frequency, Period, ServiceDuration, TimeUnit, TimingQuantity};
+///
+/// let tq: TimingQuantity = "1&TAB^Q6H^X10^20230312080000^^R".parse().unwrap();
+/// assert_eq!(tq.quantity, Some("1".parse().unwrap()));
+/// assert_eq!(tq.quantity_units.as_deref(), Some("TAB"));
+/// assert_eq!(tq.repeat_patterns[0].frequency, Frequency::Every(Period::new(6, TimeUnit::Hour)));
+/// assert_eq!(tq.service_duration, Some(ServiceDuration::Occurrences(10)));
+/// assert_eq!(tq.start, Some("20230312080000".parse().unwrap()));
+/// assert_eq!(tq.priorities, vec!["R".to_string()]);
+/// assert_eq!(tq.to_string(), "1&TAB^Q6H^X10^20230312080000^^R");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimingQuantity {
+    /// The quantity of the service given each time (`TQ.1.1`, `TQ1-2.1`)
+    pub quantity: Option<Decimal>,
+    /// The units of the quantity (`TQ.1.2`, `TQ1-2.2`)
+    pub quantity_units: Option<String>,
+    /// The repeat patterns (`TQ.2.1`, `TQ1-3`)
+    pub repeat_patterns: Vec<RepeatPattern>,
+    /// The times of day the service is given (`TQ.2.2`, `TQ1-4`)
+    pub explicit_times: Vec<Time>,
+    /// How long the service should continue (`TQ.3`, `TQ1-6`)
+    pub service_duration: Option<ServiceDuration>,
+    /// When the service should start (`TQ.4`, `TQ1-7`)
+    pub start: Option<TimeStamp>,
+    /// When the service should end (`TQ.5`, `TQ1-8`)
+    pub end: Option<TimeStamp>,
+    /// The priority codes (`TQ.6`, `TQ1-9`), such as `S` (stat) or `R` (routine)
+    pub priorities: Vec<String>,
+    /// The condition for giving the service (`TQ.7`, `TQ1-10`)
+    pub condition: Option<String>,
+    /// Instructions for the service (`TQ.8`, `TQ1-11`)
+    pub text: Option<String>,
+    /// How this timing relates to the next one (`TQ.9`, `TQ1-12`), i.e. `S` (synchronous),
+    /// `A` (asynchronous), or `C` (actuation time)
+    pub conjunction: Option<String>,
+    /// The total number of times the service should be given (`TQ.12`, `TQ1-14`)
+    pub total_occurrences: Option<u32>,
+}
+
+impl TimingQuantity {
+    /// Parse a `TQ` field repeat, decoding text components with the given separators
+    pub fn from_repeat(
+        repeat: &Repeat,
+        separators: &Separators,
+    ) -> Result<TimingQuantity, TimingParseError> {
+        let sub = |n, s| {
+            repeat
+                .component(n)
+                .and_then(|c| c.subcomponent(s))
+                .map(|s| s.raw_value())
+                .filter(|v| !v.is_empty())
+        };
+        let text = |n| {
+            repeat
+                .component(n)
+                .map(|c| c.raw_value())
+                .filter(|v| !v.is_empty())
+                .map(|v| separators.decode(v).to_string())
+        };
+        let timestamp = |n, name| match repeat.component(n).filter(|c| !c.is_empty()) {
+            Some(component) => TimeStamp::from_component(component, separators)
+                .map(Some)
+                .map_err(|e| TimingParseError::InvalidDateTime(name, e)),
+            None => Ok(None),
+        };
+
+        Ok(TimingQuantity {
+            quantity: parse_quantity(sub(1, 1))?,
+            quantity_units: sub(1, 2).map(str::to_string),
+            repeat_patterns: sub(2, 1)
+                .map(|code| Frequency::from_code(code).into())
+                .into_iter()
+                .collect(),
+            explicit_times: sub(2, 2)
+                .map(|times| times.split(',').map(parse_explicit_time).collect())
+                .transpose()?
+                .unwrap_or_default(),
+            service_duration: sub(3, 1).map(str::parse).transpose()?,
+            start: timestamp(4, "start date/time")?,
+            end: timestamp(5, "end date/time")?,
+            priorities: text(6).into_iter().collect(),
+            condition: text(7),
+            text: text(8),
+            conjunction: text(9),
+            total_occurrences: parse_count(sub(12, 1))?,
+        })
+    }
+
+    /// Parse the first repeat of a `TQ` field, decoding text components with the given
+    /// separators. Empty fields result in an empty timing.
+    pub fn from_field(
+        field: &Field,
+        separators: &Separators,
+    ) -> Result<TimingQuantity, TimingParseError> {
+        match field.repeat(1) {
+            Some(repeat) => TimingQuantity::from_repeat(repeat, separators),
+            None => Ok(TimingQuantity::default()),
+        }
+    }
+
+    /// Parse a `TQ1` segment, decoding text fields with the given separators
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hl7_parser::{datetime::{Frequency, TimingQuantity}, Message};
+    ///
+    /// let message = Message::parse(
+    ///     "MSH|^~\\&|||||||RDE^O11|1|P|2.5.1\rTQ1|1|2^TAB|BID~QHS|0800~1900~2200",
+    /// ).unwrap();
+    /// let tq1 = message.segment("TQ1").unwrap();
+    /// let timing = TimingQuantity::from_tq1(tq1, &message.separators).unwrap();
+    /// assert_eq!(timing.repeat_patterns[1].frequency, Frequency::Bedtime);
+    /// assert_eq!(timing.explicit_times.len(), 3);
+    /// ```
+    pub fn from_tq1(
+        segment: &Segment,
+        separators: &Separators,
+    ) -> Result<TimingQuantity, TimingParseError> {
+        let repeats = |n| {
+            segment
+                .field(n)
+                .into_iter()
+                .flat_map(|f| f.repeats())
+                .filter(|r| !r.is_empty())
+        };
+        let component = |n, c| {
+            segment
+                .field(n)
+                .and_then(|f| f.repeat(1))
+                .and_then(|r| r.component(c))
+                .and_then(|c| c.subcomponent(1))
+                .map(|s| s.raw_value())
+                .filter(|v| !v.is_empty())
+        };
+        let text = |n| {
+            segment
+                .field(n)
+                .map(|f| f.raw_value())
+                .filter(|v| !v.is_empty())
+                .map(|v| separators.decode(v).to_string())
+        };
+        let timestamp = |n, name| match segment.field(n).filter(|f| !f.is_empty()) {
+            Some(field) => TimeStamp::from_field(field, separators)
+                .map(Some)
+                .map_err(|e| TimingParseError::InvalidDateTime(name, e)),
+            None => Ok(None),
+        };
+        let service_duration = match (component(6, 1), component(6, 2)) {
+            (Some(quantity), Some(units)) => Some(ServiceDuration::Period(Period::from_quantity(
+                quantity, units,
+            )?)),
+            (Some(quantity), None) => {
+                return Err(TimingParseError::InvalidValue(
+                    "service duration",
+                    quantity.to_string(),
+                ))
+            }
+            _ => None,
+        };
+
+        Ok(TimingQuantity {
+            quantity: parse_quantity(component(2, 1))?,
+            quantity_units: component(2, 2).map(str::to_string),
+            repeat_patterns: repeats(3)
+                .map(|r| RepeatPattern::from_repeat(r, separators))
+                .collect::<Result<_, _>>()?,
+            explicit_times: repeats(4)
+                .map(|r| parse_explicit_time(r.raw_value()))
+                .collect::<Result<_, _>>()?,
+            service_duration,
+            start: timestamp(7, "start date/time")?,
+            end: timestamp(8, "end date/time")?,
+            priorities: repeats(9)
+                .filter_map(|r| r.component(1).and_then(|c| c.subcomponent(1)))
+                .map(|s| s.raw_value().to_string())
+                .collect(),
+            condition: text(10),
+            text: text(11),
+            conjunction: text(12),
+            total_occurrences: parse_count(component(14, 1))?,
+        })
+    }
+}
+
+fn parse_quantity(value: Option<&str>) -> Result<Option<Decimal>, TimingParseError> {
+    value
+        .map(|v| {
+            v.parse()
+                .map_err(|_| TimingParseError::InvalidValue("quantity", v.to_string()))
+        })
+        .transpose()
+}
+
+fn parse_count(value: Option<&str>) -> Result<Option<u32>, TimingParseError> {
+    value
+        .map(|v| {
+            v.parse()
+                .map_err(|_| TimingParseError::InvalidValue("total occurrences", v.to_string()))
+        })
+        .transpose()
+}
+
+fn parse_explicit_time(value: &str) -> Result<Time, TimingParseError> {
+    parse_time(value, false).map_err(|e| TimingParseError::InvalidDateTime("explicit time", e))
+}
+
+/// Parse a `TQ` value using the default separators
+impl FromStr for TimingQuantity {
+    type Err = TimingParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TimingQuantity::from_repeat(&crate::parser::parse_repeat(s)?, &Separators::default())
+    }
+}
+
+/// Format as a `TQ` value using the default separators. Only the frequency of the first repeat
+/// pattern and the first priority can be represented.
+impl Display for TimingQuantity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let separators = Separators::default();
+        let encode = |v: &Option<String>| {
+            v.as_deref()
+                .map(|v| separators.encode(v).to_string())
+                .unwrap_or_default()
+        };
+        let subcomponents = |parts: &[String]| {
+            let mut s = String::new();
+            let len = parts
+                .iter()
+                .rposition(|c| !c.is_empty())
+                .map_or(0, |i| i + 1);
+            for (i, part) in parts[..len].iter().enumerate() {
+                if i > 0 {
+                    s.push(separators.subcomponent);
+                }
+                s.push_str(part);
+            }
+            s
+        };
+        let times = self
+            .explicit_times
+            .iter()
+            .map(|t| t.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let components = [
+            subcomponents(&[display(self.quantity), encode(&self.quantity_units)]),
+            subcomponents(&[
+                display(self.repeat_patterns.first().map(|p| &p.frequency)),
+                times,
+            ]),
+            display(self.service_duration),
+            display(self.start.as_ref()),
+            display(self.end.as_ref()),
+            encode(&self.priorities.first().cloned()),
+            encode(&self.condition),
+            encode(&self.text),
+            encode(&self.conjunction),
+            String::new(),
+            String::new(),
+            display(self.total_occurrences),
+        ];
+        write_components(f, &components, separators.component)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Message;
+    use pretty_assertions_sorted::assert_eq;
+
+    #[test]
+    fn can_parse_frequencies() {
+        let cases = [
+            ("Q30M", Frequency::Every(Period::new(30, TimeUnit::Minute))),
+            ("q2d", Frequency::Every(Period::new(2, TimeUnit::Day))),
+            ("Q1L", Frequency::Every(Period::new(1, TimeUnit::Month))),
+            ("BID", Frequency::TimesPerDay(2)),
+            ("6ID", Frequency::TimesPerDay(6)),
+            (
+                "Q2J2",
+                Frequency::Weekly {
+                    every: 2,
+                    days: vec![2],
+                },
+            ),
+            (
+                "QJ135",
+                Frequency::Weekly {
+                    every: 1,
+                    days: vec![1, 3, 5],
+                },
+            ),
+            ("QOD", Frequency::EveryOtherDay),
+            ("QSHIFT", Frequency::EveryShift),
+            ("PRN", Frequency::AsNeeded(None)),
+            ("AC", Frequency::Other("AC".into())),
+            ("Q0H", Frequency::Other("Q0H".into())),
+            ("QJ8", Frequency::Other("QJ8".into())),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(Frequency::from_code(code), expected, "{code}");
+        }
+        assert_eq!(Frequency::from_code("q2d").to_string(), "Q2D");
+        assert_eq!(Frequency::from_code("QJ135").to_string(), "Q1J135");
+    }
+
+    #[test]
+    fn frequencies_have_intervals() {
+        let interval = |code| Frequency::from_code(code).interval();
+        assert_eq!(interval("BID"), Some(Period::new(12, TimeUnit::Hour)));
+        assert_eq!(interval("QID"), Some(Period::new(6, TimeUnit::Hour)));
+        assert_eq!(interval("5ID"), Some(Period::new(288, TimeUnit::Minute)));
+        assert_eq!(interval("Q2J3"), Some(Period::new(2, TimeUnit::Week)));
+        assert_eq!(interval("QJ135"), None);
+        assert_eq!(interval("QOD"), Some(Period::new(2, TimeUnit::Day)));
+        assert_eq!(interval("PRNQ4H"), Some(Period::new(4, TimeUnit::Hour)));
+        assert_eq!(interval("PRN"), None);
+        assert_eq!(interval("ONCE"), None);
+    }
+
+    #[test]
+    fn can_parse_service_durations() {
+        assert_eq!(
+            "L3".parse::<ServiceDuration>().unwrap(),
+            ServiceDuration::Period(Period::new(3, TimeUnit::Month))
+        );
+        assert_eq!(
+            "indef".parse::<ServiceDuration>().unwrap(),
+            ServiceDuration::Indefinite
+        );
+        assert_eq!("T20".parse::<ServiceDuration>().unwrap().to_string(), "T20");
+        assert!("Z3".parse::<ServiceDuration>().is_err());
+        assert!("D".parse::<ServiceDuration>().is_err());
+    }
+
+    #[test]
+    fn can_parse_repeat_patterns() {
+        let pattern: RepeatPattern = "Q1J6^DW^6^6^1^W^Y".parse().unwrap();
+        assert_eq!(
+            pattern.frequency,
+            Frequency::Weekly {
+                every: 1,
+                days: vec![6],
+            }
+        );
+        assert_eq!(pattern.calendar_alignment.as_deref(), Some("DW"));
+        assert_eq!(pattern.phase_range_begin, Some(6));
+        assert_eq!(pattern.period, Some(Period::new(1, TimeUnit::Week)));
+        assert_eq!(pattern.institution_specified_time, Some(true));
+        assert_eq!(pattern.to_string(), "Q1J6^DW^6^6^1^W^Y");
+
+        let pattern: RepeatPattern = "Q6H&Every 6 hours&HL70335".parse().unwrap();
+        assert_eq!(pattern, Frequency::from_code("Q6H").into());
+        assert!("Q6H^^^^1^fortnight".parse::<RepeatPattern>().is_err());
+        assert!("Q6H^^^^^^maybe".parse::<RepeatPattern>().is_err());
+
+        let message = Message::parse("MSH|^~#&|\rZZZ|Q6H^^^^^^^AC#T#PC").unwrap();
+        let repeat = message
+            .segment("ZZZ")
+            .unwrap()
+            .field(1)
+            .unwrap()
+            .repeat(1)
+            .unwrap();
+        let pattern = RepeatPattern::from_repeat(repeat, &message.separators).unwrap();
+        assert_eq!(pattern.event.as_deref(), Some("AC&PC"));
+        assert_eq!(pattern.to_string(), "Q6H^^^^^^^AC\\T\\PC");
+    }
+
+    #[test]
+    fn can_parse_timing_quantities() {
+        let tq: TimingQuantity = "^PRNQ4H&0800,1600^INDEF^^^^^Take \\T\\ swallow"
+            .parse()
+            .unwrap();
+        assert_eq!(tq.quantity, None);
+        assert_eq!(
+            tq.repeat_patterns[0].frequency,
+            Frequency::from_code("PRNQ4H")
+        );
+        assert_eq!(tq.explicit_times.len(), 2);
+        assert_eq!(tq.explicit_times[1].hour, 16);
+        assert_eq!(tq.service_duration, Some(ServiceDuration::Indefinite));
+        assert_eq!(tq.text.as_deref(), Some("Take & swallow"));
+        assert_eq!(
+            tq.to_string(),
+            "^PRNQ4H&0800,1600^INDEF^^^^^Take \\T\\ swallow"
+        );
+
+        let tq: TimingQuantity = "^^^^^^^^^^^3".parse().unwrap();
+        assert_eq!(tq.total_occurrences, Some(3));
+        assert!("one^Q6H".parse::<TimingQuantity>().is_err());
+        assert!("^Q6H&noon".parse::<TimingQuantity>().is_err());
+        assert!("^^^tomorrow".parse::<TimingQuantity>().is_err());
+    }
+
+    #[test]
+    fn can_parse_tq1_segments() {
+        let message = Message::parse(
+            "MSH|^~\\&|||||||RDE^O11|1|P|2.5.1\rTQ1|1|500^mg&milligram&UCUM|Q8H~QHS^^^^^^^HS^30^M||^|7^d|20230312080000|20230319|S~R|If pain \\T\\ fever|With food|S||21",
+        )
+        .unwrap();
+        let tq1 = message.segment("TQ1").unwrap();
+        let timing = TimingQuantity::from_tq1(tq1, &message.separators).unwrap();
+        assert_eq!(
+            timing,
+            TimingQuantity {
+                quantity: Some("500".parse().unwrap()),
+                quantity_units: Some("mg".into()),
+                repeat_patterns: vec![
+                    Frequency::from_code("Q8H").into(),
+                    RepeatPattern {
+                        event: Some("HS".into()),
+                        event_offset: Some(Period::new(30, TimeUnit::Minute)),
+                        ..Frequency::Bedtime.into()
+                    },
+                ],
+                explicit_times: vec![],
+                service_duration: Some(ServiceDuration::Period(Period::new(7, TimeUnit::Day))),
+                start: Some("20230312080000".parse().unwrap()),
+                end: Some("20230319".parse().unwrap()),
+                priorities: vec!["S".into(), "R".into()],
+                condition: Some("If pain & fever".into()),
+                text: Some("With food".into()),
+                conjunction: Some("S".into()),
+                total_occurrences: Some(21),
+            }
+        );
+
+        let message = Message::parse("MSH|^~\\&|||||||RDE^O11|1|P|2.5.1\rTQ1|1||||||bad").unwrap();
+        let tq1 = message.segment("TQ1").unwrap();
+        assert!(TimingQuantity::from_tq1(tq1, &message.separators).is_err());
+    }
+}
@@ -0,0 +1,374 @@
+use super::{TimeStamp, TimeStampOffset};
+use crate::Message;
+use jiff::{
+    civil::DateTime,
+    tz::{AmbiguousOffset, Disambiguation, Offset, TimeZone},
+    Timestamp,
+};
+
+/// Errors that can result from resolving a timestamp to an instant
+#[derive(thiserror::Error, Debug)]
+pub enum ResolveError {
+    /// The timestamp has no offset, and the context has no zone or default offset to use
+    #[error("Timestamp has no offset, and no time zone or default offset was configured")]
+    NoZone,
+    /// The timestamp isn't a valid date and time
+    #[error("Timestamp {0} is not a valid date and time: {1}")]
+    InvalidTimeStamp(String, #[source] jiff::Error),
+    /// The timestamp's offset is out of range
+    #[error("Offset {0} is out of range: {1}")]
+    InvalidOffset(TimeStampOffset, #[source] jiff::Error),
+    /// The local time falls in a gap or fold in the zone, and the context rejects those
+    #[error("Local time {0} is ambiguous in time zone {1}")]
+    Ambiguous(String, String),
+    /// The zone name isn't in the time zone database
+    #[error("Unknown time zone `{0}`: {1}")]
+    UnknownZone(String, #[source] jiff::Error),
+}
+
+/// Where the offset used to resolve a timestamp came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OffsetSource {
+    /// The timestamp had its own offset
+    TimeStamp,
+    /// The timestamp's local time was looked up in the context's time zone, named by its IANA
+    /// name if it has one
+    Zone(Option<String>),
+    /// The context's default offset was used, such as the offset of the message's `MSH-7`
+    DefaultOffset,
+}
+
+/// A local time which doesn't map to exactly one instant in a time zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ambiguity {
+    /// The local time was skipped, such as when clocks go forward for daylight saving time
+    Gap {
+        /// The offset before the gap
+        before: Offset,
+        /// The offset after the gap
+        after: Offset,
+    },
+    /// The local time happened twice, such as when clocks go back at the end of daylight
+    /// saving time
+    Fold {
+        /// The offset of the first occurrence
+        before: Offset,
+        /// The offset of the second occurrence
+        after: Offset,
+    },
+}
+
+/// The result of resolving a timestamp to an instant, along with how the offset was decided
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolution {
+    /// The instant the timestamp refers to. Missing parts of the timestamp are taken as the
+    /// start of the span it covers, e.g. midnight for a date.
+    pub instant: Timestamp,
+    /// The offset from UTC that was used
+    pub offset: Offset,
+    /// Where the offset came from
+    pub source: OffsetSource,
+    /// Set if the local time fell in a gap or fold in the context's time zone, in which case the
+    /// offset was chosen using the context's [`Disambiguation`]
+    pub ambiguity: Option<Ambiguity>,
+}
+
+/// How to resolve timestamps which don't have an offset. HL7 says these are in the sender's
+/// local time, which a receiver knows from the offset of the message's `MSH-7` or from site
+/// configuration.
+///
+/// A timestamp's own offset always wins. Otherwise, the configured time zone is used, since it
+/// knows about daylight saving time; then the default offset; and otherwise resolution fails.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::{datetime::{OffsetSource, TimeStamp, ZoneContext}, Message};
+///
+/// let message = Message::parse("MSH|^~\\&|||||20230312195905-0700||ADT^A01|1|P|2.5.1\rPID|1||||Doe||19800101").unwrap();
+/// let context = ZoneContext::from_message(&message);
+/// let birth: TimeStamp = "198001011200".parse().unwrap();
+/// let resolution = birth.resolve(&context).unwrap();
+/// assert_eq!(resolution.instant.to_string(), "1980-01-01T19:00:00Z");
+/// assert_eq!(resolution.source, OffsetSource::DefaultOffset);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ZoneContext {
+    zone: Option<TimeZone>,
+    default_offset: Option<Offset>,
+    disambiguation: Disambiguation,
+}
+
+impl Default for ZoneContext {
+    fn default() -> Self {
+        ZoneContext::new()
+    }
+}
+
+impl ZoneContext {
+    /// A context with no zone or default offset, which only resolves timestamps with their own
+    /// offsets. Gaps and folds are resolved the way [`Disambiguation::Compatible`] does.
+    pub fn new() -> Self {
+        ZoneContext {
+            zone: None,
+            default_offset: None,
+            disambiguation: Disambiguation::Compatible,
+        }
+    }
+
+    /// A context using the offset of the message's `MSH-7` as the default offset, if it has one
+    pub fn from_message(message: &Message) -> Self {
+        let offset = message
+            .segment("MSH")
+            .and_then(|msh| msh.field(7))
+            .and_then(|field| TimeStamp::from_field(field, &message.separators).ok())
+            .and_then(|ts| ts.offset)
+            .and_then(|offset| to_offset(offset).ok());
+        ZoneContext {
+            default_offset: offset,
+            ..ZoneContext::new()
+        }
+    }
+
+    /// A context with no default offset which uses the UTC zone
+    pub fn utc() -> Self {
+        ZoneContext::new().with_zone(TimeZone::UTC)
+    }
+
+    /// Use the given time zone for timestamps without offsets
+    pub fn with_zone(mut self, zone: TimeZone) -> Self {
+        self.zone = Some(zone);
+        self
+    }
+
+    /// Use the time zone with the given IANA name, such as `America/Edmonton`, from the time
+    /// zone database
+    pub fn with_zone_name(self, name: &str) -> Result<Self, ResolveError> {
+        let zone =
+            TimeZone::get(name).map_err(|e| ResolveError::UnknownZone(name.to_string(), e))?;
+        Ok(self.with_zone(zone))
+    }
+
+    /// Use the given offset for timestamps without offsets when there is no time zone
+    pub fn with_default_offset(mut self, offset: TimeStampOffset) -> Result<Self, ResolveError> {
+        self.default_offset = Some(to_offset(offset)?);
+        Ok(self)
+    }
+
+    /// How to resolve local times which fall in a gap or fold in the time zone.
+    /// [`Disambiguation::Reject`] makes them an error.
+    pub fn with_disambiguation(mut self, disambiguation: Disambiguation) -> Self {
+        self.disambiguation = disambiguation;
+        self
+    }
+}
+
+fn to_offset(offset: TimeStampOffset) -> Result<Offset, ResolveError> {
+    let sign = if offset.hours < 0 { -1 } else { 1 };
+    let seconds = offset.hours as i32 * 3600 + sign * offset.minutes as i32 * 60;
+    Offset::from_seconds(seconds).map_err(|e| ResolveError::InvalidOffset(offset, e))
+}
+
+impl TimeStamp {
+    /// Resolve the timestamp to an instant, using its own offset if it has one or the context's
+    /// zone or default offset if it doesn't. Missing parts of the timestamp are taken as the
+    /// start of the span it covers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hl7_parser::datetime::{Ambiguity, OffsetSource, TimeStamp, ZoneContext};
+    /// use jiff::tz::{Disambiguation, TimeZone};
+    ///
+    /// let zone = TimeZone::posix("MST7MDT,M3.2.0,M11.1.0").unwrap();
+    /// let context = ZoneContext::new().with_zone(zone);
+    ///
+    /// // 02:30 didn't happen on 2023-03-12, as clocks went from 02:00 to 03:00
+    /// let ts: TimeStamp = "202303120230".parse().unwrap();
+    /// let resolution = ts.resolve(&context).unwrap();
+    /// assert_eq!(resolution.instant.to_string(), "2023-03-12T09:30:00Z");
+    /// assert!(matches!(resolution.ambiguity, Some(Ambiguity::Gap { .. })));
+    ///
+    /// let strict = context.with_disambiguation(Disambiguation::Reject);
+    /// assert!(ts.resolve(&strict).is_err());
+    /// ```
+    pub fn resolve(&self, context: &ZoneContext) -> Result<Resolution, ResolveError> {
+        let invalid = |e| ResolveError::InvalidTimeStamp(self.to_string(), e);
+        let datetime = DateTime::new(
+            self.year as i16,
+            self.month.unwrap_or(1) as i8,
+            self.day.unwrap_or(1) as i8,
+            self.hour.unwrap_or_default() as i8,
+            self.minute.unwrap_or_default() as i8,
+            self.second.unwrap_or_default() as i8,
+            self.microsecond.unwrap_or_default() as i32 * 1_000,
+        )
+        .map_err(invalid)?;
+
+        let fixed = |offset: Offset, source: OffsetSource| {
+            let instant = offset.to_timestamp(datetime).map_err(invalid)?;
+            Ok(Resolution {
+                instant,
+                offset,
+                source,
+                ambiguity: None,
+            })
+        };
+        if let Some(offset) = self.offset {
+            return fixed(to_offset(offset)?, OffsetSource::TimeStamp);
+        }
+        let Some(zone) = &context.zone else {
+            return match context.default_offset {
+                Some(offset) => fixed(offset, OffsetSource::DefaultOffset),
+                None => Err(ResolveError::NoZone),
+            };
+        };
+
+        let ambiguous = zone.to_ambiguous_timestamp(datetime);
+        let ambiguity = match ambiguous.offset() {
+            AmbiguousOffset::Unambiguous { .. } => None,
+            AmbiguousOffset::Gap { before, after } => Some(Ambiguity::Gap { before, after }),
+            AmbiguousOffset::Fold { before, after } => Some(Ambiguity::Fold { before, after }),
+        };
+        let zone_name = || {
+            zone.iana_name()
+                .unwrap_or("the configured zone")
+                .to_string()
+        };
+        let instant = ambiguous
+            .disambiguate(context.disambiguation)
+            .map_err(|_| ResolveError::Ambiguous(datetime.to_string(), zone_name()))?;
+        Ok(Resolution {
+            instant,
+            offset: zone.to_offset(instant),
+            source: OffsetSource::Zone(zone.iana_name().map(str::to_string)),
+            ambiguity,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    fn ts(s: &str) -> TimeStamp {
+        s.parse().expect("can parse timestamp")
+    }
+
+    fn mountain() -> ZoneContext {
+        ZoneContext::new().with_zone(TimeZone::posix("MST7MDT,M3.2.0,M11.1.0").unwrap())
+    }
+
+    #[test]
+    fn own_offset_wins() {
+        let resolution = ts("202301011200-0330").resolve(&mountain()).unwrap();
+        assert_eq!(resolution.instant.to_string(), "2023-01-01T15:30:00Z");
+        assert_eq!(resolution.offset, Offset::from_seconds(-12_600).unwrap());
+        assert_eq!(resolution.source, OffsetSource::TimeStamp);
+        assert_eq!(resolution.ambiguity, None);
+    }
+
+    #[test]
+    fn zone_follows_daylight_saving_time() {
+        let winter = ts("202301011200").resolve(&mountain()).unwrap();
+        assert_eq!(winter.instant.to_string(), "2023-01-01T19:00:00Z");
+        assert_eq!(winter.source, OffsetSource::Zone(None));
+        let summer = ts("202307011200").resolve(&mountain()).unwrap();
+        assert_eq!(summer.instant.to_string(), "2023-07-01T18:00:00Z");
+        assert_eq!(summer.offset, Offset::from_hours(-6).unwrap());
+    }
+
+    #[test]
+    fn zone_is_preferred_over_default_offset() {
+        let context = mountain()
+            .with_default_offset(TimeStampOffset {
+                hours: 5,
+                minutes: 30,
+            })
+            .unwrap();
+        let resolution = ts("20230101").resolve(&context).unwrap();
+        assert_eq!(resolution.instant.to_string(), "2023-01-01T07:00:00Z");
+
+        let context = ZoneContext::new()
+            .with_default_offset(TimeStampOffset {
+                hours: 5,
+                minutes: 30,
+            })
+            .unwrap();
+        let resolution = ts("20230101").resolve(&context).unwrap();
+        assert_eq!(resolution.instant.to_string(), "2022-12-31T18:30:00Z");
+        assert_eq!(resolution.source, OffsetSource::DefaultOffset);
+    }
+
+    #[test]
+    fn folds_are_disambiguated() {
+        // 01:30 happened twice on 2023-11-05, first in MDT and then in MST
+        let fold = ts("202311050130");
+        let earlier = fold.resolve(&mountain()).unwrap();
+        assert_eq!(earlier.instant.to_string(), "2023-11-05T07:30:00Z");
+        assert_eq!(
+            earlier.ambiguity,
+            Some(Ambiguity::Fold {
+                before: Offset::from_hours(-6).unwrap(),
+                after: Offset::from_hours(-7).unwrap(),
+            })
+        );
+
+        let later = fold
+            .resolve(&mountain().with_disambiguation(Disambiguation::Later))
+            .unwrap();
+        assert_eq!(later.instant.to_string(), "2023-11-05T08:30:00Z");
+        assert_eq!(later.offset, Offset::from_hours(-7).unwrap());
+
+        let rejected = fold.resolve(&mountain().with_disambiguation(Disambiguation::Reject));
+        assert!(matches!(rejected, Err(ResolveError::Ambiguous(..))));
+    }
+
+    #[test]
+    fn gaps_are_disambiguated() {
+        let gap = ts("202303120230");
+        let resolution = gap
+            .resolve(&mountain().with_disambiguation(Disambiguation::Earlier))
+            .unwrap();
+        assert_eq!(resolution.instant.to_string(), "2023-03-12T08:30:00Z");
+        assert_eq!(
+            resolution.ambiguity,
+            Some(Ambiguity::Gap {
+                before: Offset::from_hours(-7).unwrap(),
+                after: Offset::from_hours(-6).unwrap(),
+            })
+        );
+    }
+
+    #[test]
+    fn needs_a_zone_or_offset() {
+        assert!(matches!(
+            ts("20230101").resolve(&ZoneContext::new()),
+            Err(ResolveError::NoZone)
+        ));
+        assert!(matches!(
+            ts("20231301").resolve(&ZoneContext::utc()),
+            Err(ResolveError::InvalidTimeStamp(..))
+        ));
+        assert!(matches!(
+            ZoneContext::new().with_zone_name("Not/A_Zone"),
+            Err(ResolveError::UnknownZone(..))
+        ));
+    }
+
+    #[test]
+    fn uses_the_message_offset() {
+        let message =
+            Message::parse("MSH|^~\\&|||||20230312195905+0100||ADT^A01|1|P|2.5.1").unwrap();
+        let resolution = ts("202307011200")
+            .resolve(&ZoneContext::from_message(&message))
+            .unwrap();
+        assert_eq!(resolution.instant.to_string(), "2023-07-01T11:00:00Z");
+
+        let message = Message::parse("MSH|^~\\&|||||20230312195905||ADT^A01|1|P|2.5.1").unwrap();
+        assert!(ts("202307011200")
+            .resolve(&ZoneContext::from_message(&message))
+            .is_err());
+    }
+}
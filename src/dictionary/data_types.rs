@@ -0,0 +1,630 @@
+// This file is generated by `dictionary/generate.py` from the tables in `dictionary/`. Don't edit
+// it by hand: change the tables and re-run the script instead.
+
+use super::{ComponentDefinition, DataTypeDefinition, Optionality, Version, Version::*};
+
+const R: Optionality = Optionality::Required;
+const O: Optionality = Optionality::Optional;
+const C: Optionality = Optionality::Conditional;
+const B: Optionality = Optionality::BackwardCompatible;
+
+/// Define a component which applies to all versions. A `table` of `0` means the component has
+/// no associated table.
+const fn c(
+    position: usize,
+    name: &'static str,
+    data_type: &'static str,
+    optionality: Optionality,
+    table: u16,
+) -> ComponentDefinition {
+    ComponentDefinition {
+        position,
+        name,
+        data_type,
+        optionality,
+        table: if table == 0 { None } else { Some(table) },
+        since: V2_1,
+        until: None,
+    }
+}
+
+impl ComponentDefinition {
+    const fn since(mut self, version: Version) -> Self {
+        self.since = version;
+        self
+    }
+
+    const fn until(mut self, version: Version) -> Self {
+        self.until = Some(version);
+        self
+    }
+}
+
+const fn primitive(name: &'static str, description: &'static str) -> DataTypeDefinition {
+    DataTypeDefinition {
+        name,
+        description,
+        components: &[],
+    }
+}
+
+pub(super) static DATA_TYPES: &[DataTypeDefinition] = &[
+    primitive("DT", "Date"),
+    primitive("DTM", "Date/Time"),
+    primitive("FT", "Formatted Text Data"),
+    primitive("GTS", "General Timing Specification"),
+    primitive("ID", "Coded Value for HL7 Defined Tables"),
+    primitive("IS", "Coded Value for User-Defined Tables"),
+    primitive("NM", "Numeric"),
+    primitive("SI", "Sequence ID"),
+    primitive("ST", "String Data"),
+    primitive("TM", "Time"),
+    primitive("TN", "Telephone Number"),
+    primitive("TX", "Text Data"),
+    primitive("varies", "Variable Data Type"),
+    DataTypeDefinition {
+        name: "AD",
+        description: "Address",
+        components: &[
+            c(1, "Street Address", "ST", O, 0),
+            c(2, "Other Designation", "ST", O, 0),
+            c(3, "City", "ST", O, 0),
+            c(4, "State or Province", "ST", O, 0),
+            c(5, "Zip or Postal Code", "ST", O, 0),
+            c(6, "Country", "ID", O, 0),
+            c(7, "Address Type", "ID", O, 190),
+            c(8, "Other Geographic Designation", "ST", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "AUI",
+        description: "Authorization Information",
+        components: &[
+            c(1, "Authorization Number", "ST", O, 0),
+            c(2, "Date", "DT", O, 0),
+            c(3, "Source", "ST", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "CE",
+        description: "Coded Element",
+        components: &[
+            c(1, "Identifier", "ST", O, 0),
+            c(2, "Text", "ST", O, 0),
+            c(3, "Name of Coding System", "ID", O, 396),
+            c(4, "Alternate Identifier", "ST", O, 0),
+            c(5, "Alternate Text", "ST", O, 0),
+            c(6, "Name of Alternate Coding System", "ID", O, 396),
+        ],
+    },
+    DataTypeDefinition {
+        name: "CK",
+        description: "Composite ID with Check Digit",
+        components: &[
+            c(1, "ID Number", "NM", O, 0),
+            c(2, "Check Digit", "NM", O, 0),
+            c(
+                3,
+                "Code Identifying the Check Digit Scheme Employed",
+                "ID",
+                O,
+                61,
+            ),
+            c(4, "Assigning Authority", "HD", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "CM_MSG",
+        description: "Message Type",
+        components: &[
+            c(1, "Message Type", "ID", R, 76),
+            c(2, "Trigger Event", "ID", O, 3),
+            c(3, "Message Structure", "ID", O, 354).since(V2_3_1),
+        ],
+    },
+    DataTypeDefinition {
+        name: "CN",
+        description: "Composite ID Number and Name",
+        components: &[
+            c(1, "ID Number", "ST", O, 0),
+            c(2, "Family Name", "ST", O, 0),
+            c(3, "Given Name", "ST", O, 0),
+            c(4, "Middle Initial or Name", "ST", O, 0),
+            c(5, "Suffix (e.g., JR or III)", "ST", O, 0),
+            c(6, "Prefix (e.g., DR)", "ST", O, 0),
+            c(7, "Degree (e.g., MD)", "ST", O, 0),
+            c(8, "Source Table", "ID", O, 0),
+            c(9, "Assigning Authority", "HD", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "CNE",
+        description: "Coded with No Exceptions",
+        components: &[
+            c(1, "Identifier", "ST", R, 0),
+            c(2, "Text", "ST", O, 0),
+            c(3, "Name of Coding System", "ID", O, 396),
+            c(4, "Alternate Identifier", "ST", O, 0),
+            c(5, "Alternate Text", "ST", O, 0),
+            c(6, "Name of Alternate Coding System", "ID", O, 396),
+            c(7, "Coding System Version ID", "ST", O, 0).since(V2_4),
+            c(8, "Alternate Coding System Version ID", "ST", O, 0).since(V2_4),
+            c(9, "Original Text", "ST", O, 0).since(V2_4),
+        ],
+    },
+    DataTypeDefinition {
+        name: "CNN",
+        description: "Composite ID Number and Name Simplified",
+        components: &[
+            c(1, "ID Number", "ST", O, 0),
+            c(2, "Family Name", "ST", O, 0),
+            c(3, "Given Name", "ST", O, 0),
+            c(
+                4,
+                "Second and Further Given Names or Initials Thereof",
+                "ST",
+                O,
+                0,
+            ),
+            c(5, "Suffix (e.g., JR or III)", "ST", O, 0),
+            c(6, "Prefix (e.g., DR)", "ST", O, 0),
+            c(7, "Degree (e.g., MD)", "IS", O, 360),
+            c(8, "Source Table", "IS", C, 297),
+            c(9, "Assigning Authority - Namespace ID", "IS", C, 363),
+            c(10, "Assigning Authority - Universal ID", "ST", C, 0),
+            c(11, "Assigning Authority - Universal ID Type", "ID", C, 301),
+        ],
+    },
+    DataTypeDefinition {
+        name: "CP",
+        description: "Composite Price",
+        components: &[
+            c(1, "Price", "MO", R, 0),
+            c(2, "Price Type", "ID", O, 205),
+            c(3, "From Value", "NM", O, 0),
+            c(4, "To Value", "NM", O, 0),
+            c(5, "Range Units", "CE", C, 0),
+            c(6, "Range Type", "ID", O, 298),
+        ],
+    },
+    DataTypeDefinition {
+        name: "CQ",
+        description: "Composite Quantity with Units",
+        components: &[c(1, "Quantity", "NM", O, 0), c(2, "Units", "CE", O, 0)],
+    },
+    DataTypeDefinition {
+        name: "CWE",
+        description: "Coded with Exceptions",
+        components: &[
+            c(1, "Identifier", "ST", O, 0),
+            c(2, "Text", "ST", O, 0),
+            c(3, "Name of Coding System", "ID", O, 396),
+            c(4, "Alternate Identifier", "ST", O, 0),
+            c(5, "Alternate Text", "ST", O, 0),
+            c(6, "Name of Alternate Coding System", "ID", O, 396),
+            c(7, "Coding System Version ID", "ST", C, 0).since(V2_4),
+            c(8, "Alternate Coding System Version ID", "ST", O, 0).since(V2_4),
+            c(9, "Original Text", "ST", O, 0).since(V2_4),
+        ],
+    },
+    DataTypeDefinition {
+        name: "CX",
+        description: "Extended Composite ID with Check Digit",
+        components: &[
+            c(1, "ID Number", "ST", R, 0),
+            c(2, "Check Digit", "ST", O, 0),
+            c(3, "Check Digit Scheme", "ID", O, 61),
+            c(4, "Assigning Authority", "HD", O, 363),
+            c(5, "Identifier Type Code", "ID", R, 203),
+            c(6, "Assigning Facility", "HD", O, 0),
+            c(7, "Effective Date", "DT", O, 0).since(V2_5),
+            c(8, "Expiration Date", "DT", O, 0).since(V2_5),
+            c(9, "Assigning Jurisdiction", "CWE", O, 0).since(V2_5),
+            c(10, "Assigning Agency or Department", "CWE", O, 0).since(V2_5),
+        ],
+    },
+    DataTypeDefinition {
+        name: "DLD",
+        description: "Discharge to Location and Date",
+        components: &[
+            c(1, "Discharge Location", "IS", R, 113),
+            c(2, "Effective Date", "TS", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "DLN",
+        description: "Driver's License Number",
+        components: &[
+            c(1, "License Number", "ST", R, 0),
+            c(2, "Issuing State, Province, Country", "IS", O, 333),
+            c(3, "Expiration Date", "DT", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "DR",
+        description: "Date/Time Range",
+        components: &[
+            c(1, "Range Start Date/Time", "TS", O, 0),
+            c(2, "Range End Date/Time", "TS", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "ED",
+        description: "Encapsulated Data",
+        components: &[
+            c(1, "Source Application", "HD", O, 0),
+            c(2, "Type of Data", "ID", R, 191),
+            c(3, "Data Subtype", "ID", O, 291),
+            c(4, "Encoding", "ID", R, 299),
+            c(5, "Data", "TX", R, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "EI",
+        description: "Entity Identifier",
+        components: &[
+            c(1, "Entity Identifier", "ST", O, 0),
+            c(2, "Namespace ID", "IS", O, 363),
+            c(3, "Universal ID", "ST", C, 0),
+            c(4, "Universal ID Type", "ID", C, 301),
+        ],
+    },
+    DataTypeDefinition {
+        name: "EIP",
+        description: "Entity Identifier Pair",
+        components: &[
+            c(1, "Placer Assigned Identifier", "EI", O, 0),
+            c(2, "Filler Assigned Identifier", "EI", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "ELD",
+        description: "Error Location and Description",
+        components: &[
+            c(1, "Segment ID", "ST", O, 0),
+            c(2, "Segment Sequence", "NM", O, 0),
+            c(3, "Field Position", "NM", O, 0),
+            c(4, "Code Identifying Error", "CE", O, 357),
+        ],
+    },
+    DataTypeDefinition {
+        name: "ERL",
+        description: "Error Location",
+        components: &[
+            c(1, "Segment ID", "ST", R, 0),
+            c(2, "Segment Sequence", "NM", R, 0),
+            c(3, "Field Position", "NM", O, 0),
+            c(4, "Field Repetition", "NM", O, 0),
+            c(5, "Component Number", "NM", O, 0),
+            c(6, "Sub-Component Number", "NM", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "FC",
+        description: "Financial Class",
+        components: &[
+            c(1, "Financial Class Code", "IS", R, 64),
+            c(2, "Effective Date", "TS", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "FN",
+        description: "Family Name",
+        components: &[
+            c(1, "Surname", "ST", R, 0),
+            c(2, "Own Surname Prefix", "ST", O, 0),
+            c(3, "Own Surname", "ST", O, 0),
+            c(4, "Surname Prefix from Partner/Spouse", "ST", O, 0),
+            c(5, "Surname from Partner/Spouse", "ST", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "HD",
+        description: "Hierarchic Designator",
+        components: &[
+            c(1, "Namespace ID", "IS", O, 300),
+            c(2, "Universal ID", "ST", C, 0),
+            c(3, "Universal ID Type", "ID", C, 301),
+        ],
+    },
+    DataTypeDefinition {
+        name: "JCC",
+        description: "Job Code/Class",
+        components: &[
+            c(1, "Job Code", "IS", O, 327),
+            c(2, "Job Class", "IS", O, 328),
+            c(3, "Job Description Text", "TX", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "MO",
+        description: "Money",
+        components: &[
+            c(1, "Quantity", "NM", O, 0),
+            c(2, "Denomination", "ID", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "MOC",
+        description: "Money and Charge Code",
+        components: &[
+            c(1, "Monetary Amount", "MO", O, 0),
+            c(2, "Charge Code", "CE", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "MSG",
+        description: "Message Type",
+        components: &[
+            c(1, "Message Code", "ID", R, 76),
+            c(2, "Trigger Event", "ID", R, 3),
+            c(3, "Message Structure", "ID", R, 354),
+        ],
+    },
+    DataTypeDefinition {
+        name: "NDL",
+        description: "Name with Date and Location",
+        components: &[
+            c(1, "Name", "CNN", O, 0),
+            c(2, "Start Date/Time", "TS", O, 0),
+            c(3, "End Date/Time", "TS", O, 0),
+            c(4, "Point of Care", "IS", O, 302),
+            c(5, "Room", "IS", O, 303),
+            c(6, "Bed", "IS", O, 304),
+            c(7, "Facility", "HD", O, 0),
+            c(8, "Location Status", "IS", O, 306),
+            c(9, "Patient Location Type", "IS", O, 305),
+            c(10, "Building", "IS", O, 307),
+            c(11, "Floor", "IS", O, 308),
+        ],
+    },
+    DataTypeDefinition {
+        name: "PL",
+        description: "Person Location",
+        components: &[
+            c(1, "Point of Care", "IS", O, 302),
+            c(2, "Room", "IS", O, 303),
+            c(3, "Bed", "IS", O, 304),
+            c(4, "Facility", "HD", O, 0),
+            c(5, "Location Status", "IS", O, 306),
+            c(6, "Person Location Type", "IS", C, 305),
+            c(7, "Building", "IS", O, 307),
+            c(8, "Floor", "IS", O, 308),
+            c(9, "Location Description", "ST", O, 0),
+            c(10, "Comprehensive Location Identifier", "EI", O, 0).since(V2_5),
+            c(11, "Assigning Authority for Location", "HD", O, 0).since(V2_5),
+        ],
+    },
+    DataTypeDefinition {
+        name: "PN",
+        description: "Person Name",
+        components: &[
+            c(1, "Family Name", "ST", O, 0),
+            c(2, "Given Name", "ST", O, 0),
+            c(3, "Middle Initial or Name", "ST", O, 0),
+            c(4, "Suffix (e.g., JR or III)", "ST", O, 0),
+            c(5, "Prefix (e.g., DR)", "ST", O, 0),
+            c(6, "Degree (e.g., MD)", "ST", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "PRL",
+        description: "Parent Result Link",
+        components: &[
+            c(1, "Parent Observation Identifier", "CE", R, 0),
+            c(2, "Parent Observation Sub-identifier", "ST", O, 0),
+            c(3, "Parent Observation Value Descriptor", "TX", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "PT",
+        description: "Processing Type",
+        components: &[
+            c(1, "Processing ID", "ID", O, 103),
+            c(2, "Processing Mode", "ID", O, 207),
+        ],
+    },
+    DataTypeDefinition {
+        name: "RI",
+        description: "Repeat Interval",
+        components: &[
+            c(1, "Repeat Pattern", "IS", O, 335),
+            c(2, "Explicit Time Interval", "ST", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "RP",
+        description: "Reference Pointer",
+        components: &[
+            c(1, "Pointer", "ST", O, 0),
+            c(2, "Application ID", "HD", O, 0),
+            c(3, "Type of Data", "ID", O, 191),
+            c(4, "Subtype", "ID", O, 291),
+        ],
+    },
+    DataTypeDefinition {
+        name: "SAD",
+        description: "Street Address",
+        components: &[
+            c(1, "Street or Mailing Address", "ST", O, 0),
+            c(2, "Street Name", "ST", O, 0),
+            c(3, "Dwelling Number", "ST", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "SN",
+        description: "Structured Numeric",
+        components: &[
+            c(1, "Comparator", "ST", O, 0),
+            c(2, "Num1", "NM", O, 0),
+            c(3, "Separator/Suffix", "ST", O, 0),
+            c(4, "Num2", "NM", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "SPS",
+        description: "Specimen Source",
+        components: &[
+            c(1, "Specimen Source Name or Code", "CWE", O, 0),
+            c(2, "Additives", "CWE", O, 371),
+            c(3, "Specimen Collection Method", "TX", O, 0),
+            c(4, "Body Site", "CWE", O, 163),
+            c(5, "Site Modifier", "CWE", O, 0),
+            c(6, "Collection Method Modifier Code", "CWE", O, 0),
+            c(7, "Specimen Role", "CWE", O, 369).since(V2_5),
+        ],
+    },
+    DataTypeDefinition {
+        name: "TQ",
+        description: "Timing Quantity",
+        components: &[
+            c(1, "Quantity", "CQ", O, 0),
+            c(2, "Interval", "RI", O, 0),
+            c(3, "Duration", "ST", O, 0),
+            c(4, "Start Date/Time", "TS", O, 0),
+            c(5, "End Date/Time", "TS", O, 0),
+            c(6, "Priority", "ST", O, 0),
+            c(7, "Condition", "ST", O, 0),
+            c(8, "Text", "TX", O, 0),
+            c(9, "Conjunction", "ID", O, 472),
+            c(10, "Order Sequencing", "ST", O, 0),
+            c(11, "Occurrence Duration", "CE", O, 0),
+            c(12, "Total Occurrences", "NM", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "TS",
+        description: "Time Stamp",
+        components: &[
+            c(1, "Time", "DTM", R, 0),
+            c(2, "Degree of Precision", "ID", B, 529).until(V2_5_1),
+        ],
+    },
+    DataTypeDefinition {
+        name: "VID",
+        description: "Version Identifier",
+        components: &[
+            c(1, "Version ID", "ID", O, 104),
+            c(2, "Internationalization Code", "CE", O, 399),
+            c(3, "International Version ID", "CE", O, 0),
+        ],
+    },
+    DataTypeDefinition {
+        name: "XAD",
+        description: "Extended Address",
+        components: &[
+            c(1, "Street Address", "ST", O, 0).until(V2_3_1),
+            c(1, "Street Address", "SAD", O, 0).since(V2_4),
+            c(2, "Other Designation", "ST", O, 0),
+            c(3, "City", "ST", O, 0),
+            c(4, "State or Province", "ST", O, 0),
+            c(5, "Zip or Postal Code", "ST", O, 0),
+            c(6, "Country", "ID", O, 399),
+            c(7, "Address Type", "ID", O, 190),
+            c(8, "Other Geographic Designation", "ST", O, 0),
+            c(9, "County/Parish Code", "IS", O, 289),
+            c(10, "Census Tract", "IS", O, 288),
+            c(11, "Address Representation Code", "ID", O, 4000).since(V2_3_1),
+            c(12, "Address Validity Range", "DR", B, 0).since(V2_4),
+            c(13, "Effective Date", "TS", O, 0).since(V2_5),
+            c(14, "Expiration Date", "TS", O, 0).since(V2_5),
+        ],
+    },
+    DataTypeDefinition {
+        name: "XCN",
+        description: "Extended Composite ID Number and Name for Persons",
+        components: &[
+            c(1, "ID Number", "ST", O, 0),
+            c(2, "Family Name", "ST", O, 0).until(V2_3_1),
+            c(2, "Family Name", "FN", O, 0).since(V2_4),
+            c(3, "Given Name", "ST", O, 0),
+            c(
+                4,
+                "Second and Further Given Names or Initials Thereof",
+                "ST",
+                O,
+                0,
+            ),
+            c(5, "Suffix (e.g., JR or III)", "ST", O, 0),
+            c(6, "Prefix (e.g., DR)", "ST", O, 0),
+            c(7, "Degree (e.g., MD)", "IS", B, 360),
+            c(8, "Source Table", "IS", C, 297),
+            c(9, "Assigning Authority", "HD", C, 363),
+            c(10, "Name Type Code", "ID", O, 200),
+            c(11, "Identifier Check Digit", "ST", O, 0),
+            c(12, "Check Digit Scheme", "ID", C, 61),
+            c(13, "Identifier Type Code", "ID", O, 203),
+            c(14, "Assigning Facility", "HD", O, 0),
+            c(15, "Name Representation Code", "ID", O, 4000).since(V2_3_1),
+            c(16, "Name Context", "CE", O, 448).since(V2_4),
+            c(17, "Name Validity Range", "DR", B, 0).since(V2_4),
+            c(18, "Name Assembly Order", "ID", O, 444).since(V2_4),
+            c(19, "Effective Date", "TS", O, 0).since(V2_5),
+            c(20, "Expiration Date", "TS", O, 0).since(V2_5),
+            c(21, "Professional Suffix", "ST", O, 0).since(V2_5),
+            c(22, "Assigning Jurisdiction", "CWE", O, 0).since(V2_5),
+            c(23, "Assigning Agency or Department", "CWE", O, 0).since(V2_5),
+        ],
+    },
+    DataTypeDefinition {
+        name: "XON",
+        description: "Extended Composite Name and Identification Number for Organizations",
+        components: &[
+            c(1, "Organization Name", "ST", O, 0),
+            c(2, "Organization Name Type Code", "IS", O, 204),
+            c(3, "ID Number", "NM", B, 0),
+            c(4, "Check Digit", "NM", O, 0),
+            c(5, "Check Digit Scheme", "ID", O, 61),
+            c(6, "Assigning Authority", "HD", O, 363),
+            c(7, "Identifier Type Code", "ID", O, 203),
+            c(8, "Assigning Facility", "HD", O, 0),
+            c(9, "Name Representation Code", "ID", O, 4000).since(V2_3_1),
+            c(10, "Organization Identifier", "ST", O, 0).since(V2_5),
+        ],
+    },
+    DataTypeDefinition {
+        name: "XPN",
+        description: "Extended Person Name",
+        components: &[
+            c(1, "Family Name", "ST", O, 0).until(V2_3_1),
+            c(1, "Family Name", "FN", O, 0).since(V2_4),
+            c(2, "Given Name", "ST", O, 0),
+            c(
+                3,
+                "Second and Further Given Names or Initials Thereof",
+                "ST",
+                O,
+                0,
+            ),
+            c(4, "Suffix (e.g., JR or III)", "ST", O, 0),
+            c(5, "Prefix (e.g., DR)", "ST", O, 0),
+            c(6, "Degree (e.g., MD)", "IS", B, 360),
+            c(7, "Name Type Code", "ID", O, 200),
+            c(8, "Name Representation Code", "ID", O, 4000).since(V2_3_1),
+            c(9, "Name Context", "CE", O, 448).since(V2_4),
+            c(10, "Name Validity Range", "DR", B, 0).since(V2_4),
+            c(11, "Name Assembly Order", "ID", O, 444).since(V2_4),
+            c(12, "Effective Date", "TS", O, 0).since(V2_5),
+            c(13, "Expiration Date", "TS", O, 0).since(V2_5),
+            c(14, "Professional Suffix", "ST", O, 0).since(V2_5),
+        ],
+    },
+    DataTypeDefinition {
+        name: "XTN",
+        description: "Extended Telecommunication Number",
+        components: &[
+            c(1, "Telephone Number", "ST", B, 0),
+            c(2, "Telecommunication Use Code", "ID", O, 201),
+            c(3, "Telecommunication Equipment Type", "ID", O, 202),
+            c(4, "Email Address", "ST", O, 0),
+            c(5, "Country Code", "NM", O, 0),
+            c(6, "Area/City Code", "NM", O, 0),
+            c(7, "Local Number", "NM", O, 0),
+            c(8, "Extension", "NM", O, 0),
+            c(9, "Any Text", "ST", O, 0),
+            c(10, "Extension Prefix", "ST", O, 0).since(V2_6),
+            c(11, "Speed Dial Code", "ST", O, 0).since(V2_6),
+            c(12, "Unformatted Telephone Number", "ST", C, 0).since(V2_6),
+        ],
+    },
+];
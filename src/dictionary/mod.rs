@@ -0,0 +1,766 @@
+//! # HL7 field and component names
+//!
+//! This module contains a dictionary of segment, field, component and data type definitions
+//! for HL7 versions 2.1 through 2.8. It can be used to put human-readable labels on locations
+//! within a message, i.e. to turn `PID.5.1` into "Patient Name / Family Name".
+//!
+//! The tables in `segments.rs` and `data_types.rs` are generated by `dictionary/generate.py`
+//! from the tab-separated tables in the `dictionary/` directory of the repository. Those tables
+//! aren't a complete copy of the specification: they only cover the 58 data types and 16
+//! segments most commonly seen in ADT, ORM, ORU and ACK messages:
+//!
+//! `MSH`, `EVN`, `PID`, `MRG`, `NK1`, `PV1`, `AL1`, `DG1`, `GT1`, `IN1`, `ORC`, `OBR`, `OBX`,
+//! `NTE`, `MSA`, and `ERR`
+//!
+//! Every other segment, including common ones such as `PD1`, `PV2`, `ROL`, `TQ1`, `SPM`, `FT1`,
+//! `PR1`, `TXA`, `IAM`, the pharmacy segments (`RXO`, `RXE`, `RXA`, ...), the scheduling
+//! segments (`SCH`, `AIS`, ...), and all `Z` segments, isn't in the dictionary and simply yields
+//! `None`, as do data types which aren't used by the segments above. Adding rows to the tables
+//! and running the generator is all it takes to cover more of the standard.
+//!
+//! Field and component definitions are version-aware: elements which were added, retyped or
+//! renamed between versions will resolve to the definition that applies to the requested
+//! version. As of v2.6, the `TS` and `CE` data types were retired in favour of `DTM` and `CWE`;
+//! this substitution is applied automatically when resolving data types for v2.6 and later.
+//!
+//! ## Examples
+//!
+//! ```
+//! use hl7_parser::dictionary::{self, Version};
+//! use hl7_parser::query::LocationQuery;
+//!
+//! let query = LocationQuery::parse("PID.5.1").unwrap();
+//! let definition = dictionary::lookup(Version::V2_5_1, &query).unwrap();
+//! assert_eq!(definition.to_string(), "Patient Name / Family Name");
+//! assert_eq!(definition.data_type(), Some("FN"));
+//!
+//! let field = dictionary::field(Version::V2_5_1, "PID", 5).unwrap();
+//! assert_eq!(field.name, "Patient Name");
+//! assert_eq!(field.data_type, "XPN");
+//! assert!(field.repeatable);
+//! ```
+
+mod data_types;
+mod segments;
+mod validate;
+
+pub use validate::{validate, Problem, Severity};
+
+use crate::{locate::LocatedCursor, query::LocationQuery, LazyMessage, Message};
+use std::{fmt::Display, str::FromStr};
+use thiserror::Error;
+
+/// A version of the HL7 v2 standard, as found in `MSH.12`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Version {
+    V2_1,
+    V2_2,
+    V2_3,
+    V2_3_1,
+    V2_4,
+    V2_5,
+    V2_5_1,
+    V2_6,
+    V2_7,
+    V2_7_1,
+    V2_8,
+}
+
+impl Version {
+    /// All versions known to the dictionary, oldest first
+    pub const ALL: [Version; 11] = [
+        Version::V2_1,
+        Version::V2_2,
+        Version::V2_3,
+        Version::V2_3_1,
+        Version::V2_4,
+        Version::V2_5,
+        Version::V2_5_1,
+        Version::V2_6,
+        Version::V2_7,
+        Version::V2_7_1,
+        Version::V2_8,
+    ];
+
+    /// The most recent version known to the dictionary
+    pub const LATEST: Version = Version::V2_8;
+
+    /// The version string as it appears in `MSH.12`, i.e. `2.5.1`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Version::V2_1 => "2.1",
+            Version::V2_2 => "2.2",
+            Version::V2_3 => "2.3",
+            Version::V2_3_1 => "2.3.1",
+            Version::V2_4 => "2.4",
+            Version::V2_5 => "2.5",
+            Version::V2_5_1 => "2.5.1",
+            Version::V2_6 => "2.6",
+            Version::V2_7 => "2.7",
+            Version::V2_7_1 => "2.7.1",
+            Version::V2_8 => "2.8",
+        }
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Error returned when a version string isn't a known HL7 v2 version
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Unknown HL7 version: `{0}`")]
+pub struct UnknownVersionError(pub String);
+
+impl FromStr for Version {
+    type Err = UnknownVersionError;
+
+    /// Parse a version string such as `2.5.1`. Sub-versions which aren't tracked by the
+    /// dictionary (such as `2.8.2`) resolve to their parent version.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let version = match s {
+            "2.1" => Version::V2_1,
+            "2.2" => Version::V2_2,
+            "2.3" => Version::V2_3,
+            "2.3.1" => Version::V2_3_1,
+            "2.4" => Version::V2_4,
+            "2.5" => Version::V2_5,
+            "2.5.1" => Version::V2_5_1,
+            "2.6" => Version::V2_6,
+            "2.7" => Version::V2_7,
+            "2.7.1" => Version::V2_7_1,
+            s if s.starts_with("2.8") => Version::V2_8,
+            _ => return Err(UnknownVersionError(s.to_string())),
+        };
+        Ok(version)
+    }
+}
+
+/// Whether a field or component is required, as defined by the standard
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Optionality {
+    /// `R`: the element must be present
+    Required,
+    /// `O`: the element may be present
+    Optional,
+    /// `C`: the element is required depending on some condition
+    Conditional,
+    /// `B`: the element is retained for backwards compatibility only
+    BackwardCompatible,
+    /// `X`: the element is not used
+    NotUsed,
+    /// `W`: the element has been withdrawn from the standard
+    Withdrawn,
+}
+
+impl Optionality {
+    /// The single-letter code used by the standard for this optionality
+    pub fn code(&self) -> char {
+        match self {
+            Optionality::Required => 'R',
+            Optionality::Optional => 'O',
+            Optionality::Conditional => 'C',
+            Optionality::BackwardCompatible => 'B',
+            Optionality::NotUsed => 'X',
+            Optionality::Withdrawn => 'W',
+        }
+    }
+}
+
+impl Display for Optionality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+/// The definition of a field within a segment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDefinition {
+    /// The 1-based position of the field within its segment
+    pub position: usize,
+    /// The name of the field, i.e. "Patient Name"
+    pub name: &'static str,
+    /// The data type of the field, i.e. "XPN"
+    pub data_type: &'static str,
+    /// Whether the field is required
+    pub optionality: Optionality,
+    /// Whether the field may repeat
+    pub repeatable: bool,
+    /// The HL7 table that the field's values are drawn from, if any
+    pub table: Option<u16>,
+    /// The first version in which this definition applies
+    pub since: Version,
+    /// The last version in which this definition applies, if it was later changed
+    pub until: Option<Version>,
+}
+
+/// The definition of a component (or subcomponent) within a data type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentDefinition {
+    /// The 1-based position of the component within its data type
+    pub position: usize,
+    /// The name of the component, i.e. "Family Name"
+    pub name: &'static str,
+    /// The data type of the component, i.e. "FN"
+    pub data_type: &'static str,
+    /// Whether the component is required
+    pub optionality: Optionality,
+    /// The HL7 table that the component's values are drawn from, if any
+    pub table: Option<u16>,
+    /// The first version in which this definition applies
+    pub since: Version,
+    /// The last version in which this definition applies, if it was later changed
+    pub until: Option<Version>,
+}
+
+/// The definition of a segment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentDefinition {
+    /// The 3-character segment ID, i.e. "PID"
+    pub name: &'static str,
+    /// The description of the segment, i.e. "Patient Identification"
+    pub description: &'static str,
+    /// The fields of the segment, for all versions. Use `SegmentDefinition::field` to resolve
+    /// a field for a specific version.
+    pub fields: &'static [FieldDefinition],
+}
+
+/// The definition of a data type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataTypeDefinition {
+    /// The data type ID, i.e. "XPN"
+    pub name: &'static str,
+    /// The description of the data type, i.e. "Extended Person Name"
+    pub description: &'static str,
+    /// The components of the data type, for all versions. Primitive data types have no
+    /// components. Use `DataTypeDefinition::component` to resolve a component for a specific
+    /// version.
+    pub components: &'static [ComponentDefinition],
+}
+
+impl FieldDefinition {
+    /// Whether this definition applies to the given version
+    pub fn applies_to(&self, version: Version) -> bool {
+        version >= self.since && self.until.is_none_or(|until| version <= until)
+    }
+
+    /// The name of the field in symbolic form, i.e. "PatientName"
+    pub fn symbolic_name(&self) -> String {
+        symbolic_name(self.name)
+    }
+}
+
+impl ComponentDefinition {
+    /// Whether this definition applies to the given version
+    pub fn applies_to(&self, version: Version) -> bool {
+        version >= self.since && self.until.is_none_or(|until| version <= until)
+    }
+
+    /// The name of the component in symbolic form, i.e. "FamilyName"
+    pub fn symbolic_name(&self) -> String {
+        symbolic_name(self.name)
+    }
+}
+
+impl SegmentDefinition {
+    /// Get the definition of a field (1-based) for the given version
+    pub fn field(&self, version: Version, position: usize) -> Option<&'static FieldDefinition> {
+        self.fields
+            .iter()
+            .find(|f| f.position == position && f.applies_to(version))
+    }
+
+    /// Get the definition of a field for the given version by its symbolic name, i.e.
+    /// "PatientName". Names are matched case-insensitively.
+    pub fn field_named(&self, version: Version, name: &str) -> Option<&'static FieldDefinition> {
+        self.fields_for(version)
+            .find(|f| f.symbolic_name().eq_ignore_ascii_case(name))
+    }
+
+    /// An iterator over the fields that apply to the given version, in order
+    pub fn fields_for(&self, version: Version) -> impl Iterator<Item = &'static FieldDefinition> {
+        self.fields.iter().filter(move |f| f.applies_to(version))
+    }
+}
+
+impl DataTypeDefinition {
+    /// Get the definition of a component (1-based) for the given version
+    pub fn component(
+        &self,
+        version: Version,
+        position: usize,
+    ) -> Option<&'static ComponentDefinition> {
+        self.components
+            .iter()
+            .find(|c| c.position == position && c.applies_to(version))
+    }
+
+    /// Get the definition of a component for the given version by its symbolic name, i.e.
+    /// "FamilyName". Names are matched case-insensitively.
+    pub fn component_named(
+        &self,
+        version: Version,
+        name: &str,
+    ) -> Option<&'static ComponentDefinition> {
+        self.components_for(version)
+            .find(|c| c.symbolic_name().eq_ignore_ascii_case(name))
+    }
+
+    /// An iterator over the components that apply to the given version, in order
+    pub fn components_for(
+        &self,
+        version: Version,
+    ) -> impl Iterator<Item = &'static ComponentDefinition> {
+        self.components
+            .iter()
+            .filter(move |c| c.applies_to(version))
+    }
+
+    /// Returns true if the data type has no components
+    pub fn is_primitive(&self) -> bool {
+        self.components.is_empty()
+    }
+}
+
+/// Convert a descriptive name into its symbolic form by dropping parenthesised remarks and
+/// anything that isn't alphanumeric, i.e. "Suffix (e.g., JR or III)" becomes "Suffix".
+fn symbolic_name(name: &str) -> String {
+    let mut depth = 0usize;
+    let mut symbolic = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if depth == 0 && c.is_ascii_alphanumeric() => symbolic.push(c),
+            _ => {}
+        }
+    }
+    symbolic
+}
+
+/// Get the definition of a segment
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::dictionary::{segment, Version};
+/// let msh = segment(Version::V2_5_1, "MSH").unwrap();
+/// assert_eq!(msh.description, "Message Header");
+/// assert!(segment(Version::V2_5_1, "ZFO").is_none());
+/// ```
+pub fn segment(version: Version, name: &str) -> Option<&'static SegmentDefinition> {
+    segments::SEGMENTS
+        .iter()
+        .find(|s| s.name == name && s.fields.iter().any(|f| f.applies_to(version)))
+}
+
+/// Get the definition of a field (1-based) within a segment
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::dictionary::{field, Version};
+/// let pid_5 = field(Version::V2_2, "PID", 5).unwrap();
+/// assert_eq!(pid_5.data_type, "PN");
+/// let pid_5 = field(Version::V2_3, "PID", 5).unwrap();
+/// assert_eq!(pid_5.data_type, "XPN");
+/// ```
+pub fn field(version: Version, segment: &str, position: usize) -> Option<&'static FieldDefinition> {
+    self::segment(version, segment).and_then(|s| s.field(version, position))
+}
+
+/// Get the definition of a data type. As of v2.6, `TS` resolves to `DTM` and `CE` resolves to
+/// `CWE`.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::dictionary::{data_type, Version};
+/// let xpn = data_type(Version::V2_5_1, "XPN").unwrap();
+/// assert_eq!(xpn.component(Version::V2_5_1, 2).unwrap().name, "Given Name");
+/// assert_eq!(data_type(Version::V2_6, "CE").unwrap().name, "CWE");
+/// ```
+pub fn data_type(version: Version, name: &str) -> Option<&'static DataTypeDefinition> {
+    let name = resolve_data_type_name(version, name);
+    data_types::DATA_TYPES.iter().find(|d| d.name == name)
+}
+
+/// Get the definition of a component (1-based) within a data type
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::dictionary::{component, Version};
+/// assert_eq!(component(Version::V2_3, "XPN", 1).unwrap().data_type, "ST");
+/// assert_eq!(component(Version::V2_5_1, "XPN", 1).unwrap().data_type, "FN");
+/// assert!(component(Version::V2_4, "XPN", 14).is_none());
+/// ```
+pub fn component(
+    version: Version,
+    data_type: &str,
+    position: usize,
+) -> Option<&'static ComponentDefinition> {
+    self::data_type(version, data_type).and_then(|d| d.component(version, position))
+}
+
+fn resolve_data_type_name(version: Version, name: &str) -> &str {
+    match name {
+        "TS" if version >= Version::V2_6 => "DTM",
+        "CE" if version >= Version::V2_6 => "CWE",
+        "CM_MSG" if version >= Version::V2_5 => "MSG",
+        "MSG" if version < Version::V2_5 => "CM_MSG",
+        name => name,
+    }
+}
+
+/// The definitions that apply to a location within a message. Returned from
+/// [`lookup`], [`LocationQuery::describe`] and [`LocatedCursor::describe`].
+///
+/// Displaying a definition joins the names of each level with ` / `, i.e.
+/// "Patient Name / Family Name".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Definition {
+    /// The version the definition was resolved for
+    pub version: Version,
+    /// The segment definition
+    pub segment: &'static SegmentDefinition,
+    /// The field definition, if the location refers to a field
+    pub field: Option<&'static FieldDefinition>,
+    /// The component definition, if the location refers to a component
+    pub component: Option<&'static ComponentDefinition>,
+    /// The subcomponent definition, if the location refers to a subcomponent
+    pub subcomponent: Option<&'static ComponentDefinition>,
+}
+
+impl Definition {
+    /// The name of the most specific element of the definition
+    pub fn name(&self) -> &'static str {
+        self.subcomponent
+            .map(|s| s.name)
+            .or_else(|| self.component.map(|c| c.name))
+            .or_else(|| self.field.map(|f| f.name))
+            .unwrap_or(self.segment.description)
+    }
+
+    /// The data type of the most specific element of the definition. Returns `None` for
+    /// segments.
+    pub fn data_type(&self) -> Option<&'static str> {
+        self.subcomponent
+            .map(|s| s.data_type)
+            .or_else(|| self.component.map(|c| c.data_type))
+            .or_else(|| self.field.map(|f| f.data_type))
+            .map(|name| resolve_data_type_name(self.version, name))
+    }
+
+    /// The optionality of the most specific element of the definition. Returns `None` for
+    /// segments.
+    pub fn optionality(&self) -> Option<Optionality> {
+        self.subcomponent
+            .map(|s| s.optionality)
+            .or_else(|| self.component.map(|c| c.optionality))
+            .or_else(|| self.field.map(|f| f.optionality))
+    }
+
+    /// Whether the field may repeat. Returns `None` for segments.
+    pub fn repeatable(&self) -> Option<bool> {
+        self.field.map(|f| f.repeatable)
+    }
+
+    /// The HL7 table of the most specific element of the definition, if any
+    pub fn table(&self) -> Option<u16> {
+        self.subcomponent
+            .map(|s| s.table)
+            .or_else(|| self.component.map(|c| c.table))
+            .or_else(|| self.field.map(|f| f.table))
+            .flatten()
+    }
+}
+
+impl Display for Definition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(field) = self.field else {
+            return write!(f, "{}", self.segment.description);
+        };
+        write!(f, "{}", field.name)?;
+        if let Some(component) = self.component {
+            write!(f, " / {}", component.name)?;
+        }
+        if let Some(subcomponent) = self.subcomponent {
+            write!(f, " / {}", subcomponent.name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Look up the definitions that apply to a location query. Returns `None` if the segment is
+/// unknown; any levels of the query that can't be resolved are left as `None`.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::dictionary::{lookup, Optionality, Version};
+/// let definition = lookup(Version::V2_5_1, &"MSH.9.2".parse().unwrap()).unwrap();
+/// assert_eq!(definition.to_string(), "Message Type / Trigger Event");
+/// assert_eq!(definition.optionality(), Some(Optionality::Required));
+/// assert_eq!(definition.table(), Some(3));
+/// ```
+pub fn lookup(version: Version, query: &LocationQuery) -> Option<Definition> {
+    let segment = self::segment(version, &query.segment)?;
+    let field = query.field.and_then(|f| segment.field(version, f));
+    let component = match (field, query.component) {
+        (Some(field), Some(c)) => self::component(version, field.data_type, c),
+        _ => None,
+    };
+    let subcomponent = match (component, query.subcomponent) {
+        (Some(component), Some(s)) => self::component(version, component.data_type, s),
+        _ => None,
+    };
+
+    Some(Definition {
+        version,
+        segment,
+        field,
+        component,
+        subcomponent,
+    })
+}
+
+impl LocationQuery {
+    /// Look up the dictionary definitions for this query. Equivalent to calling
+    /// `hl7_parser::dictionary::lookup(version, self)`.
+    pub fn describe(&self, version: Version) -> Option<Definition> {
+        lookup(version, self)
+    }
+}
+
+impl LocatedCursor<'_> {
+    /// Look up the dictionary definitions for the cursor location. The version is taken from
+    /// `MSH.12` of the message, falling back to the latest version if it is missing or
+    /// unknown.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let message = hl7_parser::Message::parse("MSH|^~\\&|||||||ADT^A01||P|2.5.1\rPID|||||DOE^JOHN").unwrap();
+    /// let cursor = message.locate_cursor(44).unwrap();
+    /// assert_eq!(cursor.to_string(), "PID.5.2");
+    /// assert_eq!(cursor.describe().unwrap().to_string(), "Patient Name / Given Name");
+    /// ```
+    pub fn describe(&self) -> Option<Definition> {
+        let version = self.message.version().unwrap_or(Version::LATEST);
+        lookup(version, &LocationQuery::from(self))
+    }
+}
+
+impl Message<'_> {
+    /// Get the HL7 version of the message from `MSH.12`. Returns `None` if the field is missing
+    /// or the version isn't known to the dictionary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hl7_parser::dictionary::Version;
+    /// let message = hl7_parser::Message::parse("MSH|^~\\&|||||||ADT^A01||P|2.3").unwrap();
+    /// assert_eq!(message.version(), Some(Version::V2_3));
+    /// ```
+    pub fn version(&self) -> Option<Version> {
+        self.segment("MSH")
+            .and_then(|msh| msh.field(12))
+            .and_then(|f| f.component(1))
+            .and_then(|c| c.raw_value().parse().ok())
+    }
+}
+
+impl LazyMessage<'_> {
+    /// Get the HL7 version of the message from `MSH.12`. See [`Message::version`].
+    pub fn version(&self) -> Option<Version> {
+        // the MSH segment is always parsed when the message is indexed
+        self.segment("MSH")
+            .ok()
+            .flatten()
+            .and_then(|msh| msh.field(12))
+            .and_then(|f| f.component(1))
+            .and_then(|c| c.raw_value().parse().ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    #[test]
+    fn can_parse_versions() {
+        for version in Version::ALL {
+            assert_eq!(version.as_str().parse::<Version>().unwrap(), version);
+        }
+        assert_eq!("2.8.2".parse::<Version>().unwrap(), Version::V2_8);
+        assert!("3.0".parse::<Version>().is_err());
+    }
+
+    #[test]
+    fn can_make_symbolic_names() {
+        assert_eq!(symbolic_name("Patient Name"), "PatientName");
+        assert_eq!(symbolic_name("Suffix (e.g., JR or III)"), "Suffix");
+        assert_eq!(symbolic_name("Date/Time of Message"), "DateTimeofMessage");
+        assert_eq!(symbolic_name("Set ID - PID"), "SetIDPID");
+    }
+
+    #[test]
+    fn field_positions_are_unique_per_version() {
+        for segment in segments::SEGMENTS {
+            for version in Version::ALL {
+                let mut positions: Vec<usize> =
+                    segment.fields_for(version).map(|f| f.position).collect();
+                let len = positions.len();
+                positions.sort_unstable();
+                positions.dedup();
+                assert_eq!(len, positions.len(), "{} {}", segment.name, version);
+            }
+        }
+    }
+
+    #[test]
+    fn component_positions_are_unique_per_version() {
+        for data_type in data_types::DATA_TYPES {
+            for version in Version::ALL {
+                let mut positions: Vec<usize> = data_type
+                    .components_for(version)
+                    .map(|c| c.position)
+                    .collect();
+                let len = positions.len();
+                positions.sort_unstable();
+                positions.dedup();
+                assert_eq!(len, positions.len(), "{} {}", data_type.name, version);
+            }
+        }
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for segment in segments::SEGMENTS {
+            for version in Version::ALL {
+                for field in segment.fields_for(version) {
+                    assert_eq!(
+                        segment.field_named(version, &field.symbolic_name()),
+                        Some(field),
+                        "{}.{} in {} is named {}",
+                        segment.name,
+                        field.position,
+                        version,
+                        field.symbolic_name()
+                    );
+                }
+            }
+        }
+        for data_type in data_types::DATA_TYPES {
+            for version in Version::ALL {
+                for component in data_type.components_for(version) {
+                    assert_eq!(
+                        data_type.component_named(version, &component.symbolic_name()),
+                        Some(component),
+                        "{}.{} in {} is named {}",
+                        data_type.name,
+                        component.position,
+                        version,
+                        component.symbolic_name()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn queries_use_the_message_version() {
+        let v2_2 = "MSH|^~\\&|||||||ADT^A01||P|2.2\rPID|1|ext|int";
+        let message = Message::parse(v2_2).unwrap();
+        let value = message.value("PID.PatientIDInternalID").unwrap();
+        assert_eq!(value.raw_value(), "int");
+        assert_eq!(value.named_query().to_string(), "PID.PatientIDInternalID");
+        assert_eq!(
+            message
+                .query("PID.PatientIDExternalID")
+                .unwrap()
+                .raw_value(),
+            "ext"
+        );
+        let lazy = LazyMessage::parse(v2_2).unwrap();
+        assert_eq!(
+            lazy.query("PID.PatientIDInternalID")
+                .unwrap()
+                .unwrap()
+                .raw_value(),
+            "int"
+        );
+        assert!(
+            Message::parse("MSH|^~\\&|||||||ADT^A01||P|2.5\rPID|1|ext|int")
+                .unwrap()
+                .query("PID.PatientIDInternalID")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn field_data_types_are_defined() {
+        for segment in segments::SEGMENTS {
+            for field in segment.fields {
+                let version = field.until.unwrap_or(Version::LATEST);
+                assert!(
+                    data_type(version, field.data_type).is_some(),
+                    "{}.{} has unknown data type {}",
+                    segment.name,
+                    field.position,
+                    field.data_type
+                );
+            }
+        }
+        for data_type in data_types::DATA_TYPES {
+            for component in data_type.components {
+                assert!(
+                    data_types::DATA_TYPES
+                        .iter()
+                        .any(|d| d.name == component.data_type),
+                    "{}.{} has unknown data type {}",
+                    data_type.name,
+                    component.position,
+                    component.data_type
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn can_lookup_subcomponents() {
+        let query = LocationQuery::parse("PID.5.1.1").unwrap();
+        let definition = lookup(Version::V2_5_1, &query).unwrap();
+        assert_eq!(
+            definition.to_string(),
+            "Patient Name / Family Name / Surname"
+        );
+        assert_eq!(definition.data_type(), Some("ST"));
+    }
+
+    #[test]
+    fn retired_data_types_resolve_by_version() {
+        let query = LocationQuery::parse("OBX.3.1").unwrap();
+        let definition = lookup(Version::V2_5_1, &query).unwrap();
+        assert_eq!(definition.field.unwrap().data_type, "CE");
+        let definition = lookup(Version::V2_7, &query).unwrap();
+        assert_eq!(definition.data_type(), Some("ST"));
+        assert_eq!(
+            definition.to_string(),
+            "Observation Identifier / Identifier"
+        );
+        assert_eq!(
+            lookup(Version::V2_7, &LocationQuery::parse("OBX.3").unwrap())
+                .unwrap()
+                .data_type(),
+            Some("CWE")
+        );
+    }
+}
@@ -0,0 +1,686 @@
+// This file is generated by `dictionary/generate.py` from the tables in `dictionary/`. Don't edit
+// it by hand: change the tables and re-run the script instead.
+
+use super::{FieldDefinition, Optionality, SegmentDefinition, Version, Version::*};
+
+const R: Optionality = Optionality::Required;
+const O: Optionality = Optionality::Optional;
+const C: Optionality = Optionality::Conditional;
+const B: Optionality = Optionality::BackwardCompatible;
+
+/// Define a field which applies to all versions. A `table` of `0` means the field has no
+/// associated table.
+const fn f(
+    position: usize,
+    name: &'static str,
+    data_type: &'static str,
+    optionality: Optionality,
+    repeatable: bool,
+    table: u16,
+) -> FieldDefinition {
+    FieldDefinition {
+        position,
+        name,
+        data_type,
+        optionality,
+        repeatable,
+        table: if table == 0 { None } else { Some(table) },
+        since: V2_1,
+        until: None,
+    }
+}
+
+impl FieldDefinition {
+    const fn since(mut self, version: Version) -> Self {
+        self.since = version;
+        self
+    }
+
+    const fn until(mut self, version: Version) -> Self {
+        self.until = Some(version);
+        self
+    }
+}
+
+pub(super) static SEGMENTS: &[SegmentDefinition] = &[
+    SegmentDefinition {
+        name: "MSH",
+        description: "Message Header",
+        fields: &[
+            f(1, "Field Separator", "ST", R, false, 0),
+            f(2, "Encoding Characters", "ST", R, false, 0),
+            f(3, "Sending Application", "HD", O, false, 361),
+            f(4, "Sending Facility", "HD", O, false, 362),
+            f(5, "Receiving Application", "HD", O, false, 361),
+            f(6, "Receiving Facility", "HD", O, false, 362),
+            f(7, "Date/Time of Message", "TS", R, false, 0),
+            f(8, "Security", "ST", O, false, 0),
+            f(9, "Message Type", "MSG", R, false, 0),
+            f(10, "Message Control ID", "ST", R, false, 0),
+            f(11, "Processing ID", "PT", R, false, 0),
+            f(12, "Version ID", "ID", R, false, 104).until(V2_3),
+            f(12, "Version ID", "VID", R, false, 0).since(V2_3_1),
+            f(13, "Sequence Number", "NM", O, false, 0),
+            f(14, "Continuation Pointer", "ST", O, false, 0),
+            f(15, "Accept Acknowledgment Type", "ID", O, false, 155).since(V2_2),
+            f(16, "Application Acknowledgment Type", "ID", O, false, 155).since(V2_2),
+            f(17, "Country Code", "ID", O, false, 399).since(V2_2),
+            f(18, "Character Set", "ID", O, true, 211).since(V2_3),
+            f(19, "Principal Language of Message", "CE", O, false, 0).since(V2_3),
+            f(
+                20,
+                "Alternate Character Set Handling Scheme",
+                "ID",
+                O,
+                false,
+                356,
+            )
+            .since(V2_3_1),
+            f(21, "Message Profile Identifier", "EI", O, true, 0).since(V2_4),
+            f(22, "Sending Responsible Organization", "XON", O, false, 0).since(V2_7),
+            f(23, "Receiving Responsible Organization", "XON", O, false, 0).since(V2_7),
+            f(24, "Sending Network Address", "HD", O, false, 0).since(V2_7),
+            f(25, "Receiving Network Address", "HD", O, false, 0).since(V2_7),
+        ],
+    },
+    SegmentDefinition {
+        name: "EVN",
+        description: "Event Type",
+        fields: &[
+            f(1, "Event Type Code", "ID", B, false, 3),
+            f(2, "Recorded Date/Time", "TS", R, false, 0),
+            f(3, "Date/Time Planned Event", "TS", O, false, 0),
+            f(4, "Event Reason Code", "IS", O, false, 62),
+            f(5, "Operator ID", "XCN", O, true, 188).since(V2_3),
+            f(6, "Event Occurred", "TS", O, false, 0).since(V2_3),
+            f(7, "Event Facility", "HD", O, false, 0).since(V2_4),
+        ],
+    },
+    SegmentDefinition {
+        name: "PID",
+        description: "Patient Identification",
+        fields: &[
+            f(1, "Set ID - PID", "SI", O, false, 0),
+            f(2, "Patient ID - External ID", "CK", O, false, 0).until(V2_2),
+            f(2, "Patient ID", "CX", B, false, 0).since(V2_3),
+            f(3, "Patient ID - Internal ID", "CK", R, true, 0).until(V2_2),
+            f(3, "Patient Identifier List", "CX", R, true, 0).since(V2_3),
+            f(4, "Alternate Patient ID", "ST", O, false, 0).until(V2_2),
+            f(4, "Alternate Patient ID - PID", "CX", B, true, 0).since(V2_3),
+            f(5, "Patient Name", "PN", R, false, 0).until(V2_2),
+            f(5, "Patient Name", "XPN", R, true, 0).since(V2_3),
+            f(6, "Mother's Maiden Name", "ST", O, false, 0).until(V2_2),
+            f(6, "Mother's Maiden Name", "XPN", O, true, 0).since(V2_3),
+            f(7, "Date/Time of Birth", "TS", O, false, 0),
+            f(8, "Sex", "ID", O, false, 1).until(V2_3_1),
+            f(8, "Administrative Sex", "IS", O, false, 1).since(V2_4),
+            f(9, "Patient Alias", "PN", O, true, 0).until(V2_2),
+            f(9, "Patient Alias", "XPN", B, true, 0).since(V2_3),
+            f(10, "Race", "ID", O, false, 5).until(V2_2),
+            f(10, "Race", "CE", O, true, 5).since(V2_3),
+            f(11, "Patient Address", "AD", O, true, 0).until(V2_2),
+            f(11, "Patient Address", "XAD", O, true, 0).since(V2_3),
+            f(12, "County Code", "IS", B, false, 289),
+            f(13, "Phone Number - Home", "TN", O, true, 0).until(V2_2),
+            f(13, "Phone Number - Home", "XTN", O, true, 0).since(V2_3),
+            f(14, "Phone Number - Business", "TN", O, true, 0).until(V2_2),
+            f(14, "Phone Number - Business", "XTN", O, true, 0).since(V2_3),
+            f(15, "Primary Language", "CE", O, false, 296).since(V2_2),
+            f(16, "Marital Status", "CE", O, false, 2),
+            f(17, "Religion", "CE", O, false, 6),
+            f(18, "Patient Account Number", "CK", O, false, 0).until(V2_2),
+            f(18, "Patient Account Number", "CX", O, false, 0).since(V2_3),
+            f(19, "SSN Number - Patient", "ST", B, false, 0),
+            f(20, "Driver's License Number - Patient", "DLN", B, false, 0),
+            f(21, "Mother's Identifier", "CX", O, true, 0).since(V2_3),
+            f(22, "Ethnic Group", "CE", O, true, 189).since(V2_3),
+            f(23, "Birth Place", "ST", O, false, 0).since(V2_3),
+            f(24, "Multiple Birth Indicator", "ID", O, false, 136).since(V2_3),
+            f(25, "Birth Order", "NM", O, false, 0).since(V2_3),
+            f(26, "Citizenship", "CE", O, true, 171).since(V2_3),
+            f(27, "Veterans Military Status", "CE", O, false, 172).since(V2_3),
+            f(28, "Nationality", "CE", B, false, 212).since(V2_3),
+            f(29, "Patient Death Date and Time", "TS", O, false, 0).since(V2_3),
+            f(30, "Patient Death Indicator", "ID", O, false, 136).since(V2_3),
+            f(31, "Identity Unknown Indicator", "ID", O, false, 136).since(V2_4),
+            f(32, "Identity Reliability Code", "IS", O, true, 445).since(V2_4),
+            f(33, "Last Update Date/Time", "TS", O, false, 0).since(V2_4),
+            f(34, "Last Update Facility", "HD", O, false, 0).since(V2_4),
+            f(35, "Species Code", "CE", C, false, 446).since(V2_4),
+            f(36, "Breed Code", "CE", C, false, 447).since(V2_4),
+            f(37, "Strain", "ST", O, false, 0).since(V2_4),
+            f(38, "Production Class Code", "CE", O, false, 429).since(V2_4),
+            f(39, "Tribal Citizenship", "CWE", O, true, 171).since(V2_5),
+        ],
+    },
+    SegmentDefinition {
+        name: "MRG",
+        description: "Merge Patient Information",
+        fields: &[
+            f(1, "Prior Patient Identifier List", "CX", R, true, 0).since(V2_3),
+            f(2, "Prior Alternate Patient ID", "CX", B, true, 0).since(V2_3),
+            f(3, "Prior Patient Account Number", "CX", O, false, 0).since(V2_3),
+            f(4, "Prior Patient ID", "CX", B, false, 0).since(V2_3),
+            f(5, "Prior Visit Number", "CX", O, false, 0).since(V2_3),
+            f(6, "Prior Alternate Visit ID", "CX", O, false, 0).since(V2_3),
+            f(7, "Prior Patient Name", "XPN", O, true, 0).since(V2_3),
+        ],
+    },
+    SegmentDefinition {
+        name: "NK1",
+        description: "Next of Kin / Associated Parties",
+        fields: &[
+            f(1, "Set ID - NK1", "SI", R, false, 0),
+            f(2, "Name", "PN", O, false, 0).until(V2_2),
+            f(2, "Name", "XPN", O, true, 0).since(V2_3),
+            f(3, "Relationship", "CE", O, false, 63),
+            f(4, "Address", "AD", O, false, 0).until(V2_2),
+            f(4, "Address", "XAD", O, true, 0).since(V2_3),
+            f(5, "Phone Number", "TN", O, true, 0).until(V2_2),
+            f(5, "Phone Number", "XTN", O, true, 0).since(V2_3),
+            f(6, "Business Phone Number", "XTN", O, true, 0).since(V2_3),
+            f(7, "Contact Role", "CE", O, false, 131).since(V2_3),
+            f(8, "Start Date", "DT", O, false, 0).since(V2_3),
+            f(9, "End Date", "DT", O, false, 0).since(V2_3),
+            f(
+                10,
+                "Next of Kin / Associated Parties Job Title",
+                "ST",
+                O,
+                false,
+                0,
+            )
+            .since(V2_3),
+            f(
+                11,
+                "Next of Kin / Associated Parties Job Code/Class",
+                "JCC",
+                O,
+                false,
+                0,
+            )
+            .since(V2_3),
+            f(
+                12,
+                "Next of Kin / Associated Parties Employee Number",
+                "CX",
+                O,
+                false,
+                0,
+            )
+            .since(V2_3),
+            f(13, "Organization Name - NK1", "XON", O, true, 0).since(V2_3),
+            f(14, "Marital Status", "CE", O, false, 2).since(V2_3),
+            f(15, "Administrative Sex", "IS", O, false, 1).since(V2_3),
+            f(16, "Date/Time of Birth", "TS", O, false, 0).since(V2_3),
+            f(17, "Living Dependency", "IS", O, true, 223).since(V2_3),
+            f(18, "Ambulatory Status", "IS", O, true, 9).since(V2_3),
+            f(19, "Citizenship", "CE", O, true, 171).since(V2_3),
+            f(20, "Primary Language", "CE", O, false, 296).since(V2_3),
+            f(21, "Living Arrangement", "IS", O, false, 220).since(V2_3),
+            f(22, "Publicity Code", "CE", O, false, 215).since(V2_3),
+            f(23, "Protection Indicator", "ID", O, false, 136).since(V2_3),
+            f(24, "Student Indicator", "IS", O, false, 231).since(V2_3),
+            f(25, "Religion", "CE", O, false, 6).since(V2_3),
+            f(26, "Mother's Maiden Name", "XPN", O, true, 0).since(V2_3),
+            f(27, "Nationality", "CE", O, false, 212).since(V2_3),
+            f(28, "Ethnic Group", "CE", O, true, 189).since(V2_3),
+            f(29, "Contact Reason", "CE", O, true, 222).since(V2_3),
+            f(30, "Contact Person's Name", "XPN", O, true, 0).since(V2_3),
+            f(31, "Contact Person's Telephone Number", "XTN", O, true, 0).since(V2_3),
+            f(32, "Contact Person's Address", "XAD", O, true, 0).since(V2_3),
+            f(
+                33,
+                "Next of Kin/Associated Party's Identifiers",
+                "CX",
+                O,
+                true,
+                0,
+            )
+            .since(V2_3),
+            f(34, "Job Status", "IS", O, false, 311).since(V2_3),
+            f(35, "Race", "CE", O, true, 5).since(V2_3),
+            f(36, "Handicap", "IS", O, false, 295).since(V2_3),
+            f(
+                37,
+                "Contact Person Social Security Number",
+                "ST",
+                O,
+                false,
+                0,
+            )
+            .since(V2_3),
+            f(38, "Next of Kin Birth Place", "ST", O, false, 0).since(V2_5),
+            f(39, "VIP Indicator", "IS", O, false, 99).since(V2_5),
+        ],
+    },
+    SegmentDefinition {
+        name: "PV1",
+        description: "Patient Visit",
+        fields: &[
+            f(1, "Set ID - PV1", "SI", O, false, 0),
+            f(2, "Patient Class", "IS", R, false, 4),
+            f(3, "Assigned Patient Location", "PL", O, false, 0),
+            f(4, "Admission Type", "IS", O, false, 7),
+            f(5, "Preadmit Number", "CX", O, false, 0),
+            f(6, "Prior Patient Location", "PL", O, false, 0),
+            f(7, "Attending Doctor", "CN", O, false, 10).until(V2_2),
+            f(7, "Attending Doctor", "XCN", O, true, 10).since(V2_3),
+            f(8, "Referring Doctor", "CN", O, false, 10).until(V2_2),
+            f(8, "Referring Doctor", "XCN", O, true, 10).since(V2_3),
+            f(9, "Consulting Doctor", "CN", O, true, 10).until(V2_2),
+            f(9, "Consulting Doctor", "XCN", B, true, 10).since(V2_3),
+            f(10, "Hospital Service", "IS", O, false, 69),
+            f(11, "Temporary Location", "PL", O, false, 0),
+            f(12, "Preadmit Test Indicator", "IS", O, false, 87),
+            f(13, "Re-admission Indicator", "IS", O, false, 92),
+            f(14, "Admit Source", "IS", O, false, 23),
+            f(15, "Ambulatory Status", "IS", O, true, 9),
+            f(16, "VIP Indicator", "IS", O, false, 99),
+            f(17, "Admitting Doctor", "CN", O, false, 10).until(V2_2),
+            f(17, "Admitting Doctor", "XCN", O, true, 10).since(V2_3),
+            f(18, "Patient Type", "IS", O, false, 18),
+            f(19, "Visit Number", "CX", O, false, 0),
+            f(20, "Financial Class", "FC", O, true, 64),
+            f(21, "Charge Price Indicator", "IS", O, false, 32),
+            f(22, "Courtesy Code", "IS", O, false, 45),
+            f(23, "Credit Rating", "IS", O, false, 46),
+            f(24, "Contract Code", "IS", O, true, 44),
+            f(25, "Contract Effective Date", "DT", O, true, 0),
+            f(26, "Contract Amount", "NM", O, true, 0),
+            f(27, "Contract Period", "NM", O, true, 0),
+            f(28, "Interest Code", "IS", O, false, 73),
+            f(29, "Transfer to Bad Debt Code", "IS", O, false, 110),
+            f(30, "Transfer to Bad Debt Date", "DT", O, false, 0),
+            f(31, "Bad Debt Agency Code", "IS", O, false, 21),
+            f(32, "Bad Debt Transfer Amount", "NM", O, false, 0),
+            f(33, "Bad Debt Recovery Amount", "NM", O, false, 0),
+            f(34, "Delete Account Indicator", "IS", O, false, 111),
+            f(35, "Delete Account Date", "DT", O, false, 0),
+            f(36, "Discharge Disposition", "IS", O, false, 112),
+            f(37, "Discharged to Location", "DLD", O, false, 113),
+            f(38, "Diet Type", "CE", O, false, 114),
+            f(39, "Servicing Facility", "IS", O, false, 115),
+            f(40, "Bed Status", "IS", B, false, 116),
+            f(41, "Account Status", "IS", O, false, 117),
+            f(42, "Pending Location", "PL", O, false, 0),
+            f(43, "Prior Temporary Location", "PL", O, false, 0),
+            f(44, "Admit Date/Time", "TS", O, false, 0),
+            f(45, "Discharge Date/Time", "TS", O, true, 0),
+            f(46, "Current Patient Balance", "NM", O, false, 0),
+            f(47, "Total Charges", "NM", O, false, 0),
+            f(48, "Total Adjustments", "NM", O, false, 0),
+            f(49, "Total Payments", "NM", O, false, 0),
+            f(50, "Alternate Visit ID", "CX", O, false, 203).since(V2_3),
+            f(51, "Visit Indicator", "IS", O, false, 326).since(V2_3),
+            f(52, "Other Healthcare Provider", "XCN", B, true, 10).since(V2_3),
+        ],
+    },
+    SegmentDefinition {
+        name: "AL1",
+        description: "Patient Allergy Information",
+        fields: &[
+            f(1, "Set ID - AL1", "SI", R, false, 0),
+            f(2, "Allergen Type Code", "CE", O, false, 127),
+            f(3, "Allergen Code/Mnemonic/Description", "CE", R, false, 0),
+            f(4, "Allergy Severity Code", "CE", O, false, 128),
+            f(5, "Allergy Reaction Code", "ST", O, true, 0),
+            f(6, "Identification Date", "DT", B, false, 0),
+        ],
+    },
+    SegmentDefinition {
+        name: "DG1",
+        description: "Diagnosis",
+        fields: &[
+            f(1, "Set ID - DG1", "SI", R, false, 0),
+            f(2, "Diagnosis Coding Method", "ID", B, false, 53),
+            f(3, "Diagnosis Code - DG1", "CE", O, false, 51),
+            f(4, "Diagnosis Description", "ST", B, false, 0),
+            f(5, "Diagnosis Date/Time", "TS", O, false, 0),
+            f(6, "Diagnosis Type", "IS", R, false, 52),
+            f(7, "Major Diagnostic Category", "CE", B, false, 118),
+            f(8, "Diagnostic Related Group", "CE", B, false, 55),
+            f(9, "DRG Approval Indicator", "ID", B, false, 136),
+            f(10, "DRG Grouper Review Code", "IS", B, false, 56),
+            f(11, "Outlier Type", "CE", B, false, 83),
+            f(12, "Outlier Days", "NM", B, false, 0),
+            f(13, "Outlier Cost", "CP", B, false, 0),
+            f(14, "Grouper Version And Type", "ST", B, false, 0),
+            f(15, "Diagnosis Priority", "ID", O, false, 359).since(V2_3),
+            f(16, "Diagnosing Clinician", "XCN", O, true, 0).since(V2_3),
+            f(17, "Diagnosis Classification", "IS", O, false, 228).since(V2_3),
+            f(18, "Confidential Indicator", "ID", O, false, 136).since(V2_3),
+            f(19, "Attestation Date/Time", "TS", O, false, 0).since(V2_3),
+            f(20, "Diagnosis Identifier", "EI", C, false, 0).since(V2_5),
+            f(21, "Diagnosis Action Code", "ID", C, false, 206).since(V2_5),
+        ],
+    },
+    SegmentDefinition {
+        name: "GT1",
+        description: "Guarantor",
+        fields: &[
+            f(1, "Set ID - GT1", "SI", R, false, 0),
+            f(2, "Guarantor Number", "CX", O, true, 0),
+            f(3, "Guarantor Name", "XPN", R, true, 0),
+            f(4, "Guarantor Spouse Name", "XPN", O, true, 0),
+            f(5, "Guarantor Address", "XAD", O, true, 0),
+            f(6, "Guarantor Ph Num - Home", "XTN", O, true, 0),
+            f(7, "Guarantor Ph Num - Business", "XTN", O, true, 0),
+            f(8, "Guarantor Date/Time of Birth", "TS", O, false, 0),
+            f(9, "Guarantor Administrative Sex", "IS", O, false, 1),
+            f(10, "Guarantor Type", "IS", O, false, 68),
+            f(11, "Guarantor Relationship", "CE", O, false, 63),
+            f(12, "Guarantor SSN", "ST", O, false, 0),
+            f(13, "Guarantor Date - Begin", "DT", O, false, 0),
+            f(14, "Guarantor Date - End", "DT", O, false, 0),
+            f(15, "Guarantor Priority", "NM", O, false, 0),
+            f(16, "Guarantor Employer Name", "XPN", O, true, 0),
+            f(17, "Guarantor Employer Address", "XAD", O, true, 0),
+            f(18, "Guarantor Employer Phone Number", "XTN", O, true, 0),
+            f(19, "Guarantor Employee ID Number", "CX", O, true, 0),
+            f(20, "Guarantor Employment Status", "IS", O, false, 66),
+            f(21, "Guarantor Organization Name", "XON", O, true, 0),
+        ],
+    },
+    SegmentDefinition {
+        name: "IN1",
+        description: "Insurance",
+        fields: &[
+            f(1, "Set ID - IN1", "SI", R, false, 0),
+            f(2, "Insurance Plan ID", "CE", R, false, 72),
+            f(3, "Insurance Company ID", "CX", R, true, 0),
+            f(4, "Insurance Company Name", "XON", O, true, 0),
+            f(5, "Insurance Company Address", "XAD", O, true, 0),
+            f(6, "Insurance Co Contact Person", "XPN", O, true, 0),
+            f(7, "Insurance Co Phone Number", "XTN", O, true, 0),
+            f(8, "Group Number", "ST", O, false, 0),
+            f(9, "Group Name", "XON", O, true, 0),
+            f(10, "Insured's Group Emp ID", "CX", O, true, 0),
+            f(11, "Insured's Group Emp Name", "XON", O, true, 0),
+            f(12, "Plan Effective Date", "DT", O, false, 0),
+            f(13, "Plan Expiration Date", "DT", O, false, 0),
+            f(14, "Authorization Information", "AUI", O, false, 0),
+            f(15, "Plan Type", "IS", O, false, 86),
+            f(16, "Name of Insured", "XPN", O, true, 0),
+            f(17, "Insured's Relationship to Patient", "CE", O, false, 63),
+            f(18, "Insured's Date of Birth", "TS", O, false, 0),
+            f(19, "Insured's Address", "XAD", O, true, 0),
+            f(20, "Assignment of Benefits", "IS", O, false, 135),
+            f(21, "Coordination of Benefits", "IS", O, false, 173),
+            f(22, "Coord of Ben. Priority", "ST", O, false, 0),
+            f(23, "Notice of Admission Flag", "ID", O, false, 136),
+            f(24, "Notice of Admission Date", "DT", O, false, 0),
+            f(25, "Report of Eligibility Flag", "ID", O, false, 136),
+            f(26, "Report of Eligibility Date", "DT", O, false, 0),
+            f(27, "Release Information Code", "IS", O, false, 93),
+            f(28, "Pre-Admit Cert (PAC)", "ST", O, false, 0),
+            f(29, "Verification Date/Time", "TS", O, false, 0),
+            f(30, "Verification By", "XCN", O, true, 0),
+            f(31, "Type of Agreement Code", "IS", O, false, 98),
+            f(32, "Billing Status", "IS", O, false, 22),
+            f(33, "Lifetime Reserve Days", "NM", O, false, 0),
+            f(34, "Delay Before L.R. Day", "NM", O, false, 0),
+            f(35, "Company Plan Code", "IS", O, false, 42),
+            f(36, "Policy Number", "ST", O, false, 0),
+            f(37, "Policy Deductible", "CP", O, false, 0),
+            f(38, "Policy Limit - Amount", "CP", B, false, 0),
+            f(39, "Policy Limit - Days", "NM", O, false, 0),
+            f(40, "Room Rate - Semi-Private", "CP", B, false, 0),
+            f(41, "Room Rate - Private", "CP", B, false, 0),
+            f(42, "Insured's Employment Status", "CE", O, false, 66),
+            f(43, "Insured's Administrative Sex", "IS", O, false, 1),
+            f(44, "Insured's Employer's Address", "XAD", O, true, 0),
+            f(45, "Verification Status", "ST", O, false, 0),
+            f(46, "Prior Insurance Plan ID", "IS", O, false, 72),
+            f(47, "Coverage Type", "IS", O, false, 309).since(V2_3),
+            f(48, "Handicap", "IS", O, false, 295).since(V2_3),
+            f(49, "Insured's ID Number", "CX", O, true, 0).since(V2_3),
+            f(50, "Signature Code", "IS", O, false, 535).since(V2_5),
+            f(51, "Signature Code Date", "DT", O, false, 0).since(V2_5),
+            f(52, "Insured's Birth Place", "ST", O, false, 0).since(V2_5),
+            f(53, "VIP Indicator", "IS", O, false, 99).since(V2_5),
+        ],
+    },
+    SegmentDefinition {
+        name: "ORC",
+        description: "Common Order",
+        fields: &[
+            f(1, "Order Control", "ID", R, false, 119),
+            f(2, "Placer Order Number", "EI", C, false, 0),
+            f(3, "Filler Order Number", "EI", C, false, 0),
+            f(4, "Placer Group Number", "EI", O, false, 0),
+            f(5, "Order Status", "ID", O, false, 38),
+            f(6, "Response Flag", "ID", O, false, 121),
+            f(7, "Quantity/Timing", "TQ", B, true, 0),
+            f(8, "Parent", "EIP", O, false, 0),
+            f(9, "Date/Time of Transaction", "TS", O, false, 0),
+            f(10, "Entered By", "XCN", O, true, 0),
+            f(11, "Verified By", "XCN", O, true, 0),
+            f(12, "Ordering Provider", "XCN", O, true, 0),
+            f(13, "Enterer's Location", "PL", O, false, 0),
+            f(14, "Call Back Phone Number", "XTN", O, true, 0),
+            f(15, "Order Effective Date/Time", "TS", O, false, 0),
+            f(16, "Order Control Code Reason", "CE", O, false, 0),
+            f(17, "Entering Organization", "CE", O, false, 0),
+            f(18, "Entering Device", "CE", O, false, 0),
+            f(19, "Action By", "XCN", O, true, 0),
+            f(20, "Advanced Beneficiary Notice Code", "CE", O, false, 339).since(V2_3_1),
+            f(21, "Ordering Facility Name", "XON", O, true, 0).since(V2_3_1),
+            f(22, "Ordering Facility Address", "XAD", O, true, 0).since(V2_3_1),
+            f(23, "Ordering Facility Phone Number", "XTN", O, true, 0).since(V2_3_1),
+            f(24, "Ordering Provider Address", "XAD", O, true, 0).since(V2_3_1),
+            f(25, "Order Status Modifier", "CWE", O, false, 0).since(V2_4),
+            f(
+                26,
+                "Advanced Beneficiary Notice Override Reason",
+                "CWE",
+                C,
+                false,
+                552,
+            )
+            .since(V2_5),
+            f(
+                27,
+                "Filler's Expected Availability Date/Time",
+                "TS",
+                O,
+                false,
+                0,
+            )
+            .since(V2_5),
+            f(28, "Confidentiality Code", "CWE", O, false, 177).since(V2_5),
+            f(29, "Order Type", "CWE", O, false, 482).since(V2_5),
+            f(30, "Enterer Authorization Mode", "CNE", O, false, 483).since(V2_5),
+            f(
+                31,
+                "Parent Universal Service Identifier",
+                "CWE",
+                O,
+                false,
+                0,
+            )
+            .since(V2_5_1),
+        ],
+    },
+    SegmentDefinition {
+        name: "OBR",
+        description: "Observation Request",
+        fields: &[
+            f(1, "Set ID - OBR", "SI", O, false, 0),
+            f(2, "Placer Order Number", "EI", C, false, 0),
+            f(3, "Filler Order Number", "EI", C, false, 0),
+            f(4, "Universal Service Identifier", "CE", R, false, 0),
+            f(5, "Priority - OBR", "ID", B, false, 0),
+            f(6, "Requested Date/Time", "TS", B, false, 0),
+            f(7, "Observation Date/Time", "TS", C, false, 0),
+            f(8, "Observation End Date/Time", "TS", O, false, 0),
+            f(9, "Collection Volume", "CQ", O, false, 0),
+            f(10, "Collector Identifier", "XCN", O, true, 0),
+            f(11, "Specimen Action Code", "ID", O, false, 65),
+            f(12, "Danger Code", "CE", O, false, 0),
+            f(13, "Relevant Clinical Information", "ST", O, false, 0),
+            f(14, "Specimen Received Date/Time", "TS", B, false, 0),
+            f(15, "Specimen Source", "SPS", B, false, 0),
+            f(16, "Ordering Provider", "XCN", O, true, 0),
+            f(17, "Order Callback Phone Number", "XTN", O, true, 0),
+            f(18, "Placer Field 1", "ST", O, false, 0),
+            f(19, "Placer Field 2", "ST", O, false, 0),
+            f(20, "Filler Field 1", "ST", O, false, 0),
+            f(21, "Filler Field 2", "ST", O, false, 0),
+            f(22, "Results Rpt/Status Chng - Date/Time", "TS", C, false, 0),
+            f(23, "Charge to Practice", "MOC", O, false, 0),
+            f(24, "Diagnostic Serv Sect ID", "ID", O, false, 74),
+            f(25, "Result Status", "ID", C, false, 123),
+            f(26, "Parent Result", "PRL", O, false, 0),
+            f(27, "Quantity/Timing", "TQ", B, true, 0),
+            f(28, "Result Copies To", "XCN", O, true, 0),
+            f(29, "Parent", "EIP", O, false, 0),
+            f(30, "Transportation Mode", "ID", O, false, 124),
+            f(31, "Reason for Study", "CE", O, true, 0),
+            f(32, "Principal Result Interpreter", "NDL", O, false, 0),
+            f(33, "Assistant Result Interpreter", "NDL", O, true, 0),
+            f(34, "Technician", "NDL", O, true, 0),
+            f(35, "Transcriptionist", "NDL", O, true, 0),
+            f(36, "Scheduled Date/Time", "TS", O, false, 0),
+            f(37, "Number of Sample Containers", "NM", O, false, 0).since(V2_3),
+            f(
+                38,
+                "Transport Logistics of Collected Sample",
+                "CE",
+                O,
+                true,
+                0,
+            )
+            .since(V2_3),
+            f(39, "Collector's Comment", "CE", O, true, 0).since(V2_3),
+            f(
+                40,
+                "Transport Arrangement Responsibility",
+                "CE",
+                O,
+                false,
+                0,
+            )
+            .since(V2_3),
+            f(41, "Transport Arranged", "ID", O, false, 224).since(V2_3),
+            f(42, "Escort Required", "ID", O, false, 225).since(V2_3),
+            f(43, "Planned Patient Transport Comment", "CE", O, true, 0).since(V2_3),
+            f(44, "Procedure Code", "CE", O, false, 88).since(V2_3_1),
+            f(45, "Procedure Code Modifier", "CE", O, true, 340).since(V2_3_1),
+            f(
+                46,
+                "Placer Supplemental Service Information",
+                "CE",
+                O,
+                true,
+                411,
+            )
+            .since(V2_4),
+            f(
+                47,
+                "Filler Supplemental Service Information",
+                "CE",
+                O,
+                true,
+                411,
+            )
+            .since(V2_4),
+            f(
+                48,
+                "Medically Necessary Duplicate Procedure Reason",
+                "CWE",
+                C,
+                false,
+                476,
+            )
+            .since(V2_5),
+            f(49, "Result Handling", "IS", O, false, 507).since(V2_5),
+            f(
+                50,
+                "Parent Universal Service Identifier",
+                "CWE",
+                O,
+                false,
+                0,
+            )
+            .since(V2_5_1),
+        ],
+    },
+    SegmentDefinition {
+        name: "OBX",
+        description: "Observation/Result",
+        fields: &[
+            f(1, "Set ID - OBX", "SI", O, false, 0),
+            f(2, "Value Type", "ID", C, false, 125),
+            f(3, "Observation Identifier", "CE", R, false, 0),
+            f(4, "Observation Sub-ID", "ST", C, false, 0),
+            f(5, "Observation Value", "varies", C, true, 0),
+            f(6, "Units", "CE", O, false, 0),
+            f(7, "References Range", "ST", O, false, 0),
+            f(8, "Abnormal Flags", "IS", O, true, 78),
+            f(9, "Probability", "NM", O, false, 0),
+            f(10, "Nature of Abnormal Test", "ID", O, true, 80),
+            f(11, "Observation Result Status", "ID", R, false, 85),
+            f(12, "Effective Date of Reference Range", "TS", O, false, 0),
+            f(13, "User Defined Access Checks", "ST", O, false, 0),
+            f(14, "Date/Time of the Observation", "TS", O, false, 0),
+            f(15, "Producer's ID", "CE", O, false, 0),
+            f(16, "Responsible Observer", "XCN", O, true, 0),
+            f(17, "Observation Method", "CE", O, true, 0).since(V2_3),
+            f(18, "Equipment Instance Identifier", "EI", O, true, 0).since(V2_5),
+            f(19, "Date/Time of the Analysis", "TS", O, false, 0).since(V2_5),
+            f(23, "Performing Organization Name", "XON", O, false, 0).since(V2_6),
+            f(24, "Performing Organization Address", "XAD", O, false, 0).since(V2_6),
+            f(
+                25,
+                "Performing Organization Medical Director",
+                "XCN",
+                O,
+                false,
+                0,
+            )
+            .since(V2_6),
+        ],
+    },
+    SegmentDefinition {
+        name: "NTE",
+        description: "Notes and Comments",
+        fields: &[
+            f(1, "Set ID - NTE", "SI", O, false, 0),
+            f(2, "Source of Comment", "ID", O, false, 105),
+            f(3, "Comment", "FT", O, true, 0),
+            f(4, "Comment Type", "CE", O, false, 364).since(V2_4),
+        ],
+    },
+    SegmentDefinition {
+        name: "MSA",
+        description: "Message Acknowledgment",
+        fields: &[
+            f(1, "Acknowledgment Code", "ID", R, false, 8),
+            f(2, "Message Control ID", "ST", R, false, 0),
+            f(3, "Text Message", "ST", B, false, 0),
+            f(4, "Expected Sequence Number", "NM", O, false, 0),
+            f(5, "Delayed Acknowledgment Type", "ID", B, false, 102),
+            f(6, "Error Condition", "CE", B, false, 357).since(V2_2),
+        ],
+    },
+    SegmentDefinition {
+        name: "ERR",
+        description: "Error",
+        fields: &[
+            f(1, "Error Code and Location", "ELD", R, true, 0).until(V2_4),
+            f(1, "Error Code and Location", "ELD", B, true, 0).since(V2_5),
+            f(2, "Error Location", "ERL", O, true, 0).since(V2_5),
+            f(3, "HL7 Error Code", "CWE", R, false, 357).since(V2_5),
+            f(4, "Severity", "ID", R, false, 516).since(V2_5),
+            f(5, "Application Error Code", "CWE", O, false, 533).since(V2_5),
+            f(6, "Application Error Parameter", "ST", O, true, 0).since(V2_5),
+            f(7, "Diagnostic Information", "TX", O, false, 0).since(V2_5),
+            f(8, "User Message", "TX", O, false, 0).since(V2_5),
+            f(9, "Inform Person Indicator", "IS", O, true, 517).since(V2_5),
+            f(10, "Override Type", "CWE", O, false, 518).since(V2_5),
+            f(11, "Override Reason Code", "CWE", O, true, 519).since(V2_5),
+            f(12, "Help Desk Contact Point", "XTN", O, true, 0).since(V2_5),
+        ],
+    },
+];
@@ -0,0 +1,199 @@
+use super::{Optionality, Version};
+use crate::{
+    datetime::{Date, Time, TimeStamp},
+    Message,
+};
+use std::{fmt::Display, ops::Range, str::FromStr};
+
+/// How serious a [`Problem`] found by [`validate`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Severity {
+    /// Something worth knowing about which doesn't make the message invalid, such as an
+    /// unknown version
+    Information,
+    /// A value which doesn't match its definition
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Information => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A problem found by [`validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Problem {
+    /// The range of the problem within the message, in bytes
+    pub range: Range<usize>,
+    /// How serious the problem is
+    pub severity: Severity,
+    /// A description of the problem
+    pub message: String,
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// Validate a message against the dictionary definitions for its version (or the latest version
+/// if it doesn't have a known version), checking that required fields are present, that fields
+/// which don't repeat don't, and that dates and times are valid. Segments which aren't in the
+/// dictionary aren't checked.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::{dictionary::{self, Severity}, Message};
+///
+/// let message = Message::parse("MSH|^~\\&|||||yesterday||ADT^A01|1|P|2.5.1\rPID|1||123").unwrap();
+/// let problems = dictionary::validate(&message);
+/// assert!(problems.iter().all(|p| p.severity == Severity::Warning));
+/// assert!(problems.iter().any(|p| p.message.starts_with("MSH.7 (Date/Time of Message) is not a valid")));
+/// assert!(problems.iter().any(|p| p.message == "PID.5 (Patient Name) is required"));
+/// ```
+pub fn validate(message: &Message) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut problem = |range: Range<usize>, severity: Severity, message: String| {
+        problems.push(Problem {
+            range,
+            severity,
+            message,
+        })
+    };
+
+    let msh_12 = message.segment("MSH").and_then(|msh| msh.field(12));
+    if let Some(field) = msh_12.filter(|f| !f.is_empty() && message.version().is_none()) {
+        problem(
+            field.range.clone(),
+            Severity::Information,
+            format!(
+                "Unknown HL7 version `{}`, using the definitions for {}",
+                field.raw_value(),
+                Version::LATEST
+            ),
+        );
+    }
+
+    let version = message.version().unwrap_or(Version::LATEST);
+    for segment in message.segments() {
+        let Some(definition) = super::segment(version, segment.name) else {
+            continue;
+        };
+        let name_range = segment.range.start..segment.range.start + segment.name.len();
+        for field_definition in definition.fields_for(version) {
+            let position = field_definition.position;
+            let field = segment.field(position).filter(|f| !f.is_empty());
+            let Some(field) = field else {
+                if field_definition.optionality == Optionality::Required {
+                    problem(
+                        name_range.clone(),
+                        Severity::Warning,
+                        format!(
+                            "{}.{position} ({}) is required",
+                            segment.name, field_definition.name
+                        ),
+                    );
+                }
+                continue;
+            };
+
+            if field.has_repeats() && !field_definition.repeatable {
+                problem(
+                    field.range.clone(),
+                    Severity::Warning,
+                    format!(
+                        "{}.{position} ({}) does not repeat",
+                        segment.name, field_definition.name
+                    ),
+                );
+            }
+
+            let Some(value) = field.component(1).map(|c| c.raw_value()) else {
+                continue;
+            };
+            let invalid = match field_definition.data_type {
+                "TS" | "DTM" => TimeStamp::from_str(value).and_then(|v| v.validate()).err(),
+                "DT" => Date::from_str(value).and_then(|v| v.validate()).err(),
+                "TM" => Time::from_str(value).and_then(|v| v.validate()).err(),
+                _ => None,
+            };
+            if let Some(error) = invalid.filter(|_| !value.is_empty()) {
+                problem(
+                    field.range.clone(),
+                    Severity::Warning,
+                    format!(
+                        "{}.{position} ({}) is not a valid {}: {error}",
+                        segment.name, field_definition.name, field_definition.data_type
+                    ),
+                );
+            }
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    #[test]
+    fn reports_impossible_dates() {
+        let message =
+            Message::parse("MSH|^~\\&|||||20230231||ADT^A01|1|P|2.5.1\rPID|1||||Doe||20240229")
+                .unwrap();
+        let problems = validate(&message)
+            .into_iter()
+            .filter(|p| p.message.contains("is not a valid"))
+            .collect::<Vec<_>>();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(
+            problems[0].message,
+            "MSH.7 (Date/Time of Message) is not a valid TS: Invalid day 31 for 2023-02, which has 28 days"
+        );
+    }
+
+    #[test]
+    fn reports_unknown_versions() {
+        let source = "MSH|^~\\&|||||20230101||ADT^A01|1|P|9.9";
+        let message = Message::parse(source).unwrap();
+        assert_eq!(
+            validate(&message),
+            vec![Problem {
+                range: source.len() - 3..source.len(),
+                severity: Severity::Information,
+                message: "Unknown HL7 version `9.9`, using the definitions for 2.8".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_missing_and_repeated_fields() {
+        let source = "MSH|^~\\&|||||20230101||ADT^A01|1|P|2.5.1\rPID|1|a~b|123";
+        let message = Message::parse(source).unwrap();
+        let pid = source.find("PID").unwrap();
+        assert_eq!(
+            validate(&message),
+            vec![
+                Problem {
+                    range: pid + 6..pid + 9,
+                    severity: Severity::Warning,
+                    message: "PID.2 (Patient ID) does not repeat".to_string(),
+                },
+                Problem {
+                    range: pid..pid + 3,
+                    severity: Severity::Warning,
+                    message: "PID.5 (Patient Name) is required".to_string(),
+                },
+            ]
+        );
+    }
+}
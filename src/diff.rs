@@ -0,0 +1,910 @@
+//! # Diffing HL7 messages
+//!
+//! [`diff`] compares two messages structurally and reports the segments, fields, repeats,
+//! components, and subcomponents which were added, removed, or changed, each keyed by a
+//! [`LocationQuery`].
+//!
+//! Segments are aligned between the two messages before they are compared, so inserting a
+//! segment only reports that segment as added rather than every following segment as changed,
+//! and a segment which was moved without being changed is reported as moved. Values are
+//! compared after decoding escape sequences, so the same value encoded differently (or encoded
+//! with different separators) is not reported as a change. Empty and missing values are
+//! treated the same.
+//!
+//! Changes are reported at the shallowest level which identifies them: if a field has no
+//! repeats, components, or subcomponents in either message, a change to it is reported for the
+//! field (e.g. `PID.8`) rather than for its first subcomponent (e.g. `PID.8[1].1.1`).
+//!
+//! ## Examples
+//!
+//! ```
+//! use hl7_parser::{diff::{diff, Change}, Message};
+//!
+//! let old = Message::parse("MSH|^~\\&|\rPID|1||123||Doe^John\rNTE|1||first").unwrap();
+//! let new = Message::parse("MSH|^~\\&|\rPID|1||123||Doe^Jane\rNTE|1||first\rNTE|2||second").unwrap();
+//!
+//! let diff = diff(&old, &new);
+//! assert_eq!(diff.changes.len(), 2);
+//! assert_eq!(diff.changes[0].query().to_string(), "PID.5.2");
+//! assert!(matches!(&diff.changes[1], Change::Added { value, .. } if value == "NTE|2||second"));
+//!
+//! assert_eq!(
+//!     diff.to_string(),
+//!     "~ PID.5.2: \"John\" -> \"Jane\"\n+ NTE[2]: \"NTE|2||second\"\n"
+//! );
+//! ```
+
+use crate::{
+    message::{Component, Field, Repeat, Segment, Separators, Subcomponent},
+    query::LocationQuery,
+    Message,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+    ops::Range,
+};
+
+/// A single difference between two messages
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "snake_case"))]
+pub enum Change {
+    /// A value which is only present in the new message. The query refers to the new message.
+    Added { query: LocationQuery, value: String },
+    /// A value which is only present in the old message. The query refers to the old message.
+    Removed { query: LocationQuery, value: String },
+    /// A value which is present in both messages but differs. The query refers to the new
+    /// message.
+    Changed {
+        query: LocationQuery,
+        old: String,
+        new: String,
+    },
+    /// A segment which is unchanged, but appears in a different order relative to the other
+    /// segments. `from` refers to the old message, and `to` to the new message.
+    Moved {
+        from: LocationQuery,
+        to: LocationQuery,
+    },
+}
+
+impl Change {
+    /// The location of the change. This is the location within the new message, except for
+    /// removed values, which are only present in the old message.
+    pub fn query(&self) -> &LocationQuery {
+        match self {
+            Change::Added { query, .. }
+            | Change::Removed { query, .. }
+            | Change::Changed { query, .. } => query,
+            Change::Moved { to, .. } => to,
+        }
+    }
+}
+
+impl Display for Change {
+    /// Render the change on a single line, prefixed with `+` for added values, `-` for removed
+    /// values, `~` for changed values, and `>` for moved segments. Values are quoted and
+    /// escaped so that whitespace and control characters are visible.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Added { query, value } => write!(f, "+ {query}: {value:?}"),
+            Change::Removed { query, value } => write!(f, "- {query}: {value:?}"),
+            Change::Changed { query, old, new } => write!(f, "~ {query}: {old:?} -> {new:?}"),
+            Change::Moved { from, to } => write!(f, "> {from} -> {to}"),
+        }
+    }
+}
+
+/// The differences between two messages, in the order they appear in the messages
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Diff {
+    pub changes: Vec<Change>,
+}
+
+impl Diff {
+    /// Whether the messages are equivalent
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Iterate over the changes
+    pub fn iter(&self) -> impl Iterator<Item = &Change> {
+        self.changes.iter()
+    }
+}
+
+impl Display for Diff {
+    /// Render each change on its own line. See [`Change`]'s `Display` implementation for the
+    /// format of each line.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+impl IntoIterator for Diff {
+    type Item = Change;
+    type IntoIter = std::vec::IntoIter<Change>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.changes.into_iter()
+    }
+}
+
+/// Compare two messages, returning the changes needed to get from `old` to `new`.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::{diff::diff, Message};
+///
+/// // values are compared decoded, so `\X0D\` and `\.br\` are both a carriage return
+/// let old = Message::parse("MSH|^~\\&|\rNTE|1||A\\X0D\\B").unwrap();
+/// let new = Message::parse("MSH|^~\\&|\rNTE|1||A\\.br\\B").unwrap();
+/// assert!(diff(&old, &new).is_empty());
+///
+/// // moving a segment is reported as a move
+/// let old = Message::parse("MSH|^~\\&|\rOBX|1||A\rOBX|2||B").unwrap();
+/// let new = Message::parse("MSH|^~\\&|\rOBX|2||B\rOBX|1||A").unwrap();
+/// assert_eq!(diff(&old, &new).to_string(), "> OBX[1] -> OBX[2]\n");
+/// ```
+pub fn diff(old: &Message, new: &Message) -> Diff {
+    let mut keys = HashMap::new();
+    let old_side = Side::new(old, &mut keys);
+    let new_side = Side::new(new, &mut keys);
+    let mut changes = Vec::new();
+
+    let alignment = align(&old_side, &new_side);
+    let mut aligned_old = vec![false; old.segments.len()];
+    let mut aligned_new = vec![false; new.segments.len()];
+    for &(i, j) in &alignment {
+        aligned_old[i] = true;
+        aligned_new[j] = true;
+    }
+
+    // segments which weren't aligned but are identical to a segment in the other message were
+    // moved, matching them up in order
+    let mut unmatched_old: HashMap<usize, VecDeque<usize>> = HashMap::new();
+    for i in (0..old.segments.len()).filter(|&i| !aligned_old[i]) {
+        unmatched_old
+            .entry(old_side.keys[i])
+            .or_default()
+            .push_back(i);
+    }
+    let mut moved_old = vec![false; old.segments.len()];
+    let moved_from: Vec<Option<usize>> = (0..new.segments.len())
+        .map(|j| {
+            if aligned_new[j] {
+                return None;
+            }
+            let from = unmatched_old.get_mut(&new_side.keys[j])?.pop_front()?;
+            moved_old[from] = true;
+            Some(from)
+        })
+        .collect();
+
+    let (mut i, mut j) = (0, 0);
+    for (o, n) in alignment
+        .iter()
+        .copied()
+        .chain(std::iter::once((old.segments.len(), new.segments.len())))
+    {
+        for i in (i..o).filter(|&i| !moved_old[i]) {
+            changes.push(Change::Removed {
+                query: old_side.query(i),
+                value: old_side.segment_value(i),
+            });
+        }
+        for (j, moved_from) in moved_from.iter().enumerate().take(n).skip(j) {
+            match *moved_from {
+                Some(from) => changes.push(Change::Moved {
+                    from: old_side.query(from),
+                    to: new_side.query(j),
+                }),
+                None => changes.push(Change::Added {
+                    query: new_side.query(j),
+                    value: new_side.segment_value(j),
+                }),
+            }
+        }
+        if o < old.segments.len() && old_side.keys[o] != new_side.keys[n] {
+            changes.extend(segment_changes(&old_side, o, &new_side, n));
+        }
+        (i, j) = (o + 1, n + 1);
+    }
+
+    Diff { changes }
+}
+
+/// One of the messages being compared
+struct Side<'a, 'm> {
+    message: &'a Message<'m>,
+    /// The 1-based index of each segment amongst the segments with the same name
+    segment_indices: Vec<usize>,
+    /// The number of segments with each name
+    name_counts: HashMap<&'m str, usize>,
+    /// For each segment, an identifier which is shared by every equivalent segment in either
+    /// message (see [`segment_key`])
+    keys: Vec<usize>,
+}
+
+impl<'a, 'm> Side<'a, 'm> {
+    /// Index the segments of a message. `keys` maps segment keys to their identifiers, and is
+    /// shared between both messages.
+    fn new(message: &'a Message<'m>, keys: &mut HashMap<String, usize>) -> Self {
+        let mut segment_indices = Vec::with_capacity(message.segments.len());
+        let mut name_counts = HashMap::new();
+        let mut segment_keys = Vec::with_capacity(message.segments.len());
+        for segment in &message.segments {
+            let count = name_counts.entry(segment.name).or_insert(0);
+            *count += 1;
+            segment_indices.push(*count);
+            let next = keys.len();
+            segment_keys.push(
+                *keys
+                    .entry(segment_key(segment, &message.separators))
+                    .or_insert(next),
+            );
+        }
+        Side {
+            message,
+            segment_indices,
+            name_counts,
+            keys: segment_keys,
+        }
+    }
+
+    fn segment(&self, i: usize) -> &'a Segment<'m> {
+        &self.message.segments[i]
+    }
+
+    fn separators(&self) -> &'a Separators {
+        &self.message.separators
+    }
+
+    /// The query for a segment, only including the segment index if there are multiple
+    /// segments with the same name
+    fn query(&self, i: usize) -> LocationQuery {
+        let name = self.segment(i).name;
+        let repeated = self.name_counts[name] > 1;
+        LocationQuery {
+            segment: name.to_string(),
+            segment_index: repeated.then_some(self.segment_indices[i]),
+            field: None,
+            repeat: None,
+            component: None,
+            subcomponent: None,
+        }
+    }
+
+    fn segment_value(&self, i: usize) -> String {
+        let segment = self.segment(i);
+        let mut value = segment.name.to_string();
+        for (n, field) in segment.fields.iter().enumerate() {
+            value.push(self.separators().field);
+            if is_msh_encoding(segment, n + 1) {
+                value.push_str(field.raw_value());
+            } else {
+                value.push_str(&Node::Field(field).decoded(self.separators()));
+            }
+        }
+        value
+    }
+}
+
+/// Whether the field is `MSH.1` or `MSH.2`, which contain the separators themselves and so
+/// can't be decoded
+fn is_msh_encoding(segment: &Segment, field: usize) -> bool {
+    segment.name == "MSH" && field <= 2
+}
+
+/// A canonical form of a segment, which is the same for two segments exactly when
+/// [`segment_changes`] finds no changes between them. Values are decoded and prefixed with
+/// their length so that decoded separators can't be mistaken for structure, empty values are
+/// written as `-`, and trailing empty values are left out.
+fn segment_key(segment: &Segment, separators: &Separators) -> String {
+    let is_empty = |n: usize, field: &Field| {
+        if is_msh_encoding(segment, n + 1) {
+            field.raw_value().is_empty()
+        } else {
+            Node::Field(field).is_empty()
+        }
+    };
+    let len = segment
+        .fields
+        .iter()
+        .enumerate()
+        .rposition(|(n, field)| !is_empty(n, field))
+        .map_or(0, |n| n + 1);
+
+    let mut key = segment.name.to_string();
+    key.push('(');
+    for (n, field) in segment.fields[..len].iter().enumerate() {
+        if is_empty(n, field) {
+            key.push('-');
+        } else if is_msh_encoding(segment, n + 1) {
+            write_key_value(field.raw_value(), &mut key);
+        } else {
+            write_key(Node::Field(field), separators, &mut key);
+        }
+    }
+    key.push(')');
+    key
+}
+
+/// Write the canonical form of a node (see [`segment_key`])
+fn write_key(node: Node, separators: &Separators, key: &mut String) {
+    if node.is_empty() {
+        key.push('-');
+        return;
+    }
+    if let Node::Subcomponent(subcomponent) = node {
+        write_key_value(&separators.decode(subcomponent.value).to_string(), key);
+        return;
+    }
+    let children = node.children();
+    let len = children
+        .iter()
+        .rposition(|child| !child.is_empty())
+        .map_or(0, |n| n + 1);
+    key.push('(');
+    for child in &children[..len] {
+        write_key(*child, separators, key);
+    }
+    key.push(')');
+}
+
+fn write_key_value(value: &str, key: &mut String) {
+    key.push_str(&value.len().to_string());
+    key.push(':');
+    key.push_str(value);
+}
+
+/// Align the segments of the two messages, returning the indices of matching segments in order.
+/// Only segments with the same name are matched, preferring to match identical segments.
+///
+/// Identical segments which only appear once in each message are aligned first, and only the
+/// segments between them are compared pairwise, so that large messages which are mostly
+/// unchanged don't need every pair of segments to be compared.
+fn align(old: &Side, new: &Side) -> Vec<(usize, usize)> {
+    let (n, m) = (old.keys.len(), new.keys.len());
+    let mut alignment = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for (o, p) in anchors(old, new).into_iter().chain(std::iter::once((n, m))) {
+        alignment.extend(align_range(old, i..o, new, j..p));
+        if o < n {
+            alignment.push((o, p));
+        }
+        (i, j) = (o + 1, p + 1);
+    }
+    alignment
+}
+
+/// Pairs of identical segments which only appear once in each message, keeping the largest set
+/// of them which are in the same order in both messages
+fn anchors(old: &Side, new: &Side) -> Vec<(usize, usize)> {
+    // for each key, the index of its last appearance in the old message, and how many times it
+    // appears in each message
+    let mut appearances: HashMap<usize, (usize, usize, usize)> = HashMap::new();
+    for (i, &key) in old.keys.iter().enumerate() {
+        let appearance = appearances.entry(key).or_insert((i, 0, 0));
+        appearance.0 = i;
+        appearance.1 += 1;
+    }
+    for key in &new.keys {
+        if let Some(appearance) = appearances.get_mut(key) {
+            appearance.2 += 1;
+        }
+    }
+    let unique: Vec<(usize, usize)> = new
+        .keys
+        .iter()
+        .enumerate()
+        .filter_map(|(j, key)| match appearances.get(key) {
+            Some(&(i, 1, 1)) => Some((i, j)),
+            _ => None,
+        })
+        .collect();
+
+    // find the longest run of pairs whose old indices are increasing, working backwards so
+    // that earlier pairs are preferred, as in `align_range`; `tails[k]` is the pair which
+    // starts the best run of length `k + 1` found so far
+    let mut tails: Vec<usize> = Vec::new();
+    let mut next: Vec<Option<usize>> = vec![None; unique.len()];
+    for (pair, &(i, _)) in unique.iter().enumerate().rev() {
+        let k = tails.partition_point(|&tail| unique[tail].0 > i);
+        next[pair] = k.checked_sub(1).map(|k| tails[k]);
+        if k == tails.len() {
+            tails.push(pair);
+        } else {
+            tails[k] = pair;
+        }
+    }
+    let mut anchors = Vec::with_capacity(tails.len());
+    let mut pair = tails.last().copied();
+    while let Some(current) = pair {
+        anchors.push(unique[current]);
+        pair = next[current];
+    }
+    anchors
+}
+
+/// Align a range of segments in the old message with a range of segments in the new message,
+/// maximising the number of matched segments, with identical segments weighted above segments
+/// which only share a name
+fn align_range(
+    old: &Side,
+    olds: Range<usize>,
+    new: &Side,
+    news: Range<usize>,
+) -> Vec<(usize, usize)> {
+    const IDENTICAL: usize = 3;
+    const SIMILAR: usize = 1;
+
+    let (n, m) = (olds.len(), news.len());
+    let weight = |i: usize, j: usize| {
+        let (i, j) = (olds.start + i, news.start + j);
+        if old.keys[i] == new.keys[j] {
+            Some(IDENTICAL)
+        } else if old.segment(i).name == new.segment(j).name {
+            Some(SIMILAR)
+        } else {
+            None
+        }
+    };
+
+    // scores[i][j] is the best score for aligning old[i..] with new[j..]
+    let mut scores = vec![vec![0; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            let matched = weight(i, j).map_or(0, |w| w + scores[i + 1][j + 1]);
+            scores[i][j] = matched.max(scores[i + 1][j]).max(scores[i][j + 1]);
+        }
+    }
+
+    let mut alignment = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        match weight(i, j) {
+            Some(w) if scores[i][j] == w + scores[i + 1][j + 1] => {
+                alignment.push((olds.start + i, news.start + j));
+                i += 1;
+                j += 1;
+            }
+            _ if scores[i][j] == scores[i + 1][j] => i += 1,
+            _ => j += 1,
+        }
+    }
+    alignment
+}
+
+/// The changes between two aligned segments
+fn segment_changes(old: &Side, i: usize, new: &Side, j: usize) -> Vec<Change> {
+    let (old_segment, new_segment) = (old.segment(i), new.segment(j));
+    let mut changes = Vec::new();
+    let mut old_query = old.query(i);
+    let mut new_query = new.query(j);
+    for field in 1..=old_segment.fields.len().max(new_segment.fields.len()) {
+        old_query.field = Some(field);
+        new_query.field = Some(field);
+        let old_field = old_segment.field(field);
+        let new_field = new_segment.field(field);
+        if is_msh_encoding(new_segment, field) {
+            let old_value = old_field.map(|f| f.raw_value()).unwrap_or_default();
+            let new_value = new_field.map(|f| f.raw_value()).unwrap_or_default();
+            if old_value != new_value {
+                changes.push(Change::Changed {
+                    query: new_query.clone(),
+                    old: old_value.to_string(),
+                    new: new_value.to_string(),
+                });
+            }
+            continue;
+        }
+        compare(
+            (old_field.map(Node::Field), old.separators(), &mut old_query),
+            (new_field.map(Node::Field), new.separators(), &mut new_query),
+            &mut changes,
+        );
+    }
+    changes
+}
+
+/// A field, repeat, component, or subcomponent
+#[derive(Debug, Clone, Copy)]
+enum Node<'a, 'm> {
+    Field(&'a Field<'m>),
+    Repeat(&'a Repeat<'m>),
+    Component(&'a Component<'m>),
+    Subcomponent(&'a Subcomponent<'m>),
+}
+
+impl<'a, 'm> Node<'a, 'm> {
+    fn children(&self) -> Vec<Node<'a, 'm>> {
+        match self {
+            Node::Field(field) => field.repeats.iter().map(Node::Repeat).collect(),
+            Node::Repeat(repeat) => repeat.components.iter().map(Node::Component).collect(),
+            Node::Component(component) => component
+                .subcomponents
+                .iter()
+                .map(Node::Subcomponent)
+                .collect(),
+            Node::Subcomponent(_) => Vec::new(),
+        }
+    }
+
+    /// Whether every subcomponent of the node is empty
+    fn is_empty(&self) -> bool {
+        match self {
+            Node::Subcomponent(subcomponent) => subcomponent.value.is_empty(),
+            _ => self.children().iter().all(Node::is_empty),
+        }
+    }
+
+    /// Set the index of a child of this node within a query
+    fn set_child_index(&self, query: &mut LocationQuery, index: Option<usize>) {
+        match self {
+            Node::Field(_) => query.repeat = index,
+            Node::Repeat(_) => query.component = index,
+            Node::Component(_) => query.subcomponent = index,
+            Node::Subcomponent(_) => {}
+        }
+    }
+
+    /// The decoded value of the node, with its children joined by the separators
+    fn decoded(&self, separators: &Separators) -> String {
+        let separator = match self {
+            Node::Field(_) => separators.repetition,
+            Node::Repeat(_) => separators.component,
+            Node::Component(_) => separators.subcomponent,
+            Node::Subcomponent(subcomponent) => {
+                return separators.decode(subcomponent.value).to_string();
+            }
+        };
+        let children: Vec<String> = self
+            .children()
+            .iter()
+            .map(|child| child.decoded(separators))
+            .collect();
+        children.join(&separator.to_string())
+    }
+}
+
+/// Compare two nodes at the same level, recursing into their children where needed. Each side
+/// is the node (if present), the separators of its message, and the query for its location.
+fn compare(
+    (old, old_separators, old_query): (Option<Node>, &Separators, &mut LocationQuery),
+    (new, new_separators, new_query): (Option<Node>, &Separators, &mut LocationQuery),
+    changes: &mut Vec<Change>,
+) {
+    let old = old.filter(|node| !node.is_empty());
+    let new = new.filter(|node| !node.is_empty());
+    let (old, new) = match (old, new) {
+        (None, None) => return,
+        (None, Some(new)) => {
+            changes.push(Change::Added {
+                query: new_query.clone(),
+                value: new.decoded(new_separators),
+            });
+            return;
+        }
+        (Some(old), None) => {
+            changes.push(Change::Removed {
+                query: old_query.clone(),
+                value: old.decoded(old_separators),
+            });
+            return;
+        }
+        (Some(old), Some(new)) => (old, new),
+    };
+
+    let old_children = old.children();
+    let new_children = new.children();
+    if old_children.is_empty() && new_children.is_empty() {
+        let old_value = old.decoded(old_separators);
+        let new_value = new.decoded(new_separators);
+        if old_value != new_value {
+            changes.push(Change::Changed {
+                query: new_query.clone(),
+                old: old_value,
+                new: new_value,
+            });
+        }
+        return;
+    }
+
+    // only identify children by index if either side has more than one of them
+    let indexed = old_children.len() > 1 || new_children.len() > 1;
+    for index in 0..old_children.len().max(new_children.len()) {
+        let query_index = indexed.then_some(index + 1);
+        old.set_child_index(old_query, query_index);
+        new.set_child_index(new_query, query_index);
+        compare(
+            (old_children.get(index).copied(), old_separators, old_query),
+            (new_children.get(index).copied(), new_separators, new_query),
+            changes,
+        );
+    }
+    old.set_child_index(old_query, None);
+    new.set_child_index(new_query, None);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    fn query(query: &str) -> LocationQuery {
+        LocationQuery::parse(query).unwrap()
+    }
+
+    fn changes(old: &str, new: &str) -> Vec<Change> {
+        let old = Message::parse(old).unwrap();
+        let new = Message::parse(new).unwrap();
+        diff(&old, &new).changes
+    }
+
+    #[test]
+    fn identical_messages_have_no_changes() {
+        let message = "MSH|^~\\&|A|B\rPID|1||123^^^MRN~456^^^SSN||Doe^John";
+        assert!(changes(message, message).is_empty());
+    }
+
+    #[test]
+    fn trailing_empty_values_are_ignored() {
+        assert!(changes("MSH|^~\\&|\rPID|1|", "MSH|^~\\&|\rPID|1||^&").is_empty());
+    }
+
+    #[test]
+    fn changes_are_reported_at_the_shallowest_level() {
+        assert_eq!(
+            changes("MSH|^~\\&|\rPID|1|||||||M", "MSH|^~\\&|\rPID|1|||||||F"),
+            vec![Change::Changed {
+                query: query("PID.8"),
+                old: "M".to_string(),
+                new: "F".to_string(),
+            }]
+        );
+        assert_eq!(
+            changes(
+                "MSH|^~\\&|\rPID|1||123^^^MRN&1.2&ISO",
+                "MSH|^~\\&|\rPID|1||123^^^MRN&1.3&ISO~456"
+            ),
+            vec![
+                Change::Changed {
+                    query: query("PID.3[1].4.2"),
+                    old: "1.2".to_string(),
+                    new: "1.3".to_string(),
+                },
+                Change::Added {
+                    query: query("PID.3[2]"),
+                    value: "456".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn added_and_removed_values_are_decoded() {
+        assert_eq!(
+            changes(
+                "MSH|^~\\&|\rPID|1||||Doe^John\\T\\Jane",
+                "MSH|^~\\&|\rPID|1||123^^^MRN"
+            ),
+            vec![
+                Change::Added {
+                    query: query("PID.3"),
+                    value: "123^^^MRN".to_string(),
+                },
+                Change::Removed {
+                    query: query("PID.5"),
+                    value: "Doe^John&Jane".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn values_are_compared_with_their_own_separators() {
+        assert_eq!(
+            changes(
+                "MSH|^~\\&|\rPID|1||123^^^MRN~456||A\\S\\B",
+                "MSH#*@!%#\rPID#1##123***MRN@456##A^B"
+            ),
+            vec![
+                Change::Changed {
+                    query: query("MSH.1"),
+                    old: "|".to_string(),
+                    new: "#".to_string(),
+                },
+                Change::Changed {
+                    query: query("MSH.2"),
+                    old: "^~\\&".to_string(),
+                    new: "*@!%".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn inserted_segments_are_aligned() {
+        assert_eq!(
+            changes(
+                "MSH|^~\\&|\rPID|1\rOBX|1||A\rOBX|2||B",
+                "MSH|^~\\&|\rPID|1\rNTE|1||note\rOBX|1||A\rOBX|2||B2\rOBX|3||C"
+            ),
+            vec![
+                Change::Added {
+                    query: query("NTE"),
+                    value: "NTE|1||note".to_string(),
+                },
+                Change::Changed {
+                    query: query("OBX[2].3"),
+                    old: "B".to_string(),
+                    new: "B2".to_string(),
+                },
+                Change::Added {
+                    query: query("OBX[3]"),
+                    value: "OBX|3||C".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn removed_segments_refer_to_the_old_message() {
+        assert_eq!(
+            changes(
+                "MSH|^~\\&|\rOBX|1||A\rOBX|2||B\rOBX|3||C",
+                "MSH|^~\\&|\rOBX|1||A\rOBX|3||C"
+            ),
+            vec![Change::Removed {
+                query: query("OBX[2]"),
+                value: "OBX|2||B".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn reordered_segments_are_moved() {
+        assert_eq!(
+            changes(
+                "MSH|^~\\&|\rPID|1\rNK1|1|Doe^Jane\rPV1|1",
+                "MSH|^~\\&|\rNK1|1|Doe^Jane\rPID|1\rPV1|1"
+            ),
+            vec![Change::Moved {
+                from: query("PID"),
+                to: query("PID"),
+            }]
+        );
+    }
+
+    #[test]
+    fn equivalent_segments_are_moved() {
+        assert_eq!(
+            changes(
+                "MSH|^~\\&|\rNTE|1||A\\S\\B\rPID|1",
+                "MSH|^~\\&|\rPID|1||\rNTE|1||A\\S\\B^&"
+            ),
+            vec![Change::Moved {
+                from: query("NTE"),
+                to: query("NTE"),
+            }]
+        );
+    }
+
+    #[test]
+    fn diffs_large_messages() {
+        let observations = |value: fn(usize) -> String| {
+            (1..=1000)
+                .map(|i| format!("OBX|{i}|NM|{}||{}", i % 7, value(i)))
+                .collect::<Vec<_>>()
+        };
+        let old = observations(|i| i.to_string());
+        let mut new = old.clone();
+        new[499] = "OBX|500|NM|3||-500".to_string();
+        new.insert(10, "NTE|1||note".to_string());
+        new.pop();
+        assert_eq!(
+            changes(
+                &format!("MSH|^~\\&|\r{}", old.join("\r")),
+                &format!("MSH|^~\\&|\r{}", new.join("\r"))
+            ),
+            vec![
+                Change::Added {
+                    query: query("NTE"),
+                    value: "NTE|1||note".to_string(),
+                },
+                Change::Changed {
+                    query: query("OBX[500].5"),
+                    old: "500".to_string(),
+                    new: "-500".to_string(),
+                },
+                Change::Removed {
+                    query: query("OBX[1000]"),
+                    value: "OBX|1000|NM|6||1000".to_string(),
+                },
+            ]
+        );
+
+        // no segments are identical, so every pair of segments is compared
+        let new = observations(|i| format!("{i}.0"));
+        let diff = changes(
+            &format!("MSH|^~\\&|\r{}", old.join("\r")),
+            &format!("MSH|^~\\&|\r{}", new.join("\r")),
+        );
+        assert_eq!(diff.len(), 1000);
+        assert!(diff
+            .iter()
+            .all(|change| matches!(change, Change::Changed { .. })));
+    }
+
+    #[test]
+    fn encoding_characters_are_compared_raw() {
+        assert_eq!(
+            changes("MSH|^~\\&|A", "MSH|^~@&|A"),
+            vec![Change::Changed {
+                query: query("MSH.2"),
+                old: "^~\\&".to_string(),
+                new: "^~@&".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn renders_changes() {
+        let diff = Diff {
+            changes: vec![
+                Change::Added {
+                    query: query("NTE[2]"),
+                    value: "NTE|2".to_string(),
+                },
+                Change::Removed {
+                    query: query("PID.13"),
+                    value: "555\r1234".to_string(),
+                },
+                Change::Changed {
+                    query: query("PID.5.2"),
+                    old: "John".to_string(),
+                    new: "Jane".to_string(),
+                },
+                Change::Moved {
+                    from: query("OBX[2]"),
+                    to: query("OBX[1]"),
+                },
+            ],
+        };
+        assert_eq!(
+            diff.to_string(),
+            "+ NTE[2]: \"NTE|2\"\n- PID.13: \"555\\r1234\"\n~ PID.5.2: \"John\" -> \"Jane\"\n> OBX[2] -> OBX[1]\n"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_changes() {
+        let changes = changes("MSH|^~\\&|\rPID|1|||||||M", "MSH|^~\\&|\rPID|1|||||||F");
+        let json = serde_json::to_value(Diff { changes }).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "kind": "changed",
+                "query": {
+                    "segment": "PID",
+                    "segment_index": null,
+                    "field": 8,
+                    "repeat": null,
+                    "component": null,
+                    "subcomponent": null
+                },
+                "old": "M",
+                "new": "F"
+            }])
+        );
+    }
+}
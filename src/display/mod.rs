@@ -1,6 +1,9 @@
 use crate::message::{Component, Field, Repeat, Separators, Subcomponent};
 use std::fmt::Display;
 
+mod render;
+pub use render::{table, tree, Layout, Rendered};
+
 /// A display implementation for segments.
 /// This will decode the escape sequences in the segment value
 /// using the separators. If the `#` flag is used, the raw value
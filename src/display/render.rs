@@ -0,0 +1,548 @@
+use crate::{
+    dictionary::{self, Version},
+    query::LocationQuery,
+    Message,
+};
+use std::{collections::HashMap, fmt::Display};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+/// How a [`Rendered`] message is laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Layout {
+    /// An indented tree, with each segment followed by its fields, and each field followed by
+    /// its repeats, components, and subcomponents
+    Tree,
+    /// A table with a row for each field, repeat, component, and subcomponent
+    Table,
+}
+
+/// A message rendered for people to read, created by [`tree`] or [`table`]. Every non-empty
+/// node of the message is shown with its location query path and decoded value, and optionally
+/// its name from the dictionary.
+///
+/// By default, colour is off, lines aren't truncated, empty fields are hidden, and fields with
+/// a single component are shown on a single line.
+#[derive(Debug, Clone)]
+pub struct Rendered<'m> {
+    message: &'m Message<'m>,
+    layout: Layout,
+    color: bool,
+    width: Option<usize>,
+    empty_fields: bool,
+    collapse: bool,
+    names: bool,
+}
+
+/// Render a message as an indented tree of its segments, fields, repeats, components, and
+/// subcomponents.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::{display, Message};
+///
+/// let message = Message::parse("MSH|^~\\&|foo\rPID|1||123~456||Doe^Jane|||F").unwrap();
+/// let tree = display::tree(&message).with_names(true).to_string();
+/// assert_eq!(
+///     tree,
+///     r#"MSH (Message Header)
+///   MSH.1 (Field Separator)      |
+///   MSH.2 (Encoding Characters)  ^~\&
+///   MSH.3 (Sending Application)  foo
+/// PID (Patient Identification)
+///   PID.1 (Set ID - PID)                  1
+///   PID.3 (Patient Identifier List)       123~456
+///     PID.3[1] (Patient Identifier List)  123
+///     PID.3[2] (Patient Identifier List)  456
+///   PID.5 (Patient Name)                  Doe^Jane
+///     PID.5.1 (Family Name)               Doe
+///     PID.5.2 (Given Name)                Jane
+///   PID.8 (Administrative Sex)            F
+/// "#
+/// );
+/// ```
+pub fn tree<'m>(message: &'m Message<'m>) -> Rendered<'m> {
+    Rendered::new(message, Layout::Tree)
+}
+
+/// Render a message as a table with a row for each field, repeat, component, and subcomponent.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::{display, Message};
+///
+/// let message = Message::parse("MSH|^~\\&|foo\rPID|1||123||Doe^Jane").unwrap();
+/// let table = display::table(&message).to_string();
+/// assert_eq!(
+///     table,
+///     "PATH     VALUE
+/// MSH.1    |
+/// MSH.2    ^~\\&
+/// MSH.3    foo
+/// PID.1    1
+/// PID.3    123
+/// PID.5    Doe^Jane
+/// PID.5.1  Doe
+/// PID.5.2  Jane
+/// "
+/// );
+/// ```
+pub fn table<'m>(message: &'m Message<'m>) -> Rendered<'m> {
+    Rendered::new(message, Layout::Table)
+}
+
+/// A node of the message to render
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Node {
+    depth: usize,
+    path: String,
+    name: Option<&'static str>,
+    value: String,
+}
+
+impl<'m> Rendered<'m> {
+    /// Render a message with the given layout
+    pub fn new(message: &'m Message<'m>, layout: Layout) -> Self {
+        Rendered {
+            message,
+            layout,
+            color: false,
+            width: None,
+            empty_fields: false,
+            collapse: true,
+            names: false,
+        }
+    }
+
+    /// Colour the output with ANSI escape codes
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Truncate lines wider than the given number of terminal columns, ending them with `…`.
+    /// Wide characters, such as CJK ideographs, take up two columns.
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Show empty fields, repeats, components, and subcomponents as well
+    pub fn with_empty_fields(mut self, empty_fields: bool) -> Self {
+        self.empty_fields = empty_fields;
+        self
+    }
+
+    /// Show fields with a single component on a single line, rather than also showing the
+    /// component. This is on by default.
+    pub fn with_collapsed_components(mut self, collapse: bool) -> Self {
+        self.collapse = collapse;
+        self
+    }
+
+    /// Show the name of each segment, field, component, and subcomponent which is in the
+    /// dictionary for the message's version (or the latest version, if the message's version
+    /// isn't known)
+    pub fn with_names(mut self, names: bool) -> Self {
+        self.names = names;
+        self
+    }
+
+    /// Collect the nodes to render, in message order. Segments have a depth of 0.
+    fn nodes(&self) -> Vec<Node> {
+        let message = self.message;
+        let version = message.version().unwrap_or(Version::LATEST);
+        let mut counts = HashMap::<&str, usize>::new();
+        for segment in message.segments() {
+            *counts.entry(segment.name).or_default() += 1;
+        }
+
+        let mut nodes = Vec::new();
+        let mut seen = HashMap::<&str, usize>::new();
+        let mut push = |depth: usize, query: &LocationQuery, value: String| {
+            nodes.push(Node {
+                depth,
+                path: query.to_string(),
+                name: self.names.then(|| name(version, query)).flatten(),
+                value: printable(&value),
+            });
+        };
+        let decode = |raw: &str| message.separators.decode(raw).to_string();
+
+        for segment in message.segments() {
+            let index = seen.entry(segment.name).or_default();
+            *index += 1;
+            let segment_query = LocationQuery {
+                segment: segment.name.to_string(),
+                segment_index: (counts[segment.name] > 1).then_some(*index),
+                field: None,
+                repeat: None,
+                component: None,
+                subcomponent: None,
+            };
+            push(0, &segment_query, String::new());
+
+            for (i, field) in segment.fields.iter().enumerate() {
+                if field.is_empty() && !self.empty_fields {
+                    continue;
+                }
+                let field_query = LocationQuery {
+                    field: Some(i + 1),
+                    ..segment_query.clone()
+                };
+                if segment.name == "MSH" && i < 2 {
+                    // the field separator and encoding characters aren't encoded values
+                    push(1, &field_query, field.raw_value().to_string());
+                    continue;
+                }
+
+                let repeated = field.repeats.len() > 1;
+                if repeated {
+                    push(1, &field_query, decode(field.raw_value()));
+                }
+                for (r, repeat) in field.repeats.iter().enumerate() {
+                    if repeat.is_empty() && !(self.empty_fields && repeated) {
+                        if !repeated {
+                            push(1, &field_query, String::new());
+                        }
+                        continue;
+                    }
+                    let (depth, repeat_query) = if repeated {
+                        let query = LocationQuery {
+                            repeat: Some(r + 1),
+                            ..field_query.clone()
+                        };
+                        (2, query)
+                    } else {
+                        (1, field_query.clone())
+                    };
+                    push(depth, &repeat_query, decode(repeat.raw_value()));
+
+                    let components = &repeat.components;
+                    let single = components.len() == 1 && components[0].subcomponents.len() <= 1;
+                    if repeat.is_empty() || (single && self.collapse) {
+                        continue;
+                    }
+                    for (c, component) in components.iter().enumerate() {
+                        if component.is_empty() && !self.empty_fields {
+                            continue;
+                        }
+                        let component_query = LocationQuery {
+                            component: Some(c + 1),
+                            ..repeat_query.clone()
+                        };
+                        push(depth + 1, &component_query, decode(component.raw_value()));
+                        if component.subcomponents.len() <= 1 {
+                            continue;
+                        }
+                        for (s, subcomponent) in component.subcomponents.iter().enumerate() {
+                            if subcomponent.value.is_empty() && !self.empty_fields {
+                                continue;
+                            }
+                            let subcomponent_query = LocationQuery {
+                                subcomponent: Some(s + 1),
+                                ..component_query.clone()
+                            };
+                            push(depth + 2, &subcomponent_query, decode(subcomponent.value));
+                        }
+                    }
+                }
+            }
+        }
+        nodes
+    }
+
+    /// Write a line made of styled parts, truncating it to the width
+    fn line(&self, f: &mut std::fmt::Formatter<'_>, parts: &[(&str, &str)]) -> std::fmt::Result {
+        let length: usize = parts.iter().map(|(text, _)| text.width()).sum();
+        let mut budget = match self.width {
+            Some(width) if length > width => width.saturating_sub(1),
+            _ => usize::MAX,
+        };
+        for (text, style) in parts {
+            if text.is_empty() {
+                continue;
+            }
+            let width = text.width();
+            let text = if width > budget {
+                let mut used = 0;
+                text.chars()
+                    .take_while(|c| {
+                        used += c.width().unwrap_or_default();
+                        used <= budget
+                    })
+                    .collect::<String>()
+            } else {
+                text.to_string()
+            };
+            budget = budget.saturating_sub(width);
+            if self.color && !style.is_empty() {
+                write!(f, "{style}{text}{RESET}")?;
+            } else {
+                write!(f, "{text}")?;
+            }
+            if budget == 0 {
+                break;
+            }
+        }
+        if self.width.is_some_and(|width| length > width) {
+            write!(f, "…")?;
+        }
+        writeln!(f)
+    }
+
+    fn fmt_tree(&self, f: &mut std::fmt::Formatter<'_>, nodes: &[Node]) -> std::fmt::Result {
+        for segment in nodes.chunk_by(|_, node| node.depth > 0) {
+            let label = |node: &Node| match node.name {
+                Some(name) => format!(" ({name})"),
+                None => String::new(),
+            };
+            let (header, fields) = segment.split_first().expect("Chunks aren't empty");
+            self.line(f, &[(&header.path, BOLD), (&label(header), DIM)])?;
+
+            let width = fields
+                .iter()
+                .map(|node| node.depth * 2 + node.path.width() + label(node).width())
+                .max()
+                .unwrap_or_default();
+            for node in fields {
+                let indent = " ".repeat(node.depth * 2);
+                let name = label(node);
+                let pad = if node.value.is_empty() {
+                    String::new()
+                } else {
+                    let used = node.depth * 2 + node.path.width() + name.width();
+                    " ".repeat(width - used + 2)
+                };
+                self.line(
+                    f,
+                    &[
+                        (&indent, ""),
+                        (&node.path, CYAN),
+                        (&name, DIM),
+                        (&pad, ""),
+                        (&node.value, ""),
+                    ],
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn fmt_table(&self, f: &mut std::fmt::Formatter<'_>, nodes: &[Node]) -> std::fmt::Result {
+        let rows = nodes
+            .iter()
+            .filter(|node| node.depth > 0)
+            .map(|node| {
+                (
+                    node.path.as_str(),
+                    node.name.unwrap_or_default(),
+                    node.value.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let path_width = rows
+            .iter()
+            .map(|(path, ..)| path.width())
+            .chain(["PATH".len()])
+            .max()
+            .unwrap_or_default();
+        let name_width = rows
+            .iter()
+            .map(|(_, name, _)| name.width())
+            .chain(["NAME".len()])
+            .max()
+            .unwrap_or_default();
+        let pad = |text: &str, width: usize| " ".repeat(width - text.width() + 2);
+
+        let header = [("PATH", path_width), ("NAME", name_width)];
+        let mut parts = Vec::new();
+        for (title, width) in header.iter().take(if self.names { 2 } else { 1 }) {
+            parts.push((title.to_string(), BOLD));
+            parts.push((pad(title, *width), ""));
+        }
+        parts.push(("VALUE".to_string(), BOLD));
+        let parts = parts
+            .iter()
+            .map(|(t, s)| (t.as_str(), *s))
+            .collect::<Vec<_>>();
+        self.line(f, &parts)?;
+
+        for (path, name, value) in rows {
+            let path_pad = if value.is_empty() && !self.names {
+                String::new()
+            } else {
+                pad(path, path_width)
+            };
+            let name_pad = if value.is_empty() {
+                String::new()
+            } else {
+                pad(name, name_width)
+            };
+            let mut parts = vec![(path, CYAN), (path_pad.as_str(), "")];
+            if self.names {
+                parts.extend([(name, DIM), (name_pad.as_str(), "")]);
+            }
+            parts.push((value, ""));
+            self.line(f, &parts)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Rendered<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let nodes = self.nodes();
+        match self.layout {
+            Layout::Tree => self.fmt_tree(f, &nodes),
+            Layout::Table => self.fmt_table(f, &nodes),
+        }
+    }
+}
+
+/// The dictionary name of a location, if the dictionary knows the location all the way down
+fn name(version: Version, query: &LocationQuery) -> Option<&'static str> {
+    let definition = dictionary::lookup(version, query)?;
+    let known = match (query.field, query.component, query.subcomponent) {
+        (None, ..) => true,
+        (Some(_), None, _) => definition.field.is_some(),
+        (Some(_), Some(_), None) => definition.component.is_some(),
+        (Some(_), Some(_), Some(_)) => definition.subcomponent.is_some(),
+    };
+    known.then(|| definition.name())
+}
+
+/// Escape control characters in a value so that it stays on one line
+fn printable(value: &str) -> String {
+    if !value.chars().any(char::is_control) {
+        return value.to_string();
+    }
+    value
+        .chars()
+        .map(|c| {
+            if c.is_control() {
+                c.escape_default().to_string()
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    const MESSAGE: &str = "MSH|^~\\&|foo\rNTE|1||a&b^c\rNTE|2||line\\X0A\\two|";
+
+    #[test]
+    fn tree_numbers_repeated_segments() {
+        let message = Message::parse(MESSAGE).unwrap();
+        assert_eq!(
+            tree(&message).to_string(),
+            r#"MSH
+  MSH.1  |
+  MSH.2  ^~\&
+  MSH.3  foo
+NTE[1]
+  NTE[1].1          1
+  NTE[1].3          a&b^c
+    NTE[1].3.1      a&b
+      NTE[1].3.1.1  a
+      NTE[1].3.1.2  b
+    NTE[1].3.2      c
+NTE[2]
+  NTE[2].1  2
+  NTE[2].3  line\ntwo
+"#
+        );
+    }
+
+    #[test]
+    fn tree_can_show_empty_fields_and_single_components() {
+        let message = Message::parse("MSH|^~\\&\rPID|1||123").unwrap();
+        assert_eq!(
+            tree(&message)
+                .with_empty_fields(true)
+                .with_collapsed_components(false)
+                .to_string(),
+            r#"MSH
+  MSH.1  |
+  MSH.2  ^~\&
+  MSH.3
+PID
+  PID.1      1
+    PID.1.1  1
+  PID.2
+  PID.3      123
+    PID.3.1  123
+"#
+        );
+    }
+
+    #[test]
+    fn table_shows_names() {
+        let message = Message::parse("MSH|^~\\&\rPID|1||123||Doe^Jane\rZFO|x").unwrap();
+        assert_eq!(
+            table(&message).with_names(true).to_string(),
+            r#"PATH     NAME                     VALUE
+MSH.1    Field Separator          |
+MSH.2    Encoding Characters      ^~\&
+PID.1    Set ID - PID             1
+PID.3    Patient Identifier List  123
+PID.5    Patient Name             Doe^Jane
+PID.5.1  Family Name              Doe
+PID.5.2  Given Name               Jane
+ZFO.1                             x
+"#
+        );
+    }
+
+    #[test]
+    fn lines_are_truncated_to_the_width() {
+        let message = Message::parse("MSH|^~\\&\rNTE|1||Lorem ipsum dolor sit amet").unwrap();
+        assert_eq!(
+            tree(&message).with_width(16).to_string(),
+            r#"MSH
+  MSH.1  |
+  MSH.2  ^~\&
+NTE
+  NTE.1  1
+  NTE.3  Lorem …
+"#
+        );
+        assert_eq!(
+            table(&message).with_width(10).to_string(),
+            "PATH   VA…\nMSH.1  |\nMSH.2  ^~…\nNTE.1  1\nNTE.3  Lo…\n"
+        );
+    }
+
+    #[test]
+    fn lines_are_truncated_to_the_display_width() {
+        let message = Message::parse("MSH|^~\\&\rNTE|1||日本語のテキスト").unwrap();
+        assert_eq!(
+            tree(&message).with_width(14).to_string(),
+            "MSH\n  MSH.1  |\n  MSH.2  ^~\\&\nNTE\n  NTE.1  1\n  NTE.3  日本…\n"
+        );
+    }
+
+    #[test]
+    fn output_can_be_coloured() {
+        let message = Message::parse("MSH|^~\\&\rPID|1").unwrap();
+        assert_eq!(
+            tree(&message).with_color(true).with_names(true).to_string(),
+            "\x1b[1mMSH\x1b[0m\x1b[2m (Message Header)\x1b[0m\n  \
+            \x1b[36mMSH.1\x1b[0m\x1b[2m (Field Separator)\x1b[0m      |\n  \
+            \x1b[36mMSH.2\x1b[0m\x1b[2m (Encoding Characters)\x1b[0m  ^~\\&\n\
+            \x1b[1mPID\x1b[0m\x1b[2m (Patient Identification)\x1b[0m\n  \
+            \x1b[36mPID.1\x1b[0m\x1b[2m (Set ID - PID)\x1b[0m  1\n"
+        );
+    }
+}
@@ -0,0 +1,532 @@
+//! Conversions from HL7 v2 data types to FHIR R4 data types, following the HL7 v2-to-FHIR data
+//! type mappings.
+//!
+//! Each conversion takes a single repeat of a field (or a raw primitive value) and returns
+//! `None` if there's nothing to convert, so that empty values are left out of resources.
+
+use crate::{
+    datetime::{parse_date, parse_timestamp, TimeStampOffset},
+    message::{Repeat, Separators},
+};
+use serde_json::{json, Map, Value};
+
+/// The system for identifier types (HL7 table 0203)
+pub const IDENTIFIER_TYPE: &str = "http://terminology.hl7.org/CodeSystem/v2-0203";
+
+/// The decoded value of a raw primitive value, or `None` if it's empty or the HL7 null (`""`)
+pub fn string(raw: &str, separators: &Separators) -> Option<String> {
+    match raw {
+        "" | "\"\"" => None,
+        raw => Some(separators.decode(raw).to_string()),
+    }
+}
+
+/// The decoded value of the first subcomponent of a component of a repeat
+pub fn component(repeat: &Repeat, number: usize, separators: &Separators) -> Option<String> {
+    let component = repeat.component(number)?;
+    string(component.subcomponent(1)?.value, separators)
+}
+
+/// Convert an HL7 timestamp (`TS` or `DTM`) to a FHIR `dateTime`, keeping its precision where
+/// FHIR allows it. FHIR times need a time zone, so timestamps without an offset use the
+/// `default_offset` (usually the offset of the message's `MSH-7`), and are truncated to their
+/// date if there is no default. Times precise to the minute are completed with `:00` seconds,
+/// while times precise only to the hour are truncated to their date.
+pub fn date_time(raw: &str, default_offset: Option<TimeStampOffset>) -> Option<String> {
+    let timestamp = parse_timestamp(raw, false).ok()?;
+    let mut value = format!("{:04}", timestamp.year);
+    let Some(month) = timestamp.month else {
+        return Some(value);
+    };
+    value.push_str(&format!("-{month:02}"));
+    let Some(day) = timestamp.day else {
+        return Some(value);
+    };
+    value.push_str(&format!("-{day:02}"));
+    let (Some(hour), Some(minute), Some(offset)) = (
+        timestamp.hour,
+        timestamp.minute,
+        timestamp.offset.or(default_offset),
+    ) else {
+        return Some(value);
+    };
+    value.push_str(&format!(
+        "T{hour:02}:{minute:02}:{:02}",
+        timestamp.second.unwrap_or_default()
+    ));
+    if let Some(microsecond) = timestamp.microsecond {
+        let fraction = format!("{microsecond:06}");
+        let fraction = fraction.trim_end_matches('0');
+        value.push('.');
+        value.push_str(if fraction.is_empty() { "0" } else { fraction });
+    }
+    let sign = if offset.hours < 0 { '-' } else { '+' };
+    value.push_str(&format!(
+        "{sign}{:02}:{:02}",
+        offset.hours.unsigned_abs(),
+        offset.minutes
+    ));
+    Some(value)
+}
+
+/// Convert an HL7 timestamp to a FHIR `instant`, which must be precise to at least the second
+/// and have a time zone. Timestamps without an offset use the `default_offset`.
+pub fn instant(raw: &str, default_offset: Option<TimeStampOffset>) -> Option<String> {
+    let timestamp = parse_timestamp(raw, false).ok()?;
+    timestamp.second?;
+    date_time(raw, default_offset).filter(|value| value.contains('T'))
+}
+
+/// Convert an HL7 date (`DT`), or the date of a timestamp, to a FHIR `date`
+pub fn date(raw: &str) -> Option<String> {
+    let date = parse_date(raw.get(..8).unwrap_or(raw), false).ok()?;
+    Some(match (date.month, date.day) {
+        (Some(month), Some(day)) => format!("{:04}-{month:02}-{day:02}", date.year),
+        (Some(month), None) => format!("{:04}-{month:02}", date.year),
+        _ => format!("{:04}", date.year),
+    })
+}
+
+/// The FHIR system for an HL7 coding system name, such as `LN` for LOINC or `HL70078` for an HL7
+/// table. Returns `None` for local coding systems.
+pub fn code_system(name: &str) -> Option<String> {
+    let system = match name {
+        "LN" => "http://loinc.org",
+        "SCT" | "SNM" => "http://snomed.info/sct",
+        "I9" | "I9C" => "http://hl7.org/fhir/sid/icd-9-cm",
+        "I10" => "http://hl7.org/fhir/sid/icd-10",
+        "I10C" => "http://hl7.org/fhir/sid/icd-10-cm",
+        "UCUM" => "http://unitsofmeasure.org",
+        "RXNORM" | "RXN" => "http://www.nlm.nih.gov/research/umls/rxnorm",
+        "CVX" => "http://hl7.org/fhir/sid/cvx",
+        "NDC" => "http://hl7.org/fhir/sid/ndc",
+        "C4" | "CPT" => "http://www.ama-assn.org/go/cpt",
+        name => {
+            let table = name.strip_prefix("HL7")?;
+            if table.is_empty() || !table.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            return Some(format!("http://terminology.hl7.org/CodeSystem/v2-{table}"));
+        }
+    };
+    Some(system.to_string())
+}
+
+/// Convert a coded element (`CE`, `CWE`, `CNE`, or a plain `ID` / `IS` code) to a FHIR
+/// `CodeableConcept`, with the primary and alternate codings. Codings without a coding system
+/// use the `default_system`, if any, which is useful for values drawn from HL7 tables.
+pub fn codeable_concept(
+    repeat: &Repeat,
+    default_system: Option<&str>,
+    separators: &Separators,
+) -> Option<Value> {
+    let coding = |first: usize| {
+        let code = component(repeat, first, separators)?;
+        let system = component(repeat, first + 2, separators)
+            .and_then(|name| code_system(&name))
+            .or_else(|| default_system.map(str::to_string));
+        Some(object([
+            ("system", system.map(Value::from)),
+            ("code", Some(code.into())),
+            (
+                "display",
+                component(repeat, first + 1, separators).map(Value::from),
+            ),
+        ]))
+    };
+    let codings = [coding(1), coding(4)]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    let text = component(repeat, 9, separators).or_else(|| {
+        if codings.is_empty() {
+            component(repeat, 2, separators)
+        } else {
+            None
+        }
+    });
+    if codings.is_empty() && text.is_none() {
+        return None;
+    }
+    Some(object([
+        ("coding", array(codings)),
+        ("text", text.map(Value::from)),
+    ]))
+}
+
+/// A FHIR `CodeableConcept` with a single code from a system
+pub fn concept(system: &str, code: &str) -> Value {
+    json!({ "coding": [{ "system": system, "code": code }] })
+}
+
+/// Convert an extended person name (`XPN`) to a FHIR `HumanName`
+pub fn human_name(repeat: &Repeat, separators: &Separators) -> Option<Value> {
+    name(repeat, 1, 7, separators)
+}
+
+/// A FHIR `HumanName` from the components of a name, starting with the family name
+fn name(repeat: &Repeat, first: usize, type_code: usize, separators: &Separators) -> Option<Value> {
+    let given = [first + 1, first + 2]
+        .into_iter()
+        .filter_map(|n| component(repeat, n, separators))
+        .map(Value::from)
+        .collect::<Vec<_>>();
+    let name_use = component(repeat, type_code, separators).and_then(|code| {
+        Some(match code.as_str() {
+            "L" => "official",
+            "D" => "usual",
+            "M" => "maiden",
+            "N" => "nickname",
+            "T" => "temp",
+            _ => return None,
+        })
+    });
+    let name = object([
+        ("use", name_use.map(Value::from)),
+        (
+            "family",
+            component(repeat, first, separators).map(Value::from),
+        ),
+        ("given", array(given)),
+        (
+            "prefix",
+            component(repeat, first + 4, separators).map(|p| json!([p])),
+        ),
+        (
+            "suffix",
+            component(repeat, first + 3, separators).map(|s| json!([s])),
+        ),
+    ]);
+    has_value(&name, &["family", "given", "prefix", "suffix"]).then_some(name)
+}
+
+/// Convert an extended composite ID number and name (`XCN`) to a FHIR `Reference` to a
+/// practitioner, with the ID as its identifier and the name as its display
+pub fn practitioner(repeat: &Repeat, separators: &Separators) -> Option<Value> {
+    let display = name(repeat, 2, 10, separators).map(|name| {
+        let name = name.as_object().cloned().unwrap_or_default();
+        let parts = |key: &str| match name.get(key) {
+            Some(Value::String(s)) => vec![s.clone()],
+            Some(Value::Array(values)) => values
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect(),
+            _ => Vec::new(),
+        };
+        [
+            parts("prefix"),
+            parts("given"),
+            parts("family"),
+            parts("suffix"),
+        ]
+        .concat()
+        .join(" ")
+    });
+    let identifier = component(repeat, 1, separators).map(|value| json!({ "value": value }));
+    if display.is_none() && identifier.is_none() {
+        return None;
+    }
+    Some(object([
+        ("identifier", identifier),
+        ("display", display.map(Value::from)),
+    ]))
+}
+
+/// Convert a hierarchic designator (`HD`) in a component to a URI, using its universal ID
+fn hd_uri(repeat: &Repeat, number: usize, separators: &Separators) -> Option<String> {
+    let hd = repeat.component(number)?;
+    let subcomponent = |n: usize| hd.subcomponent(n).and_then(|s| string(s.value, separators));
+    let id = subcomponent(2)?;
+    match subcomponent(3).as_deref() {
+        Some("ISO") => Some(format!("urn:oid:{id}")),
+        Some("UUID") => Some(format!("urn:uuid:{}", id.to_lowercase())),
+        Some("URI") => Some(id),
+        _ => None,
+    }
+}
+
+/// Convert an extended composite ID (`CX`) to a FHIR `Identifier`. The system is taken from the
+/// universal ID of the assigning authority, and its namespace becomes the assigner.
+pub fn identifier(repeat: &Repeat, separators: &Separators) -> Option<Value> {
+    let value = component(repeat, 1, separators)?;
+    let assigner =
+        component(repeat, 4, separators).map(|namespace| json!({ "display": namespace }));
+    Some(object([
+        (
+            "type",
+            component(repeat, 5, separators).map(|code| concept(IDENTIFIER_TYPE, &code)),
+        ),
+        ("system", hd_uri(repeat, 4, separators).map(Value::from)),
+        ("value", Some(value.into())),
+        ("assigner", assigner),
+    ]))
+}
+
+/// Convert an entity identifier (`EI`) to a FHIR `Identifier` with the given type code
+pub fn entity_identifier(
+    repeat: &Repeat,
+    type_code: &str,
+    separators: &Separators,
+) -> Option<Value> {
+    let value = component(repeat, 1, separators)?;
+    let system = match component(repeat, 4, separators).as_deref() {
+        Some("ISO") => component(repeat, 3, separators).map(|id| format!("urn:oid:{id}")),
+        _ => None,
+    };
+    Some(object([
+        ("type", Some(concept(IDENTIFIER_TYPE, type_code))),
+        ("system", system.map(Value::from)),
+        ("value", Some(value.into())),
+        (
+            "assigner",
+            component(repeat, 2, separators).map(|namespace| json!({ "display": namespace })),
+        ),
+    ]))
+}
+
+/// Convert an extended address (`XAD`) to a FHIR `Address`
+pub fn address(repeat: &Repeat, separators: &Separators) -> Option<Value> {
+    let lines = [1, 2]
+        .into_iter()
+        .filter_map(|n| component(repeat, n, separators))
+        .map(Value::from)
+        .collect::<Vec<_>>();
+    let address_type = component(repeat, 7, separators);
+    let (address_use, kind) = match address_type.as_deref() {
+        Some("H") => (Some("home"), None),
+        Some("B" | "O") => (Some("work"), None),
+        Some("C") => (Some("temp"), None),
+        Some("BA") => (Some("old"), None),
+        Some("M") => (None, Some("postal")),
+        _ => (None, None),
+    };
+    let address = object([
+        ("use", address_use.map(Value::from)),
+        ("type", kind.map(Value::from)),
+        ("line", array(lines)),
+        ("city", component(repeat, 3, separators).map(Value::from)),
+        ("state", component(repeat, 4, separators).map(Value::from)),
+        (
+            "postalCode",
+            component(repeat, 5, separators).map(Value::from),
+        ),
+        ("country", component(repeat, 6, separators).map(Value::from)),
+    ]);
+    has_value(
+        &address,
+        &["line", "city", "state", "postalCode", "country"],
+    )
+    .then_some(address)
+}
+
+/// Convert an extended telecommunication number (`XTN`) to a FHIR `ContactPoint`, using the
+/// `default_use` if the number doesn't have a use code (i.e. `home` for `PID.13`)
+pub fn contact_point(
+    repeat: &Repeat,
+    default_use: Option<&str>,
+    separators: &Separators,
+) -> Option<Value> {
+    let equipment = component(repeat, 3, separators);
+    let email = component(repeat, 4, separators);
+    let system = match equipment.as_deref() {
+        Some("Internet" | "X.400") => "email",
+        Some("FX") => "fax",
+        Some("BP") => "pager",
+        _ if component(repeat, 1, separators).is_none() && email.is_some() => "email",
+        _ => "phone",
+    };
+    let value = if system == "email" {
+        email.or_else(|| component(repeat, 1, separators))
+    } else {
+        component(repeat, 1, separators).or_else(|| {
+            let parts = [5, 6, 7]
+                .into_iter()
+                .filter_map(|n| component(repeat, n, separators))
+                .collect::<Vec<_>>();
+            (!parts.is_empty()).then(|| parts.join(" "))
+        })
+    }?;
+    let contact_use = match component(repeat, 2, separators).as_deref() {
+        Some("PRN" | "ORN" | "VHN") => Some("home"),
+        Some("WPN") => Some("work"),
+        Some("PRS") => Some("mobile"),
+        Some(_) => None,
+        None => default_use,
+    };
+    let contact_use = match equipment.as_deref() {
+        Some("CP") => Some("mobile"),
+        _ => contact_use,
+    };
+    Some(object([
+        ("system", Some(system.into())),
+        ("value", Some(value.into())),
+        ("use", contact_use.map(Value::from)),
+    ]))
+}
+
+/// A JSON object with the entries that have values, leaving out `None`s
+pub fn object<const N: usize>(entries: [(&str, Option<Value>); N]) -> Value {
+    Value::Object(
+        entries
+            .into_iter()
+            .filter_map(|(key, value)| Some((key.to_string(), value?)))
+            .collect::<Map<_, _>>(),
+    )
+}
+
+/// A JSON array of the values, or `None` if there aren't any
+pub fn array(values: Vec<Value>) -> Option<Value> {
+    (!values.is_empty()).then_some(Value::Array(values))
+}
+
+/// Whether an object has any of the keys
+fn has_value(object: &Value, keys: &[&str]) -> bool {
+    keys.iter().any(|key| object.get(key).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Message;
+    use pretty_assertions_sorted::assert_eq;
+
+    fn first_repeat<'m>(message: &'m Message, segment: &str, field: usize) -> &'m Repeat<'m> {
+        message
+            .segment(segment)
+            .and_then(|s| s.field(field))
+            .and_then(|f| f.repeat(1))
+            .unwrap()
+    }
+
+    #[test]
+    fn converts_dates_and_times() {
+        let mountain = Some(TimeStampOffset {
+            hours: -7,
+            minutes: 0,
+        });
+        assert_eq!(date_time("2024", None).as_deref(), Some("2024"));
+        assert_eq!(date_time("202402", None).as_deref(), Some("2024-02"));
+        assert_eq!(
+            date_time("20240229", mountain).as_deref(),
+            Some("2024-02-29")
+        );
+        assert_eq!(
+            date_time("202402291307", mountain).as_deref(),
+            Some("2024-02-29T13:07:00-07:00")
+        );
+        assert_eq!(
+            date_time("202402291307", None).as_deref(),
+            Some("2024-02-29")
+        );
+        assert_eq!(
+            date_time("2024022913+0000", None).as_deref(),
+            Some("2024-02-29")
+        );
+        assert_eq!(
+            date_time("20240229130759.25-0600", mountain).as_deref(),
+            Some("2024-02-29T13:07:59.25-06:00")
+        );
+        assert_eq!(date_time("A0-", None), None);
+        assert_eq!(
+            instant("20240229130759", mountain).as_deref(),
+            Some("2024-02-29T13:07:59-07:00")
+        );
+        assert_eq!(instant("202402291307", mountain), None);
+        assert_eq!(instant("20240229130759", None), None);
+        assert_eq!(date("19241010").as_deref(), Some("1924-10-10"));
+        assert_eq!(date("192410101200").as_deref(), Some("1924-10-10"));
+        assert_eq!(date(""), None);
+    }
+
+    #[test]
+    fn converts_coded_elements() {
+        let message = Message::parse(
+            "MSH|^~\\&|\rOBX|1|CE|6690-2^Leukocytes^LN^wbc^WBC^Local|1|^PENICILLIN|H",
+        )
+        .unwrap();
+        let separators = &message.separators;
+        assert_eq!(
+            codeable_concept(first_repeat(&message, "OBX", 3), None, separators),
+            Some(json!({
+                "coding": [
+                    { "system": "http://loinc.org", "code": "6690-2", "display": "Leukocytes" },
+                    { "code": "wbc", "display": "WBC" },
+                ],
+            }))
+        );
+        assert_eq!(
+            codeable_concept(first_repeat(&message, "OBX", 5), None, separators),
+            Some(json!({ "text": "PENICILLIN" }))
+        );
+        assert_eq!(
+            codeable_concept(
+                first_repeat(&message, "OBX", 6),
+                Some("http://terminology.hl7.org/CodeSystem/v2-0078"),
+                separators
+            ),
+            Some(concept(
+                "http://terminology.hl7.org/CodeSystem/v2-0078",
+                "H"
+            ))
+        );
+        assert_eq!(code_system("HL70203").as_deref(), Some(IDENTIFIER_TYPE));
+        assert_eq!(code_system("HL7"), None);
+        assert_eq!(code_system("pCLOCD"), None);
+    }
+
+    #[test]
+    fn converts_people_and_places() {
+        let message = Message::parse(
+            "MSH|^~\\&|\r\
+             PID|1||123^^^MIE&1.2.3&ISO^MR||Doe^Jane^Q^Jr^Dr^^L||||||1 Main St^Apt 2^Town^ST^12345^USA^H||^PRN^PH^^1^555^1234567~^NET^Internet^jane@example.com\r\
+             PV1|1|I|||||37^DISNEY^WALT^^^DR",
+        )
+        .unwrap();
+        let separators = &message.separators;
+        assert_eq!(
+            identifier(first_repeat(&message, "PID", 3), separators),
+            Some(json!({
+                "type": concept(IDENTIFIER_TYPE, "MR"),
+                "system": "urn:oid:1.2.3",
+                "value": "123",
+                "assigner": { "display": "MIE" },
+            }))
+        );
+        assert_eq!(
+            human_name(first_repeat(&message, "PID", 5), separators),
+            Some(json!({
+                "use": "official",
+                "family": "Doe",
+                "given": ["Jane", "Q"],
+                "prefix": ["Dr"],
+                "suffix": ["Jr"],
+            }))
+        );
+        assert_eq!(
+            address(first_repeat(&message, "PID", 11), separators),
+            Some(json!({
+                "use": "home",
+                "line": ["1 Main St", "Apt 2"],
+                "city": "Town",
+                "state": "ST",
+                "postalCode": "12345",
+                "country": "USA",
+            }))
+        );
+        let phones = message.segment("PID").unwrap().field(13).unwrap();
+        assert_eq!(
+            phones
+                .repeats()
+                .filter_map(|r| contact_point(r, Some("work"), separators))
+                .collect::<Vec<_>>(),
+            vec![
+                json!({ "system": "phone", "value": "1 555 1234567", "use": "home" }),
+                json!({ "system": "email", "value": "jane@example.com" }),
+            ]
+        );
+        assert_eq!(
+            practitioner(first_repeat(&message, "PV1", 7), separators),
+            Some(json!({ "identifier": { "value": "37" }, "display": "DR WALT DISNEY" }))
+        );
+    }
+}
@@ -0,0 +1,426 @@
+//! # FHIR R4
+//!
+//! Maps messages to FHIR R4 `message` bundles as plain [`serde_json::Value`]s, following the
+//! HL7 v2-to-FHIR mapping guidance:
+//!
+//! | Segment | Resource |
+//! |---------|----------|
+//! | `MSH`   | `MessageHeader` |
+//! | `PID`   | `Patient` |
+//! | `PV1`   | `Encounter` |
+//! | `NK1`   | `RelatedPerson` |
+//! | `AL1`   | `AllergyIntolerance` |
+//! | `OBR`   | `DiagnosticReport` |
+//! | `OBX`   | `Observation`, added to the results of the preceding `DiagnosticReport` |
+//!
+//! Segments are mapped in order, and resources refer to the resources mapped before them, i.e.
+//! an `Observation`'s subject is the most recent `Patient`. Every resource is given an ID which
+//! is derived from the message header, so mapping the same message twice gives the same bundle,
+//! and entries are referred to by `urn:uuid:` URLs.
+//!
+//! The mapping for any segment can be replaced (or added, for segments such as `Z` segments
+//! which aren't mapped by default) with [`Mapper::with_mapping`], and the default mappings for
+//! each resource are available in [`resources`] to build on. The conversions from HL7 data
+//! types to FHIR data types are in [`datatypes`].
+//!
+//! Coded values whose coding system doesn't have a known FHIR system (such as local codes) are
+//! mapped without a system. Timestamps without an offset are taken to be in the offset of the
+//! message's `MSH-7`, and are mapped to just their date if `MSH-7` doesn't have an offset either,
+//! since FHIR times need a time zone.
+//!
+//! ## Examples
+//!
+//! ```
+//! use hl7_parser::{fhir, Message};
+//!
+//! let message = Message::parse(
+//!     "MSH|^~\\&|LAB|HOSP|||20240102||ORU^R01|1|P|2.5.1\r\
+//!      PID|1||123^^^HOSP^MR||Doe^John\r\
+//!      ZPI|VIP",
+//! )
+//! .unwrap();
+//!
+//! let bundle = fhir::to_bundle(&message);
+//! assert_eq!(bundle["entry"][1]["resource"]["name"][0]["family"], "Doe");
+//!
+//! let mapper = fhir::Mapper::new().with_mapping("ZPI", |segment, context| {
+//!     let vip = segment.field(1).is_some_and(|field| field.raw_value() == "VIP");
+//!     if let Some(patient) = context.last_mut("Patient") {
+//!         patient["meta"] = serde_json::json!({ "tag": [{ "code": if vip { "VIP" } else { "standard" } }] });
+//!     }
+//! });
+//! let bundle = mapper.map(&message);
+//! assert_eq!(bundle["entry"][1]["resource"]["meta"]["tag"][0]["code"], "VIP");
+//! ```
+
+pub mod datatypes;
+pub mod resources;
+
+use crate::{
+    datetime::{TimeStamp, TimeStampOffset},
+    message::{Segment, Separators},
+    Message,
+};
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+/// A mapping from a segment to FHIR resources, which adds the resources to the bundle (or
+/// updates resources already in it) through the [`Context`]
+pub type Mapping = Arc<dyn Fn(&Segment<'_>, &mut Context<'_>) + Send + Sync>;
+
+/// Maps messages to FHIR bundles using a mapping for each segment name
+#[derive(Clone)]
+pub struct Mapper {
+    mappings: BTreeMap<String, Mapping>,
+}
+
+impl fmt::Debug for Mapper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mapper")
+            .field("mappings", &self.mappings.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Default for Mapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mapper {
+    /// A mapper with the default mappings for `MSH`, `PID`, `PV1`, `NK1`, `AL1`, `OBR`, and
+    /// `OBX` segments
+    pub fn new() -> Self {
+        Self::empty()
+            .with_mapping("MSH", |segment, context| {
+                context.add(resources::message_header(segment, context));
+            })
+            .with_mapping("PID", |segment, context| {
+                context.add(resources::patient(segment, context));
+            })
+            .with_mapping("PV1", |segment, context| {
+                context.add(resources::encounter(segment, context));
+            })
+            .with_mapping("NK1", |segment, context| {
+                context.add(resources::related_person(segment, context));
+            })
+            .with_mapping("AL1", |segment, context| {
+                context.add(resources::allergy_intolerance(segment, context));
+            })
+            .with_mapping("OBR", |segment, context| {
+                context.add(resources::diagnostic_report(segment, context));
+            })
+            .with_mapping("OBX", |segment, context| {
+                let observation = context.add(resources::observation(segment, context));
+                if let Some(report) = context.last_mut("DiagnosticReport") {
+                    match report.get_mut("result").and_then(Value::as_array_mut) {
+                        Some(results) => results.push(observation),
+                        None => report["result"] = json!([observation]),
+                    }
+                }
+            })
+    }
+
+    /// A mapper without any mappings
+    pub fn empty() -> Self {
+        Mapper {
+            mappings: BTreeMap::new(),
+        }
+    }
+
+    /// Map segments with the given name using a mapping, replacing any existing mapping
+    pub fn with_mapping(
+        mut self,
+        segment: impl Into<String>,
+        mapping: impl Fn(&Segment<'_>, &mut Context<'_>) + Send + Sync + 'static,
+    ) -> Self {
+        self.mappings.insert(segment.into(), Arc::new(mapping));
+        self
+    }
+
+    /// Stop mapping segments with the given name
+    pub fn without_mapping(mut self, segment: &str) -> Self {
+        self.mappings.remove(segment);
+        self
+    }
+
+    /// Map a message to a FHIR `message` bundle
+    pub fn map(&self, message: &Message) -> Value {
+        let mut context = Context {
+            message,
+            default_offset: message
+                .segment("MSH")
+                .and_then(|msh| msh.field(7))
+                .and_then(|field| TimeStamp::from_field(field, &message.separators).ok())
+                .and_then(|ts| ts.offset),
+            entries: Vec::new(),
+            seed: message
+                .segment("MSH")
+                .map(|msh| msh.raw_value())
+                .unwrap_or_default()
+                .to_string(),
+        };
+        for segment in message.segments() {
+            if let Some(mapping) = self.mappings.get(segment.name) {
+                mapping(segment, &mut context);
+            }
+        }
+
+        let field = |n: usize, c: usize| {
+            message
+                .query(format!("MSH.{n}.{c}").as_str())
+                .and_then(|value| datatypes::string(value.raw_value(), &message.separators))
+        };
+        let entries = context
+            .entries
+            .into_iter()
+            .map(|entry| json!({ "fullUrl": entry.url, "resource": entry.resource }))
+            .collect::<Vec<_>>();
+        datatypes::object([
+            ("resourceType", Some("Bundle".into())),
+            ("id", Some(id(&context.seed, "Bundle", 0).into())),
+            (
+                "identifier",
+                field(10, 1).map(|control_id| json!({ "value": control_id })),
+            ),
+            ("type", Some("message".into())),
+            (
+                "timestamp",
+                field(7, 1)
+                    .and_then(|raw| datatypes::instant(&raw, context.default_offset))
+                    .map(Value::from),
+            ),
+            ("entry", Some(entries.into())),
+        ])
+    }
+}
+
+/// Map a message to a FHIR `message` bundle using the default mappings
+pub fn to_bundle(message: &Message) -> Value {
+    Mapper::new().map(message)
+}
+
+/// An entry in the bundle
+#[derive(Debug)]
+struct Entry {
+    url: String,
+    resource_type: String,
+    resource: Value,
+}
+
+/// The state of a message being mapped, which mappings use to add resources to the bundle and
+/// refer to resources that have already been added
+#[derive(Debug)]
+pub struct Context<'a> {
+    message: &'a Message<'a>,
+    default_offset: Option<TimeStampOffset>,
+    entries: Vec<Entry>,
+    seed: String,
+}
+
+impl<'a> Context<'a> {
+    /// The message being mapped
+    pub fn message(&self) -> &'a Message<'a> {
+        self.message
+    }
+
+    /// The offset of the message's `MSH-7`, which timestamps without their own offset are taken
+    /// to be in
+    pub fn default_offset(&self) -> Option<TimeStampOffset> {
+        self.default_offset
+    }
+
+    /// The separators of the message being mapped, for decoding values
+    pub fn separators(&self) -> &'a Separators {
+        &self.message.separators
+    }
+
+    /// Add a resource to the bundle, giving it an ID, and return a reference to it. Resources
+    /// without a `resourceType` are ignored.
+    pub fn add(&mut self, mut resource: Value) -> Value {
+        let resource_type = resource
+            .get("resourceType")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        if resource_type.is_empty() {
+            return Value::Null;
+        }
+        let count = self
+            .entries
+            .iter()
+            .filter(|entry| entry.resource_type == resource_type)
+            .count();
+        let id = id(&self.seed, &resource_type, count + 1);
+        resource["id"] = id.clone().into();
+        let url = format!("urn:uuid:{id}");
+        self.entries.push(Entry {
+            url: url.clone(),
+            resource_type,
+            resource,
+        });
+        json!({ "reference": url })
+    }
+
+    /// A reference to the most recently added resource of a type
+    pub fn reference(&self, resource_type: &str) -> Option<Value> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|entry| entry.resource_type == resource_type)
+            .map(|entry| json!({ "reference": entry.url }))
+    }
+
+    /// The most recently added resource of a type
+    pub fn last_mut(&mut self, resource_type: &str) -> Option<&mut Value> {
+        self.entries
+            .iter_mut()
+            .rev()
+            .find(|entry| entry.resource_type == resource_type)
+            .map(|entry| &mut entry.resource)
+    }
+}
+
+/// A name-based UUID for the nth resource of a type in the message with the seed, so that
+/// mapping the same message always gives the same IDs
+fn id(seed: &str, resource_type: &str, n: usize) -> String {
+    let name = format!("{seed}\n{resource_type}\n{n}");
+    // two FNV-1a hashes with different offset bases, mixed with the splitmix64 finalizer
+    let hash = |basis: u64| {
+        let hash = name.bytes().fold(basis, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3)
+        });
+        let hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        let hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        hash ^ (hash >> 31)
+    };
+    // set the version (8, custom) and variant bits
+    let high = (hash(0xcbf2_9ce4_8422_2325) & !0xf000) | 0x8000;
+    let low = (hash(0x8422_2325_cbf2_9ce4) & !(0xc000 << 48)) | (0x8000 << 48);
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        high >> 32,
+        (high >> 16) & 0xffff,
+        high & 0xffff,
+        low >> 48,
+        low & 0xffff_ffff_ffff
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    #[test]
+    fn ids_are_stable_uuids() {
+        let a = id("MSH|^~\\&|A", "Patient", 1);
+        assert_eq!(a, id("MSH|^~\\&|A", "Patient", 1));
+        assert_ne!(a, id("MSH|^~\\&|A", "Patient", 2));
+        assert_ne!(a, id("MSH|^~\\&|B", "Patient", 1));
+        let groups = a.split('-').map(str::len).collect::<Vec<_>>();
+        assert_eq!(groups, vec![8, 4, 4, 4, 12]);
+        assert_eq!(&a[14..15], "8");
+        assert!(matches!(&a[19..20], "8" | "9" | "a" | "b"));
+    }
+
+    #[test]
+    fn links_resources() {
+        let message = Message::parse(
+            "MSH|^~\\&|LAB|HOSP|||20240102||ORU^R01|1|P|2.5.1\r\
+             PID|1||123^^^HOSP^MR||Doe^John\r\
+             PV1|1|O\r\
+             OBR|1||F1|CBC^Blood count^LN|||20240101\r\
+             OBX|1|NM|WBC^White cells^LN||7.0|10*9/L^^UCUM|||||F\r\
+             OBX|2|SN|PLT^Platelets^LN||<^10||||||P",
+        )
+        .unwrap();
+        let bundle = to_bundle(&message);
+        let resources = bundle["entry"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["resource"]["resourceType"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            resources,
+            vec![
+                "MessageHeader",
+                "Patient",
+                "Encounter",
+                "DiagnosticReport",
+                "Observation",
+                "Observation"
+            ]
+        );
+        let url = |n: usize| bundle["entry"][n]["fullUrl"].clone();
+        let report = &bundle["entry"][3]["resource"];
+        assert_eq!(report["subject"]["reference"], url(1));
+        assert_eq!(report["encounter"]["reference"], url(2));
+        assert_eq!(
+            report["result"],
+            json!([{ "reference": url(4) }, { "reference": url(5) }])
+        );
+        assert_eq!(
+            bundle["entry"][4]["resource"]["valueQuantity"],
+            json!({
+                "value": 7.0,
+                "unit": "10*9/L",
+                "system": "http://unitsofmeasure.org",
+                "code": "10*9/L",
+            })
+        );
+        assert_eq!(
+            bundle["entry"][5]["resource"]["valueQuantity"],
+            json!({ "value": 10.0, "comparator": "<" })
+        );
+        assert_eq!(bundle["entry"][5]["resource"]["status"], "preliminary");
+        assert_eq!(to_bundle(&message), bundle);
+    }
+
+    #[test]
+    fn timestamps_use_the_offset_of_the_message() {
+        let message = Message::parse(
+            "MSH|^~\\&|LAB|HOSP|||20240102083015-0700||ORU^R01|1|P|2.5.1\r\
+             OBR|1||F1|CBC^Blood count^LN|||202401010930",
+        )
+        .unwrap();
+        let bundle = to_bundle(&message);
+        assert_eq!(bundle["timestamp"], "2024-01-02T08:30:15-07:00");
+        let report = &bundle["entry"][1]["resource"];
+        assert_eq!(report["effectiveDateTime"], "2024-01-01T09:30:00-07:00");
+
+        let message = Message::parse(
+            "MSH|^~\\&|LAB|HOSP|||20240102083015||ORU^R01|1|P|2.5.1\r\
+             OBR|1||F1|CBC^Blood count^LN|||202401010930",
+        )
+        .unwrap();
+        let bundle = to_bundle(&message);
+        assert_eq!(bundle.get("timestamp"), None);
+        let report = &bundle["entry"][1]["resource"];
+        assert_eq!(report["effectiveDateTime"], "2024-01-01");
+    }
+
+    #[test]
+    fn mappings_can_be_replaced_and_removed() {
+        let message = Message::parse(
+            "MSH|^~\\&|ADT|HOSP|||20240102||ADT^A01|1|P|2.5.1\r\
+             PID|1||123^^^HOSP^MR||Doe^John\r\
+             NK1|1|Doe^Jane|SPO",
+        )
+        .unwrap();
+        let mapper =
+            Mapper::new()
+                .without_mapping("NK1")
+                .with_mapping("PID", |segment, context| {
+                    let mut patient = resources::patient(segment, context);
+                    patient["active"] = true.into();
+                    context.add(patient);
+                });
+        let bundle = mapper.map(&message);
+        assert_eq!(bundle["entry"].as_array().unwrap().len(), 2);
+        assert_eq!(bundle["entry"][1]["resource"]["active"], true);
+        assert_eq!(Mapper::empty().map(&message)["entry"], json!([]));
+    }
+}
@@ -0,0 +1,537 @@
+//! The default mappings from segments to FHIR R4 resources, following the HL7 v2-to-FHIR
+//! segment mappings.
+//!
+//! These build a resource from a segment without adding it to the bundle, so that custom
+//! mappings can start from the default resource and change it before adding it with
+//! [`Context::add`].
+
+use super::{datatypes::*, Context};
+use crate::message::{Repeat, Segment};
+use serde_json::{json, Value};
+
+/// The non-empty repeats of a field
+fn repeats<'s, 'm>(segment: &'s Segment<'m>, field: usize) -> impl Iterator<Item = &'s Repeat<'m>> {
+    segment
+        .field(field)
+        .into_iter()
+        .flat_map(|field| field.repeats())
+        .filter(|repeat| !repeat.is_empty())
+}
+
+/// The first non-empty repeat of a field
+fn first<'s, 'm>(segment: &'s Segment<'m>, field: usize) -> Option<&'s Repeat<'m>> {
+    repeats(segment, field).next()
+}
+
+/// The decoded value of the first repeat of a field
+fn text(segment: &Segment, field: usize, context: &Context) -> Option<String> {
+    string(first(segment, field)?.raw_value(), context.separators())
+}
+
+/// The decoded value of the first subcomponent of a component of the first repeat of a field
+fn text_component(
+    segment: &Segment,
+    field: usize,
+    number: usize,
+    context: &Context,
+) -> Option<String> {
+    component(first(segment, field)?, number, context.separators())
+}
+
+/// A `dateTime` from the first repeat of a field
+fn field_date_time(segment: &Segment, field: usize, context: &Context) -> Option<Value> {
+    text_component(segment, field, 1, context)
+        .and_then(|raw| date_time(&raw, context.default_offset()))
+        .map(Value::from)
+}
+
+/// A `CodeableConcept` from the first repeat of a field, using the HL7 table for codes without a
+/// coding system
+fn field_concept(
+    segment: &Segment,
+    field: usize,
+    table: Option<&str>,
+    context: &Context,
+) -> Option<Value> {
+    let system = table.map(|table| format!("http://terminology.hl7.org/CodeSystem/v2-{table}"));
+    codeable_concept(
+        first(segment, field)?,
+        system.as_deref(),
+        context.separators(),
+    )
+}
+
+/// A `CodeableConcept` for a code that isn't known, since the code is required
+fn unknown_code() -> Value {
+    json!({ "text": "unknown" })
+}
+
+/// Map `MSH` to a `MessageHeader`
+pub fn message_header(segment: &Segment, context: &Context) -> Value {
+    let separators = context.separators();
+    let endpoint = |field: usize| {
+        let hd = first(segment, field)?;
+        let name = component(hd, 1, separators);
+        let id = component(hd, 2, separators);
+        let universal = match component(hd, 3, separators).as_deref() {
+            Some("ISO") => id.map(|id| format!("urn:oid:{id}")),
+            Some("UUID") => id.map(|id| format!("urn:uuid:{}", id.to_lowercase())),
+            Some("URI" | "DNS") => id,
+            _ => None,
+        };
+        let endpoint = universal.or_else(|| name.clone())?;
+        Some(object([
+            ("name", name.map(Value::from)),
+            ("endpoint", Some(endpoint.into())),
+        ]))
+    };
+    object([
+        ("resourceType", Some("MessageHeader".into())),
+        (
+            "eventCoding",
+            Some(json!({
+                "system": "http://terminology.hl7.org/CodeSystem/v2-0003",
+                "code": text_component(segment, 9, 2, context).unwrap_or_default(),
+            })),
+        ),
+        ("destination", endpoint(5).map(|d| json!([d]))),
+        (
+            "source",
+            Some(endpoint(3).unwrap_or_else(|| json!({ "endpoint": "unknown" }))),
+        ),
+    ])
+}
+
+/// Map `PID` to a `Patient`
+pub fn patient(segment: &Segment, context: &Context) -> Value {
+    let separators = context.separators();
+    let mut identifiers = repeats(segment, 3)
+        .chain(repeats(segment, 2))
+        .chain(repeats(segment, 4))
+        .filter_map(|repeat| identifier(repeat, separators))
+        .collect::<Vec<_>>();
+    if let Some(ssn) = text(segment, 19, context) {
+        identifiers.push(json!({
+            "type": concept(IDENTIFIER_TYPE, "SS"),
+            "system": "http://hl7.org/fhir/sid/us-ssn",
+            "value": ssn,
+        }));
+    }
+    let telecom = repeats(segment, 13)
+        .filter_map(|repeat| contact_point(repeat, Some("home"), separators))
+        .chain(
+            repeats(segment, 14)
+                .filter_map(|repeat| contact_point(repeat, Some("work"), separators)),
+        )
+        .collect();
+    let gender = text(segment, 8, context).map(|sex| match sex.as_str() {
+        "M" => "male",
+        "F" => "female",
+        "O" | "A" => "other",
+        _ => "unknown",
+    });
+    let deceased = field_date_time(segment, 29, context)
+        .map(|date_time| ("deceasedDateTime", date_time))
+        .or_else(|| {
+            text(segment, 30, context).and_then(|indicator| match indicator.as_str() {
+                "Y" => Some(("deceasedBoolean", true.into())),
+                "N" => Some(("deceasedBoolean", false.into())),
+                _ => None,
+            })
+        });
+
+    let mut patient = object([
+        ("resourceType", Some("Patient".into())),
+        ("identifier", array(identifiers)),
+        (
+            "name",
+            array(
+                repeats(segment, 5)
+                    .filter_map(|repeat| human_name(repeat, separators))
+                    .collect(),
+            ),
+        ),
+        ("telecom", array(telecom)),
+        ("gender", gender.map(Value::from)),
+        (
+            "birthDate",
+            text_component(segment, 7, 1, context)
+                .and_then(|raw| date(&raw))
+                .map(Value::from),
+        ),
+        (
+            "address",
+            array(
+                repeats(segment, 11)
+                    .filter_map(|repeat| address(repeat, separators))
+                    .collect(),
+            ),
+        ),
+        (
+            "maritalStatus",
+            field_concept(segment, 16, Some("0002"), context),
+        ),
+    ]);
+    if let Some((key, value)) = deceased {
+        patient[key] = value;
+    }
+    patient
+}
+
+/// Map `PV1` to an `Encounter`, for the patient
+pub fn encounter(segment: &Segment, context: &Context) -> Value {
+    let separators = context.separators();
+    let class = match text(segment, 2, context).as_deref() {
+        Some("I") => ("IMP", "inpatient encounter"),
+        Some("O") => ("AMB", "ambulatory"),
+        Some("E") => ("EMER", "emergency"),
+        Some("P") => ("PRENC", "pre-admission"),
+        _ => ("", ""),
+    };
+    let class = match class {
+        ("", _) => json!({
+            "system": "http://terminology.hl7.org/CodeSystem/v3-NullFlavor",
+            "code": "UNK",
+        }),
+        (code, display) => json!({
+            "system": "http://terminology.hl7.org/CodeSystem/v3-ActCode",
+            "code": code,
+            "display": display,
+        }),
+    };
+    let end = field_date_time(segment, 45, context);
+    let participants = [(7, "ATND"), (8, "REF"), (9, "CON"), (17, "ADM")]
+        .into_iter()
+        .flat_map(|(field, role)| {
+            repeats(segment, field).filter_map(move |repeat| {
+                Some(json!({
+                    "type": [concept(
+                        "http://terminology.hl7.org/CodeSystem/v3-ParticipationType",
+                        role,
+                    )],
+                    "individual": practitioner(repeat, separators)?,
+                }))
+            })
+        })
+        .collect();
+    let location = first(segment, 3).and_then(|repeat| {
+        let parts = (1..=3)
+            .filter_map(|n| component(repeat, n, separators))
+            .collect::<Vec<_>>();
+        (!parts.is_empty()).then(|| json!([{ "location": { "display": parts.join(" ") } }]))
+    });
+    let period = object([
+        ("start", field_date_time(segment, 44, context)),
+        ("end", end.clone()),
+    ]);
+    let hospitalization = object([
+        (
+            "admitSource",
+            field_concept(segment, 14, Some("0023"), context),
+        ),
+        (
+            "dischargeDisposition",
+            field_concept(segment, 36, Some("0112"), context),
+        ),
+    ]);
+
+    object([
+        ("resourceType", Some("Encounter".into())),
+        (
+            "identifier",
+            first(segment, 19)
+                .and_then(|repeat| identifier(repeat, separators))
+                .map(|identifier| json!([identifier])),
+        ),
+        (
+            "status",
+            Some(
+                if end.is_some() {
+                    "finished"
+                } else {
+                    "in-progress"
+                }
+                .into(),
+            ),
+        ),
+        ("class", Some(class)),
+        ("subject", context.reference("Patient")),
+        ("participant", array(participants)),
+        (
+            "period",
+            period
+                .as_object()
+                .is_some_and(|p| !p.is_empty())
+                .then_some(period),
+        ),
+        (
+            "hospitalization",
+            hospitalization
+                .as_object()
+                .is_some_and(|h| !h.is_empty())
+                .then_some(hospitalization),
+        ),
+        ("location", location),
+    ])
+}
+
+/// Map `NK1` to a `RelatedPerson`, for the patient
+pub fn related_person(segment: &Segment, context: &Context) -> Value {
+    let separators = context.separators();
+    let telecom = repeats(segment, 5)
+        .filter_map(|repeat| contact_point(repeat, Some("home"), separators))
+        .chain(
+            repeats(segment, 6)
+                .filter_map(|repeat| contact_point(repeat, Some("work"), separators)),
+        )
+        .collect();
+    let period = object([
+        (
+            "start",
+            text_component(segment, 8, 1, context)
+                .and_then(|raw| date(&raw))
+                .map(Value::from),
+        ),
+        (
+            "end",
+            text_component(segment, 9, 1, context)
+                .and_then(|raw| date(&raw))
+                .map(Value::from),
+        ),
+    ]);
+    object([
+        ("resourceType", Some("RelatedPerson".into())),
+        ("patient", context.reference("Patient")),
+        (
+            "relationship",
+            field_concept(segment, 3, Some("0063"), context).map(|r| json!([r])),
+        ),
+        (
+            "name",
+            array(
+                repeats(segment, 2)
+                    .filter_map(|repeat| human_name(repeat, separators))
+                    .collect(),
+            ),
+        ),
+        ("telecom", array(telecom)),
+        (
+            "address",
+            array(
+                repeats(segment, 4)
+                    .filter_map(|repeat| address(repeat, separators))
+                    .collect(),
+            ),
+        ),
+        (
+            "period",
+            period
+                .as_object()
+                .is_some_and(|p| !p.is_empty())
+                .then_some(period),
+        ),
+    ])
+}
+
+/// Map `AL1` to an `AllergyIntolerance`, for the patient
+pub fn allergy_intolerance(segment: &Segment, context: &Context) -> Value {
+    let category = text_component(segment, 2, 1, context).and_then(|code| {
+        Some(match code.as_str() {
+            "DA" | "MA" => "medication",
+            "FA" => "food",
+            "EA" | "AA" | "PA" | "LA" => "environment",
+            _ => return None,
+        })
+    });
+    let criticality = text_component(segment, 4, 1, context).and_then(|code| {
+        Some(match code.as_str() {
+            "SV" => "high",
+            "MO" | "MI" => "low",
+            "U" => "unable-to-assess",
+            _ => return None,
+        })
+    });
+    let manifestations = repeats(segment, 5)
+        .filter_map(|repeat| string(repeat.raw_value(), context.separators()))
+        .map(|reaction| json!({ "text": reaction }))
+        .collect::<Vec<_>>();
+    object([
+        ("resourceType", Some("AllergyIntolerance".into())),
+        ("category", category.map(|c| json!([c]))),
+        ("criticality", criticality.map(Value::from)),
+        ("code", field_concept(segment, 3, None, context)),
+        ("patient", context.reference("Patient")),
+        (
+            "onsetDateTime",
+            text_component(segment, 6, 1, context)
+                .and_then(|raw| date(&raw))
+                .map(Value::from),
+        ),
+        (
+            "reaction",
+            array(manifestations).map(|m| json!([{ "manifestation": m }])),
+        ),
+    ])
+}
+
+/// Map `OBR` to a `DiagnosticReport`, for the patient and encounter. Its results are added by
+/// the `OBX` mapping.
+pub fn diagnostic_report(segment: &Segment, context: &Context) -> Value {
+    let separators = context.separators();
+    let identifiers = [(2, "PLAC"), (3, "FILL")]
+        .into_iter()
+        .filter_map(|(field, code)| entity_identifier(first(segment, field)?, code, separators))
+        .collect();
+    let status = match text(segment, 25, context).as_deref() {
+        Some("O" | "I") => "registered",
+        Some("S" | "A") => "partial",
+        Some("P" | "R") => "preliminary",
+        Some("C") => "corrected",
+        Some("F") => "final",
+        Some("X") => "cancelled",
+        _ => "unknown",
+    };
+    object([
+        ("resourceType", Some("DiagnosticReport".into())),
+        ("identifier", array(identifiers)),
+        ("status", Some(status.into())),
+        (
+            "code",
+            Some(field_concept(segment, 4, None, context).unwrap_or_else(unknown_code)),
+        ),
+        ("subject", context.reference("Patient")),
+        ("encounter", context.reference("Encounter")),
+        ("effectiveDateTime", field_date_time(segment, 7, context)),
+    ])
+}
+
+/// Map `OBX` to an `Observation`, for the patient and encounter
+pub fn observation(segment: &Segment, context: &Context) -> Value {
+    let status = match text(segment, 11, context).as_deref() {
+        Some("C") => "corrected",
+        Some("D" | "W") => "entered-in-error",
+        Some("F" | "U") => "final",
+        Some("I" | "O") => "registered",
+        Some("P" | "R" | "S") => "preliminary",
+        Some("X" | "N") => "cancelled",
+        _ => "unknown",
+    };
+    let interpretation = repeats(segment, 8)
+        .filter_map(|repeat| {
+            codeable_concept(
+                repeat,
+                Some("http://terminology.hl7.org/CodeSystem/v3-ObservationInterpretation"),
+                context.separators(),
+            )
+        })
+        .collect();
+    let mut observation = object([
+        ("resourceType", Some("Observation".into())),
+        ("status", Some(status.into())),
+        (
+            "code",
+            Some(field_concept(segment, 3, None, context).unwrap_or_else(unknown_code)),
+        ),
+        ("subject", context.reference("Patient")),
+        ("encounter", context.reference("Encounter")),
+        ("effectiveDateTime", field_date_time(segment, 14, context)),
+        ("interpretation", array(interpretation)),
+        (
+            "referenceRange",
+            text(segment, 7, context).map(|range| json!([{ "text": range }])),
+        ),
+    ]);
+    if let Some((key, value)) = observation_value(segment, context) {
+        observation[key] = value;
+    }
+    observation
+}
+
+/// The value of an observation, using the value type in `OBX.2`
+fn observation_value(segment: &Segment, context: &Context) -> Option<(&'static str, Value)> {
+    let separators = context.separators();
+    let value = first(segment, 5)?;
+    let units = first(segment, 6);
+    match text(segment, 2, context).as_deref() {
+        Some("NM") => {
+            let value = string(value.raw_value(), separators)?;
+            Some(("valueQuantity", quantity(value.trim(), units, context)?))
+        }
+        Some("SN") => structured_numeric(value, units, context)
+            .or_else(|| Some(("valueString", string(value.raw_value(), separators)?.into()))),
+        Some("CE" | "CWE" | "CNE" | "CF") => Some((
+            "valueCodeableConcept",
+            codeable_concept(value, None, separators)?,
+        )),
+        Some("TS" | "DTM" | "DT") => Some((
+            "valueDateTime",
+            date_time(&component(value, 1, separators)?, context.default_offset())?.into(),
+        )),
+        Some("ST" | "TX" | "FT") => {
+            let lines = repeats(segment, 5)
+                .map(|repeat| separators.decode(repeat.raw_value()).to_string())
+                .collect::<Vec<_>>();
+            Some(("valueString", lines.join("\n").into()))
+        }
+        _ => Some(("valueString", string(value.raw_value(), separators)?.into())),
+    }
+}
+
+/// A `Quantity` with the units in `OBX.6`, using UCUM codes where the units are UCUM
+fn quantity(value: &str, units: Option<&Repeat>, context: &Context) -> Option<Value> {
+    let number = value.parse::<f64>().ok().filter(|n| n.is_finite())?;
+    let separators = context.separators();
+    let (unit, system, code) = match units {
+        Some(units) => {
+            let code = component(units, 1, separators);
+            let ucum = component(units, 3, separators).as_deref() == Some("UCUM");
+            (
+                component(units, 2, separators).or_else(|| code.clone()),
+                ucum.then_some("http://unitsofmeasure.org"),
+                code.filter(|_| ucum),
+            )
+        }
+        None => (None, None, None),
+    };
+    Some(object([
+        ("value", Some(number.into())),
+        ("unit", unit.map(Value::from)),
+        ("system", system.map(Value::from)),
+        ("code", code.map(Value::from)),
+    ]))
+}
+
+/// The value of a structured numeric (`SN`), as a quantity with a comparator, a range, or a
+/// ratio
+fn structured_numeric(
+    value: &Repeat,
+    units: Option<&Repeat>,
+    context: &Context,
+) -> Option<(&'static str, Value)> {
+    let separators = context.separators();
+    let part = |n: usize| component(value, n, separators);
+    let first = part(2)?;
+    let first = quantity(&first, units, context)?;
+    match (part(3).as_deref(), part(4)) {
+        (Some("-"), Some(second)) => Some((
+            "valueRange",
+            json!({ "low": first, "high": quantity(&second, units, context)? }),
+        )),
+        (Some(":" | "/"), Some(second)) => Some((
+            "valueRatio",
+            json!({ "numerator": first, "denominator": quantity(&second, units, context)? }),
+        )),
+        (None, None) => {
+            let mut first = first;
+            match part(1).as_deref() {
+                Some(comparator @ ("<" | "<=" | ">=" | ">")) => {
+                    first["comparator"] = comparator.into();
+                }
+                None | Some("=") => {}
+                Some(_) => return None,
+            }
+            Some(("valueQuantity", first))
+        }
+        _ => None,
+    }
+}
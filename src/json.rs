@@ -0,0 +1,458 @@
+//! # Canonical JSON
+//!
+//! A lossless JSON representation of messages, which converts back to the original message
+//! byte-for-byte. This is intended for storing and querying messages in document databases,
+//! where the serde representation of [`MessageBuilder`](crate::builder::MessageBuilder) is a
+//! poor fit: it has no stable schema, and loses the separators and the difference between
+//! empty and absent values.
+//!
+//! ## Schema
+//!
+//! A message is an object with its segments and the separator between them:
+//!
+//! ```json
+//! {
+//!   "segment_separator": "\r",
+//!   "segments": [
+//!     { "name": "MSH", "fields": ["|", "^~\\&", "APP", "", "", "", "", "", [["ADT", "A01"]]] },
+//!     { "name": "PID", "fields": ["1", "", ["123", "456"], null, [["Doe", "John"]]] }
+//!   ]
+//! }
+//! ```
+//!
+//! `segment_separator` is the separator between the first two segments. Messages parsed with
+//! lenient newlines can mix separators, so a segment which is followed by a different one
+//! records it in its own `separator`, such as `{ "name": "PID", "fields": ["1"], "separator":
+//! "\n" }`.
+//!
+//! Each segment's `fields` lists every field in the segment, including empty ones, where
+//! `fields[0]` is field 1 (`MSH.1`, the field separator, for the header). Values are nested in
+//! the same way as the message: a field is an array of repeats, a repeat is an array of
+//! components, and a component is an array of subcomponents. Any of these which holds a single
+//! value is collapsed to that value, so `"a"` is a field with a single value, `["a", "b"]` is a
+//! field with two repeats, `[["a", "b"]]` is a field with two components, and `[[["a", "b"]]]`
+//! is a field with two subcomponents.
+//!
+//! Values are strings with escape sequences decoded, except that:
+//!
+//! - `MSH.1` and `MSH.2` are the separators themselves
+//! - the HL7 null value (`""`) is `null`
+//! - values which decoding would change irreversibly (for example formatting escape sequences
+//!   like `\H\`, or `\.br\` which decodes to the same newline as `\X0D\`) are objects holding
+//!   the raw value, such as `{ "raw": "\\H\\bold\\N\\" }`
+//!
+//! ## Examples
+//!
+//! ```
+//! use hl7_parser::{json, Message};
+//!
+//! let source = "MSH|^~\\&|APP\rPID|1||123~456||Doe^John\\T\\Jane|\"\"";
+//! let message = Message::parse(source).unwrap();
+//! let value = json::to_json(&message);
+//! assert_eq!(value["segments"][1]["fields"][2], serde_json::json!(["123", "456"]));
+//! assert_eq!(value["segments"][1]["fields"][4], serde_json::json!([["Doe", "John&Jane"]]));
+//! assert_eq!(value["segments"][1]["fields"][5], serde_json::Value::Null);
+//!
+//! assert_eq!(json::from_json(&value).unwrap(), source);
+//! ```
+
+use crate::{message::Separators, Message};
+use serde_json::{json, Map, Value};
+
+/// Errors that can occur when converting JSON to a message
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+pub enum JsonError {
+    /// A value in the document doesn't match the schema
+    #[error("Expected {expected} at `{path}`")]
+    InvalidValue {
+        /// The path to the value, such as `segments[1].fields[4][0]`
+        path: String,
+        /// A description of the expected value
+        expected: &'static str,
+    },
+
+    /// The first segment isn't an `MSH` segment with its separators
+    #[error("The first segment must be an MSH segment with MSH.1 and MSH.2")]
+    MissingHeader,
+}
+
+/// Convert a message to its canonical JSON representation
+pub fn to_json(message: &Message) -> Value {
+    let separators = &message.separators;
+    let source = message.raw_value();
+    let segment_separator = match message.segments.as_slice() {
+        [first, second, ..] => &source[first.range.end..second.range.start],
+        _ => "\r",
+    };
+
+    let segments = message
+        .segments
+        .iter()
+        .enumerate()
+        .map(|(i, segment)| {
+            let fields = segment
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(i, field)| {
+                    if segment.name == "MSH" && i < 2 {
+                        return Value::String(field.raw_value().to_string());
+                    }
+                    collapse(field.repeats.iter().map(|repeat| {
+                        collapse(repeat.components.iter().map(|component| {
+                            collapse(
+                                component
+                                    .subcomponents
+                                    .iter()
+                                    .map(|subcomponent| leaf(subcomponent.value, separators)),
+                            )
+                        }))
+                    }))
+                })
+                .collect::<Vec<_>>();
+            let mut object = json!({ "name": segment.name, "fields": fields });
+            let (separator, default) = match message.segments.get(i + 1) {
+                Some(next) => (
+                    &source[segment.range.end..next.range.start],
+                    segment_separator,
+                ),
+                None => (&source[segment.range.end..], ""),
+            };
+            if separator != default {
+                object["separator"] = Value::String(separator.to_string());
+            }
+            object
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "segment_separator": segment_separator,
+        "segments": segments,
+    })
+}
+
+/// Collapse a list of values with a single value that isn't a list into that value
+fn collapse(values: impl Iterator<Item = Value>) -> Value {
+    let mut values = values.collect::<Vec<_>>();
+    match values.as_slice() {
+        [] => Value::String(String::new()),
+        [value] if !value.is_array() => values.remove(0),
+        _ => Value::Array(values),
+    }
+}
+
+/// The JSON value of a raw subcomponent value
+fn leaf(raw: &str, separators: &Separators) -> Value {
+    if raw == "\"\"" {
+        return Value::Null;
+    }
+    let decoded = separators.decode(raw).to_string();
+    if separators.encode(&decoded).to_string() == raw {
+        Value::String(decoded)
+    } else {
+        json!({ "raw": raw })
+    }
+}
+
+/// Convert the canonical JSON representation of a message back to the message, which is the
+/// same as the [`raw_value`](Message::raw_value) of the message it was converted from
+pub fn from_json(value: &Value) -> Result<String, JsonError> {
+    let object = value
+        .as_object()
+        .ok_or_else(|| invalid(String::new(), "an object"))?;
+    let segment_separator = read_separator(
+        object.get("segment_separator"),
+        "segment_separator".to_string(),
+        "\r",
+    )?;
+    let segments = object
+        .get("segments")
+        .and_then(Value::as_array)
+        .ok_or_else(|| invalid("segments".to_string(), "an array"))?;
+
+    let separators = header_separators(segments)?;
+    let mut raw = String::new();
+    for (i, segment) in segments.iter().enumerate() {
+        let path = format!("segments[{i}]");
+        raw.push_str(&render_segment(segment, &path, &separators)?);
+        let default = if i + 1 < segments.len() {
+            segment_separator
+        } else {
+            ""
+        };
+        raw.push_str(read_separator(
+            segment.get("separator"),
+            format!("{path}.separator"),
+            default,
+        )?);
+    }
+    Ok(raw)
+}
+
+/// Read a segment separator, which must be a non-empty string of carriage returns and newlines
+fn read_separator<'v>(
+    value: Option<&'v Value>,
+    path: String,
+    default: &'v str,
+) -> Result<&'v str, JsonError> {
+    match value {
+        None => Ok(default),
+        Some(Value::String(s)) if !s.is_empty() && s.chars().all(|c| c == '\r' || c == '\n') => {
+            Ok(s)
+        }
+        Some(_) => Err(invalid(path, "a string of carriage returns and newlines")),
+    }
+}
+
+/// Read the separators from `MSH.1` and `MSH.2` of the first segment
+fn header_separators(segments: &[Value]) -> Result<Separators, JsonError> {
+    let header = segments
+        .first()
+        .and_then(Value::as_object)
+        .filter(|segment| segment.get("name").and_then(Value::as_str) == Some("MSH"));
+    let fields = header
+        .and_then(|header| header.get("fields"))
+        .and_then(Value::as_array);
+    let (Some(Value::String(field)), Some(Value::String(encoding))) = (
+        fields.and_then(|f| f.first()),
+        fields.and_then(|f| f.get(1)),
+    ) else {
+        return Err(JsonError::MissingHeader);
+    };
+
+    let mut separators = Separators::default();
+    let mut field = field.chars();
+    match (field.next(), field.next()) {
+        (Some(c), None) => separators.field = c,
+        _ => return Err(JsonError::MissingHeader),
+    }
+    let mut encoding = encoding.chars();
+    for separator in [
+        &mut separators.component,
+        &mut separators.repetition,
+        &mut separators.escape,
+        &mut separators.subcomponent,
+    ] {
+        *separator = encoding.next().ok_or(JsonError::MissingHeader)?;
+    }
+    Ok(separators)
+}
+
+/// Render a segment as its raw value
+fn render_segment(
+    segment: &Value,
+    path: &str,
+    separators: &Separators,
+) -> Result<String, JsonError> {
+    let segment = segment
+        .as_object()
+        .ok_or_else(|| invalid(path.to_string(), "a segment object"))?;
+    let name = segment
+        .get("name")
+        .and_then(Value::as_str)
+        .filter(|name| !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric()))
+        .ok_or_else(|| invalid(format!("{path}.name"), "a segment name"))?;
+    let fields = segment
+        .get("fields")
+        .and_then(Value::as_array)
+        .ok_or_else(|| invalid(format!("{path}.fields"), "an array"))?;
+
+    let mut raw = name.to_string();
+    for (i, field) in fields.iter().enumerate() {
+        let path = format!("{path}.fields[{i}]");
+        match (name, i) {
+            // the separators were read from the header
+            ("MSH", 0) => raw.push(separators.field),
+            ("MSH", 1) => raw.push_str(field.as_str().ok_or_else(|| invalid(path, "a string"))?),
+            _ => {
+                raw.push(separators.field);
+                render(field, 0, &path, separators, &mut raw)?;
+            }
+        }
+    }
+    Ok(raw)
+}
+
+/// Render a field (level 0), repeat (level 1), component (level 2), or subcomponent (level 3)
+fn render(
+    value: &Value,
+    level: usize,
+    path: &str,
+    separators: &Separators,
+    raw: &mut String,
+) -> Result<(), JsonError> {
+    let separator = match level {
+        0 => separators.repetition,
+        1 => separators.component,
+        _ => separators.subcomponent,
+    };
+    match value {
+        Value::Array(values) if level < 3 => {
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    raw.push(separator);
+                }
+                render(value, level + 1, &format!("{path}[{i}]"), separators, raw)?;
+            }
+        }
+        Value::Null => raw.push_str("\"\""),
+        Value::String(value) => raw.push_str(&separators.encode(value).to_string()),
+        Value::Object(object) => raw.push_str(raw_value(object, path, separators)?),
+        _ => {
+            return Err(invalid(
+                path.to_string(),
+                "a string, null, array, or raw value",
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// The value of a `{ "raw": ... }` object, which mustn't contain any separators
+fn raw_value<'v>(
+    object: &'v Map<String, Value>,
+    path: &str,
+    separators: &Separators,
+) -> Result<&'v str, JsonError> {
+    let reserved = [
+        separators.field,
+        separators.component,
+        separators.repetition,
+        separators.subcomponent,
+        '\r',
+        '\n',
+    ];
+    match (object.len(), object.get("raw")) {
+        (1, Some(Value::String(raw))) if !raw.contains(reserved) => Ok(raw),
+        _ => Err(invalid(
+            path.to_string(),
+            "a raw value without any separators",
+        )),
+    }
+}
+
+fn invalid(path: String, expected: &'static str) -> JsonError {
+    JsonError::InvalidValue { path, expected }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    #[test]
+    fn nests_and_collapses_values() {
+        let source = "MSH|^~\\&|APP||||||ADT^A01\r\
+                      PID|1||a~b~|x^y&z^|~^&|\\F\\\\S\\\r\
+                      ZPI|";
+        let message = Message::parse(source).unwrap();
+        let value = to_json(&message);
+        assert_eq!(
+            value,
+            json!({
+                "segment_separator": "\r",
+                "segments": [
+                    {
+                        "name": "MSH",
+                        "fields": ["|", "^~\\&", "APP", "", "", "", "", "", [["ADT", "A01"]]],
+                    },
+                    {
+                        "name": "PID",
+                        "fields": [
+                            "1",
+                            "",
+                            ["a", "b", ""],
+                            [["x", ["y", "z"], ""]],
+                            ["", ["", ["", ""]]],
+                            "|^",
+                        ],
+                    },
+                    { "name": "ZPI", "fields": [""] },
+                ],
+            })
+        );
+        assert_eq!(from_json(&value).unwrap(), source);
+    }
+
+    #[test]
+    fn keeps_nulls_and_raw_values() {
+        let source = "MSH#*~!@#\n\
+                      OBX#\"\"#a!H!b!N!c!.br!d!X0D!e!E!f!Xzz#g@\"\"";
+        let message = Message::parse_with_lenient_newlines(source, true).unwrap();
+        let value = to_json(&message);
+        assert_eq!(value["segment_separator"], "\n");
+        assert_eq!(
+            value["segments"][1]["fields"],
+            json!([
+                null,
+                { "raw": "a!H!b!N!c!.br!d!X0D!e!E!f!Xzz" },
+                [[["g", null]]],
+            ])
+        );
+        assert_eq!(from_json(&value).unwrap(), source);
+    }
+
+    #[test]
+    fn keeps_mixed_segment_separators() {
+        let source = "MSH|^~\\&|A\r\nPID|1\nPV1|1\r\nNK1|1";
+        let message = Message::parse_with_lenient_newlines(source, true).unwrap();
+        let value = to_json(&message);
+        assert_eq!(value["segment_separator"], "\r\n");
+        assert_eq!(value["segments"][0].get("separator"), None);
+        assert_eq!(value["segments"][1]["separator"], "\n");
+        assert_eq!(value["segments"][2].get("separator"), None);
+        assert_eq!(value["segments"][3].get("separator"), None);
+        assert_eq!(from_json(&value).unwrap(), source);
+    }
+
+    #[test]
+    fn reports_invalid_documents() {
+        let msh = json!({ "name": "MSH", "fields": ["|", "^~\\&"] });
+        assert_eq!(
+            from_json(&json!({ "segments": [{ "name": "PID", "fields": [] }] })),
+            Err(JsonError::MissingHeader)
+        );
+        assert_eq!(
+            from_json(&json!({ "segments": [{ "name": "MSH", "fields": ["||", "^~\\&"] }] })),
+            Err(JsonError::MissingHeader)
+        );
+        assert_eq!(
+            from_json(
+                &json!({ "segments": [msh, { "name": "PID", "fields": ["1", [[[["a"]]]]] }] })
+            ),
+            Err(invalid(
+                "segments[1].fields[1][0][0][0]".to_string(),
+                "a string, null, array, or raw value"
+            ))
+        );
+        assert_eq!(
+            from_json(
+                &json!({ "segments": [msh, { "name": "PID", "fields": [{ "raw": "a|b" }] }] })
+            ),
+            Err(invalid(
+                "segments[1].fields[0]".to_string(),
+                "a raw value without any separators"
+            ))
+        );
+        assert_eq!(
+            from_json(&json!({ "segment_separator": "|", "segments": [msh] })),
+            Err(invalid(
+                "segment_separator".to_string(),
+                "a string of carriage returns and newlines"
+            ))
+        );
+        assert_eq!(
+            from_json(
+                &json!({ "segments": [msh, { "name": "PID", "fields": [], "separator": "" }] })
+            ),
+            Err(invalid(
+                "segments[1].separator".to_string(),
+                "a string of carriage returns and newlines"
+            ))
+        );
+        assert_eq!(
+            from_json(&json!({ "segments": [msh] })).unwrap(),
+            "MSH|^~\\&"
+        );
+    }
+}
@@ -23,7 +23,7 @@
 
 /// Structs for representing HL7 messages.
 pub mod message;
-pub use message::Message;
+pub use message::{LazyMessage, Message};
 
 pub mod builder;
 
@@ -47,14 +47,52 @@ pub mod parser;
 /// `time` crates.
 pub mod datetime;
 
+/// Typed representations of HL7 data types, such as exact decimal numbers.
+pub mod types;
+
+/// Typed views of common HL7 segments, such as observations (`OBX`).
+pub mod segments;
+
+/// A dictionary of HL7 segment, field, and data type definitions, used to give names and
+/// metadata to locations within a message.
+pub mod dictionary;
+
+/// Structural comparison of two HL7 messages.
+pub mod diff;
+
+/// Conversion of HL7 messages between versions of the standard.
+pub mod version;
+
+/// De-identification of HL7 messages using configurable rules.
+#[cfg(feature = "anonymize")]
+pub mod anonymize;
+
+/// Declarative transformations from one HL7 message to another, described in YAML or JSON.
+#[cfg(feature = "transform")]
+pub mod transform;
+
+/// Conversion of HL7 messages to and from the HL7 v2.xml encoding.
+#[cfg(feature = "xml")]
+pub mod xml;
+
+/// A lossless JSON representation of HL7 messages.
+#[cfg(feature = "json")]
+pub mod json;
+
+/// Mapping of HL7 messages to FHIR R4 resources.
+#[cfg(feature = "fhir")]
+pub mod fhir;
+
 /// Parses an HL7 message into a structured form. Equivalent to calling `Message::parse(message)`.
-pub fn parse_message(message: &str) -> Result<Message, parser::ParseError> {
+pub fn parse_message(message: &str) -> Result<Message<'_>, parser::ParseError> {
     Message::parse(message)
 }
 
 /// Parses an HL7 message into a structured form, allowing lenient newlines. Equivalent to calling
 /// `Message::parse_with_lenient_newlines(message, true)`.
-pub fn parse_message_with_lenient_newlines(message: &str) -> Result<Message, parser::ParseError> {
+pub fn parse_message_with_lenient_newlines(
+    message: &str,
+) -> Result<Message<'_>, parser::ParseError> {
     Message::parse_with_lenient_newlines(message, true)
 }
 
@@ -0,0 +1,387 @@
+use super::LocatedCursor;
+use crate::{
+    message::{Field, Segment, Separators},
+    query::LocationQuery,
+    Message,
+};
+use std::{collections::HashMap, ops::Range};
+
+/// Options for finding a value within a message with [`find`] or `Message::find`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FindOptions {
+    /// Match against the raw values in the message, including escape sequences, rather than
+    /// the decoded values
+    pub raw: bool,
+    /// Match regardless of case
+    pub ignore_case: bool,
+    /// Treat the value as a regular expression. This requires the `regex` feature; without it,
+    /// finding returns [`FindError::RegexUnsupported`].
+    pub regex: bool,
+}
+
+impl FindOptions {
+    /// Match decoded values exactly, i.e. the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match against the raw values in the message, including escape sequences
+    pub fn raw(mut self) -> Self {
+        self.raw = true;
+        self
+    }
+
+    /// Match regardless of case
+    pub fn ignore_case(mut self) -> Self {
+        self.ignore_case = true;
+        self
+    }
+
+    /// Treat the value as a regular expression. This requires the `regex` feature.
+    pub fn regex(mut self) -> Self {
+        self.regex = true;
+        self
+    }
+}
+
+/// Errors that can occur when finding a value within a message
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+pub enum FindError {
+    /// The value to find is empty
+    #[error("Cannot find an empty value")]
+    EmptyValue,
+
+    /// The value is not a valid regular expression
+    #[error("Invalid regular expression: {0}")]
+    InvalidRegex(String),
+
+    /// A regular expression was requested, but the `regex` feature isn't enabled
+    #[error("Regular expressions require the `regex` feature")]
+    RegexUnsupported,
+}
+
+/// A location where a value was found within a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FindMatch<'m> {
+    /// The smallest part of the message which contains the entire match. This is only located
+    /// down to the field if the match spans multiple repeats, to the repeat if the match spans
+    /// multiple components, and so on.
+    pub cursor: LocatedCursor<'m>,
+    /// The canonical query for the location of the match
+    pub query: LocationQuery,
+    /// The byte range of the match within the message. When matching decoded values, the range
+    /// covers the entire escape sequence of any decoded character in the match.
+    pub range: Range<usize>,
+}
+
+impl<'m> FindMatch<'m> {
+    /// The raw text of the match, as it appears in the message
+    pub fn raw_value(&self) -> &'m str {
+        &self.cursor.message.source[self.range.clone()]
+    }
+}
+
+/// The compiled form of the value to find
+enum Matcher {
+    Text {
+        value: String,
+        ignore_case: bool,
+    },
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+}
+
+impl Matcher {
+    fn new(value: &str, options: &FindOptions) -> Result<Self, FindError> {
+        if value.is_empty() {
+            return Err(FindError::EmptyValue);
+        }
+        if options.regex {
+            #[cfg(feature = "regex")]
+            return regex::RegexBuilder::new(value)
+                .case_insensitive(options.ignore_case)
+                .build()
+                .map(Matcher::Regex)
+                .map_err(|e| FindError::InvalidRegex(e.to_string()));
+            #[cfg(not(feature = "regex"))]
+            return Err(FindError::RegexUnsupported);
+        }
+        Ok(Matcher::Text {
+            value: value.to_string(),
+            ignore_case: options.ignore_case,
+        })
+    }
+
+    /// The byte ranges of all non-overlapping, non-empty matches within the haystack
+    fn find_all(&self, haystack: &str) -> Vec<Range<usize>> {
+        match self {
+            Matcher::Text {
+                value,
+                ignore_case: false,
+            } => haystack
+                .match_indices(value.as_str())
+                .map(|(i, m)| i..i + m.len())
+                .collect(),
+            Matcher::Text {
+                value,
+                ignore_case: true,
+            } => {
+                let mut matches = Vec::new();
+                let mut from = 0;
+                while let Some(m) = haystack[from..].char_indices().find_map(|(i, _)| {
+                    let start = from + i;
+                    caseless_prefix(&haystack[start..], value).map(|len| start..start + len)
+                }) {
+                    from = m.end;
+                    matches.push(m);
+                }
+                matches
+            }
+            #[cfg(feature = "regex")]
+            Matcher::Regex(regex) => regex
+                .find_iter(haystack)
+                .map(|m| m.range())
+                .filter(|m| !m.is_empty())
+                .collect(),
+        }
+    }
+}
+
+/// If `haystack` starts with `value` ignoring case, the length of the match in the haystack
+fn caseless_prefix(haystack: &str, value: &str) -> Option<usize> {
+    let mut len = 0;
+    let mut chars = haystack.chars();
+    for expected in value.chars() {
+        let c = chars.next()?;
+        if !c.to_lowercase().eq(expected.to_lowercase()) {
+            return None;
+        }
+        len += c.len_utf8();
+    }
+    Some(len)
+}
+
+/// Find every occurrence of a value within the fields of a message. Matches never span
+/// multiple fields. Each match is returned along with the smallest part of the message that
+/// contains it and the canonical query for that location.
+///
+/// By default, values are matched against the decoded contents of each field, so `"DOE&SMITH"`
+/// will match `DOE\T\SMITH` in the message.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::{locate::FindOptions, Message};
+/// let message = Message::parse(
+///     "MSH|^~\\&|||||||ADT^A01|1234|P|2.3\rPID|||123456^^^MRN~987^^^SSN||DOE\\T\\SMITH^JOHN\rNK1||Doe^Jane",
+/// ).unwrap();
+///
+/// let matches = message.find("123456", &FindOptions::new()).unwrap();
+/// assert_eq!(matches.len(), 1);
+/// assert_eq!(matches[0].query.to_string(), "PID.3[1].1");
+///
+/// let matches = message.find("e&s", &FindOptions::new().ignore_case()).unwrap();
+/// assert_eq!(matches[0].query.to_string(), "PID.5.1");
+/// assert_eq!(matches[0].raw_value(), "E\\T\\S");
+///
+/// let matches = message.find("doe", &FindOptions::new().ignore_case()).unwrap();
+/// assert_eq!(matches.len(), 2);
+/// ```
+pub fn find<'m>(
+    message: &'m Message<'m>,
+    value: &str,
+    options: &FindOptions,
+) -> Result<Vec<FindMatch<'m>>, FindError> {
+    let matcher = Matcher::new(value, options)?;
+    let mut matches = Vec::new();
+    let mut seg_indices: HashMap<&str, usize> = HashMap::new();
+    for segment in message.segments() {
+        let seg_index = seg_indices.entry(segment.name).or_insert(0);
+        *seg_index += 1;
+        for (i, field) in segment.fields().enumerate() {
+            let ranges = if options.raw {
+                matcher
+                    .find_all(field.raw_value())
+                    .into_iter()
+                    .map(|m| field.range.start + m.start..field.range.start + m.end)
+                    .collect()
+            } else {
+                let (decoded, sources) =
+                    decode_with_sources(field.raw_value(), field.range.start, &message.separators);
+                matcher
+                    .find_all(&decoded)
+                    .into_iter()
+                    .map(|m| sources[m.start].start..sources[m.end - 1].end)
+                    .collect::<Vec<_>>()
+            };
+
+            for range in ranges {
+                let cursor = locate_range(message, segment, *seg_index, (i + 1, field), &range);
+                matches.push(FindMatch {
+                    query: LocationQuery::from(&cursor),
+                    cursor,
+                    range,
+                });
+            }
+        }
+    }
+    Ok(matches)
+}
+
+/// Locate the smallest part of a field which contains the entire range
+fn locate_range<'m>(
+    message: &'m Message<'m>,
+    segment: &'m Segment<'m>,
+    seg_index: usize,
+    field: (usize, &'m Field<'m>),
+    range: &Range<usize>,
+) -> LocatedCursor<'m> {
+    let contains = |r: &Range<usize>| r.start <= range.start && range.end <= r.end;
+    let repeat = field
+        .1
+        .repeats()
+        .enumerate()
+        .find(|(_, r)| contains(&r.range))
+        .map(|(i, r)| (i + 1, r));
+    let component = repeat.and_then(|(_, r)| {
+        r.components()
+            .enumerate()
+            .find(|(_, c)| contains(&c.range))
+            .map(|(i, c)| (i + 1, c))
+    });
+    let sub_component = component.and_then(|(_, c)| {
+        c.subcomponents()
+            .enumerate()
+            .find(|(_, s)| contains(&s.range))
+            .map(|(i, s)| (i + 1, s))
+    });
+    LocatedCursor {
+        message,
+        segment: Some((segment.name, seg_index, segment)),
+        field: Some(field),
+        repeat,
+        component,
+        sub_component,
+    }
+}
+
+/// Decode the escape sequences in a raw value which starts at `offset` within the message,
+/// returning the decoded value along with the range of the message that each byte of the
+/// decoded value came from. Escape sequences are decoded as they are by `Separators::decode`.
+fn decode_with_sources(
+    raw: &str,
+    offset: usize,
+    separators: &Separators,
+) -> (String, Vec<Range<usize>>) {
+    let mut decoded = String::with_capacity(raw.len());
+    let mut sources = Vec::with_capacity(raw.len());
+    let mut push = |s: &str, source: Range<usize>| {
+        decoded.push_str(s);
+        sources.extend(std::iter::repeat_n(source, s.len()));
+    };
+
+    let mut escape_start: Option<usize> = None;
+    for (i, c) in raw.char_indices() {
+        match escape_start {
+            Some(start) if c == separators.escape => {
+                escape_start = None;
+                let source = offset + start..offset + i + c.len_utf8();
+                let mut buf = [0; 4];
+                let sequence = &raw[start + separators.escape.len_utf8()..i];
+                push(separators.unescape(sequence, &mut buf), source);
+            }
+            Some(_) => {}
+            None if c == separators.escape => escape_start = Some(i),
+            None => push(
+                &raw[i..i + c.len_utf8()],
+                offset + i..offset + i + c.len_utf8(),
+            ),
+        }
+    }
+    (decoded, sources)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    #[test]
+    fn can_decode_with_sources() {
+        let separators = Separators::default();
+        let (decoded, sources) = decode_with_sources("a\\T\\bé\\X0A\\", 10, &separators);
+        assert_eq!(decoded, "a&bé\n");
+        assert_eq!(
+            sources,
+            vec![10..11, 11..14, 14..15, 15..17, 15..17, 17..22]
+        );
+    }
+
+    #[test]
+    fn can_find_raw_values() {
+        let message = Message::parse("MSH|^~\\&|\rPID|1|A\\T\\B|A&B").unwrap();
+        let matches = find(&message, "A&B", &FindOptions::new()).unwrap();
+        let queries: Vec<String> = matches.iter().map(|m| m.query.to_string()).collect();
+        assert_eq!(queries, vec!["PID.2", "PID.3"]);
+        assert_eq!(matches[0].range, 16..21);
+        assert_eq!(matches[1].range, 22..25);
+
+        let matches = find(&message, "A&B", &FindOptions::new().raw()).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].query.to_string(), "PID.3");
+        assert_eq!(matches[0].raw_value(), "A&B");
+
+        let matches = find(&message, "&", &FindOptions::new().raw()).unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].query.to_string(), "MSH.2");
+        assert_eq!(matches[1].query.to_string(), "PID.3");
+    }
+
+    #[test]
+    fn can_find_repeated_segments_ignoring_case() {
+        let message = Message::parse("MSH|^~\\&|\rOBX|1|Ärger\rOBX|2|x^äRGER&y").unwrap();
+        let matches = find(&message, "ärger", &FindOptions::new().ignore_case()).unwrap();
+        let queries: Vec<String> = matches.iter().map(|m| m.query.to_string()).collect();
+        assert_eq!(queries, vec!["OBX[1].2", "OBX[2].2.2.1"]);
+        assert_eq!(matches[1].raw_value(), "äRGER");
+        assert_eq!(matches[1].cursor.sub_component.unwrap().0, 1);
+    }
+
+    #[test]
+    fn cant_find_empty_values() {
+        let message = Message::parse("MSH|^~\\&|").unwrap();
+        assert_eq!(
+            find(&message, "", &FindOptions::new()),
+            Err(FindError::EmptyValue)
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn can_find_regular_expressions() {
+        let message = Message::parse("MSH|^~\\&|\rPID|||123^^^MRN~456^^^SSN").unwrap();
+        let matches = find(
+            &message,
+            r"\d{3}\^+mrn",
+            &FindOptions::new().regex().ignore_case(),
+        )
+        .unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].query.to_string(), "PID.3[1]");
+        assert!(matches!(
+            find(&message, "(", &FindOptions::new().regex()),
+            Err(FindError::InvalidRegex(_))
+        ));
+    }
+
+    #[cfg(not(feature = "regex"))]
+    #[test]
+    fn reports_regular_expressions_without_the_feature() {
+        let message = Message::parse("MSH|^~\\&|\rPID|||123").unwrap();
+        assert_eq!(
+            find(&message, "1.3", &FindOptions::new().regex()),
+            Err(FindError::RegexUnsupported)
+        );
+    }
+}
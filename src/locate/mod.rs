@@ -4,9 +4,15 @@ use crate::{
     message::{
         Component, DecodedSeparatorsDisplay, Field, Repeat, Segment, Separators, Subcomponent,
     },
+    query::LocationQuery,
     Message,
 };
 
+mod find;
+pub use find::*;
+mod position;
+pub use position::*;
+
 /// Results from locating a cursor within a message
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
@@ -24,8 +30,9 @@ pub struct LocatedCursor<'s> {
     pub sub_component: Option<(usize, &'s Subcomponent<'s>)>,
 }
 
-/// Locate a cursor within a message. The offset is the character (distinct from byte) offset
-/// within the message.
+/// Locate a cursor within a message. The offset is the byte (distinct from character) offset
+/// within the message, matching the `range` of each part of the message. To locate a cursor
+/// using a character offset, a UTF-16 offset, or a line and column, see [`locate_cursor_at`].
 pub fn locate_cursor<'m>(message: &'m Message<'m>, offset: usize) -> Option<LocatedCursor<'m>> {
     let mut cursor = LocatedCursor {
         message,
@@ -105,6 +112,31 @@ pub fn locate_cursor<'m>(message: &'m Message<'m>, offset: usize) -> Option<Loca
     Some(cursor)
 }
 
+/// Locate a cursor within a message using any kind of [`Offset`], such as a line and column.
+/// Returns `None` if the offset isn't a valid position within the message.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::{locate::{locate_cursor_at, LineCol, Offset}, Message};
+/// let message = Message::parse_with_lenient_newlines(
+///     "MSH|^~\\&\r\nPID|1||Zoë^Ängström",
+///     true,
+/// ).unwrap();
+/// let cursor = locate_cursor_at(&message, LineCol::new(1, 14)).unwrap();
+/// assert_eq!(cursor.to_string(), "PID.3.2");
+/// assert_eq!(cursor.raw_value(), Some("Ängström"));
+/// let cursor = locate_cursor_at(&message, Offset::Char(24)).unwrap();
+/// assert_eq!(cursor.to_string(), "PID.3.2");
+/// ```
+pub fn locate_cursor_at<'m>(
+    message: &'m Message<'m>,
+    offset: impl Into<Offset>,
+) -> Option<LocatedCursor<'m>> {
+    let offset = Positions::new(message.source).to_byte(offset)?;
+    locate_cursor(message, offset)
+}
+
 impl Display for LocatedCursor<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if let Some((seg_name, seg_idx, _)) = self.segment {
@@ -135,6 +167,48 @@ impl Display for LocatedCursor<'_> {
     }
 }
 
+impl From<&LocatedCursor<'_>> for LocationQuery {
+    /// Convert a located cursor into a location query that would select the same location.
+    /// Indices are only included where they are significant, matching the cursor's `Display`
+    /// implementation.
+    fn from(cursor: &LocatedCursor<'_>) -> Self {
+        let mut query = LocationQuery {
+            segment: String::new(),
+            segment_index: None,
+            field: None,
+            repeat: None,
+            component: None,
+            subcomponent: None,
+        };
+        if let Some((seg_name, seg_idx, _)) = cursor.segment {
+            query.segment = seg_name.to_string();
+            if cursor.message.segment_count(seg_name) > 1 {
+                query.segment_index = Some(seg_idx);
+            }
+        }
+        if let Some((field_idx, field)) = cursor.field {
+            query.field = Some(field_idx);
+            if let Some((repeat_idx, repeat)) = cursor.repeat {
+                if field.has_repeats() {
+                    query.repeat = Some(repeat_idx);
+                }
+                if let Some((component_idx, component)) = cursor.component {
+                    if repeat.has_components() {
+                        query.component = Some(component_idx);
+                    }
+                    if let Some((sub_component_idx, _)) = cursor.sub_component {
+                        if component.has_subcomponents() {
+                            query.component = Some(component_idx);
+                            query.subcomponent = Some(sub_component_idx);
+                        }
+                    }
+                }
+            }
+        }
+        query
+    }
+}
+
 impl<'m> LocatedCursor<'m> {
     /// Get the raw value of the field, repeat, component, or sub-component at the cursor location.
     /// Returns `None` if the cursor is not located at a field, repeat, component, or
@@ -222,4 +296,16 @@ mod test {
             .to_string();
         assert_eq!(value, "1^2");
     }
+
+    #[test]
+    fn can_convert_cursor_to_query() {
+        let message = Message::parse("MSH|^~\\&|asdf\rPID|1|0\rPID|2|a^b&c~d").unwrap();
+        let cursor = locate_cursor(&message, 18).expect("cursor is located");
+        assert_eq!(LocationQuery::from(&cursor).to_string(), "PID[1].1");
+
+        let cursor = locate_cursor(&message, 32).expect("cursor is located");
+        let query = LocationQuery::from(&cursor);
+        assert_eq!(query.to_string(), cursor.to_string());
+        assert_eq!(message.query(query).unwrap().raw_value(), "c");
+    }
 }
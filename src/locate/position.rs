@@ -0,0 +1,289 @@
+use std::ops::Range;
+
+/// A zero-based line and column within a message. Lines are separated by `\r`, `\n`, or `\r\n`.
+/// Unless otherwise noted, columns are counted in characters (Unicode scalar values).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineCol {
+    /// The zero-based line number
+    pub line: usize,
+    /// The zero-based column within the line
+    pub column: usize,
+}
+
+impl LineCol {
+    /// Create a new zero-based line and column
+    pub const fn new(line: usize, column: usize) -> Self {
+        LineCol { line, column }
+    }
+}
+
+/// An offset within a message, in one of the units that editors and other tools commonly use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Offset {
+    /// A byte offset, as used by the `range` of every node in a message
+    Byte(usize),
+    /// A character (Unicode scalar value) offset
+    Char(usize),
+    /// A UTF-16 code unit offset, as used by JavaScript strings
+    Utf16(usize),
+    /// A line and column, with the column counted in characters
+    LineCol(LineCol),
+    /// A line and column, with the column counted in UTF-16 code units, as used by the
+    /// Language Server Protocol
+    LineColUtf16(LineCol),
+}
+
+impl From<LineCol> for Offset {
+    fn from(line_col: LineCol) -> Self {
+        Offset::LineCol(line_col)
+    }
+}
+
+/// A position within a message, expressed in every supported unit
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    /// The byte offset
+    pub byte: usize,
+    /// The character (Unicode scalar value) offset
+    pub char: usize,
+    /// The UTF-16 code unit offset
+    pub utf16: usize,
+    /// The line and column, with the column counted in characters
+    pub line_col: LineCol,
+    /// The line and column, with the column counted in UTF-16 code units
+    pub line_col_utf16: LineCol,
+}
+
+/// The start of a line, in each of the units that offsets are counted in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LineStart {
+    byte: usize,
+    char: usize,
+    utf16: usize,
+}
+
+/// An index of the lines in a message, used to convert between byte offsets, character
+/// offsets, UTF-16 offsets, and line/column positions. Building the index is linear in the
+/// length of the message; each conversion is then logarithmic in the number of lines plus
+/// linear in the length of the line.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::locate::{LineCol, Offset, Positions};
+/// let positions = Positions::new("MSH|^~\\&\r\nPID|1||Zoë^Ängström");
+/// let position = positions.at_byte(29).unwrap();
+/// assert_eq!(position.char, 27);
+/// assert_eq!(position.line_col, LineCol::new(1, 17));
+/// assert_eq!(positions.to_byte(Offset::LineCol(LineCol::new(1, 17))), Some(29));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Positions<'s> {
+    source: &'s str,
+    lines: Vec<LineStart>,
+}
+
+impl<'s> Positions<'s> {
+    /// Index the lines of the given source
+    pub fn new(source: &'s str) -> Self {
+        let mut lines = vec![LineStart {
+            byte: 0,
+            char: 0,
+            utf16: 0,
+        }];
+        let mut chars = 0;
+        let mut utf16 = 0;
+        let mut iter = source.char_indices().peekable();
+        while let Some((i, c)) = iter.next() {
+            chars += 1;
+            utf16 += c.len_utf16();
+            let is_break = match c {
+                '\r' => iter.peek().map(|&(_, next)| next) != Some('\n'),
+                '\n' => true,
+                _ => false,
+            };
+            if is_break {
+                lines.push(LineStart {
+                    byte: i + 1,
+                    char: chars,
+                    utf16,
+                });
+            }
+        }
+        Positions { source, lines }
+    }
+
+    /// The source that was indexed
+    pub fn source(&self) -> &'s str {
+        self.source
+    }
+
+    /// The number of lines in the source. This is always at least 1.
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// The text of a line, excluding its line separator
+    fn line_text(&self, line: usize) -> Option<&'s str> {
+        let start = self.lines.get(line)?.byte;
+        let end = self
+            .lines
+            .get(line + 1)
+            .map(|next| next.byte)
+            .unwrap_or(self.source.len());
+        Some(self.source[start..end].trim_end_matches(['\r', '\n']))
+    }
+
+    /// Get the position at a byte offset. Returns `None` if the offset is past the end of the
+    /// source or is not on a character boundary. The end of the source is a valid position.
+    pub fn at_byte(&self, byte: usize) -> Option<Position> {
+        if !self.source.is_char_boundary(byte) {
+            return None;
+        }
+        let line = self.lines.partition_point(|l| l.byte <= byte) - 1;
+        let start = self.lines[line];
+        let text = &self.source[start.byte..byte];
+        let column = text.chars().count();
+        let column_utf16 = text.encode_utf16().count();
+        Some(Position {
+            byte,
+            char: start.char + column,
+            utf16: start.utf16 + column_utf16,
+            line_col: LineCol::new(line, column),
+            line_col_utf16: LineCol::new(line, column_utf16),
+        })
+    }
+
+    /// Get the position at any kind of offset. Returns `None` if the offset is past the end of
+    /// the source, is not on a character boundary, or is past the end of its line.
+    pub fn at(&self, offset: impl Into<Offset>) -> Option<Position> {
+        self.to_byte(offset).and_then(|byte| self.at_byte(byte))
+    }
+
+    /// Convert any kind of offset into a byte offset. Returns `None` if the offset is past the
+    /// end of the source, is not on a character boundary, or is past the end of its line.
+    pub fn to_byte(&self, offset: impl Into<Offset>) -> Option<usize> {
+        match offset.into() {
+            Offset::Byte(byte) => self.source.is_char_boundary(byte).then_some(byte),
+            Offset::Char(char) => {
+                let line = self.lines.partition_point(|l| l.char <= char) - 1;
+                let start = self.lines[line];
+                byte_in_line(&self.source[start.byte..], char - start.char, |_| 1)
+                    .map(|byte| start.byte + byte)
+            }
+            Offset::Utf16(utf16) => {
+                let line = self.lines.partition_point(|l| l.utf16 <= utf16) - 1;
+                let start = self.lines[line];
+                byte_in_line(
+                    &self.source[start.byte..],
+                    utf16 - start.utf16,
+                    char::len_utf16,
+                )
+                .map(|byte| start.byte + byte)
+            }
+            Offset::LineCol(LineCol { line, column }) => {
+                let text = self.line_text(line)?;
+                byte_in_line(text, column, |_| 1).map(|byte| self.lines[line].byte + byte)
+            }
+            Offset::LineColUtf16(LineCol { line, column }) => {
+                let text = self.line_text(line)?;
+                byte_in_line(text, column, char::len_utf16).map(|byte| self.lines[line].byte + byte)
+            }
+        }
+    }
+
+    /// Convert a byte range, such as the `range` of a field, into a range of lines and
+    /// columns, with the columns counted in characters
+    pub fn range_to_line_col(&self, range: Range<usize>) -> Option<Range<LineCol>> {
+        Some(self.at_byte(range.start)?.line_col..self.at_byte(range.end)?.line_col)
+    }
+
+    /// Convert a byte range, such as the `range` of a field, into a range of lines and
+    /// columns, with the columns counted in UTF-16 code units
+    pub fn range_to_line_col_utf16(&self, range: Range<usize>) -> Option<Range<LineCol>> {
+        Some(self.at_byte(range.start)?.line_col_utf16..self.at_byte(range.end)?.line_col_utf16)
+    }
+}
+
+/// The byte offset within `text` after `units` units have been counted, where `len` gives the
+/// number of units in each character. Returns `None` if the offset falls inside a character or
+/// is past the end of `text`.
+fn byte_in_line(text: &str, units: usize, len: impl Fn(char) -> usize) -> Option<usize> {
+    let mut counted = 0;
+    for (i, c) in text.char_indices() {
+        if counted == units {
+            return Some(i);
+        }
+        counted += len(c);
+        if counted > units {
+            return None;
+        }
+    }
+    (counted == units).then_some(text.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    #[test]
+    fn can_convert_between_offsets() {
+        // 'ë' is 2 bytes and 1 UTF-16 code unit, '😀' is 4 bytes and 2 UTF-16 code units
+        let positions = Positions::new("ab\rë😀x\r\nyz\nw");
+        assert_eq!(positions.line_count(), 4);
+
+        let position = positions.at_byte(9).unwrap();
+        assert_eq!(
+            position,
+            Position {
+                byte: 9,
+                char: 5,
+                utf16: 6,
+                line_col: LineCol::new(1, 2),
+                line_col_utf16: LineCol::new(1, 3),
+            }
+        );
+        assert_eq!(positions.to_byte(Offset::Char(5)), Some(9));
+        assert_eq!(positions.to_byte(Offset::Utf16(6)), Some(9));
+        assert_eq!(positions.to_byte(LineCol::new(1, 2)), Some(9));
+        assert_eq!(
+            positions.to_byte(Offset::LineColUtf16(LineCol::new(1, 3))),
+            Some(9)
+        );
+
+        assert_eq!(positions.at_byte(12).unwrap().line_col, LineCol::new(2, 0));
+        assert_eq!(positions.at_byte(15).unwrap().line_col, LineCol::new(3, 0));
+        assert_eq!(positions.at_byte(16).unwrap().line_col, LineCol::new(3, 1));
+        assert_eq!(positions.at(Offset::Char(6)).unwrap().byte, 10);
+    }
+
+    #[test]
+    fn rejects_invalid_offsets() {
+        let positions = Positions::new("ë😀\r\nx");
+        assert_eq!(positions.at_byte(1), None);
+        assert_eq!(positions.at_byte(10), None);
+        assert_eq!(positions.to_byte(Offset::Utf16(2)), None);
+        assert_eq!(positions.to_byte(Offset::Char(6)), None);
+        assert_eq!(positions.to_byte(LineCol::new(0, 3)), None);
+        assert_eq!(positions.to_byte(LineCol::new(2, 0)), None);
+        assert_eq!(positions.to_byte(LineCol::new(0, 2)), Some(6));
+        assert_eq!(positions.to_byte(LineCol::new(1, 1)), Some(9));
+    }
+
+    #[test]
+    fn can_convert_ranges() {
+        let positions = Positions::new("abc\r\nZoë|Ängström");
+        assert_eq!(
+            positions.range_to_line_col(10..20),
+            Some(LineCol::new(1, 4)..LineCol::new(1, 12))
+        );
+        assert_eq!(
+            positions.range_to_line_col_utf16(0..20),
+            Some(LineCol::new(0, 0)..LineCol::new(1, 12))
+        );
+    }
+}
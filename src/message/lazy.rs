@@ -0,0 +1,594 @@
+use std::{cell::OnceCell, fmt::Display, ops::Range, sync::OnceLock};
+
+use super::{Component, Field, Message, Repeat, Segment, Separators};
+use crate::{
+    dictionary::Version,
+    locate::{LineCol, LocatedCursor, Offset, Positions},
+    parser::{
+        msh::msh,
+        scan::{self, newline_len, segment_end, segment_name, Delimiters},
+        segment::segment,
+        ParseError, Span,
+    },
+    query::{
+        parse_value_query, query_segment, FromHl7Value, IntoLocationQuery, LocationQuery,
+        LocationQueryResult, QueryValue, QueryValueError, QueryValueErrorKind,
+    },
+};
+
+/// A lazily-parsed HL7 message. Only the `MSH` segment and the boundaries of the other segments
+/// are parsed up front; everything else is split out on demand, as it is accessed, and cached.
+///
+/// This is useful when only a few values are needed from each message, such as when routing or
+/// triaging messages by `MSH.9` and `PID.3`, as most of the message is never parsed. Queries
+/// only split out what they need:
+///
+/// - the first query for a segment's fields records where each of the segment's fields starts
+///   and ends,
+/// - querying a field parses just that field into its repeats, components, and subcomponents,
+/// - querying a repeat, component, or subcomponent records where each repeat of the field and
+///   each component of the repeat starts and ends, then parses just that repeat or component.
+///
+/// Indexing allocates a list of segment boundaries, and each of the steps above allocates a
+/// list of boundaries or the parsed value, so it is not zero-allocation, but values which are
+/// never queried are never allocated. Accessing a whole segment (with `segment`, `segments`, or
+/// a query for a segment such as `PID`) parses the whole segment, just as `Message::parse`
+/// would. If the message's separators can't be scanned unambiguously (i.e. two of them are the
+/// same character), the segment being queried is parsed in full instead.
+///
+/// If a segment was indexed but can't be parsed when it is accessed, `segment`, `segments`,
+/// `query`, `value`, and `query_value` return the error that `Message::parse` would have
+/// reported, rather than treating the segment as missing.
+///
+/// `LazyMessage` supports the same `segment`, `query`, `value`, and `locate_cursor` API as
+/// [`Message`] apart from these errors, and produces identical results.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::LazyMessage;
+/// let message = LazyMessage::parse("MSH|^~\\&|foo|bar|baz|quux|20010504094523||ADT^A01|1234|P|2.3|||\rPID|||1234^^^MRN||DOE^JOHN").unwrap();
+/// assert_eq!(message.segment_count("PID"), 1);
+/// assert_eq!(message.query("MSH.9.2").unwrap().unwrap().raw_value(), "A01");
+/// assert_eq!(message.query("PID.3.1").unwrap().unwrap().raw_value(), "1234");
+/// ```
+#[derive(Debug, Clone)]
+pub struct LazyMessage<'m> {
+    source: &'m str,
+    segments: Vec<LazySegment<'m>>,
+    /// The separators used in the message
+    pub separators: Separators,
+    /// The delimiters to scan segments with, if the separators can be scanned unambiguously
+    delimiters: Option<Delimiters>,
+    /// A fully-parsed copy of the message, only created when needed by `locate_cursor`
+    message: OnceLock<Option<Message<'m>>>,
+}
+
+/// The location of a segment within a lazily-parsed message, along with its fields and its
+/// parsed form once they have been accessed
+#[derive(Debug, Clone)]
+struct LazySegment<'m> {
+    name: &'m str,
+    range: Range<usize>,
+    fields: OnceLock<Result<Vec<LazyField<'m>>, ParseError>>,
+    parsed: OnceLock<Result<Segment<'m>, ParseError>>,
+}
+
+/// The location of a field within a lazily-parsed segment
+#[derive(Debug, Clone)]
+struct LazyField<'m> {
+    range: Range<usize>,
+    repeats: OnceLock<Vec<LazyRepeat<'m>>>,
+    parsed: OnceLock<Field<'m>>,
+}
+
+/// The location of a repeat within a lazily-parsed field
+#[derive(Debug, Clone)]
+struct LazyRepeat<'m> {
+    range: Range<usize>,
+    components: OnceLock<Vec<LazyComponent<'m>>>,
+    parsed: OnceLock<Repeat<'m>>,
+}
+
+/// The location of a component within a lazily-parsed repeat
+#[derive(Debug, Clone)]
+struct LazyComponent<'m> {
+    range: Range<usize>,
+    parsed: OnceLock<Component<'m>>,
+}
+
+impl<'m> LazySegment<'m> {
+    fn new(name: &'m str, range: Range<usize>) -> Self {
+        LazySegment {
+            name,
+            range,
+            fields: OnceLock::new(),
+            parsed: OnceLock::new(),
+        }
+    }
+}
+
+impl LazyField<'_> {
+    fn new(range: Range<usize>) -> Self {
+        LazyField {
+            range,
+            repeats: OnceLock::new(),
+            parsed: OnceLock::new(),
+        }
+    }
+}
+
+impl<'m> LazyMessage<'m> {
+    /// Index a message from a string. Only the `MSH` segment is fully parsed; the rest of the
+    /// message is parsed on demand. This will return an error if the `MSH` segment is not
+    /// valid.
+    pub fn parse(input: &'m str) -> Result<Self, ParseError> {
+        Self::parse_with_lenient_newlines(input, false)
+    }
+
+    /// Index a message from a string, allowing `\n` and `\r\n` as segment separators if
+    /// `lenient_newlines` is true.
+    pub fn parse_with_lenient_newlines(
+        input: &'m str,
+        lenient_newlines: bool,
+    ) -> Result<Self, ParseError> {
+        let (i, msh) = msh(lenient_newlines)(Span::new(input))?;
+        let mut separators = msh.separators;
+        separators.lenient_newlines = lenient_newlines;
+        let msh: Segment = msh.into();
+
+        let mut segments = vec![LazySegment::new(msh.name, msh.range.clone())];
+        segments[0].parsed = OnceLock::from(Ok(msh));
+
+        // mirror the eager parser: an optional segment separator directly after the MSH
+        // segment, followed by segment separator-delimited segments for as long as they look
+        // like segments
+        let mut start = i.offset + newline_len(&input[i.offset..], lenient_newlines);
+        let mut end = start;
+        while let Some(name) = segment_name(&input[start..], separators.field) {
+            end = segment_end(input.as_bytes(), start, lenient_newlines);
+            segments.push(LazySegment::new(name, start..end));
+
+            let separator = newline_len(&input[end..], lenient_newlines);
+            if separator == 0 {
+                break;
+            }
+            start = end + separator;
+        }
+
+        Ok(LazyMessage {
+            source: &input[..end],
+            segments,
+            separators,
+            delimiters: Delimiters::new(&separators),
+            message: OnceLock::new(),
+        })
+    }
+
+    /// Get the parsed form of a segment, parsing it if it hasn't been parsed yet
+    fn parse_segment<'s>(
+        &'s self,
+        lazy: &'s LazySegment<'m>,
+    ) -> Result<&'s Segment<'m>, ParseError> {
+        lazy.parsed
+            .get_or_init(|| {
+                if let Some(segment) =
+                    scan::segment(self.source, lazy.range.clone(), &self.separators)
+                {
+                    return Ok(segment);
+                }
+                let input = Span {
+                    input: &self.source[lazy.range.clone()],
+                    offset: lazy.range.start,
+                };
+                let (rest, segment) = segment(self.separators)(input)?;
+                if !rest.input.is_empty() {
+                    return Err(ParseError::FailedToParse {
+                        position: rest.offset,
+                        fragment: rest.input.to_string(),
+                    });
+                }
+                Ok(segment)
+            })
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    /// Get the boundaries of a segment's fields, finding them if they haven't been found yet
+    fn fields<'s>(
+        &'s self,
+        lazy: &'s LazySegment<'m>,
+        delimiters: Delimiters,
+    ) -> Result<&'s [LazyField<'m>], ParseError> {
+        lazy.fields
+            .get_or_init(|| {
+                let source = &self.source[lazy.range.clone()];
+                let fields = match segment_name(source, self.separators.field) {
+                    Some(name) => {
+                        // the field separator is ASCII, so it is a single byte
+                        let body = lazy.range.start + name.len() + 1..lazy.range.end;
+                        scan::split(self.source, body, delimiters.field)
+                    }
+                    // let the parser decide whether this is a segment, and report why not
+                    None => self
+                        .parse_segment(lazy)?
+                        .fields()
+                        .map(|f| f.range.clone())
+                        .collect(),
+                };
+                Ok(fields.into_iter().map(LazyField::new).collect())
+            })
+            .as_deref()
+            .map_err(Clone::clone)
+    }
+
+    /// Find the nth segment with the given name without parsing it
+    fn lazy_segment_n(&self, name: &str, n: usize) -> Option<&LazySegment<'m>> {
+        debug_assert!(n > 0, "Segments are 1-indexed");
+        self.segments.iter().filter(|s| s.name == name).nth(n - 1)
+    }
+
+    /// Find a segment with the given name, parsing it if necessary. If there are more than one
+    /// segments with this name, return the first one. Returns an error if the segment can't be
+    /// parsed.
+    pub fn segment(&self, name: &str) -> Result<Option<&Segment<'m>>, ParseError> {
+        self.segment_n(name, 1)
+    }
+
+    /// Find the nth segment with the given name, parsing it if necessary. If there are fewer
+    /// than n segments with this name, return `None`. Returns an error if the segment can't be
+    /// parsed.
+    /// Segments are 1-indexed.
+    pub fn segment_n(&self, name: &str, n: usize) -> Result<Option<&Segment<'m>>, ParseError> {
+        self.lazy_segment_n(name, n)
+            .map(|s| self.parse_segment(s))
+            .transpose()
+    }
+
+    /// Count the number of segments with the given name. This does not parse any segments.
+    pub fn segment_count(&self, name: &str) -> usize {
+        self.segments.iter().filter(|s| s.name == name).count()
+    }
+
+    /// An iterator over the names of the segments of the message, in order. This does not
+    /// parse any segments.
+    pub fn segment_names(&self) -> impl Iterator<Item = &'m str> + '_ {
+        self.segments.iter().map(|s| s.name)
+    }
+
+    /// An iterator over the segments of the message, parsing each segment as it is reached.
+    /// Segments which can't be parsed produce an error.
+    pub fn segments(&self) -> impl Iterator<Item = Result<&Segment<'m>, ParseError>> {
+        self.segments.iter().map(|s| self.parse_segment(s))
+    }
+
+    /// Get the raw value of the message. This is the value as it appears in the message,
+    /// without any decoding of escape sequences, and including all segments and
+    /// their separators.
+    pub fn raw_value(&self) -> &'m str {
+        self.source
+    }
+
+    /// Fully parse the message, producing the same result as `Message::parse`. Any segments
+    /// that have already been parsed are re-used. Returns an error if any segment can't be
+    /// parsed.
+    pub fn to_message(&self) -> Result<Message<'m>, ParseError> {
+        let segments = self
+            .segments
+            .iter()
+            .map(|s| self.parse_segment(s).cloned())
+            .collect::<Result<_, _>>()?;
+        Ok(Message {
+            source: self.source,
+            segments,
+            separators: self.separators,
+        })
+    }
+
+    /// Locate the cursor within the message. Note that this requires the entire message to be
+    /// parsed, which is done once and cached.
+    pub fn locate_cursor(&self, cursor: usize) -> Option<LocatedCursor<'_>> {
+        self.message
+            .get_or_init(|| self.to_message().ok())
+            .as_ref()?
+            .locate_cursor(cursor)
+    }
+
+    /// Locate the cursor within the message using any kind of [`Offset`], such as a line and
+    /// column. Like `locate_cursor`, this requires the entire message to be parsed.
+    pub fn locate_cursor_at(&self, offset: impl Into<Offset>) -> Option<LocatedCursor<'_>> {
+        self.message
+            .get_or_init(|| self.to_message().ok())
+            .as_ref()?
+            .locate_cursor_at(offset)
+    }
+
+    /// Index the lines of the message. See `Message::positions`.
+    pub fn positions(&self) -> Positions<'m> {
+        Positions::new(self.source)
+    }
+
+    /// Convert a byte range within the message into a range of lines and columns. See
+    /// `Message::range_to_line_col`.
+    pub fn range_to_line_col(&self, range: Range<usize>) -> Option<Range<LineCol>> {
+        self.positions().range_to_line_col(range)
+    }
+
+    /// Query the message for a specific location. Only the part of the segment that the query
+    /// refers to is parsed. Symbolic names are resolved for the HL7 version in `MSH.12`.
+    /// Returns `Ok(None)` if the query is invalid or the location doesn't exist, and an error if
+    /// the segment can't be parsed.
+    ///
+    /// # Examples
+    /// ```
+    /// let message =
+    /// hl7_parser::LazyMessage::parse("MSH|^~\\&|foo|bar|baz|quux|20010504094523||ADT^A01|1234|P|2.3|||").unwrap();
+    /// let field = message.query("MSH.3").unwrap().unwrap().raw_value();
+    /// assert_eq!(field, "foo");
+    /// let component = message.query("MSH.7.1").unwrap().unwrap().raw_value();
+    /// assert_eq!(component, "20010504094523");
+    /// ```
+    pub fn query<Q>(&self, query: Q) -> Result<Option<LocationQueryResult<'_>>, ParseError>
+    where
+        Q: IntoLocationQuery,
+    {
+        match query.into_location_query(|| self.version().unwrap_or(Version::LATEST)) {
+            Ok(query) => self.query_location(&query),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Query the message for a location which has already been resolved, parsing only the
+    /// part of the segment that the query refers to
+    fn query_location(
+        &self,
+        query: &LocationQuery,
+    ) -> Result<Option<LocationQueryResult<'_>>, ParseError> {
+        let Some(lazy) = self.lazy_segment_n(&query.segment, query.segment_index.unwrap_or(1))
+        else {
+            return Ok(None);
+        };
+        let (Some(field), Some(delimiters), None) =
+            (query.field, self.delimiters, lazy.parsed.get())
+        else {
+            // the whole segment is needed, has already been parsed, or can't be scanned
+            return Ok(query_segment(self.parse_segment(lazy)?, query));
+        };
+
+        debug_assert!(field > 0, "Field numbers are 1-indexed");
+        let Some(field) = self.fields(lazy, delimiters)?.get(field - 1) else {
+            return Ok(None);
+        };
+        let Some(component) = query.component else {
+            if query.repeat.is_none() {
+                let field = field
+                    .parsed
+                    .get_or_init(|| scan::field(self.source, field.range.clone(), delimiters));
+                return Ok(Some(LocationQueryResult::Field(field)));
+            }
+            return Ok(self
+                .repeat(field, query.repeat.unwrap_or(1), delimiters)
+                .map(|repeat| {
+                    LocationQueryResult::Repeat(repeat.parsed.get_or_init(|| {
+                        scan::repeat(self.source, repeat.range.clone(), delimiters)
+                    }))
+                }));
+        };
+
+        let Some(repeat) = self.repeat(field, query.repeat.unwrap_or(1), delimiters) else {
+            return Ok(None);
+        };
+        debug_assert!(component > 0, "Component numbers are 1-indexed");
+        let components = repeat.components.get_or_init(|| {
+            scan::split(self.source, repeat.range.clone(), delimiters.component)
+                .into_iter()
+                .map(|range| LazyComponent {
+                    range,
+                    parsed: OnceLock::new(),
+                })
+                .collect()
+        });
+        let Some(component) = components.get(component - 1) else {
+            return Ok(None);
+        };
+        let component = component.parsed.get_or_init(|| {
+            scan::component(
+                self.source,
+                component.range.clone(),
+                delimiters.subcomponent,
+            )
+        });
+        Ok(match query.subcomponent {
+            Some(subcomponent) => component
+                .subcomponent(subcomponent)
+                .map(LocationQueryResult::Subcomponent),
+            None => Some(LocationQueryResult::Component(component)),
+        })
+    }
+
+    /// Get the boundaries of the nth repeat of a field, finding the boundaries of its repeats
+    /// if they haven't been found yet
+    fn repeat<'s>(
+        &self,
+        field: &'s LazyField<'m>,
+        n: usize,
+        delimiters: Delimiters,
+    ) -> Option<&'s LazyRepeat<'m>> {
+        debug_assert!(n > 0, "Repeat numbers are 1-indexed");
+        field
+            .repeats
+            .get_or_init(|| {
+                scan::split(self.source, field.range.clone(), delimiters.repetition)
+                    .into_iter()
+                    .map(|range| LazyRepeat {
+                        range,
+                        components: OnceLock::new(),
+                        parsed: OnceLock::new(),
+                    })
+                    .collect()
+            })
+            .get(n - 1)
+    }
+
+    /// Query the message for a specific location, returning the result along with enough
+    /// context to decode it and convert it into other types. See `Message::value`. If the
+    /// segment can't be parsed, the error's kind is `QueryValueErrorKind::InvalidSegment`.
+    pub fn value(
+        &self,
+        query: impl IntoLocationQuery + Display,
+    ) -> Result<QueryValue<'_>, QueryValueError> {
+        let version = OnceCell::new();
+        let version = || *version.get_or_init(|| self.version().unwrap_or(Version::LATEST));
+        let query = parse_value_query(query, version)?;
+        match self.query_location(&query) {
+            Ok(Some(result)) => Ok(QueryValue::new(query, result, &self.separators, version())),
+            Ok(None) => Err(QueryValueError {
+                query: query.to_string(),
+                position: None,
+                kind: QueryValueErrorKind::NotFound,
+            }),
+            Err(e) => Err(invalid_segment(&query, e)),
+        }
+    }
+
+    /// Query the message for a specific location and convert the value into another type
+    /// using the [`FromHl7Value`] trait. See `Message::query_value`. If the segment can't be
+    /// parsed, the error's kind is `QueryValueErrorKind::InvalidSegment`.
+    pub fn query_value<'s, T: FromHl7Value<'s>>(
+        &'s self,
+        query: impl IntoLocationQuery + Display,
+    ) -> Result<T, QueryValueError> {
+        let version = OnceCell::new();
+        let version = || *version.get_or_init(|| self.version().unwrap_or(Version::LATEST));
+        let query = parse_value_query(query, version)?;
+        match self.query_location(&query) {
+            Ok(Some(result)) => {
+                T::from_hl7_value(&QueryValue::new(query, result, &self.separators, version()))
+            }
+            Ok(None) => T::from_missing(&query),
+            Err(e) => Err(invalid_segment(&query, e)),
+        }
+    }
+}
+
+fn invalid_segment(query: &LocationQuery, error: ParseError) -> QueryValueError {
+    QueryValueError {
+        query: query.to_string(),
+        position: None,
+        kind: QueryValueErrorKind::InvalidSegment(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    #[test]
+    fn can_index_segments() {
+        let message =
+            LazyMessage::parse("MSH|^~\\&|\rABC|foo\rXYZ|bar\rABC|baz").expect("can parse");
+        assert_eq!(
+            message.segment_names().collect::<Vec<_>>(),
+            vec!["MSH", "ABC", "XYZ", "ABC"]
+        );
+        assert!(message.segments[1].parsed.get().is_none());
+        assert_eq!(
+            message.segment_n("ABC", 2).unwrap().unwrap().raw_value(),
+            "ABC|baz"
+        );
+        assert!(message.segments[1].parsed.get().is_none());
+        assert!(message.segments[3].parsed.get().is_some());
+    }
+
+    #[test]
+    fn stops_indexing_at_invalid_segments() {
+        let source = "MSH|^~\\&|\rABC|foo\r\rXYZ|bar";
+        let lazy = LazyMessage::parse(source).expect("can parse");
+        let eager = Message::parse(source).expect("can parse");
+        assert_eq!(lazy.segment_count("XYZ"), 0);
+        assert_eq!(lazy.raw_value(), eager.raw_value());
+        assert_eq!(lazy.to_message().unwrap(), eager);
+
+        let source = "MSH|^~\\&|\r";
+        let lazy = LazyMessage::parse(source).expect("can parse");
+        let eager = Message::parse(source).expect("can parse");
+        assert_eq!(lazy.raw_value(), eager.raw_value());
+    }
+
+    #[test]
+    fn can_index_lenient_newlines() {
+        let source = "MSH|^~\\&|\r\nABC|foo\nXYZ|bar\rABC|baz\r\n";
+        let lazy = LazyMessage::parse_with_lenient_newlines(source, true).expect("can parse");
+        let eager = Message::parse_with_lenient_newlines(source, true).expect("can parse");
+        assert_eq!(lazy.segment_count("ABC"), 2);
+        assert_eq!(lazy.to_message().unwrap(), eager);
+    }
+
+    #[test]
+    fn only_parses_what_is_queried() {
+        let message =
+            LazyMessage::parse("MSH|^~\\&|\rPID|1|a^b&c~d^e|f\rOBX|1").expect("can parse");
+        let pid = &message.segments[1];
+
+        let result = message.query("PID.2[2].2").unwrap().unwrap();
+        assert_eq!(result.raw_value(), "e");
+        assert!(pid.parsed.get().is_none());
+        let fields = pid.fields.get().unwrap().as_ref().unwrap();
+        assert_eq!(fields.len(), 3);
+        assert!(fields.iter().all(|f| f.parsed.get().is_none()));
+        let repeats = fields[1].repeats.get().unwrap();
+        assert_eq!(repeats.len(), 2);
+        assert!(repeats.iter().all(|r| r.parsed.get().is_none()));
+        assert!(repeats[0].components.get().is_none());
+        let components = repeats[1].components.get().unwrap();
+        assert!(components[0].parsed.get().is_none());
+        assert!(components[1].parsed.get().is_some());
+        assert!(fields[2].repeats.get().is_none());
+        assert!(message.segments[2].fields.get().is_none());
+
+        let result = message.query("PID.2").unwrap().unwrap();
+        assert_eq!(result.raw_value(), "a^b&c~d^e");
+        assert!(fields[1].parsed.get().is_some());
+        assert!(pid.parsed.get().is_none());
+
+        assert_eq!(message.query("PID.4").unwrap(), None);
+        assert_eq!(message.query("PID.2[3]").unwrap(), None);
+        assert_eq!(message.query("PID.2.3").unwrap(), None);
+        assert_eq!(message.query("PID.2.1.3").unwrap(), None);
+        assert_eq!(
+            message.query("PID.2.2.2").unwrap().unwrap().raw_value(),
+            "c"
+        );
+    }
+
+    #[test]
+    fn reports_segments_which_cant_be_parsed() {
+        let mut lazy = LazyMessage::parse("MSH|^~\\&|\rABC|foo\rXYZ|bar").expect("can parse");
+        // an index which doesn't line up with a segment
+        lazy.segments[1].range.start += 1;
+        let error = ParseError::FailedToParse {
+            position: 11,
+            fragment: "BC|foo".to_string(),
+        };
+        assert_eq!(lazy.segment("ABC"), Err(error.clone()));
+        assert_eq!(lazy.query("ABC.1"), Err(error.clone()));
+        assert_eq!(lazy.query("ABC"), Err(error.clone()));
+        assert!(matches!(
+            lazy.value("ABC.1").unwrap_err().kind,
+            QueryValueErrorKind::InvalidSegment(ref e) if *e == error
+        ));
+        assert!(lazy.query_value::<Option<String>>("ABC.1").is_err());
+        assert_eq!(
+            lazy.segments().map(|s| s.is_ok()).collect::<Vec<_>>(),
+            vec![true, false, true]
+        );
+        assert_eq!(lazy.to_message(), Err(error));
+        assert_eq!(lazy.query("XYZ.1").unwrap().unwrap().raw_value(), "bar");
+    }
+
+    #[test]
+    fn cant_parse_invalid_msh() {
+        assert!(LazyMessage::parse("ABC|^~\\&|").is_err());
+    }
+}
@@ -10,13 +10,20 @@ mod field;
 pub use field::*;
 mod segment;
 pub use segment::*;
+mod lazy;
+pub use lazy::*;
 
-use crate::locate::LocatedCursor;
+use crate::locate::{FindError, FindMatch, FindOptions, LineCol, LocatedCursor, Offset, Positions};
 
 use crate::{
+    dictionary::Version,
     parser::ParseError,
-    query::{LocationQuery, LocationQueryResult},
+    query::{
+        parse_value_query, query_segment, FromHl7Value, IntoLocationQuery, LocationQuery,
+        LocationQueryResult, QueryValue, QueryValueError, QueryValueErrorKind,
+    },
 };
+use std::{cell::OnceCell, fmt::Display, ops::Range};
 
 /// A parsed HL7 message. This is the top-level structure that you get when you parse a message.
 /// It contains the segments of the message, as well as the separators used in the message.
@@ -55,9 +62,7 @@ impl<'m> Message<'m> {
     /// assert_eq!(last_name.raw_value(), "DOE");
     /// ```
     pub fn parse(input: &'m str) -> Result<Self, ParseError> {
-        crate::parser::message::message(false)(input.into())
-            .map(|(_, m)| m)
-            .map_err(|e| e.into())
+        crate::parser::parse_message_with_lenient_newlines(input, false)
     }
 
     /// Parse a message from a string, allowing lenient newlines.
@@ -85,9 +90,7 @@ impl<'m> Message<'m> {
         input: &'m str,
         lenient_newlines: bool,
     ) -> Result<Self, ParseError> {
-        crate::parser::message::message(lenient_newlines)(input.into())
-            .map(|(_, m)| m)
-            .map_err(|e| e.into())
+        crate::parser::parse_message_with_lenient_newlines(input, lenient_newlines)
     }
 
     /// Find a segment with the given name. If there are more than one segments
@@ -148,12 +151,91 @@ impl<'m> Message<'m> {
 
     /// Locate the cursor within the message. Equivalent to calling
     /// `hl7_parser::locate::locate_cursor` with the message and the cursor position.
-    pub fn locate_cursor(&self, cursor: usize) -> Option<LocatedCursor> {
+    pub fn locate_cursor(&self, cursor: usize) -> Option<LocatedCursor<'_>> {
         crate::locate::locate_cursor(self, cursor)
     }
 
+    /// Locate the cursor within the message using any kind of [`Offset`], such as a line and
+    /// column or a UTF-16 offset. Equivalent to calling `hl7_parser::locate::locate_cursor_at`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hl7_parser::{locate::LineCol, Message};
+    /// let message = Message::parse_with_lenient_newlines("MSH|^~\\&\nPID|1||Zoë^Ängström", true).unwrap();
+    /// let cursor = message.locate_cursor_at(LineCol::new(1, 12)).unwrap();
+    /// assert_eq!(cursor.raw_value(), Some("Ängström"));
+    /// ```
+    pub fn locate_cursor_at(&self, offset: impl Into<Offset>) -> Option<LocatedCursor<'_>> {
+        crate::locate::locate_cursor_at(self, offset)
+    }
+
+    /// Find every occurrence of a value within the fields of the message, matching decoded or
+    /// raw values, optionally ignoring case. Equivalent to calling `hl7_parser::locate::find`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hl7_parser::{locate::FindOptions, Message};
+    /// let message = Message::parse("MSH|^~\\&|\rPID|||123^^^MRN\rNK1|||||||||||||||||||123").unwrap();
+    /// let matches = message.find("123", &FindOptions::new()).unwrap();
+    /// let queries: Vec<String> = matches.iter().map(|m| m.query.to_string()).collect();
+    /// assert_eq!(queries, vec!["PID.3.1", "NK1.19"]);
+    /// assert_eq!(matches[0].range, 16..19);
+    /// ```
+    pub fn find(
+        &'m self,
+        value: &str,
+        options: &FindOptions,
+    ) -> Result<Vec<FindMatch<'m>>, FindError> {
+        crate::locate::find(self, value, options)
+    }
+
+    /// Compare this message with a newer version of it, returning the changes needed to get
+    /// from this message to `other`. Equivalent to calling `hl7_parser::diff::diff`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hl7_parser::Message;
+    /// let old = Message::parse("MSH|^~\\&|\rPID|1||||Doe^John").unwrap();
+    /// let new = Message::parse("MSH|^~\\&|\rPID|1||||Doe^Jane").unwrap();
+    /// assert_eq!(old.diff(&new).to_string(), "~ PID.5.2: \"John\" -> \"Jane\"\n");
+    /// ```
+    pub fn diff(&self, other: &Message) -> crate::diff::Diff {
+        crate::diff::diff(self, other)
+    }
+
+    /// Index the lines of the message, for converting between byte offsets, character offsets,
+    /// UTF-16 offsets, and line/column positions. If many conversions are needed, the index
+    /// should be kept and re-used.
+    pub fn positions(&self) -> Positions<'m> {
+        Positions::new(self.source)
+    }
+
+    /// Convert a byte range within the message, such as the `range` of a field, into a range of
+    /// zero-based lines and columns, with columns counted in characters. Lines may be separated
+    /// by `\r`, `\n`, or `\r\n`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hl7_parser::{locate::LineCol, Message};
+    /// let message = Message::parse_with_lenient_newlines("MSH|^~\\&\r\nPID|1||Zoë^Ängström", true).unwrap();
+    /// let range = message.query("PID.3.2").unwrap().range();
+    /// assert_eq!(
+    ///     message.range_to_line_col(range),
+    ///     Some(LineCol::new(1, 11)..LineCol::new(1, 19))
+    /// );
+    /// ```
+    pub fn range_to_line_col(&self, range: Range<usize>) -> Option<Range<LineCol>> {
+        self.positions().range_to_line_col(range)
+    }
+
     /// Query the message for a specific location. This is a more flexible way to
-    /// access the fields, components, and subcomponents of the message.
+    /// access the fields, components, and subcomponents of the message. Symbolic names such as
+    /// `PID.PatientName` are resolved for the HL7 version in `MSH.12`, or the latest version if
+    /// it's missing or unknown.
     ///
     /// # Examples
     /// ```
@@ -166,41 +248,79 @@ impl<'m> Message<'m> {
     /// ```
     pub fn query<Q>(&'m self, query: Q) -> Option<LocationQueryResult<'m>>
     where
-        Q: TryInto<LocationQuery>,
+        Q: IntoLocationQuery,
     {
-        let query = query.try_into().ok()?;
-        let segment_index = query.segment_index.unwrap_or(1);
-
-        if let Some(field) = query.field {
-            let repeat = query.repeat.unwrap_or(1);
-            if let Some(component) = query.component {
-                if let Some(subcomponent) = query.subcomponent {
-                    self.segment_n(&query.segment, segment_index)
-                        .and_then(|s| s.field(field))
-                        .and_then(|f| f.repeat(repeat))
-                        .and_then(|r| r.component(component))
-                        .and_then(|c| c.subcomponent(subcomponent))
-                        .map(LocationQueryResult::Subcomponent)
-                } else {
-                    self.segment_n(&query.segment, segment_index)
-                        .and_then(|s| s.field(field))
-                        .and_then(|f| f.repeat(repeat))
-                        .and_then(|r| r.component(component))
-                        .map(LocationQueryResult::Component)
-                }
-            } else if query.repeat.is_some() {
-                self.segment_n(&query.segment, segment_index)
-                    .and_then(|s| s.field(field))
-                    .and_then(|f| f.repeat(repeat))
-                    .map(LocationQueryResult::Repeat)
-            } else {
-                self.segment_n(&query.segment, segment_index)
-                    .and_then(|s| s.field(field))
-                    .map(LocationQueryResult::Field)
+        let query = query
+            .into_location_query(|| self.version().unwrap_or(Version::LATEST))
+            .ok()?;
+        self.query_location(&query)
+    }
+
+    /// Query the message for a location which has already been resolved
+    fn query_location(&'m self, query: &LocationQuery) -> Option<LocationQueryResult<'m>> {
+        self.segment_n(&query.segment, query.segment_index.unwrap_or(1))
+            .and_then(|segment| query_segment(segment, query))
+    }
+
+    /// Query the message for a specific location, returning the result along with enough
+    /// context to decode it and convert it into other types. Unlike `query`, this returns an
+    /// error describing what went wrong if the query is invalid or the location doesn't exist.
+    ///
+    /// # Examples
+    /// ```
+    /// let message =
+    /// hl7_parser::Message::parse("MSH|^~\\&|foo|bar|baz|quux|20010504094523||ADT^A01|1234|P|2.3|||").unwrap();
+    /// let timestamp = message.value("MSH.7").unwrap().as_timestamp().unwrap();
+    /// assert_eq!(timestamp.year, 2001);
+    /// let error = message.value("PID.5").unwrap_err();
+    /// assert_eq!(error.to_string(), "Failed to query `PID.5`: no value exists at this location");
+    /// ```
+    pub fn value(
+        &'m self,
+        query: impl IntoLocationQuery + Display,
+    ) -> Result<QueryValue<'m>, QueryValueError> {
+        let version = OnceCell::new();
+        let version = || *version.get_or_init(|| self.version().unwrap_or(Version::LATEST));
+        let query = parse_value_query(query, version)?;
+        match self.query_location(&query) {
+            Some(result) => Ok(QueryValue::new(query, result, &self.separators, version())),
+            None => Err(QueryValueError {
+                query: query.to_string(),
+                position: None,
+                kind: QueryValueErrorKind::NotFound,
+            }),
+        }
+    }
+
+    /// Query the message for a specific location and convert the value into another type
+    /// using the [`FromHl7Value`] trait. Escape sequences are decoded before conversion. If the
+    /// location doesn't exist, `Option` types return `None` and all other types return an
+    /// error.
+    ///
+    /// # Examples
+    /// ```
+    /// use hl7_parser::datetime::TimeStamp;
+    /// let message =
+    /// hl7_parser::Message::parse("MSH|^~\\&|foo|bar|baz|quux|20010504094523||ADT^A01|1234|P|2.3|||").unwrap();
+    /// let timestamp = message.query_value::<TimeStamp>("MSH.7").unwrap();
+    /// assert_eq!(timestamp.year, 2001);
+    /// let control_id = message.query_value::<u32>("MSH.10").unwrap();
+    /// assert_eq!(control_id, 1234);
+    /// let patient_name = message.query_value::<Option<String>>("PID.5").unwrap();
+    /// assert_eq!(patient_name, None);
+    /// ```
+    pub fn query_value<T: FromHl7Value<'m>>(
+        &'m self,
+        query: impl IntoLocationQuery + Display,
+    ) -> Result<T, QueryValueError> {
+        let version = OnceCell::new();
+        let version = || *version.get_or_init(|| self.version().unwrap_or(Version::LATEST));
+        let query = parse_value_query(query, version)?;
+        match self.query_location(&query) {
+            Some(result) => {
+                T::from_hl7_value(&QueryValue::new(query, result, &self.separators, version()))
             }
-        } else {
-            self.segment_n(&query.segment, segment_index)
-                .map(LocationQueryResult::Segment)
+            None => T::from_missing(&query),
         }
     }
 }
@@ -59,6 +59,7 @@ impl Separators {
         EncodedSeparatorsDisplay {
             separators: self,
             value,
+            keep_escapes: false,
         }
     }
 
@@ -81,6 +82,96 @@ impl Separators {
         }
     }
 
+    /// Encode a string in the same way as [`Separators::encode`], except that well-formed escape
+    /// sequences which don't stand for a separator (such as `\H\`, `\.sp\`, `\X41\`, or
+    /// `\Zxx\`) are kept as they are. This is the inverse of [`Separators::decode_separators`].
+    pub(crate) fn encode_keeping_escapes<'m>(
+        &'m self,
+        value: &'m str,
+    ) -> EncodedSeparatorsDisplay<'m> {
+        EncodedSeparatorsDisplay {
+            separators: self,
+            value,
+            keep_escapes: true,
+        }
+    }
+
+    /// Decode only the escape sequences which stand for separators, keeping every other escape
+    /// sequence (including `\X0D\` and `\.br\`) and any unterminated escape sequence as it is.
+    /// Encoding the result with [`Separators::encode_keeping_escapes`] gives back the original
+    /// value, unless it has an escaped escape character followed by text which reads as a
+    /// well-formed escape sequence (e.g. `\E\H\E\`) or an escape sequence which isn't
+    /// recognised, which is encoded as text.
+    pub(crate) fn decode_separators(&self, value: &str) -> String {
+        let mut decoded = String::with_capacity(value.len());
+        let mut rest = value;
+        while let Some(start) = rest.find(self.escape) {
+            let after = &rest[start + self.escape.len_utf8()..];
+            let Some(end) = after.find(self.escape) else {
+                break;
+            };
+            let sequence_end = start + 2 * self.escape.len_utf8() + end;
+            match self.classify(&after[..end]) {
+                Escape::Separator(c) => {
+                    decoded.push_str(&rest[..start]);
+                    decoded.push(c);
+                }
+                _ => decoded.push_str(&rest[..sequence_end]),
+            }
+            rest = &rest[sequence_end..];
+        }
+        decoded.push_str(rest);
+        decoded
+    }
+
+    /// Classify an escape sequence (the characters between a pair of escape characters)
+    pub(crate) fn classify<'a>(&self, sequence: &'a str) -> Escape<'a> {
+        let is_hex = |digits: &str| {
+            !digits.is_empty()
+                && digits.len().is_multiple_of(2)
+                && digits.bytes().all(|b| b.is_ascii_hexdigit())
+        };
+        match sequence {
+            "F" => Escape::Separator(self.field),
+            "R" => Escape::Separator(self.repetition),
+            "S" => Escape::Separator(self.component),
+            "T" => Escape::Separator(self.subcomponent),
+            "E" => Escape::Separator(self.escape),
+            "H" | "N" => Escape::Formatting(sequence),
+            _ if FORMATTING_COMMANDS.iter().any(|command| {
+                sequence
+                    .strip_prefix(command)
+                    .is_some_and(|argument| argument.is_empty() || argument.starts_with(' '))
+            }) =>
+            {
+                Escape::Formatting(sequence)
+            }
+            _ => match sequence.split_at_checked(1) {
+                Some(("X", digits)) if is_hex(digits) => Escape::Hex(digits),
+                Some(("Z", _)) => Escape::Other(sequence),
+                Some(("C", digits)) if digits.len() == 4 && is_hex(digits) => {
+                    Escape::Other(sequence)
+                }
+                Some(("M", digits)) if matches!(digits.len(), 4 | 6) && is_hex(digits) => {
+                    Escape::Other(sequence)
+                }
+                _ => Escape::Unrecognised(sequence),
+            },
+        }
+    }
+
+    /// The text that an escape sequence (the characters between a pair of escape characters)
+    /// stands for. Sequences other than separators, line endings, and `\.br\` stand for
+    /// themselves. `buf` holds the separator that a separator escape sequence stands for.
+    pub(crate) fn unescape<'a>(&self, sequence: &'a str, buf: &'a mut [u8; 4]) -> &'a str {
+        match self.classify(sequence) {
+            Escape::Separator(c) => c.encode_utf8(buf),
+            Escape::Hex("0A") => "\n",
+            Escape::Hex("0D") | Escape::Formatting(".br") => "\r",
+            _ => sequence,
+        }
+    }
+
     /// Allow lenient newlines in the message. This will allow `\n` and `\r\n` to be treated
     /// the same as `\r` as the separator for segments.
     pub fn with_lenient_newlines(&mut self, lenient_newlines: bool) -> Self {
@@ -89,6 +180,25 @@ impl Separators {
     }
 }
 
+/// The formatting commands of formatted text (`FT`), which may be followed by a space and an
+/// argument
+const FORMATTING_COMMANDS: [&str; 8] = [".br", ".sp", ".fi", ".nf", ".in", ".ti", ".sk", ".ce"];
+
+/// What an escape sequence stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Escape<'a> {
+    /// `F`, `R`, `S`, `T`, or `E`: one of the separators
+    Separator(char),
+    /// `Xhh...`: hexadecimal data, holding the digits
+    Hex(&'a str),
+    /// `H`, `N`, or a formatting command such as `.br` or `.sp 2`
+    Formatting(&'a str),
+    /// `Zxx...` (locally defined), `Cxxyy`, or `Mxxyy[zz]` (character set changes)
+    Other(&'a str),
+    /// Anything else
+    Unrecognised(&'a str),
+}
+
 impl Display for Separators {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -104,25 +214,37 @@ impl Display for Separators {
 pub struct EncodedSeparatorsDisplay<'m> {
     pub(crate) separators: &'m Separators,
     pub(crate) value: &'m str,
+    pub(crate) keep_escapes: bool,
 }
 
 impl Display for EncodedSeparatorsDisplay<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for c in self.value.chars() {
+        let escape = self.separators.escape;
+        let mut kept_until = 0;
+        for (i, c) in self.value.char_indices() {
+            if i < kept_until {
+                continue;
+            }
             if c == '\r' {
-                write!(f, "{escape}X0D{escape}", escape = self.separators.escape)?;
+                write!(f, "{escape}X0D{escape}")?;
             } else if c == '\n' {
-                write!(f, "{escape}X0A{escape}", escape = self.separators.escape)?;
+                write!(f, "{escape}X0A{escape}")?;
             } else if c == self.separators.field {
-                write!(f, "{escape}F{escape}", escape = self.separators.escape)?;
+                write!(f, "{escape}F{escape}")?;
             } else if c == self.separators.repetition {
-                write!(f, "{escape}R{escape}", escape = self.separators.escape)?;
+                write!(f, "{escape}R{escape}")?;
             } else if c == self.separators.component {
-                write!(f, "{escape}S{escape}", escape = self.separators.escape)?;
+                write!(f, "{escape}S{escape}")?;
             } else if c == self.separators.subcomponent {
-                write!(f, "{escape}T{escape}", escape = self.separators.escape)?;
-            } else if c == self.separators.escape {
-                write!(f, "{escape}E{escape}", escape = self.separators.escape)?;
+                write!(f, "{escape}T{escape}")?;
+            } else if c == escape {
+                match self.kept_escape(i + c.len_utf8()) {
+                    Some(end) => {
+                        f.write_str(&self.value[i..end])?;
+                        kept_until = end;
+                    }
+                    None => write!(f, "{escape}E{escape}")?,
+                }
             } else {
                 write!(f, "{}", c)?;
             }
@@ -131,6 +253,21 @@ impl Display for EncodedSeparatorsDisplay<'_> {
     }
 }
 
+impl EncodedSeparatorsDisplay<'_> {
+    /// If the escape sequence starting at `start` should be kept as it is, the end of the
+    /// sequence (after its closing escape character)
+    fn kept_escape(&self, start: usize) -> Option<usize> {
+        if !self.keep_escapes {
+            return None;
+        }
+        let end = start + self.value[start..].find(self.separators.escape)?;
+        match self.separators.classify(&self.value[start..end]) {
+            Escape::Separator(_) | Escape::Unrecognised(_) => None,
+            _ => Some(end + self.separators.escape.len_utf8()),
+        }
+    }
+}
+
 /// A display implementation which decodes the escape sequences in the value using the separators.
 pub struct DecodedSeparatorsDisplay<'m> {
     pub(crate) separators: &'m Separators,
@@ -141,22 +278,15 @@ impl Display for DecodedSeparatorsDisplay<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut escaped = false;
         let mut escape_i: usize = 0;
-        for (i, c) in self.value.chars().enumerate() {
+        for (i, c) in self.value.char_indices() {
             if c == self.separators.escape {
                 if escaped {
                     escaped = false;
-                    match &self.value[escape_i..i] {
-                        "F" => write!(f, "{}", self.separators.field)?,
-                        "R" => write!(f, "{}", self.separators.repetition)?,
-                        "S" => write!(f, "{}", self.separators.component)?,
-                        "T" => write!(f, "{}", self.separators.subcomponent)?,
-                        "E" => write!(f, "{}", self.separators.escape)?,
-                        "X0A" => writeln!(f)?,
-                        "X0D" | ".br" => write!(f, "\r")?,
-                        v => write!(f, "{v}")?,
-                    }
+                    let mut buf = [0; 4];
+                    let sequence = &self.value[escape_i..i];
+                    f.write_str(self.separators.unescape(sequence, &mut buf))?;
                 } else {
-                    escape_i = i + 1;
+                    escape_i = i + c.len_utf8();
                     escaped = true;
                 }
             } else if !escaped {
@@ -201,4 +331,44 @@ mod tests {
         let actual = separators.decode(input).to_string();
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn can_decode_after_multibyte_characters() {
+        let separators = Separators::default();
+
+        let input = r"Ünïcödé\F\Straße\T\ß";
+        let expected = "Ünïcödé|Straße&ß";
+        let actual = separators.decode(input).to_string();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn can_classify_escape_sequences() {
+        let separators = Separators::default();
+
+        assert_eq!(separators.classify("T"), Escape::Separator('&'));
+        assert_eq!(separators.classify("X0D0a"), Escape::Hex("0D0a"));
+        assert_eq!(separators.classify("H"), Escape::Formatting("H"));
+        assert_eq!(separators.classify(".sp 2"), Escape::Formatting(".sp 2"));
+        assert_eq!(separators.classify("Zxx"), Escape::Other("Zxx"));
+        assert_eq!(separators.classify("C2842"), Escape::Other("C2842"));
+        assert_eq!(separators.classify("X0"), Escape::Unrecognised("X0"));
+        assert_eq!(separators.classify(".spx"), Escape::Unrecognised(".spx"));
+        assert_eq!(separators.classify("temp"), Escape::Unrecognised("temp"));
+    }
+
+    #[test]
+    fn can_decode_and_encode_only_separators() {
+        let separators = Separators::default();
+
+        let input = r"\H\A\T\B\N\\.br\\X0D\\Zxx\\E\temp\E\ \unterminated";
+        let decoded = separators.decode_separators(input);
+        assert_eq!(decoded, r"\H\A&B\N\\.br\\X0D\\Zxx\\temp\ \unterminated");
+        let encoded = separators.encode_keeping_escapes(&decoded).to_string();
+        assert_eq!(
+            encoded,
+            r"\H\A\T\B\N\\.br\\X0D\\Zxx\\E\temp\E\ \E\unterminated"
+        );
+        assert_eq!(separators.encode(r"\H\").to_string(), r"\E\H\E\");
+    }
 }
@@ -6,13 +6,14 @@ pub(crate) type Span<'m> = span::Span<'m>;
 mod component;
 mod field;
 pub(crate) mod message;
-mod msh;
+pub(crate) mod msh;
 mod repeat;
+pub(crate) mod scan;
 pub(crate) mod segment;
 mod subcomponent;
 
 /// Errors that can occur during parsing
-#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ParseError {
     /// The parsing failed for some reason
@@ -141,15 +142,18 @@ pub fn parse_msh(input: &str) -> Result<(Separators, Segment<'_>), ParseError> {
 
 /// Parse a complete HL7 message.
 pub fn parse_message(input: &str) -> Result<crate::Message<'_>, ParseError> {
-    crate::parser::message::message(false)(Span::new(input))
-        .map(|(_, m)| m)
-        .map_err(|e| e.into())
+    parse_message_with_lenient_newlines(input, false)
 }
 
 pub fn parse_message_with_lenient_newlines(
     input: &str,
     lenient_newlines: bool,
 ) -> Result<crate::Message<'_>, ParseError> {
+    // the scanner is much faster, but defers to the nom parser for producing errors and for
+    // messages with unusual separators
+    if let Some(message) = scan::message(input, lenient_newlines) {
+        return Ok(message);
+    }
     crate::parser::message::message(lenient_newlines)(Span::new(input))
         .map(|(_, m)| m)
         .map_err(|e| e.into())
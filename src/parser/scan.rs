@@ -0,0 +1,403 @@
+//! A fast delimiter scanner which builds the same structures as the nom parsers.
+//!
+//! Since the separators are all ASCII, they can never appear inside a multi-byte UTF-8
+//! sequence, so the message can be split by searching for the delimiter bytes directly using
+//! `memchr` rather than walking the input one character at a time.
+
+use crate::message::{Component, Field, Message, Repeat, Segment, Separators, Subcomponent};
+use memchr::{memchr, memchr2, memchr3, memchr_iter};
+use std::ops::Range;
+
+/// The delimiter bytes which split a segment into fields, repeats, components, and
+/// subcomponents
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Delimiters {
+    pub(crate) field: u8,
+    pub(crate) repetition: u8,
+    pub(crate) component: u8,
+    pub(crate) subcomponent: u8,
+}
+
+impl Delimiters {
+    /// Get the delimiters for the given separators. Returns `None` if the separators can't be
+    /// scanned unambiguously, i.e. if two of them are the same character, or if one of them is
+    /// a segment separator. The nom parsers must be used in that case.
+    pub(crate) fn new(separators: &Separators) -> Option<Self> {
+        let delimiters = [
+            separators.field,
+            separators.repetition,
+            separators.component,
+            separators.subcomponent,
+        ];
+        for (i, &c) in delimiters.iter().enumerate() {
+            if !c.is_ascii() || c == '\r' || c == '\n' || delimiters[..i].contains(&c) {
+                return None;
+            }
+        }
+        Some(Delimiters {
+            field: delimiters[0] as u8,
+            repetition: delimiters[1] as u8,
+            component: delimiters[2] as u8,
+            subcomponent: delimiters[3] as u8,
+        })
+    }
+}
+
+/// Scan a complete message, producing the same result as the nom message parser.
+///
+/// Returns `None` if the message doesn't start with a valid `MSH` segment, or if its separators
+/// can't be scanned; the nom parser should be used instead in that case, which will either
+/// produce the appropriate error or parse the message.
+pub(crate) fn message(input: &str, lenient_newlines: bool) -> Option<Message<'_>> {
+    let separators = msh_separators(input, lenient_newlines)?;
+    let delimiters = Delimiters::new(&separators)?;
+    let bytes = input.as_bytes();
+
+    // the encoding characters may be followed by a field separator before the first
+    // regular field
+    let body_start = if bytes.get(8) == Some(&delimiters.field) {
+        9
+    } else {
+        8
+    };
+    let msh_end = segment_end(bytes, body_start, lenient_newlines);
+    let mut fields = vec![
+        Field::new_single(&input[3..4], 3..4),
+        Field::new_single(&input[4..8], 4..8),
+    ];
+    fields_into(input, body_start..msh_end, delimiters, &mut fields);
+    let mut segments = vec![Segment {
+        source: &input[..msh_end],
+        name: "MSH",
+        fields,
+        range: 0..msh_end,
+    }];
+
+    // an optional segment separator directly after the MSH segment, followed by segment
+    // separator-delimited segments for as long as they look like segments
+    let mut start = msh_end + newline_len(&input[msh_end..], lenient_newlines);
+    let mut end = start;
+    while let Some(name) = segment_name(&input[start..], separators.field) {
+        end = segment_end(bytes, start, lenient_newlines);
+        segments.push(scan_segment(input, name.len(), start..end, delimiters));
+
+        let separator = newline_len(&input[end..], lenient_newlines);
+        if separator == 0 {
+            break;
+        }
+        start = end + separator;
+    }
+
+    Some(Message {
+        source: &input[..end],
+        segments,
+        separators,
+    })
+}
+
+/// Scan a single segment which occupies `range` of `source`, and which has already been checked
+/// to start with a segment name and field separator. Returns `None` if the separators can't be
+/// scanned, in which case the nom segment parser should be used instead.
+pub(crate) fn segment<'m>(
+    source: &'m str,
+    range: Range<usize>,
+    separators: &Separators,
+) -> Option<Segment<'m>> {
+    let delimiters = Delimiters::new(separators)?;
+    let name = segment_name(&source[range.clone()], separators.field)?;
+    Some(scan_segment(source, name.len(), range, delimiters))
+}
+
+fn scan_segment(
+    source: &str,
+    name_len: usize,
+    range: Range<usize>,
+    delimiters: Delimiters,
+) -> Segment<'_> {
+    let mut fields = Vec::new();
+    // the field separator is ASCII, so it is a single byte
+    fields_into(
+        source,
+        range.start + name_len + 1..range.end,
+        delimiters,
+        &mut fields,
+    );
+    Segment {
+        source: &source[range.clone()],
+        name: &source[range.start..range.start + name_len],
+        fields,
+        range,
+    }
+}
+
+/// Split `range` of `source` into fields, pushing them onto `fields`. At least one (possibly
+/// empty) field is always produced.
+fn fields_into<'m>(
+    source: &'m str,
+    range: Range<usize>,
+    delimiters: Delimiters,
+    fields: &mut Vec<Field<'m>>,
+) {
+    let bytes = source.as_bytes();
+    let mut field_start = range.start;
+    let mut repeat_start = range.start;
+    let mut component_start = range.start;
+    let mut repeats = Vec::new();
+    let mut components = Vec::new();
+
+    let mut position = range.start;
+    loop {
+        let found = memchr3(
+            delimiters.field,
+            delimiters.repetition,
+            delimiters.component,
+            &bytes[position..range.end],
+        )
+        .map(|i| position + i);
+        let at = found.unwrap_or(range.end);
+
+        components.push(component(
+            source,
+            component_start..at,
+            delimiters.subcomponent,
+        ));
+        component_start = at + 1;
+        if found.is_some_and(|at| bytes[at] == delimiters.component) {
+            position = at + 1;
+            continue;
+        }
+
+        repeats.push(Repeat {
+            source: &source[repeat_start..at],
+            components: std::mem::take(&mut components),
+            range: repeat_start..at,
+        });
+        repeat_start = at + 1;
+        if found.is_some_and(|at| bytes[at] == delimiters.repetition) {
+            position = at + 1;
+            continue;
+        }
+
+        fields.push(Field {
+            source: &source[field_start..at],
+            repeats: std::mem::take(&mut repeats),
+            range: field_start..at,
+        });
+        field_start = at + 1;
+        if found.is_none() {
+            break;
+        }
+        position = at + 1;
+    }
+}
+
+/// Split `range` of `source` at each `delimiter`, without looking any further into the pieces.
+/// At least one (possibly empty) piece is always produced.
+pub(crate) fn split(source: &str, range: Range<usize>, delimiter: u8) -> Vec<Range<usize>> {
+    let mut start = range.start;
+    let mut pieces = Vec::new();
+    for i in memchr_iter(delimiter, &source.as_bytes()[range.clone()]) {
+        pieces.push(start..range.start + i);
+        start = range.start + i + 1;
+    }
+    pieces.push(start..range.end);
+    pieces
+}
+
+/// Split a single field which occupies `range` of `source` into its repeats, components, and
+/// subcomponents
+pub(crate) fn field(source: &str, range: Range<usize>, delimiters: Delimiters) -> Field<'_> {
+    let mut fields = Vec::with_capacity(1);
+    fields_into(source, range, delimiters, &mut fields);
+    debug_assert_eq!(fields.len(), 1, "a field can't contain a field separator");
+    fields.swap_remove(0)
+}
+
+/// Split a single repeat which occupies `range` of `source` into its components and
+/// subcomponents
+pub(crate) fn repeat(source: &str, range: Range<usize>, delimiters: Delimiters) -> Repeat<'_> {
+    let mut field = field(source, range, delimiters);
+    debug_assert_eq!(
+        field.repeats.len(),
+        1,
+        "a repeat can't contain a repetition separator"
+    );
+    field.repeats.swap_remove(0)
+}
+
+/// Split `range` of `source` into subcomponents
+pub(crate) fn component(source: &str, range: Range<usize>, subcomponent: u8) -> Component<'_> {
+    let bytes = &source.as_bytes()[range.clone()];
+    let Some(first) = memchr(subcomponent, bytes) else {
+        return Component::new_single(&source[range.clone()], range);
+    };
+
+    let mut subcomponents = Vec::new();
+    let mut start = range.start;
+    let mut end = range.start + first;
+    loop {
+        subcomponents.push(Subcomponent {
+            value: &source[start..end],
+            range: start..end,
+        });
+        if end == range.end {
+            break;
+        }
+        start = end + 1;
+        end = memchr(subcomponent, &source.as_bytes()[start..range.end])
+            .map(|i| start + i)
+            .unwrap_or(range.end);
+    }
+
+    Component {
+        source: &source[range.clone()],
+        subcomponents,
+        range,
+    }
+}
+
+/// The separators declared by the `MSH` segment at the start of the input, if the input starts
+/// with one
+fn msh_separators(input: &str, lenient_newlines: bool) -> Option<Separators> {
+    let bytes = input.as_bytes();
+    if !bytes.starts_with(b"MSH") || !bytes.get(3..8)?.is_ascii() {
+        return None;
+    }
+    Some(Separators {
+        field: bytes[3] as char,
+        component: bytes[4] as char,
+        repetition: bytes[5] as char,
+        escape: bytes[6] as char,
+        subcomponent: bytes[7] as char,
+        lenient_newlines,
+    })
+}
+
+/// The offset of the end of the segment starting at `start`, i.e. the offset of the next
+/// segment separator or the end of the input
+pub(crate) fn segment_end(bytes: &[u8], start: usize, lenient_newlines: bool) -> usize {
+    let remaining = &bytes[start..];
+    let end = if lenient_newlines {
+        memchr2(b'\r', b'\n', remaining)
+    } else {
+        memchr(b'\r', remaining)
+    };
+    start + end.unwrap_or(remaining.len())
+}
+
+/// The name of the segment at the start of the input, if the input starts with a segment
+pub(crate) fn segment_name(input: &str, field_separator: char) -> Option<&str> {
+    let mut chars = input.char_indices();
+    for _ in 0..3 {
+        chars.next().filter(|(_, c)| c.is_alphanumeric())?;
+    }
+    let (end, c) = chars.next()?;
+    (c == field_separator).then(|| &input[..end])
+}
+
+/// The length of the segment separator at the start of the input, or 0 if there isn't one
+pub(crate) fn newline_len(input: &str, lenient_newlines: bool) -> usize {
+    if !lenient_newlines {
+        return usize::from(input.starts_with('\r'));
+    }
+    if input.starts_with("\r\n") {
+        2
+    } else if input.starts_with(['\r', '\n']) {
+        1
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{message::message as nom_message, Span};
+    use pretty_assertions_sorted::assert_eq;
+
+    fn assert_parity(input: &str) {
+        for lenient_newlines in [false, true] {
+            let expected = nom_message(lenient_newlines)(Span::new(input)).map(|(_, m)| m);
+            let actual = message(input, lenient_newlines);
+            match (expected, actual) {
+                (Ok(expected), Some(actual)) => assert_eq!(
+                    actual, expected,
+                    "{input:?} (lenient newlines: {lenient_newlines})"
+                ),
+                (Err(_), None) => {}
+                (Ok(_), None) => assert!(
+                    Delimiters::new(&msh_separators(input, lenient_newlines).unwrap()).is_none(),
+                    "{input:?} should be scannable (lenient newlines: {lenient_newlines})"
+                ),
+                (Err(e), Some(_)) => panic!(
+                    "{input:?} was scanned but can't be parsed: {e:?} (lenient newlines: {lenient_newlines})"
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn scans_test_assets_like_the_parser() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_assets");
+        for entry in std::fs::read_dir(dir).expect("can read test assets") {
+            let path = entry.expect("can read test asset").path();
+            if path.extension().is_some_and(|e| e == "hl7") {
+                let source = std::fs::read_to_string(&path).expect("can read test asset");
+                assert_parity(&source);
+                assert_parity(&source.replace("\r\n", "\r").replace('\n', "\r"));
+            }
+        }
+    }
+
+    #[test]
+    fn scans_edge_cases_like_the_parser() {
+        for input in [
+            "",
+            "MSH",
+            "MSH|^~\\",
+            "MSH|^~\\&",
+            "MSH|^~\\&|",
+            "MSH|^~\\&\r",
+            "MSH|^~\\&x|y",
+            "MSH|^~\\&||",
+            "MSH|^~\\&|\r",
+            "MSH|^~\\&|\r\r",
+            "MSH|^~\\&|\rPID",
+            "MSH|^~\\&|\rPID|",
+            "MSH|^~\\&|\rPID|\r",
+            "MSH|^~\\&|\rPI|D|a",
+            "MSH|^~\\&|\rPÏD|a\rZ1Z|b",
+            "MSH|^~\\&|\rPID|a\r\rOBX|b",
+            "MSH|^~\\&|a^b&c~d^^&|\rPID|^&~|&&&\n|~~",
+            "MSH|^~\\&|a\r\nPID|b\nOBX|c\r\nZZZ|d\r\n",
+            "MSH|^~\\&|\u{e9}^\u{1f600}&\u{e9}\rPID|\u{e9}~\u{1f600}",
+            "MSH#$%@*#a$b%c*d",
+            "MSH|^^\\&|a^b",
+            "MSH|^~\\\r|a\rPID|b",
+            "MSH\u{e9}^~\\&|a",
+            "PID|^~\\&|a",
+        ] {
+            assert_parity(input);
+        }
+    }
+
+    #[test]
+    fn scans_generated_messages_like_the_parser() {
+        // every message of up to 5 characters after the encoding characters, using an
+        // alphabet that includes all of the delimiters and a segment name
+        let alphabet = ['P', '|', '^', '~', '&', '\r', '\n'];
+        let mut bodies = vec![String::new()];
+        for _ in 0..5 {
+            let longer: Vec<String> = bodies
+                .iter()
+                .filter(|b| b.len() == bodies.last().map(String::len).unwrap_or_default())
+                .flat_map(|b| alphabet.iter().map(move |c| format!("{b}{c}")))
+                .collect();
+            bodies.extend(longer);
+        }
+        for body in bodies {
+            assert_parity(&format!("MSH|^~\\&{body}"));
+            assert_parity(&format!("MSH|^~\\&|\rPPP{body}"));
+        }
+    }
+}
@@ -9,7 +9,7 @@ pub fn subcomponent<'i>(
     move |i| subcomponent_parser(i, seps)
 }
 
-fn subcomponent_parser(i: Span, seps: Separators) -> IResult<Span, Subcomponent<'_>> {
+fn subcomponent_parser(i: Span<'_>, seps: Separators) -> IResult<Span<'_>, Subcomponent<'_>> {
     let pos_start = i.offset;
 
     let sep = if seps.lenient_newlines {
@@ -37,6 +37,20 @@
 //! assert_eq!(query.subcomponent, None);
 //! ```
 //!
+//! ## Named queries
+//!
+//! Fields, components, and subcomponents can also be referred to by their symbolic names from
+//! the [dictionary](crate::dictionary), which can make queries easier to read. Names are matched
+//! case-insensitively and can be mixed with numeric indices. Displaying a query with the
+//! alternate flag (`{:#}`) renders it with names.
+//!
+//! ```
+//! use hl7_parser::query::LocationQuery;
+//! let query = LocationQuery::parse("PID.PatientName[1].FamilyName").unwrap();
+//! assert_eq!(query.to_string(), "PID.5[1].1");
+//! assert_eq!(format!("{query:#}"), "PID.PatientName[1].FamilyName");
+//! ```
+//!
 //! ## Building location queries
 //!
 //! A location query can also be built using a builder pattern. This is useful when you want to
@@ -56,13 +70,16 @@
 //! ```
 
 mod parser;
+mod value;
 
 use std::{fmt::Display, str::FromStr};
 
 pub use parser::QueryParseError;
 use thiserror::Error;
+pub use value::*;
 
 use crate::{
+    dictionary::{self, Version},
     message::{Component, Field, Repeat, Segment, Separators, Subcomponent},
     parser::Span,
 };
@@ -110,10 +127,38 @@ pub struct LocationQuery {
 /// assert_eq!(query.component, Some(4));
 /// assert_eq!(query.subcomponent, None);
 /// ```
+///
+/// Fields, components, and subcomponents may also be referred to by their symbolic names
+/// (resolved through the [dictionary](crate::dictionary) for the latest HL7 version), and
+/// `-` may be used in place of `.`:
+///
+/// ```
+/// use hl7_parser::query::parse_location_query;
+/// let query = parse_location_query("PID.PatientName.FamilyName").unwrap();
+/// assert_eq!(query.to_string(), "PID.5.1");
+/// let query = parse_location_query("MSH-MessageType.TriggerEvent").unwrap();
+/// assert_eq!(query.to_string(), "MSH.9.2");
+/// let query = parse_location_query("PID-5.1").unwrap();
+/// assert_eq!(query.to_string(), "PID.5.1");
+/// ```
 pub fn parse_location_query(query: &str) -> Result<LocationQuery, QueryParseError> {
-    parser::parse_query(Span::new(query))
-        .map(|(_, m)| m)
-        .map_err(|e| e.into())
+    parse_location_query_with_version(query, Version::LATEST)
+}
+
+/// Parse a location query from a string, resolving any symbolic names through the dictionary
+/// for the given HL7 version
+///
+/// # Examples
+/// ```
+/// use hl7_parser::{dictionary::Version, query::parse_location_query_with_version};
+/// let query = parse_location_query_with_version("PID.PatientName.GivenName", Version::V2_2).unwrap();
+/// assert_eq!(query.to_string(), "PID.5.2");
+/// ```
+pub fn parse_location_query_with_version(
+    query: &str,
+    version: Version,
+) -> Result<LocationQuery, QueryParseError> {
+    parser::parse_query(Span::new(query), || version)
 }
 
 impl FromStr for LocationQuery {
@@ -148,8 +193,73 @@ impl TryFrom<&String> for LocationQuery {
     }
 }
 
+/// Values which can be turned into a [`LocationQuery`], resolving any symbolic names through the
+/// dictionary for an HL7 version. This is how `Message::query` and friends resolve names for the
+/// version in the message's `MSH.12`.
+pub trait IntoLocationQuery {
+    /// Convert into a location query, resolving symbolic names for the version returned by
+    /// `version`. `version` is only called if there are symbolic names to resolve.
+    fn into_location_query(
+        self,
+        version: impl FnOnce() -> Version,
+    ) -> Result<LocationQuery, QueryParseError>;
+}
+
+impl IntoLocationQuery for LocationQuery {
+    fn into_location_query(
+        self,
+        _version: impl FnOnce() -> Version,
+    ) -> Result<LocationQuery, QueryParseError> {
+        Ok(self)
+    }
+}
+
+impl IntoLocationQuery for &LocationQuery {
+    fn into_location_query(
+        self,
+        _version: impl FnOnce() -> Version,
+    ) -> Result<LocationQuery, QueryParseError> {
+        Ok(self.clone())
+    }
+}
+
+impl IntoLocationQuery for &str {
+    fn into_location_query(
+        self,
+        version: impl FnOnce() -> Version,
+    ) -> Result<LocationQuery, QueryParseError> {
+        parser::parse_query(Span::new(self), version)
+    }
+}
+
+impl IntoLocationQuery for String {
+    fn into_location_query(
+        self,
+        version: impl FnOnce() -> Version,
+    ) -> Result<LocationQuery, QueryParseError> {
+        parser::parse_query(Span::new(&self), version)
+    }
+}
+
+impl IntoLocationQuery for &String {
+    fn into_location_query(
+        self,
+        version: impl FnOnce() -> Version,
+    ) -> Result<LocationQuery, QueryParseError> {
+        parser::parse_query(Span::new(self), version)
+    }
+}
+
+/// Displays the query in its canonical numeric form, i.e. `PID.5.1`. The alternate form
+/// (`{:#}`) displays the query with symbolic names for the latest HL7 version, i.e.
+/// `PID.PatientName.FamilyName`, as a query on its own doesn't know which message it's for. Use
+/// [`LocationQuery::named`] for a specific version, or [`QueryValue::named_query`] for the
+/// version of the message a value came from.
 impl Display for LocationQuery {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            return write!(f, "{}", self.named(Version::LATEST));
+        }
         write!(f, "{}", self.segment)?;
         if let Some(i) = self.segment_index {
             write!(f, "[{}]", i)?;
@@ -179,6 +289,77 @@ impl LocationQuery {
     pub fn parse(query: &str) -> Result<Self, QueryParseError> {
         parse_location_query(query)
     }
+
+    /// Parse a location query from a string, resolving any symbolic names for the given HL7
+    /// version. Equivalent to `parse_location_query_with_version`.
+    pub fn parse_with_version(query: &str, version: Version) -> Result<Self, QueryParseError> {
+        parse_location_query_with_version(query, version)
+    }
+
+    /// Display the query using symbolic names for the given HL7 version. Any part of the query
+    /// that isn't known to the dictionary is displayed numerically.
+    ///
+    /// # Examples
+    /// ```
+    /// use hl7_parser::{dictionary::Version, query::LocationQuery};
+    /// let query = LocationQuery::parse("PID.5[2].1.1").unwrap();
+    /// assert_eq!(
+    ///     query.named(Version::V2_5_1).to_string(),
+    ///     "PID.PatientName[2].FamilyName.Surname"
+    /// );
+    /// assert_eq!(format!("{query:#}"), "PID.PatientName[2].FamilyName.Surname");
+    ///
+    /// let query = LocationQuery::parse("ZFO.1.2").unwrap();
+    /// assert_eq!(query.named(Version::V2_5_1).to_string(), "ZFO.1.2");
+    /// ```
+    pub fn named(&self, version: Version) -> NamedLocationQueryDisplay<'_> {
+        NamedLocationQueryDisplay {
+            query: self,
+            version,
+        }
+    }
+}
+
+/// Displays a location query using symbolic names. Created by [`LocationQuery::named`].
+#[derive(Debug, Clone, Copy)]
+pub struct NamedLocationQueryDisplay<'q> {
+    query: &'q LocationQuery,
+    version: Version,
+}
+
+impl Display for NamedLocationQueryDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let query = self.query;
+        let definition = dictionary::lookup(self.version, query);
+        write!(f, "{}", query.segment)?;
+        if let Some(i) = query.segment_index {
+            write!(f, "[{}]", i)?;
+        }
+        let Some(field) = query.field else {
+            return Ok(());
+        };
+        match definition.as_ref().and_then(|d| d.field) {
+            Some(definition) => write!(f, ".{}", definition.symbolic_name())?,
+            None => write!(f, ".{}", field)?,
+        }
+        if let Some(i) = query.repeat {
+            write!(f, "[{}]", i)?;
+        }
+        let Some(component) = query.component else {
+            return Ok(());
+        };
+        match definition.as_ref().and_then(|d| d.component) {
+            Some(definition) => write!(f, ".{}", definition.symbolic_name())?,
+            None => write!(f, ".{}", component)?,
+        }
+        if let Some(subcomponent) = query.subcomponent {
+            match definition.as_ref().and_then(|d| d.subcomponent) {
+                Some(definition) => write!(f, ".{}", definition.symbolic_name())?,
+                None => write!(f, ".{}", subcomponent)?,
+            }
+        }
+        Ok(())
+    }
 }
 
 /// A builder for creating a location query with error checking.
@@ -406,6 +587,41 @@ impl<'m> LocationQueryResult<'m> {
     }
 }
 
+/// Query within a single segment. The segment name and index of the query are assumed to have
+/// already been matched.
+pub(crate) fn query_segment<'m>(
+    segment: &'m Segment<'m>,
+    query: &LocationQuery,
+) -> Option<LocationQueryResult<'m>> {
+    let Some(field) = query.field else {
+        return Some(LocationQueryResult::Segment(segment));
+    };
+    let repeat = query.repeat.unwrap_or(1);
+    if let Some(component) = query.component {
+        if let Some(subcomponent) = query.subcomponent {
+            segment
+                .field(field)
+                .and_then(|f| f.repeat(repeat))
+                .and_then(|r| r.component(component))
+                .and_then(|c| c.subcomponent(subcomponent))
+                .map(LocationQueryResult::Subcomponent)
+        } else {
+            segment
+                .field(field)
+                .and_then(|f| f.repeat(repeat))
+                .and_then(|r| r.component(component))
+                .map(LocationQueryResult::Component)
+        }
+    } else if query.repeat.is_some() {
+        segment
+            .field(field)
+            .and_then(|f| f.repeat(repeat))
+            .map(LocationQueryResult::Repeat)
+    } else {
+        segment.field(field).map(LocationQueryResult::Field)
+    }
+}
+
 /// Display the result of a location query, using the separators to decode escape sequences
 pub struct LocationQueryResultDisplay<'m> {
     value: &'m str,
@@ -422,6 +638,51 @@ impl<'m> Display for LocationQueryResultDisplay<'m> {
     }
 }
 
+/// (De)serialize location queries as strings, such as `PID.5[1].1`, for formats which are
+/// meant to be read and written by hand
+#[cfg(feature = "serde")]
+pub(crate) mod serde_string {
+    use crate::query::LocationQuery;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        query: &LocationQuery,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(query)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<LocationQuery, D::Error> {
+        let query = String::deserialize(deserializer)?;
+        LocationQuery::parse(&query).map_err(D::Error::custom)
+    }
+}
+
+/// (De)serialize lists of location queries as lists of strings, such as `["PID.5.2", "PID.5.1"]`
+#[cfg(feature = "transform")]
+pub(crate) mod serde_strings {
+    use crate::query::LocationQuery;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        queries: &[LocationQuery],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(queries.iter().map(|query| query.to_string()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<LocationQuery>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|query| LocationQuery::parse(query).map_err(D::Error::custom))
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,9 +1,13 @@
 use super::LocationQuery;
-use crate::parser::Span;
+use crate::{
+    dictionary::{self, Version},
+    parser::Span,
+};
 use nom::{
+    branch::alt,
     bytes::complete::{tag, take_while1},
     character::complete::one_of,
-    combinator::opt,
+    combinator::{map, opt, verify},
     sequence::{delimited, preceded},
     IResult,
 };
@@ -20,6 +24,11 @@ pub enum QueryParseError {
     /// The input was incomplete
     #[error("Query parsing failed because of incomplete input.{}", .0.map(|s| format!(" Need at least {s} more characters to continue.")).unwrap_or_default())]
     IncompleteInput(Option<usize>),
+
+    /// A symbolic field, component, or subcomponent name couldn't be resolved through the
+    /// dictionary
+    #[error("Query parsing failed at position {position}: unknown name `{name}`")]
+    UnknownName { position: usize, name: String },
 }
 
 impl<'s> From<nom::Err<nom::error::Error<Span<'s>>>> for QueryParseError {
@@ -42,7 +51,9 @@ impl<'s> From<nom::Err<nom::error::Error<Span<'s>>>> for QueryParseError {
 
 fn nonzero_integer(s: Span) -> IResult<Span, usize> {
     let (_s, val) = take_while1(|c: char| c.is_ascii_digit())(s)?;
-    let val = val.input.parse::<usize>().map_err(|_| todo!())?;
+    let val = val.input.parse::<usize>().map_err(|_| {
+        nom::Err::Failure(nom::error::Error::new(s, nom::error::ErrorKind::TooLarge))
+    })?;
     if val == 0 {
         return Err(nom::Err::Error(nom::error::Error::new(
             s,
@@ -60,38 +71,131 @@ fn preceeded_nonzero_integer(s: Span) -> IResult<Span, usize> {
     preceded(one_of(".- "), nonzero_integer)(s)
 }
 
-pub fn parse_query(i: Span) -> IResult<Span, LocationQuery> {
+/// A single path element of a query, either a 1-based index or a symbolic name to be resolved
+/// through the dictionary
+#[derive(Debug, Clone, Copy)]
+enum PathPart<'i> {
+    Index(usize),
+    Name(Span<'i>),
+}
+
+fn symbolic_name(s: Span) -> IResult<Span, Span> {
+    verify(
+        take_while1(|c: char| c.is_ascii_alphanumeric()),
+        |name: &Span| name.input.starts_with(|c: char| c.is_ascii_alphabetic()),
+    )(s)
+}
+
+fn preceeded_path_part(s: Span) -> IResult<Span, PathPart> {
+    alt((
+        map(preceeded_nonzero_integer, PathPart::Index),
+        map(preceded(one_of(".- "), symbolic_name), PathPart::Name),
+    ))(s)
+}
+
+fn unknown_name(name: Span) -> QueryParseError {
+    QueryParseError::UnknownName {
+        position: name.offset,
+        name: name.input.to_string(),
+    }
+}
+
+/// Parse a query, resolving any symbolic names through the dictionary. `version` is only called
+/// if the query contains symbolic names.
+pub fn parse_query(
+    i: Span,
+    version: impl FnOnce() -> Version,
+) -> Result<LocationQuery, QueryParseError> {
     let (i, segment) = crate::parser::segment::parse_segment_name(i)?;
     let (i, segment_index) = opt(nonzero_array_access)(i)?;
-    let (i, field) = opt(preceeded_nonzero_integer)(i)?;
+    let (i, field) = opt(preceeded_path_part)(i)?;
     let (i, repeat) = if field.is_some() {
         opt(nonzero_array_access)(i)?
     } else {
         (i, None)
     };
     let (i, component) = if field.is_some() {
-        opt(preceeded_nonzero_integer)(i)?
+        opt(preceeded_path_part)(i)?
     } else {
         (i, None)
     };
-    let (i, subcomponent) = if component.is_some() {
-        opt(preceeded_nonzero_integer)(i)?
+    let (_i, subcomponent) = if component.is_some() {
+        opt(preceeded_path_part)(i)?
     } else {
         (i, None)
     };
 
-    let segment = segment.input.to_string();
-    Ok((
-        i,
-        LocationQuery {
-            segment,
+    let index = |part: Option<PathPart>| match part {
+        Some(PathPart::Index(i)) => Some(i),
+        _ => None,
+    };
+    let is_name = |part: &Option<PathPart>| matches!(part, Some(PathPart::Name(_)));
+    if ![field, component, subcomponent].iter().any(is_name) {
+        return Ok(LocationQuery {
+            segment: segment.input.to_string(),
             segment_index,
-            field,
+            field: index(field),
             repeat,
-            component,
-            subcomponent,
-        },
-    ))
+            component: index(component),
+            subcomponent: index(subcomponent),
+        });
+    }
+
+    // resolve any symbolic names through the dictionary, walking down the data types
+    let version = version();
+    let segment_definition = dictionary::segment(version, segment.input);
+    let field_definition = match field {
+        Some(PathPart::Name(name)) => Some(
+            segment_definition
+                .and_then(|s| s.field_named(version, name.input))
+                .ok_or_else(|| unknown_name(name))?,
+        ),
+        Some(PathPart::Index(f)) => segment_definition.and_then(|s| s.field(version, f)),
+        None => None,
+    };
+    let component_definition = match component {
+        Some(PathPart::Name(name)) => Some(
+            field_definition
+                .and_then(|f| dictionary::data_type(version, f.data_type))
+                .and_then(|d| d.component_named(version, name.input))
+                .ok_or_else(|| unknown_name(name))?,
+        ),
+        Some(PathPart::Index(c)) => {
+            field_definition.and_then(|f| dictionary::component(version, f.data_type, c))
+        }
+        None => None,
+    };
+    let subcomponent = match subcomponent {
+        Some(PathPart::Name(name)) => Some(
+            component_definition
+                .and_then(|c| dictionary::data_type(version, c.data_type))
+                .and_then(|d| d.component_named(version, name.input))
+                .map(|s| s.position)
+                .ok_or_else(|| unknown_name(name))?,
+        ),
+        Some(PathPart::Index(s)) => Some(s),
+        None => None,
+    };
+    let field = match field {
+        Some(PathPart::Index(f)) => Some(f),
+        Some(PathPart::Name(_)) => field_definition.map(|f| f.position),
+        None => None,
+    };
+    let component = match component {
+        Some(PathPart::Index(c)) => Some(c),
+        Some(PathPart::Name(_)) => component_definition.map(|c| c.position),
+        None => None,
+    };
+
+    let segment = segment.input.to_string();
+    Ok(LocationQuery {
+        segment,
+        segment_index,
+        field,
+        repeat,
+        component,
+        subcomponent,
+    })
 }
 
 #[cfg(test)]
@@ -136,10 +240,96 @@ mod tests {
         assert!(nonzero_array_access(input).is_err());
     }
 
+    #[test]
+    fn can_parse_path_parts() {
+        let input = Span::new(".PatientName");
+        let actual = preceeded_path_part(input).unwrap().1;
+        assert!(matches!(actual, PathPart::Name(name) if name.input == "PatientName"));
+
+        let input = Span::new("-5");
+        let actual = preceeded_path_part(input).unwrap().1;
+        assert!(matches!(actual, PathPart::Index(5)));
+
+        let input = Span::new(".5Name");
+        let actual = preceeded_path_part(input).unwrap();
+        assert!(matches!(actual.1, PathPart::Index(5)));
+        assert_eq!(actual.0.input, "Name");
+
+        let input = Span::new(".0");
+        assert!(preceeded_path_part(input).is_err());
+    }
+
+    #[test]
+    fn can_parse_named_queries() {
+        let input = Span::new("PID.PatientName.FamilyName");
+        let actual = parse_query(input, || Version::LATEST).unwrap();
+        assert_eq!(actual.segment, "PID");
+        assert_eq!(actual.field, Some(5));
+        assert_eq!(actual.component, Some(1));
+        assert_eq!(actual.subcomponent, None);
+
+        let input = Span::new("PID[2].patientname[3].FamilyName.Surname");
+        let actual = parse_query(input, || Version::LATEST).unwrap();
+        assert_eq!(actual.segment_index, Some(2));
+        assert_eq!(actual.field, Some(5));
+        assert_eq!(actual.repeat, Some(3));
+        assert_eq!(actual.component, Some(1));
+        assert_eq!(actual.subcomponent, Some(1));
+
+        let input = Span::new("MSH.9.TriggerEvent");
+        let actual = parse_query(input, || Version::LATEST).unwrap();
+        assert_eq!(actual.field, Some(9));
+        assert_eq!(actual.component, Some(2));
+    }
+
+    #[test]
+    fn numeric_queries_dont_need_a_version() {
+        let input = Span::new("PID[2].5[3].1.1");
+        let actual = parse_query(input, || panic!("the version was resolved")).unwrap();
+        assert_eq!(actual.to_string(), "PID[2].5[3].1.1");
+    }
+
+    #[test]
+    fn named_queries_resolve_by_version() {
+        let input = Span::new("PID.PatientName.GivenName");
+        let actual = parse_query(input, || Version::V2_2).unwrap();
+        assert_eq!(actual.component, Some(2));
+
+        // PN has no components with this name
+        let input = Span::new("PID.PatientName.FamilyName.Surname");
+        let actual = parse_query(input, || Version::V2_2).unwrap_err();
+        assert!(matches!(
+            actual,
+            QueryParseError::UnknownName { position: 27, ref name } if name == "Surname"
+        ));
+    }
+
+    #[test]
+    fn cant_parse_unknown_names() {
+        let input = Span::new("PID.PatientNom");
+        let actual = parse_query(input, || Version::LATEST).unwrap_err();
+        assert!(matches!(
+            actual,
+            QueryParseError::UnknownName { position: 4, ref name } if name == "PatientNom"
+        ));
+
+        let input = Span::new("ZZZ.Whatever");
+        assert!(parse_query(input, || Version::LATEST).is_err());
+    }
+
+    #[test]
+    fn cant_parse_out_of_range_indices() {
+        let input = Span::new("PID.99999999999999999999999");
+        assert!(parse_query(input, || Version::LATEST).is_err());
+
+        let input = Span::new("PID[99999999999999999999999].3");
+        assert!(parse_query(input, || Version::LATEST).is_err());
+    }
+
     #[test]
     fn can_parse_full_query() {
         let input = Span::new("MSH[1].2[3].4.5");
-        let actual = parse_query(input).unwrap().1;
+        let actual = parse_query(input, || Version::LATEST).unwrap();
         assert_eq!(actual.segment, "MSH");
         assert_eq!(actual.segment_index, Some(1));
         assert_eq!(actual.field, Some(2));
@@ -151,7 +341,7 @@ mod tests {
     #[test]
     fn can_parse_truncated_queries() {
         let input = Span::new("MSH[1].2[3].4");
-        let actual = parse_query(input).unwrap().1;
+        let actual = parse_query(input, || Version::LATEST).unwrap();
         assert_eq!(actual.segment, "MSH");
         assert_eq!(actual.segment_index, Some(1));
         assert_eq!(actual.field, Some(2));
@@ -160,7 +350,7 @@ mod tests {
         assert_eq!(actual.subcomponent, None);
 
         let input = Span::new("MSH[1].2[3]");
-        let actual = parse_query(input).unwrap().1;
+        let actual = parse_query(input, || Version::LATEST).unwrap();
         assert_eq!(actual.segment, "MSH");
         assert_eq!(actual.segment_index, Some(1));
         assert_eq!(actual.field, Some(2));
@@ -169,7 +359,7 @@ mod tests {
         assert_eq!(actual.subcomponent, None);
 
         let input = Span::new("MSH[1].2");
-        let actual = parse_query(input).unwrap().1;
+        let actual = parse_query(input, || Version::LATEST).unwrap();
         assert_eq!(actual.segment, "MSH");
         assert_eq!(actual.segment_index, Some(1));
         assert_eq!(actual.field, Some(2));
@@ -178,7 +368,7 @@ mod tests {
         assert_eq!(actual.subcomponent, None);
 
         let input = Span::new("MSH[1]");
-        let actual = parse_query(input).unwrap().1;
+        let actual = parse_query(input, || Version::LATEST).unwrap();
         assert_eq!(actual.segment, "MSH");
         assert_eq!(actual.segment_index, Some(1));
         assert_eq!(actual.field, None);
@@ -187,7 +377,7 @@ mod tests {
         assert_eq!(actual.subcomponent, None);
 
         let input = Span::new("MSH");
-        let actual = parse_query(input).unwrap().1;
+        let actual = parse_query(input, || Version::LATEST).unwrap();
         assert_eq!(actual.segment, "MSH");
         assert_eq!(actual.segment_index, None);
         assert_eq!(actual.field, None);
@@ -196,7 +386,7 @@ mod tests {
         assert_eq!(actual.subcomponent, None);
 
         let input = Span::new("PID.3");
-        let actual = parse_query(input).unwrap().1;
+        let actual = parse_query(input, || Version::LATEST).unwrap();
         assert_eq!(actual.segment, "PID");
         assert_eq!(actual.segment_index, None);
         assert_eq!(actual.field, Some(3));
@@ -0,0 +1,614 @@
+use super::{
+    IntoLocationQuery, LocationQuery, LocationQueryResult, NamedLocationQueryDisplay,
+    QueryParseError,
+};
+use crate::{
+    datetime::{
+        parse_date, parse_time, parse_timestamp, Date, DateTimeParseError, Time, TimeStamp,
+    },
+    dictionary::Version,
+    message::Separators,
+    parser::ParseError,
+    types::{
+        CompositeQuantity, Decimal, DecimalParseError, Money, NumericParseError, NumericRange,
+        StructuredNumeric,
+    },
+};
+use std::{
+    borrow::Cow,
+    fmt::Display,
+    num::{ParseFloatError, ParseIntError},
+    ops::Deref,
+};
+use thiserror::Error;
+
+/// The result of a location query, along with the query that produced it and the separators of
+/// the message it came from. This allows the value to be decoded and converted into other types
+/// without having to pass the message's separators around.
+///
+/// `QueryValue` dereferences to [`LocationQueryResult`], so `raw_value`, `range`, and `display`
+/// are all available on it as well.
+///
+/// # Examples
+///
+/// ```
+/// let message = hl7_parser::Message::parse(
+///     "MSH|^~\\&|||||20230312195905||ADT^A01|1234|P|2.5.1\rPID|||||DOE\\S\\JR^JOHN||19800101|||||||||||||||||||||||N"
+/// ).unwrap();
+/// let name = message.value("PID.5.1").unwrap();
+/// assert_eq!(name.raw_value(), "DOE\\S\\JR");
+/// assert_eq!(name.decoded(), "DOE^JR");
+///
+/// let birth_date = message.value("PID.7").unwrap().as_date().unwrap();
+/// assert_eq!(birth_date.year, 1980);
+///
+/// let deceased = message.value("PID.30").unwrap().as_bool().unwrap();
+/// assert!(!deceased);
+///
+/// let error = message.value("PID.5").unwrap().as_i64().unwrap_err();
+/// assert_eq!(
+///     error.to_string(),
+///     "Failed to convert value of `PID.5` at position 58: invalid integer 'DOE^JR^JOHN': invalid digit found in string"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct QueryValue<'m> {
+    query: LocationQuery,
+    result: LocationQueryResult<'m>,
+    separators: &'m Separators,
+    version: Version,
+}
+
+/// An error that can occur when querying a message for a value, or converting that value
+/// into another type. Includes the query and, if the location was found, the position of the
+/// value within the message.
+#[derive(Debug, Error)]
+#[error("Failed to {} `{query}`{}: {kind}",
+    if .position.is_some() { "convert value of" } else { "query" },
+    .position.map(|p| format!(" at position {p}")).unwrap_or_default())]
+pub struct QueryValueError {
+    /// The query that produced the error
+    pub query: String,
+    /// The byte offset of the start of the value within the message, if the location exists
+    pub position: Option<usize>,
+    /// What went wrong
+    pub kind: QueryValueErrorKind,
+}
+
+/// The kinds of errors that can occur when querying a message for a value
+#[derive(Debug, Error)]
+pub enum QueryValueErrorKind {
+    /// The query couldn't be parsed
+    #[error("invalid query: {0}")]
+    InvalidQuery(QueryParseError),
+    /// The location doesn't exist in the message
+    #[error("no value exists at this location")]
+    NotFound,
+    /// The segment containing the location couldn't be parsed. Only returned by
+    /// [`LazyMessage`](crate::LazyMessage), which parses segments as they are accessed.
+    #[error("the segment can't be parsed: {0}")]
+    InvalidSegment(ParseError),
+    /// The value is empty but a value was required
+    #[error("value is empty")]
+    Empty,
+    /// The value isn't a valid date, time, or timestamp
+    #[error("invalid date/time '{value}': {source}")]
+    InvalidDateTime {
+        value: String,
+        source: DateTimeParseError,
+    },
+    /// The value isn't a valid integer
+    #[error("invalid integer '{value}': {source}")]
+    InvalidInteger {
+        value: String,
+        source: ParseIntError,
+    },
+    /// The value isn't a valid floating point number
+    #[error("invalid number '{value}': {source}")]
+    InvalidFloat {
+        value: String,
+        source: ParseFloatError,
+    },
+    /// The value isn't a valid decimal number
+    #[error("invalid decimal '{value}': {source}")]
+    InvalidDecimal {
+        value: String,
+        source: DecimalParseError,
+    },
+    /// The value isn't a valid structured numeric, quantity, money, or numeric range
+    #[error("invalid numeric value '{value}': {source}")]
+    InvalidNumeric {
+        value: String,
+        source: NumericParseError,
+    },
+    /// The value isn't `Y` or `N`
+    #[error("invalid boolean '{0}', expected 'Y' or 'N'")]
+    InvalidBoolean(String),
+    /// The value is present but isn't one of the values the type accepts. Intended for
+    /// [`FromHl7Value`] implementations of coded values.
+    #[error("invalid value '{0}'")]
+    InvalidValue(String),
+}
+
+impl<'m> QueryValue<'m> {
+    pub(crate) fn new(
+        query: LocationQuery,
+        result: LocationQueryResult<'m>,
+        separators: &'m Separators,
+        version: Version,
+    ) -> Self {
+        QueryValue {
+            query,
+            result,
+            separators,
+            version,
+        }
+    }
+
+    /// The query that produced this value
+    pub fn query(&self) -> &LocationQuery {
+        &self.query
+    }
+
+    /// The query that produced this value, displayed with symbolic names for the version of the
+    /// message the value came from (from `MSH.12`, or the latest version if that's missing)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let message = hl7_parser::Message::parse("MSH|^~\\&|||||||ADT^A01|1|P|2.2\rPID|1|A|B").unwrap();
+    /// let value = message.value("PID.PatientIDInternalID").unwrap();
+    /// assert_eq!(value.raw_value(), "B");
+    /// assert_eq!(value.named_query().to_string(), "PID.PatientIDInternalID");
+    /// ```
+    pub fn named_query(&self) -> NamedLocationQueryDisplay<'_> {
+        self.query.named(self.version)
+    }
+
+    /// The underlying query result
+    pub fn result(&self) -> &LocationQueryResult<'m> {
+        &self.result
+    }
+
+    /// The separators of the message the value came from
+    pub fn separators(&self) -> &'m Separators {
+        self.separators
+    }
+
+    /// Get the value with any escape sequences decoded. Only allocates if the value actually
+    /// contains escape sequences.
+    pub fn decoded(&self) -> Cow<'m, str> {
+        let raw = self.result.raw_value();
+        if raw.contains(self.separators.escape) {
+            Cow::Owned(self.separators.decode(raw).to_string())
+        } else {
+            Cow::Borrowed(raw)
+        }
+    }
+
+    /// Returns true if the value is empty
+    pub fn is_empty(&self) -> bool {
+        self.result.raw_value().is_empty()
+    }
+
+    /// Parse the value as a timestamp (`TS` / `DTM`). If the value is a field, repeat, or
+    /// component with a degree of precision (`TS.2`) after the time, the timestamp is truncated
+    /// to that precision.
+    pub fn as_timestamp(&self) -> Result<TimeStamp, QueryValueError> {
+        let value = self.non_empty()?;
+        let parsed = match self.result {
+            LocationQueryResult::Field(field) if field.repeats.len() == 1 => {
+                TimeStamp::from_field(field, self.separators)
+            }
+            LocationQueryResult::Repeat(repeat) => TimeStamp::from_repeat(repeat, self.separators),
+            LocationQueryResult::Component(component) => {
+                TimeStamp::from_component(component, self.separators)
+            }
+            _ => parse_timestamp(&value, false),
+        };
+        parsed.map_err(|source| {
+            self.error(QueryValueErrorKind::InvalidDateTime {
+                value: value.to_string(),
+                source,
+            })
+        })
+    }
+
+    /// Parse the value as a date (`DT`)
+    pub fn as_date(&self) -> Result<Date, QueryValueError> {
+        let value = self.non_empty()?;
+        parse_date(&value, false).map_err(|source| {
+            self.error(QueryValueErrorKind::InvalidDateTime {
+                value: value.to_string(),
+                source,
+            })
+        })
+    }
+
+    /// Parse the value as a time (`TM`)
+    pub fn as_time(&self) -> Result<Time, QueryValueError> {
+        let value = self.non_empty()?;
+        parse_time(&value, false).map_err(|source| {
+            self.error(QueryValueErrorKind::InvalidDateTime {
+                value: value.to_string(),
+                source,
+            })
+        })
+    }
+
+    /// Parse the value as an integer. Leading and trailing whitespace is ignored.
+    pub fn as_i64(&self) -> Result<i64, QueryValueError> {
+        self.parse_integer()
+    }
+
+    /// Parse the value as an exact decimal number (`NM`)
+    pub fn as_decimal(&self) -> Result<Decimal, QueryValueError> {
+        let value = self.non_empty()?;
+        value.parse().map_err(|source| {
+            self.error(QueryValueErrorKind::InvalidDecimal {
+                value: value.to_string(),
+                source,
+            })
+        })
+    }
+
+    /// Parse the value as an HL7 yes/no indicator, where `Y` is `true` and `N` is `false`.
+    /// Lowercase values are also accepted.
+    pub fn as_bool(&self) -> Result<bool, QueryValueError> {
+        let value = self.non_empty()?;
+        match value.trim() {
+            "Y" | "y" => Ok(true),
+            "N" | "n" => Ok(false),
+            _ => Err(self.error(QueryValueErrorKind::InvalidBoolean(value.to_string()))),
+        }
+    }
+
+    /// Convert the value into any type implementing [`FromHl7Value`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hl7_parser::datetime::TimeStamp;
+    /// let message = hl7_parser::Message::parse("MSH|^~\\&|||||20230312195905||ADT^A01|1234|P|2.5.1").unwrap();
+    /// let value = message.value("MSH.7").unwrap();
+    /// let timestamp: TimeStamp = value.parse().unwrap();
+    /// assert_eq!(timestamp.year, 2023);
+    /// ```
+    pub fn parse<T: FromHl7Value<'m>>(&self) -> Result<T, QueryValueError> {
+        T::from_hl7_value(self)
+    }
+
+    /// Create an error for this value
+    pub fn error(&self, kind: QueryValueErrorKind) -> QueryValueError {
+        QueryValueError {
+            query: self.query.to_string(),
+            position: Some(self.result.range().start),
+            kind,
+        }
+    }
+
+    fn non_empty(&self) -> Result<Cow<'m, str>, QueryValueError> {
+        let value = self.decoded();
+        if value.trim().is_empty() {
+            return Err(self.error(QueryValueErrorKind::Empty));
+        }
+        Ok(value)
+    }
+
+    fn parse_integer<I: std::str::FromStr<Err = ParseIntError>>(
+        &self,
+    ) -> Result<I, QueryValueError> {
+        let value = self.non_empty()?;
+        value.trim().parse().map_err(|source| {
+            self.error(QueryValueErrorKind::InvalidInteger {
+                value: value.to_string(),
+                source,
+            })
+        })
+    }
+}
+
+/// Parse a query for `value` / `query_value`, keeping the original query text for errors
+pub(crate) fn parse_value_query(
+    query: impl IntoLocationQuery + Display,
+    version: impl FnOnce() -> Version,
+) -> Result<LocationQuery, QueryValueError> {
+    let query_string = query.to_string();
+    query
+        .into_location_query(version)
+        .map_err(|e| QueryValueError {
+            query: query_string,
+            position: None,
+            kind: QueryValueErrorKind::InvalidQuery(e),
+        })
+}
+
+impl<'m> Deref for QueryValue<'m> {
+    type Target = LocationQueryResult<'m>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.result
+    }
+}
+
+/// A type that can be converted from a queried HL7 value. Used by
+/// [`Message::query_value`](crate::Message::query_value) and [`QueryValue::parse`].
+///
+/// Implementing this trait for your own types lets them be queried directly:
+///
+/// ```
+/// use hl7_parser::query::{FromHl7Value, QueryValue, QueryValueError, QueryValueErrorKind};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Sex {
+///     Male,
+///     Female,
+///     Other,
+/// }
+///
+/// impl FromHl7Value<'_> for Sex {
+///     fn from_hl7_value(value: &QueryValue<'_>) -> Result<Self, QueryValueError> {
+///         match value.decoded().as_ref() {
+///             "M" => Ok(Sex::Male),
+///             "F" => Ok(Sex::Female),
+///             "O" => Ok(Sex::Other),
+///             "" => Err(value.error(QueryValueErrorKind::Empty)),
+///             other => Err(value.error(QueryValueErrorKind::InvalidValue(other.to_string()))),
+///         }
+///     }
+/// }
+///
+/// let message = hl7_parser::Message::parse("MSH|^~\\&|\rPID||||||||F\rPID||||||||Q").unwrap();
+/// assert_eq!(message.query_value::<Sex>("PID.8").unwrap(), Sex::Female);
+/// let error = message.query_value::<Sex>("PID[2].8").unwrap_err();
+/// assert!(matches!(error.kind, QueryValueErrorKind::InvalidValue(ref v) if v == "Q"));
+/// ```
+pub trait FromHl7Value<'m>: Sized {
+    /// Convert a queried value
+    fn from_hl7_value(value: &QueryValue<'m>) -> Result<Self, QueryValueError>;
+
+    /// Called when the queried location doesn't exist in the message. By default this is an
+    /// error, but optional types may treat it as an absent value.
+    fn from_missing(query: &LocationQuery) -> Result<Self, QueryValueError> {
+        Err(QueryValueError {
+            query: query.to_string(),
+            position: None,
+            kind: QueryValueErrorKind::NotFound,
+        })
+    }
+}
+
+/// Empty and missing values are converted to `None`
+impl<'m, T: FromHl7Value<'m>> FromHl7Value<'m> for Option<T> {
+    fn from_hl7_value(value: &QueryValue<'m>) -> Result<Self, QueryValueError> {
+        if value.is_empty() {
+            Ok(None)
+        } else {
+            T::from_hl7_value(value).map(Some)
+        }
+    }
+
+    fn from_missing(_query: &LocationQuery) -> Result<Self, QueryValueError> {
+        Ok(None)
+    }
+}
+
+impl<'m> FromHl7Value<'m> for Cow<'m, str> {
+    fn from_hl7_value(value: &QueryValue<'m>) -> Result<Self, QueryValueError> {
+        Ok(value.decoded())
+    }
+}
+
+impl<'m> FromHl7Value<'m> for String {
+    fn from_hl7_value(value: &QueryValue<'m>) -> Result<Self, QueryValueError> {
+        Ok(value.decoded().into_owned())
+    }
+}
+
+impl<'m> FromHl7Value<'m> for TimeStamp {
+    fn from_hl7_value(value: &QueryValue<'m>) -> Result<Self, QueryValueError> {
+        value.as_timestamp()
+    }
+}
+
+impl<'m> FromHl7Value<'m> for Date {
+    fn from_hl7_value(value: &QueryValue<'m>) -> Result<Self, QueryValueError> {
+        value.as_date()
+    }
+}
+
+impl<'m> FromHl7Value<'m> for Time {
+    fn from_hl7_value(value: &QueryValue<'m>) -> Result<Self, QueryValueError> {
+        value.as_time()
+    }
+}
+
+impl<'m> FromHl7Value<'m> for Decimal {
+    fn from_hl7_value(value: &QueryValue<'m>) -> Result<Self, QueryValueError> {
+        value.as_decimal()
+    }
+}
+
+impl<'m> FromHl7Value<'m> for bool {
+    fn from_hl7_value(value: &QueryValue<'m>) -> Result<Self, QueryValueError> {
+        value.as_bool()
+    }
+}
+
+impl<'m> FromHl7Value<'m> for f64 {
+    fn from_hl7_value(value: &QueryValue<'m>) -> Result<Self, QueryValueError> {
+        let v = value.non_empty()?;
+        v.trim().parse().map_err(|source| {
+            value.error(QueryValueErrorKind::InvalidFloat {
+                value: v.to_string(),
+                source,
+            })
+        })
+    }
+}
+
+macro_rules! impl_from_hl7_value_for_integer {
+    ($($t:ty),*) => {
+        $(
+            impl<'m> FromHl7Value<'m> for $t {
+                fn from_hl7_value(value: &QueryValue<'m>) -> Result<Self, QueryValueError> {
+                    value.parse_integer()
+                }
+            }
+        )*
+    };
+}
+
+impl_from_hl7_value_for_integer!(i8, i16, i32, i64, u8, u16, u32, u64, usize, isize);
+
+macro_rules! impl_from_hl7_value_for_numeric {
+    ($($t:ty),*) => {
+        $(
+            impl<'m> FromHl7Value<'m> for $t {
+                fn from_hl7_value(value: &QueryValue<'m>) -> Result<Self, QueryValueError> {
+                    let v = value.non_empty()?;
+                    let parsed = match value.result {
+                        LocationQueryResult::Field(field) => <$t>::from_field(field, value.separators),
+                        LocationQueryResult::Repeat(repeat) => <$t>::from_repeat(repeat, value.separators),
+                        LocationQueryResult::Component(component) => {
+                            <$t>::from_component(component, value.separators)
+                        }
+                        _ => crate::parser::parse_repeat_with_separators(&v, *value.separators)
+                            .map_err(NumericParseError::from)
+                            .and_then(|repeat| <$t>::from_repeat(&repeat, value.separators)),
+                    };
+                    parsed.map_err(|source| {
+                        value.error(QueryValueErrorKind::InvalidNumeric {
+                            value: v.to_string(),
+                            source,
+                        })
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_from_hl7_value_for_numeric!(StructuredNumeric, CompositeQuantity, Money, NumericRange);
+
+#[cfg(test)]
+mod tests {
+    use crate::{datetime::TimeStamp, Message};
+    use pretty_assertions_sorted::assert_eq;
+
+    const SOURCE: &str = "MSH|^~\\&|||||20230312195905.1234-0700||ADT^A01|1234|P|2.5.1\rPID|1||||DOE\\T\\SMITH^JOHN||19800101|||||||||||||||||||||||Y\rOBX|1|NM|||+12.50|";
+
+    #[test]
+    fn can_decode_values() {
+        let message = Message::parse(SOURCE).unwrap();
+        let value = message.value("PID.5.1").unwrap();
+        assert_eq!(value.decoded(), "DOE&SMITH");
+        assert!(matches!(value.decoded(), std::borrow::Cow::Owned(_)));
+
+        let value = message.value("PID.5.2").unwrap();
+        assert!(matches!(
+            value.decoded(),
+            std::borrow::Cow::Borrowed("JOHN")
+        ));
+    }
+
+    #[test]
+    fn can_convert_values() {
+        let message = Message::parse(SOURCE).unwrap();
+        let timestamp = message.value("MSH.7").unwrap().as_timestamp().unwrap();
+        assert_eq!(timestamp.microsecond, Some(123_400));
+        assert_eq!(message.value("PID.1").unwrap().as_i64().unwrap(), 1);
+        assert_eq!(
+            message
+                .value("OBX.5")
+                .unwrap()
+                .as_decimal()
+                .unwrap()
+                .to_string(),
+            "12.50"
+        );
+        assert!(message.value("PID.30").unwrap().as_bool().unwrap());
+    }
+
+    #[test]
+    fn can_convert_numeric_values() {
+        use crate::types::{Comparator, StructuredNumeric};
+
+        let message =
+            Message::parse("MSH|^~\\&|||||||ORU^R01|1|P|2.5.1\rOBX|1|SN|||<^0.5").unwrap();
+        let sn: StructuredNumeric = message.value("OBX.5").unwrap().parse().unwrap();
+        assert_eq!(sn.comparator, Some(Comparator::LessThan));
+        assert_eq!(sn.num1.unwrap().to_string(), "0.5");
+        assert!(message
+            .value("OBX.2")
+            .unwrap()
+            .parse::<StructuredNumeric>()
+            .is_err());
+    }
+
+    #[test]
+    fn timestamps_use_their_degree_of_precision() {
+        let message =
+            Message::parse("MSH|^~\\&|||||200305141030^D||ADT^A01|1|P|2.3\rZAP|1|x^199901^L")
+                .unwrap();
+        let timestamp = message.value("MSH.7").unwrap().as_timestamp().unwrap();
+        assert_eq!(timestamp.to_string(), "20030514");
+        assert_eq!(
+            message
+                .query_value::<TimeStamp>("MSH.7.1")
+                .unwrap()
+                .to_string(),
+            "200305141030"
+        );
+        assert!(message.value("ZAP.2").unwrap().as_timestamp().is_err());
+    }
+
+    #[test]
+    fn can_query_typed_values() {
+        let message = Message::parse(SOURCE).unwrap();
+        assert_eq!(message.query_value::<u32>("PID.1").unwrap(), 1);
+        assert_eq!(
+            message.query_value::<String>("PID.5.1").unwrap(),
+            "DOE&SMITH"
+        );
+        assert_eq!(message.query_value::<Option<i64>>("PID.2").unwrap(), None);
+        assert_eq!(message.query_value::<Option<i64>>("ZZZ.2").unwrap(), None);
+        assert_eq!(message.query_value::<f64>("OBX.5").unwrap(), 12.5);
+    }
+
+    #[test]
+    fn errors_include_query_and_position() {
+        let message = Message::parse(SOURCE).unwrap();
+
+        let error = message.query_value::<i64>("PID.2").unwrap_err();
+        assert_eq!(error.query, "PID.2");
+        assert_eq!(
+            error.position,
+            Some(message.query("PID.2").unwrap().range().start)
+        );
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Failed to convert value of `PID.2` at position {}: value is empty",
+                error.position.unwrap()
+            )
+        );
+
+        let error = message.query_value::<i64>("ZZZ.2").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Failed to query `ZZZ.2`: no value exists at this location"
+        );
+
+        let error = message.query_value::<i64>("P.1").unwrap_err();
+        assert_eq!(error.query, "P.1");
+        assert!(error
+            .to_string()
+            .starts_with("Failed to query `P.1`: invalid query"));
+
+        let error = message.query_value::<bool>("PID.1").unwrap_err();
+        assert!(error
+            .to_string()
+            .ends_with("invalid boolean '1', expected 'Y' or 'N'"));
+    }
+}
@@ -0,0 +1,2 @@
+mod obx;
+pub use obx::*;
@@ -0,0 +1,373 @@
+use crate::{
+    datetime::{parse_date, parse_time, Date, DateTimeParseError, Time, TimeStamp},
+    message::{Repeat, Segment, Separators},
+    types::{
+        CodedElement, CompositeQuantity, Decimal, DecimalParseError, EncapsulatedData,
+        EncapsulatedDataError, EncapsulatedDataReader, FormattedText, Money, NumericParseError,
+        NumericRange, ReferencePointer, StructuredNumeric,
+    },
+    Message,
+};
+
+/// An observation (`OBX`) segment, such as a single lab result
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::{segments::ObservationValue, Message};
+///
+/// let message = Message::parse(
+///     "MSH|^~\\&|||||||ORU^R01|1|P|2.5.1\rOBX|1|NM|GLU^Glucose^LN||+5.60|mmol/L\rOBX|2|ST|CMT^Comment||Fasting \\T\\ rested",
+/// ).unwrap();
+/// let values = message
+///     .observations()
+///     .map(|obx| obx.value().unwrap())
+///     .collect::<Vec<_>>();
+/// assert_eq!(values[0], ObservationValue::Numeric(vec!["5.60".parse().unwrap()]));
+/// assert_eq!(values[1], ObservationValue::String(vec!["Fasting & rested".to_string()]));
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Obx<'m> {
+    segment: &'m Segment<'m>,
+    /// The segments after this one in the message, which may continue the observation value
+    following: &'m [Segment<'m>],
+    separators: &'m Separators,
+}
+
+/// The value of an observation (`OBX-5`), typed according to its value type (`OBX-2`). Each
+/// variant holds every repeat of the value. Empty repeats are skipped, except for text values,
+/// where they are blank lines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ObservationValue {
+    /// `NM` values
+    Numeric(Vec<Decimal>),
+    /// `SN` values
+    StructuredNumeric(Vec<StructuredNumeric>),
+    /// `CQ` values
+    Quantity(Vec<CompositeQuantity>),
+    /// `MO` values
+    Money(Vec<Money>),
+    /// `NR` values
+    NumericRange(Vec<NumericRange>),
+    /// `ST`, `ID`, and `IS` values, decoded
+    String(Vec<String>),
+    /// `TX` values, decoded, with one line per repeat
+    Text(Vec<String>),
+    /// `FT` values, with one paragraph per repeat
+    FormattedText(Vec<FormattedText>),
+    /// `CE`, `CWE`, `CNE`, and `CF` values
+    Coded(Vec<CodedElement>),
+    /// `TS` and `DTM` values, truncated to their degree of precision (`TS.2`) if present
+    DateTime(Vec<TimeStamp>),
+    /// `DT` values
+    Date(Vec<Date>),
+    /// `TM` values
+    Time(Vec<Time>),
+    /// `ED` values
+    Encapsulated(Vec<EncapsulatedData>),
+    /// `RP` values
+    ReferencePointer(Vec<ReferencePointer>),
+    /// Values of any other type, or with no type, decoded
+    Other {
+        /// The value type from `OBX-2`, which may be empty
+        value_type: String,
+        /// The decoded repeats of the value
+        values: Vec<String>,
+    },
+}
+
+/// Errors that can result from reading an observation value. `repeat` is the 1-based repeat of
+/// `OBX-5` that couldn't be parsed.
+#[derive(thiserror::Error, Debug)]
+pub enum ObservationValueError {
+    #[error("Invalid number '{value}' in OBX-5 repeat {repeat}: {source}")]
+    InvalidNumber {
+        repeat: usize,
+        value: String,
+        source: DecimalParseError,
+    },
+    #[error("Invalid numeric value '{value}' in OBX-5 repeat {repeat}: {source}")]
+    InvalidNumeric {
+        repeat: usize,
+        value: String,
+        source: NumericParseError,
+    },
+    #[error("Invalid date/time '{value}' in OBX-5 repeat {repeat}: {source}")]
+    InvalidDateTime {
+        repeat: usize,
+        value: String,
+        source: DateTimeParseError,
+    },
+}
+
+impl<'m> Obx<'m> {
+    /// View a segment as an observation, or `None` if it isn't an `OBX` segment. Use
+    /// [`Message::observations`] instead to also follow `ADD` segments continuing the value.
+    pub fn new(segment: &'m Segment<'m>, separators: &'m Separators) -> Option<Obx<'m>> {
+        (segment.name == "OBX").then_some(Obx {
+            segment,
+            following: &[],
+            separators,
+        })
+    }
+
+    /// The underlying segment
+    pub fn segment(&self) -> &'m Segment<'m> {
+        self.segment
+    }
+
+    /// The value type (`OBX-2`), such as `NM` or `CWE`, or `None` if it's empty
+    pub fn value_type(&self) -> Option<&'m str> {
+        self.segment
+            .field(2)
+            .and_then(|f| f.component(1))
+            .map(|c| c.raw_value().trim())
+            .filter(|v| !v.is_empty())
+    }
+
+    /// A reader for embedded data (`ED`) in the observation value (`OBX-5`), which decodes the
+    /// data as it's read rather than copying it out of the message first. Data split across
+    /// the repeats of `OBX-5` is joined, followed by the data in any `ADD` segments directly
+    /// after the observation.
+    pub fn encapsulated_data(&self) -> Result<EncapsulatedDataReader<'m>, EncapsulatedDataError> {
+        let field = self
+            .segment
+            .field(5)
+            .ok_or(EncapsulatedDataError::MissingEncoding)?;
+        let reader = EncapsulatedDataReader::from_field(field, self.separators)?;
+        Ok(self
+            .following
+            .iter()
+            .take_while(|segment| segment.name == "ADD")
+            .filter_map(|segment| segment.field(1))
+            .fold(reader, |reader, field| {
+                reader.with_continuation(field.raw_value())
+            }))
+    }
+
+    /// Read the observation value (`OBX-5`), using the value type (`OBX-2`) to decide how
+    /// to parse it
+    pub fn value(&self) -> Result<ObservationValue, ObservationValueError> {
+        let separators = self.separators;
+        let repeats = self
+            .segment
+            .field(5)
+            .map(|field| field.repeats.iter().collect::<Vec<_>>())
+            .unwrap_or_default();
+        let decoded = |repeat: &Repeat| separators.decode(repeat.raw_value()).to_string();
+        let text = || repeats.iter().map(|r| decoded(r)).collect();
+        let date_time = |repeat, value: String, source| ObservationValueError::InvalidDateTime {
+            repeat,
+            value,
+            source,
+        };
+
+        let value_type = self.value_type().unwrap_or_default();
+        Ok(match value_type.to_ascii_uppercase().as_str() {
+            "NM" => ObservationValue::Numeric(parse_repeats(&repeats, |repeat, r| {
+                let value = decoded(r);
+                value
+                    .parse()
+                    .map_err(|source| ObservationValueError::InvalidNumber {
+                        repeat,
+                        value,
+                        source,
+                    })
+            })?),
+            "SN" => ObservationValue::StructuredNumeric(parse_numeric(
+                &repeats,
+                separators,
+                StructuredNumeric::from_repeat,
+            )?),
+            "CQ" => ObservationValue::Quantity(parse_numeric(
+                &repeats,
+                separators,
+                CompositeQuantity::from_repeat,
+            )?),
+            "MO" => {
+                ObservationValue::Money(parse_numeric(&repeats, separators, Money::from_repeat)?)
+            }
+            "NR" => ObservationValue::NumericRange(parse_numeric(
+                &repeats,
+                separators,
+                NumericRange::from_repeat,
+            )?),
+            "ST" | "ID" | "IS" => ObservationValue::String(text()),
+            "TX" => ObservationValue::Text(text()),
+            "FT" => ObservationValue::FormattedText(
+                repeats
+                    .iter()
+                    .map(|r| FormattedText::parse(r.raw_value(), separators))
+                    .collect(),
+            ),
+            "CE" | "CWE" | "CNE" | "CF" => {
+                ObservationValue::Coded(parse_repeats(&repeats, |_, r| {
+                    Ok(CodedElement::from_repeat(r, separators))
+                })?)
+            }
+            "TS" | "DTM" => ObservationValue::DateTime(parse_repeats(&repeats, |repeat, r| {
+                TimeStamp::from_repeat(r, separators)
+                    .map_err(|source| date_time(repeat, r.raw_value().to_string(), source))
+            })?),
+            "DT" => ObservationValue::Date(parse_repeats(&repeats, |repeat, r| {
+                let value = decoded(r);
+                parse_date(value.trim(), false).map_err(|source| date_time(repeat, value, source))
+            })?),
+            "TM" => ObservationValue::Time(parse_repeats(&repeats, |repeat, r| {
+                let value = decoded(r);
+                parse_time(value.trim(), false).map_err(|source| date_time(repeat, value, source))
+            })?),
+            "ED" => ObservationValue::Encapsulated(parse_repeats(&repeats, |_, r| {
+                Ok(EncapsulatedData::from_repeat(r, separators))
+            })?),
+            "RP" => ObservationValue::ReferencePointer(parse_repeats(&repeats, |_, r| {
+                Ok(ReferencePointer::from_repeat(r, separators))
+            })?),
+            _ => ObservationValue::Other {
+                value_type: value_type.to_string(),
+                values: text(),
+            },
+        })
+    }
+}
+
+/// Parse the non-empty repeats of a value. The parse function is given the 1-based repeat
+/// number along with the repeat, for reporting errors.
+fn parse_repeats<T>(
+    repeats: &[&Repeat],
+    parse: impl Fn(usize, &Repeat) -> Result<T, ObservationValueError>,
+) -> Result<Vec<T>, ObservationValueError> {
+    repeats
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| !r.is_empty())
+        .map(|(i, r)| parse(i + 1, r))
+        .collect()
+}
+
+fn parse_numeric<T>(
+    repeats: &[&Repeat],
+    separators: &Separators,
+    parse: fn(&Repeat, &Separators) -> Result<T, NumericParseError>,
+) -> Result<Vec<T>, ObservationValueError> {
+    parse_repeats(repeats, |repeat, r| {
+        parse(r, separators).map_err(|source| ObservationValueError::InvalidNumeric {
+            repeat,
+            value: r.raw_value().to_string(),
+            source,
+        })
+    })
+}
+
+impl Message<'_> {
+    /// Iterate over the observation (`OBX`) segments of the message
+    pub fn observations(&self) -> impl Iterator<Item = Obx<'_>> {
+        self.segments.iter().enumerate().filter_map(|(i, segment)| {
+            Obx::new(segment, &self.separators).map(|obx| Obx {
+                following: &self.segments[i + 1..],
+                ..obx
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    fn value(obx: &str) -> Result<ObservationValue, ObservationValueError> {
+        let source = format!("MSH|^~\\&|||||||ORU^R01|1|P|2.5.1\r{obx}");
+        let message = Message::parse(&source).unwrap();
+        let obx = message.observations().next().unwrap();
+        obx.value()
+    }
+
+    #[test]
+    fn can_read_numeric_values() {
+        assert_eq!(
+            value("OBX|1|NM|||12.50~ 3. ~").unwrap(),
+            ObservationValue::Numeric(vec!["12.50".parse().unwrap(), "3".parse().unwrap()])
+        );
+        assert!(matches!(
+            value("OBX|1|NM|||1~two"),
+            Err(ObservationValueError::InvalidNumber { repeat: 2, .. })
+        ));
+        let ObservationValue::StructuredNumeric(sn) = value("OBX|1|SN|||>^100").unwrap() else {
+            panic!("expected a structured numeric");
+        };
+        assert_eq!(sn[0].num1.unwrap().to_string(), "100");
+        assert!(matches!(
+            value("OBX|1|SN|||~^1^?^2"),
+            Err(ObservationValueError::InvalidNumeric { repeat: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn can_read_text_values() {
+        assert_eq!(
+            value("OBX|1|TX|||Line 1~~Line \\F\\ 3").unwrap(),
+            ObservationValue::Text(vec![
+                "Line 1".to_string(),
+                String::new(),
+                "Line | 3".to_string()
+            ])
+        );
+        let ObservationValue::FormattedText(ft) =
+            value("OBX|1|FT|||Result:\\.br\\\\.in 2\\normal").unwrap()
+        else {
+            panic!("expected formatted text");
+        };
+        assert_eq!(ft[0].to_plain_text(), "Result:\n  normal");
+        assert_eq!(
+            value("OBX|1||||x^y").unwrap(),
+            ObservationValue::Other {
+                value_type: String::new(),
+                values: vec!["x^y".to_string()]
+            }
+        );
+    }
+
+    #[test]
+    fn can_read_coded_and_date_values() {
+        let ObservationValue::Coded(coded) =
+            value("OBX|1|CWE|||POS^Positive^HL70078~NEG^Negative^HL70078").unwrap()
+        else {
+            panic!("expected coded values");
+        };
+        assert_eq!(coded.len(), 2);
+        assert_eq!(coded[1].text.as_deref(), Some("Negative"));
+
+        let ObservationValue::DateTime(timestamps) = value("OBX|1|TS|||202303121530^D").unwrap()
+        else {
+            panic!("expected timestamps");
+        };
+        assert_eq!(timestamps[0].hour, None);
+        assert!(matches!(
+            value("OBX|1|DT|||20230231x"),
+            Err(ObservationValueError::InvalidDateTime { repeat: 1, .. })
+        ));
+
+        let ObservationValue::Encapsulated(ed) =
+            value("OBX|1|ED|||LAB^AP^PDF^Base64^SGk=").unwrap()
+        else {
+            panic!("expected encapsulated data");
+        };
+        assert_eq!(ed[0].data, "SGk=");
+    }
+
+    #[test]
+    fn follows_add_segments() {
+        let message = Message::parse(
+            "MSH|^~\\&|||||||ORU^R01|1|P|2.5.1\rOBX|1|ED|||^TX^^Hex^48\rADD|65\rADD|6C6C\rNTE|1||6F\rADD|6F",
+        )
+        .unwrap();
+        let obx = message.observations().next().unwrap();
+        let bytes = obx.encapsulated_data().unwrap().into_bytes().unwrap();
+        assert_eq!(bytes, b"Hell");
+
+        let obx = Obx::new(message.segment("OBX").unwrap(), &message.separators).unwrap();
+        let bytes = obx.encapsulated_data().unwrap().into_bytes().unwrap();
+        assert_eq!(bytes, b"H");
+    }
+}
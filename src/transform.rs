@@ -0,0 +1,936 @@
+//! # Declarative message transformations
+//!
+//! A [`Spec`] describes how to turn a source message into a new message, and can be written by
+//! hand in YAML or JSON so that mappings can be changed without recompiling. Applying a spec to
+//! a [`Message`] produces a [`MessageBuilder`] using the source message's separators, in two
+//! stages:
+//!
+//! 1. The output starts as a copy of the source message (or empty, with `base: empty`), and the
+//!    [`SegmentRule`]s drop, keep, rename, or copy segments from the source message, in order.
+//! 2. The [`Rule`]s set values in the output, in order. Each rule takes a value from the source
+//!    message (a query, a concatenation of queries, or a constant), optionally maps it through a
+//!    table of values, and writes it to one or more queries in the output. Segments which don't
+//!    exist in the output are added to the end of it.
+//!
+//! Values in a spec are raw HL7 values, as they appear in a message encoded with the source
+//! message's separators. This means that copying `PID.5` copies all of its components, and
+//! that values can contain structure, such as `Doe^John`.
+//!
+//! Segment rules, rules, and the conditions that restrict them can apply to each segment with a
+//! given name, using `for_each` for rules. Within a segment, queries for that segment which
+//! don't have a segment index (such as `OBX.5`) refer to the current segment rather than the
+//! first one.
+//!
+//! ## Examples
+//!
+//! ```
+//! use hl7_parser::{transform::Spec, Message};
+//!
+//! let spec = Spec::from_yaml(r#"
+//! segments:
+//!   - drop: ZPI
+//!   - drop: OBX
+//!     when:
+//!       - query: OBX.11
+//!         equals: W
+//! rules:
+//!   - value: "2.5.1"
+//!     to: MSH.12
+//!   - from: PID.8
+//!     map: { M: male, F: female }
+//!     default: unknown
+//!     to: PID.8
+//!   - concat:
+//!       from: [PID.5.2, PID.5.1]
+//!       separator: " "
+//!     to: ZNM.1
+//!   - for_each: OBX
+//!     from: OBX.3.2
+//!     to: OBX.3.1
+//! "#).unwrap();
+//!
+//! let message = Message::parse(
+//!     "MSH|^~\\&|||||||ORU^R01|1|P|2.3\r\
+//!      PID|1||123||Doe^John||19800101|M\r\
+//!      ZPI|secret\r\
+//!      OBX|1|NM|1^HR||72||||||F\r\
+//!      OBX|2|NM|2^BP||120||||||W\r\
+//!      OBX|3|NM|3^RR||16||||||F",
+//! ).unwrap();
+//!
+//! let output = spec.transform(&message).unwrap();
+//! assert_eq!(
+//!     output.render_with_segment_separators("\n").to_string(),
+//!     "MSH|^~\\&|||||||ORU^R01|1|P|2.5.1\n\
+//!      PID|1||123||Doe^John||19800101|male\n\
+//!      OBX|1|NM|HR^HR||72||||||F\n\
+//!      OBX|3|NM|RR^RR||16||||||F\n\
+//!      ZNM|John Doe"
+//! );
+//! ```
+
+use crate::{
+    builder::{MessageBuilder, Patch, PatchError, SegmentBuilder},
+    query::LocationQuery,
+    Message,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// A description of how to transform a message into a new message
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Spec {
+    /// What the output starts as
+    #[serde(default)]
+    pub base: Base,
+    /// Rules for dropping, keeping, renaming, and copying segments, applied in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub segments: Vec<SegmentRule>,
+    /// Rules for setting values in the output, applied in order after the segment rules
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<Rule>,
+}
+
+/// What the output of a transformation starts as
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Base {
+    /// A copy of the source message
+    #[default]
+    Copy,
+    /// A message with no segments, which segment rules can copy segments into
+    Empty,
+}
+
+/// A rule for dropping, keeping, renaming, or copying segments
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "raw::SegmentRule", into = "raw::SegmentRule")]
+pub struct SegmentRule {
+    pub action: SegmentAction,
+    /// Conditions which must all be true of a segment in the source message for the rule to
+    /// apply to it
+    pub when: Vec<Condition>,
+}
+
+/// What a [`SegmentRule`] does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SegmentAction {
+    /// Remove the segments with the given name from the output
+    Drop(String),
+    /// Remove every segment from the output which doesn't have one of the given names
+    Keep(Vec<String>),
+    /// Rename the segments with the given name
+    Rename { from: String, to: String },
+    /// Add every segment with the given name from the source message to the end of the output
+    Copy(String),
+}
+
+/// A condition on a value in the source message.
+///
+/// With `equals`, the value must be equal to the given value. With `any_of`, the value must be
+/// one of the given values. With neither, the value must be present and not empty.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Condition {
+    #[serde(with = "crate::query::serde_string")]
+    pub query: LocationQuery,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equals: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub any_of: Vec<String>,
+    /// Invert the condition
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub negate: bool,
+}
+
+/// A rule which takes a value from the source message and writes it to the output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "raw::Rule", into = "raw::Rule")]
+pub struct Rule {
+    pub source: Source,
+    pub target: Target,
+    /// A table of values to replace the source value with
+    pub map: BTreeMap<String, String>,
+    /// The value to use if the source value is missing, empty, or isn't in the `map`
+    pub default: Option<String>,
+    /// Apply the rule once for each segment in the output which came from a segment in the
+    /// source message with the given name
+    pub for_each: Option<String>,
+    /// Conditions which must all be true for the rule to apply
+    pub when: Vec<Condition>,
+}
+
+/// Where a [`Rule`] takes its value from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// The value at a query in the source message. Rules are skipped if the value is missing or
+    /// empty (and they don't have a default).
+    From(LocationQuery),
+    /// The values at several queries in the source message, joined together
+    Concat(Concat),
+    /// A constant value. An empty value clears the target.
+    Value(String),
+}
+
+/// The values at several queries, joined with a separator. Missing and empty values are
+/// skipped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Concat {
+    #[serde(with = "crate::query::serde_strings")]
+    pub from: Vec<LocationQuery>,
+    #[serde(default)]
+    pub separator: String,
+}
+
+/// Where a [`Rule`] writes its value to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// The value at a query in the output
+    To(LocationQuery),
+    /// Split the value and write each part to a query in the output
+    Split(Split),
+}
+
+/// Split a value on a separator, writing each part to the corresponding query. The last query
+/// receives the rest of the value if there are more parts than queries.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Split {
+    pub separator: String,
+    #[serde(with = "crate::query::serde_strings")]
+    pub to: Vec<LocationQuery>,
+}
+
+/// Errors that can occur when reading or applying a transformation spec
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+pub enum TransformError {
+    /// The spec couldn't be parsed from YAML or JSON
+    #[error("Invalid transform spec: {0}")]
+    InvalidSpec(String),
+
+    /// A rule can't be applied, such as a split without any targets
+    #[error("Invalid rule: {0}")]
+    InvalidRule(String),
+
+    /// A value couldn't be written to the output
+    #[error(transparent)]
+    Patch(#[from] PatchError),
+}
+
+impl Spec {
+    /// Create a spec which copies the source message as it is
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a spec from YAML
+    pub fn from_yaml(spec: &str) -> Result<Self, TransformError> {
+        serde_norway::from_str(spec).map_err(|e| TransformError::InvalidSpec(e.to_string()))
+    }
+
+    /// Parse a spec from JSON
+    pub fn from_json(spec: &str) -> Result<Self, TransformError> {
+        serde_json::from_str(spec).map_err(|e| TransformError::InvalidSpec(e.to_string()))
+    }
+
+    /// Set what the output starts as
+    pub fn with_base(mut self, base: Base) -> Self {
+        self.base = base;
+        self
+    }
+
+    /// Add a segment rule to the end of the spec
+    pub fn with_segment_rule(mut self, rule: SegmentRule) -> Self {
+        self.segments.push(rule);
+        self
+    }
+
+    /// Add a rule to the end of the spec
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Apply the spec to a message, returning a builder for the transformed message which uses
+    /// the source message's separators
+    pub fn transform(&self, message: &Message) -> Result<MessageBuilder, TransformError> {
+        let source: Vec<(Occurrence, SegmentBuilder)> = MessageBuilder::from(message)
+            .segments()
+            .iter()
+            .zip(occurrences(message.segments().map(|s| s.name)))
+            .map(|(segment, occurrence)| (occurrence, segment.clone()))
+            .collect();
+
+        let mut output = match self.base {
+            Base::Copy => source.clone(),
+            Base::Empty => Vec::new(),
+        };
+        for rule in &self.segments {
+            let applies = |occurrence: &Occurrence| {
+                rule.when
+                    .iter()
+                    .all(|condition| condition.matches(message, Some(occurrence)))
+            };
+            match &rule.action {
+                SegmentAction::Drop(name) => output
+                    .retain(|(occurrence, segment)| &segment.name != name || !applies(occurrence)),
+                SegmentAction::Keep(names) => output.retain(|(occurrence, segment)| {
+                    names.contains(&segment.name) || !applies(occurrence)
+                }),
+                SegmentAction::Rename { from, to } => {
+                    for (occurrence, segment) in output.iter_mut() {
+                        if &segment.name == from && applies(occurrence) {
+                            segment.name.clone_from(to);
+                        }
+                    }
+                }
+                SegmentAction::Copy(name) => output.extend(
+                    source
+                        .iter()
+                        .filter(|(occurrence, _)| &occurrence.name == name && applies(occurrence))
+                        .cloned(),
+                ),
+            }
+        }
+
+        // each segment of the output, along with the segment of the source message it came from
+        let segments: Vec<(Occurrence, Occurrence)> = output
+            .iter()
+            .map(|(occurrence, _)| occurrence.clone())
+            .zip(occurrences(output.iter().map(|(_, s)| s.name.as_str())))
+            .collect();
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for (_, target) in &segments {
+            *counts.entry(target.name.clone()).or_default() += 1;
+        }
+        let mut builder = MessageBuilder::new(message.separators);
+        *builder.segments_mut() = output.into_iter().map(|(_, segment)| segment).collect();
+
+        let mut patch = Patch::new();
+        for rule in &self.rules {
+            let contexts: Vec<Option<&(Occurrence, Occurrence)>> = match &rule.for_each {
+                Some(name) => segments
+                    .iter()
+                    .filter(|(source, _)| &source.name == name)
+                    .map(Some)
+                    .collect(),
+                None => vec![None],
+            };
+            for context in contexts {
+                let source = context.map(|(source, _)| source);
+                if !rule.when.iter().all(|c| c.matches(message, source)) {
+                    continue;
+                }
+                let Some(value) = rule.value(message, source) else {
+                    continue;
+                };
+                for (query, value) in rule.target.values(&value)? {
+                    let query = match context {
+                        Some((source, target)) => target.resolve_output(source, query),
+                        None => query.clone(),
+                    };
+                    let count = counts.entry(query.segment.clone()).or_default();
+                    while *count < query.segment_index.unwrap_or(1) {
+                        *count += 1;
+                        patch = patch.with_insert(
+                            LocationQuery {
+                                segment: query.segment.clone(),
+                                segment_index: Some(*count),
+                                field: None,
+                                repeat: None,
+                                component: None,
+                                subcomponent: None,
+                            },
+                            &query.segment,
+                        );
+                    }
+                    patch = patch.with_set(query, value);
+                }
+            }
+        }
+        patch.apply(&mut builder)?;
+        Ok(builder)
+    }
+}
+
+impl SegmentRule {
+    /// Create a segment rule which applies to every segment
+    pub fn new(action: SegmentAction) -> Self {
+        SegmentRule {
+            action,
+            when: Vec::new(),
+        }
+    }
+
+    /// Only apply the rule to segments for which the condition is true
+    pub fn when(mut self, condition: Condition) -> Self {
+        self.when.push(condition);
+        self
+    }
+}
+
+impl Condition {
+    /// A condition that the value at the query is present and not empty
+    pub fn exists(query: LocationQuery) -> Self {
+        Condition {
+            query,
+            equals: None,
+            any_of: Vec::new(),
+            negate: false,
+        }
+    }
+
+    /// A condition that the value at the query is equal to the given value
+    pub fn equals<S: ToString>(query: LocationQuery, value: S) -> Self {
+        Condition {
+            equals: Some(value.to_string()),
+            ..Self::exists(query)
+        }
+    }
+
+    /// A condition that the value at the query is one of the given values
+    pub fn any_of<S: ToString>(query: LocationQuery, values: impl IntoIterator<Item = S>) -> Self {
+        Condition {
+            any_of: values.into_iter().map(|v| v.to_string()).collect(),
+            ..Self::exists(query)
+        }
+    }
+
+    /// Invert the condition
+    pub fn negate(mut self) -> Self {
+        self.negate = !self.negate;
+        self
+    }
+
+    /// Check the condition against a message, within a segment if there is one
+    fn matches(&self, message: &Message, segment: Option<&Occurrence>) -> bool {
+        let value = get(message, &self.query, segment).unwrap_or_default();
+        let matches = match &self.equals {
+            Some(expected) => &value == expected,
+            None if !self.any_of.is_empty() => self.any_of.contains(&value),
+            None => !value.is_empty(),
+        };
+        matches != self.negate
+    }
+}
+
+impl Rule {
+    /// Create a rule which writes a value to the target
+    pub fn new(source: Source, target: Target) -> Self {
+        Rule {
+            source,
+            target,
+            map: BTreeMap::new(),
+            default: None,
+            for_each: None,
+            when: Vec::new(),
+        }
+    }
+
+    /// Create a rule which copies the value at one query to another
+    pub fn copy(from: LocationQuery, to: LocationQuery) -> Self {
+        Self::new(Source::From(from), Target::To(to))
+    }
+
+    /// Replace the source value `from` with `to`
+    pub fn with_map<F: ToString, T: ToString>(mut self, from: F, to: T) -> Self {
+        self.map.insert(from.to_string(), to.to_string());
+        self
+    }
+
+    /// Use the given value if the source value is missing, empty, or isn't in the map
+    pub fn with_default<S: ToString>(mut self, value: S) -> Self {
+        self.default = Some(value.to_string());
+        self
+    }
+
+    /// Apply the rule once for each segment that came from a segment in the source message
+    /// with the given name
+    pub fn for_each<S: ToString>(mut self, segment: S) -> Self {
+        self.for_each = Some(segment.to_string());
+        self
+    }
+
+    /// Only apply the rule if the condition is true
+    pub fn when(mut self, condition: Condition) -> Self {
+        self.when.push(condition);
+        self
+    }
+
+    /// The value to write, or `None` if the rule should be skipped
+    fn value(&self, message: &Message, segment: Option<&Occurrence>) -> Option<String> {
+        let value = match &self.source {
+            Source::From(query) => get(message, query, segment),
+            Source::Concat(concat) => {
+                let parts: Vec<String> = concat
+                    .from
+                    .iter()
+                    .filter_map(|query| get(message, query, segment))
+                    .filter(|part| !part.is_empty())
+                    .collect();
+                Some(parts.join(&concat.separator))
+            }
+            Source::Value(value) => return Some(value.clone()),
+        };
+        match value.filter(|value| !value.is_empty()) {
+            Some(value) if self.map.is_empty() => Some(value),
+            Some(value) => self
+                .map
+                .get(&value)
+                .or(self.default.as_ref())
+                .cloned()
+                .or(Some(value)),
+            None => self.default.clone(),
+        }
+    }
+}
+
+impl Target {
+    /// The queries to write to, along with the value to write to each of them
+    fn values<'t>(
+        &'t self,
+        value: &str,
+    ) -> Result<Vec<(&'t LocationQuery, String)>, TransformError> {
+        match self {
+            Target::To(query) => Ok(vec![(query, value.to_string())]),
+            Target::Split(split) => {
+                if split.to.is_empty() || split.separator.is_empty() {
+                    return Err(TransformError::InvalidRule(
+                        "a split needs a separator and at least one target".to_string(),
+                    ));
+                }
+                Ok(split
+                    .to
+                    .iter()
+                    .zip(value.splitn(split.to.len(), split.separator.as_str()))
+                    .map(|(query, part)| (query, part.to_string()))
+                    .collect())
+            }
+        }
+    }
+}
+
+/// The forms of rules which are read and written by hand, with one key for each kind of source,
+/// target, or action
+mod raw {
+    use super::{Concat, Condition, SegmentAction, Source, Split, Target, TransformError};
+    use crate::query::LocationQuery;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct Rename {
+        from: String,
+        to: String,
+    }
+
+    #[derive(Default, Serialize, Deserialize)]
+    pub(super) struct SegmentRule {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        drop: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        keep: Option<Vec<String>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        rename: Option<Rename>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        copy: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        when: Vec<Condition>,
+    }
+
+    #[derive(Default, Serialize, Deserialize)]
+    pub(super) struct Rule {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        from: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        concat: Option<Concat>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        value: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        to: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        split: Option<Split>,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        map: BTreeMap<String, String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        default: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        for_each: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        when: Vec<Condition>,
+    }
+
+    fn query(query: &str) -> Result<LocationQuery, TransformError> {
+        LocationQuery::parse(query).map_err(|e| TransformError::InvalidRule(e.to_string()))
+    }
+
+    impl TryFrom<SegmentRule> for super::SegmentRule {
+        type Error = TransformError;
+
+        fn try_from(rule: SegmentRule) -> Result<Self, Self::Error> {
+            let action = match (rule.drop, rule.keep, rule.rename, rule.copy) {
+                (Some(name), None, None, None) => SegmentAction::Drop(name),
+                (None, Some(names), None, None) => SegmentAction::Keep(names),
+                (None, None, Some(Rename { from, to }), None) => SegmentAction::Rename { from, to },
+                (None, None, None, Some(name)) => SegmentAction::Copy(name),
+                _ => {
+                    return Err(TransformError::InvalidRule(
+                        "a segment rule needs exactly one of `drop`, `keep`, `rename`, or `copy`"
+                            .to_string(),
+                    ))
+                }
+            };
+            Ok(super::SegmentRule {
+                action,
+                when: rule.when,
+            })
+        }
+    }
+
+    impl From<super::SegmentRule> for SegmentRule {
+        fn from(rule: super::SegmentRule) -> Self {
+            let mut raw = SegmentRule {
+                when: rule.when,
+                ..Default::default()
+            };
+            match rule.action {
+                SegmentAction::Drop(name) => raw.drop = Some(name),
+                SegmentAction::Keep(names) => raw.keep = Some(names),
+                SegmentAction::Rename { from, to } => raw.rename = Some(Rename { from, to }),
+                SegmentAction::Copy(name) => raw.copy = Some(name),
+            }
+            raw
+        }
+    }
+
+    impl TryFrom<Rule> for super::Rule {
+        type Error = TransformError;
+
+        fn try_from(rule: Rule) -> Result<Self, Self::Error> {
+            let source = match (rule.from, rule.concat, rule.value) {
+                (Some(from), None, None) => Source::From(query(&from)?),
+                (None, Some(concat), None) => Source::Concat(concat),
+                (None, None, Some(value)) => Source::Value(value),
+                _ => {
+                    return Err(TransformError::InvalidRule(
+                        "a rule needs exactly one of `from`, `concat`, or `value`".to_string(),
+                    ))
+                }
+            };
+            let target = match (rule.to, rule.split) {
+                (Some(to), None) => Target::To(query(&to)?),
+                (None, Some(split)) => Target::Split(split),
+                _ => {
+                    return Err(TransformError::InvalidRule(
+                        "a rule needs exactly one of `to` or `split`".to_string(),
+                    ))
+                }
+            };
+            Ok(super::Rule {
+                source,
+                target,
+                map: rule.map,
+                default: rule.default,
+                for_each: rule.for_each,
+                when: rule.when,
+            })
+        }
+    }
+
+    impl From<super::Rule> for Rule {
+        fn from(rule: super::Rule) -> Self {
+            let mut raw = Rule {
+                map: rule.map,
+                default: rule.default,
+                for_each: rule.for_each,
+                when: rule.when,
+                ..Default::default()
+            };
+            match rule.source {
+                Source::From(query) => raw.from = Some(query.to_string()),
+                Source::Concat(concat) => raw.concat = Some(concat),
+                Source::Value(value) => raw.value = Some(value),
+            }
+            match rule.target {
+                Target::To(query) => raw.to = Some(query.to_string()),
+                Target::Split(split) => raw.split = Some(split),
+            }
+            raw
+        }
+    }
+}
+
+/// A segment within a message, identified by its name and its (1-based) index among the
+/// segments with that name
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Occurrence {
+    name: String,
+    index: usize,
+}
+
+impl Occurrence {
+    /// Make a query for this segment's name without a segment index refer to this segment
+    fn resolve(&self, query: &LocationQuery) -> LocationQuery {
+        let mut query = query.clone();
+        if query.segment == self.name && query.segment_index.is_none() {
+            query.segment_index = Some(self.index);
+        }
+        query
+    }
+
+    /// Make a query for this output segment's name, or the name of the source segment it came
+    /// from, without a segment index refer to this segment
+    fn resolve_output(&self, source: &Occurrence, query: &LocationQuery) -> LocationQuery {
+        let mut query = query.clone();
+        if query.segment_index.is_none()
+            && (query.segment == self.name || query.segment == source.name)
+        {
+            query.segment.clone_from(&self.name);
+            query.segment_index = Some(self.index);
+        }
+        query
+    }
+}
+
+/// The occurrence of each of a list of segment names
+fn occurrences<'n>(names: impl Iterator<Item = &'n str>) -> Vec<Occurrence> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    names
+        .map(|name| {
+            let index = counts.entry(name).or_default();
+            *index += 1;
+            Occurrence {
+                name: name.to_string(),
+                index: *index,
+            }
+        })
+        .collect()
+}
+
+/// The raw value at a query in a message, within a segment if there is one
+fn get(message: &Message, query: &LocationQuery, segment: Option<&Occurrence>) -> Option<String> {
+    let query = match segment {
+        Some(segment) => segment.resolve(query),
+        None => query.clone(),
+    };
+    message
+        .query(query)
+        .map(|result| result.raw_value().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    const MESSAGE: &str = "MSH|^~\\&|APP|FAC|||20240102030405||ORU^R01|1|P|2.3\r\
+        PID|1||123^^^MRN~456^^^SSN||Doe^John^Q||19800101|M|||1 Main St^^Town^ON\r\
+        ZPI|secret\r\
+        OBR|1|||CBC\r\
+        OBX|1|NM|HGB^Hemoglobin||140|g/L|||||F\r\
+        OBX|2|TX|NOTE^Note||Looks good||||||F\r\
+        NTE|1||A comment";
+
+    fn query(query: &str) -> LocationQuery {
+        LocationQuery::parse(query).unwrap()
+    }
+
+    fn transform(spec: &Spec) -> String {
+        let message = Message::parse(MESSAGE).unwrap();
+        spec.transform(&message)
+            .unwrap()
+            .render_with_segment_separators("\n")
+            .to_string()
+    }
+
+    #[test]
+    fn copies_the_message_without_rules() {
+        assert_eq!(transform(&Spec::new()), MESSAGE.replace('\r', "\n"));
+    }
+
+    #[test]
+    fn filters_renames_and_copies_segments() {
+        let spec = Spec::new()
+            .with_base(Base::Empty)
+            .with_segment_rule(SegmentRule::new(SegmentAction::Copy("MSH".to_string())))
+            .with_segment_rule(SegmentRule::new(SegmentAction::Copy("PID".to_string())))
+            .with_segment_rule(
+                SegmentRule::new(SegmentAction::Copy("OBX".to_string()))
+                    .when(Condition::equals(query("OBX.2"), "NM")),
+            )
+            .with_segment_rule(SegmentRule::new(SegmentAction::Rename {
+                from: "OBX".to_string(),
+                to: "ZBX".to_string(),
+            }));
+        let output = transform(&spec);
+        let names: Vec<&str> = output.lines().map(|l| &l[..3]).collect();
+        assert_eq!(names, vec!["MSH", "PID", "ZBX"]);
+        assert!(output.ends_with("ZBX|1|NM|HGB^Hemoglobin||140|g/L|||||F"));
+
+        let spec = Spec::new()
+            .with_segment_rule(SegmentRule::new(SegmentAction::Drop("ZPI".to_string())))
+            .with_segment_rule(
+                SegmentRule::new(SegmentAction::Keep(vec![
+                    "MSH".to_string(),
+                    "PID".to_string(),
+                ]))
+                .when(Condition::equals(query("OBX.2"), "TX").negate()),
+            );
+        let output = transform(&spec);
+        let names: Vec<&str> = output.lines().map(|l| &l[..3]).collect();
+        // conditions on segments without the queried field are checked against the first OBX
+        assert_eq!(names, vec!["MSH", "PID", "OBX"]);
+    }
+
+    #[test]
+    fn maps_concatenates_and_splits_values() {
+        let spec = Spec::new()
+            .with_rule(
+                Rule::copy(query("PID.8"), query("PID.8"))
+                    .with_map("M", "male")
+                    .with_map("F", "female")
+                    .with_default("unknown"),
+            )
+            .with_rule(Rule::copy(query("PID.99"), query("PID.10")).with_default("U"))
+            .with_rule(Rule::new(
+                Source::Concat(Concat {
+                    from: vec![query("PID.5.2"), query("PID.5.4"), query("PID.5.1")],
+                    separator: " ".to_string(),
+                }),
+                Target::To(query("NK1.2")),
+            ))
+            .with_rule(Rule::new(
+                Source::From(query("PID.11.1")),
+                Target::Split(Split {
+                    separator: " ".to_string(),
+                    to: vec![query("ZAD.1"), query("ZAD.2")],
+                }),
+            ))
+            .with_rule(Rule::new(
+                Source::Value(String::new()),
+                Target::To(query("PID.3[2]")),
+            ));
+        let output = transform(&spec);
+        let message = Message::parse_with_lenient_newlines(&output, true).unwrap();
+        let value = |q: &str| message.query(q).unwrap().raw_value().to_string();
+        assert_eq!(value("PID.8"), "male");
+        assert_eq!(value("PID.10"), "U");
+        assert_eq!(value("PID.3"), "123^^^MRN~");
+        assert_eq!(value("NK1.2"), "John Doe");
+        assert_eq!(value("ZAD.1"), "1");
+        assert_eq!(value("ZAD.2"), "Main St");
+    }
+
+    #[test]
+    fn applies_rules_for_each_segment() {
+        let spec = Spec::new()
+            .with_segment_rule(SegmentRule::new(SegmentAction::Rename {
+                from: "OBX".to_string(),
+                to: "ZBX".to_string(),
+            }))
+            .with_rule(
+                Rule::copy(query("OBX.3.2"), query("OBX.3"))
+                    .for_each("OBX")
+                    .when(Condition::any_of(query("OBX.2"), ["NM", "SN"])),
+            )
+            .with_rule(Rule::copy(query("OBX.1"), query("ZBX.4")).for_each("OBX"))
+            .with_rule(
+                Rule::copy(query("NTE.3"), query("NTE[2].3"))
+                    .when(Condition::exists(query("NTE.3"))),
+            );
+        let output = transform(&spec);
+        assert!(output.contains("\nZBX|1|NM|Hemoglobin|1|140|g/L|||||F\n"));
+        assert!(output.contains("\nZBX|2|TX|NOTE^Note|2|Looks good||||||F\n"));
+        assert!(output.ends_with("\nNTE|1||A comment\nNTE|||A comment"));
+    }
+
+    #[test]
+    fn reads_specs_from_yaml_and_json() {
+        let yaml = Spec::from_yaml(
+            r#"
+base: empty
+segments:
+  - copy: PID
+    when:
+      - query: PID.8
+        any_of: [M, F]
+rules:
+  - from: PID.5
+    to: PID.5
+    when:
+      - query: PID.5.1
+        equals: Doe
+        negate: true
+  - value: 2.5
+    to: PID.1
+  - concat:
+      from: [PID.5.2, PID.5.1]
+    split:
+      separator: o
+      to: [PID.2, PID.4]
+"#,
+        )
+        .unwrap();
+        let json = Spec::from_json(&serde_json::to_string(&yaml).unwrap()).unwrap();
+        assert_eq!(yaml, json);
+        assert_eq!(
+            yaml,
+            Spec::new()
+                .with_base(Base::Empty)
+                .with_segment_rule(
+                    SegmentRule::new(SegmentAction::Copy("PID".to_string()))
+                        .when(Condition::any_of(query("PID.8"), ["M", "F"]))
+                )
+                .with_rule(
+                    Rule::copy(query("PID.5"), query("PID.5"))
+                        .when(Condition::equals(query("PID.5.1"), "Doe").negate())
+                )
+                .with_rule(Rule::new(
+                    Source::Value("2.5".to_string()),
+                    Target::To(query("PID.1"))
+                ))
+                .with_rule(Rule::new(
+                    Source::Concat(Concat {
+                        from: vec![query("PID.5.2"), query("PID.5.1")],
+                        separator: String::new(),
+                    }),
+                    Target::Split(Split {
+                        separator: "o".to_string(),
+                        to: vec![query("PID.2"), query("PID.4")],
+                    })
+                ))
+        );
+        assert_eq!(
+            transform(&yaml),
+            "PID|2.5|J|123^^^MRN~456^^^SSN|hnDoe|Doe^John^Q||19800101|M|||1 Main St^^Town^ON"
+        );
+
+        let error = Spec::from_yaml("rules:\n  - from: PID.5\n").unwrap_err();
+        assert!(matches!(error, TransformError::InvalidSpec(_)), "{error}");
+        let error = Spec::from_json(r#"{"rules": [{"from": "PID.five", "to": "PID.5"}]}"#);
+        assert!(matches!(error, Err(TransformError::InvalidSpec(_))));
+    }
+
+    #[test]
+    fn reports_invalid_rules() {
+        let message = Message::parse(MESSAGE).unwrap();
+        let spec = Spec::new().with_rule(Rule::new(
+            Source::Value("x".to_string()),
+            Target::Split(Split {
+                separator: String::new(),
+                to: vec![query("PID.2")],
+            }),
+        ));
+        assert!(matches!(
+            spec.transform(&message),
+            Err(TransformError::InvalidRule(_))
+        ));
+
+        let spec = Spec::new().with_rule(Rule::new(
+            Source::Value("x".to_string()),
+            Target::To(query("MSH.2")),
+        ));
+        assert!(matches!(
+            spec.transform(&message),
+            Err(TransformError::Patch(PatchError::EncodingCharacters(_)))
+        ));
+    }
+}
@@ -0,0 +1,94 @@
+use crate::message::{Component, Repeat, Separators};
+use std::collections::HashMap;
+
+/// A coded value, as found in HL7 `CE`, `CWE`, and `CNE` values, such as
+/// `6690-2^Leukocytes^LN`. Values are decoded, and empty components are `None`.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::{message::Separators, types::CodedElement};
+///
+/// let repeat = hl7_parser::parser::parse_repeat("POS^Positive^HL70078^P^Pos \\T\\ reactive^L").unwrap();
+/// let coded = CodedElement::from_repeat(&repeat, &Separators::default());
+/// assert_eq!(coded.identifier.as_deref(), Some("POS"));
+/// assert_eq!(coded.coding_system.as_deref(), Some("HL70078"));
+/// assert_eq!(coded.alternate_text.as_deref(), Some("Pos & reactive"));
+/// assert_eq!(coded.original_text, None);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CodedElement {
+    /// The code (`CWE.1`)
+    pub identifier: Option<String>,
+    /// The display text of the code (`CWE.2`)
+    pub text: Option<String>,
+    /// The coding system the code is from, such as `LN` for LOINC (`CWE.3`)
+    pub coding_system: Option<String>,
+    /// An alternate code (`CWE.4`)
+    pub alternate_identifier: Option<String>,
+    /// The display text of the alternate code (`CWE.5`)
+    pub alternate_text: Option<String>,
+    /// The coding system the alternate code is from (`CWE.6`)
+    pub alternate_coding_system: Option<String>,
+    /// The original text the value was coded from, only present in `CWE` and `CNE` values
+    /// (`CWE.9`)
+    pub original_text: Option<String>,
+}
+
+impl CodedElement {
+    /// Read a coded value from the components of a field repeat
+    pub fn from_repeat(repeat: &Repeat, separators: &Separators) -> CodedElement {
+        Self::from_parts(|n| component_text(repeat, n, separators))
+    }
+
+    /// Read a coded value from the subcomponents of a component, such as the units of a
+    /// quantity (`CQ.2`)
+    pub fn from_component(component: &Component, separators: &Separators) -> CodedElement {
+        Self::from_parts(|n| {
+            component
+                .subcomponent(n)
+                .map(|s| s.raw_value())
+                .filter(|v| !v.is_empty())
+                .map(|v| separators.decode(v).to_string())
+        })
+    }
+
+    fn from_parts(part: impl Fn(usize) -> Option<String>) -> CodedElement {
+        CodedElement {
+            identifier: part(1),
+            text: part(2),
+            coding_system: part(3),
+            alternate_identifier: part(4),
+            alternate_text: part(5),
+            alternate_coding_system: part(6),
+            original_text: part(9),
+        }
+    }
+
+    /// The non-empty parts of the value, by their 1-based position
+    pub(crate) fn parts(&self) -> HashMap<usize, String> {
+        [
+            (1, &self.identifier),
+            (2, &self.text),
+            (3, &self.coding_system),
+            (4, &self.alternate_identifier),
+            (5, &self.alternate_text),
+            (6, &self.alternate_coding_system),
+            (9, &self.original_text),
+        ]
+        .into_iter()
+        .filter_map(|(n, part)| part.clone().map(|part| (n, part)))
+        .collect()
+    }
+}
+
+/// The decoded value of the first subcomponent of a component, or `None` if it's empty
+pub(crate) fn component_text(repeat: &Repeat, n: usize, separators: &Separators) -> Option<String> {
+    repeat
+        .component(n)
+        .and_then(|c| c.subcomponent(1))
+        .map(|s| s.raw_value())
+        .filter(|v| !v.is_empty())
+        .map(|v| separators.decode(v).to_string())
+}
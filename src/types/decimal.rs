@@ -0,0 +1,314 @@
+use std::{cmp::Ordering, fmt::Display, hash::Hash, str::FromStr};
+
+/// The maximum number of significant digits that can be stored in a [`Decimal`]
+const MAX_DIGITS: usize = 38;
+
+/// An exact decimal number, as found in HL7 `NM` values. The value is stored as an integer
+/// mantissa and a base-10 scale, so `"12.50"` is stored as `1250` with a scale of `2` and is
+/// displayed exactly as `12.50` again. Unlike `f64`, no precision is lost when parsing or
+/// displaying a value.
+///
+/// Equality and ordering are numeric, i.e. `1.5 == 1.50`.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::types::Decimal;
+/// let value: Decimal = "+012.50".parse().unwrap();
+/// assert_eq!(value.mantissa(), 1250);
+/// assert_eq!(value.scale(), 2);
+/// assert_eq!(value.to_string(), "12.50");
+/// assert_eq!(value, "12.5".parse().unwrap());
+/// ```
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+/// Errors that can result from parsing a decimal number
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum DecimalParseError {
+    #[error("Decimal value is empty")]
+    Empty,
+    #[error("Unexpected character '{1}' in decimal at position {0}")]
+    UnexpectedCharacter(usize, char),
+    #[error("Decimal value has more than {MAX_DIGITS} digits")]
+    TooManyDigits,
+}
+
+impl Decimal {
+    /// Create a decimal from a mantissa and scale, i.e. `Decimal::new(1250, 2)` is `12.50`
+    pub const fn new(mantissa: i128, scale: u32) -> Self {
+        Decimal { mantissa, scale }
+    }
+
+    /// The integer mantissa of the decimal, i.e. `1250` for `12.50`
+    pub const fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    /// The number of digits after the decimal point, i.e. `2` for `12.50`
+    pub const fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Returns true if the value is less than zero
+    pub const fn is_negative(&self) -> bool {
+        self.mantissa < 0
+    }
+
+    /// Returns true if the value has no fractional part, i.e. `12.00`
+    pub fn is_integer(&self) -> bool {
+        self.normalized().scale == 0
+    }
+
+    /// Remove any trailing zeros after the decimal point, i.e. `12.50` becomes `12.5`
+    pub fn normalized(&self) -> Self {
+        let mut value = *self;
+        while value.scale > 0 && value.mantissa % 10 == 0 {
+            value.mantissa /= 10;
+            value.scale -= 1;
+        }
+        value
+    }
+
+    /// Convert the value to an integer, if it has no fractional part and fits in an `i64`
+    pub fn to_i64(&self) -> Option<i64> {
+        let value = self.normalized();
+        if value.scale != 0 {
+            return None;
+        }
+        i64::try_from(value.mantissa).ok()
+    }
+
+    /// Convert the value to the nearest `f64`. Note that this may lose precision.
+    pub fn to_f64(&self) -> f64 {
+        // parsing the exact representation gives the correctly rounded result
+        self.to_string()
+            .parse()
+            .expect("decimal display is a valid float")
+    }
+}
+
+/// Parse a decimal number. Leading and trailing whitespace, a leading `+`, leading zeros, and a
+/// trailing decimal point are all accepted, i.e. `" +007. "` parses as `7`.
+impl FromStr for Decimal {
+    type Err = DecimalParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let leading_whitespace = s.len() - s.trim_start().len();
+        let trimmed = s.trim();
+        if trimmed.is_empty() {
+            return Err(DecimalParseError::Empty);
+        }
+
+        let mut negative = false;
+        let mut mantissa: i128 = 0;
+        let mut scale: u32 = 0;
+        let mut digits = 0usize;
+        let mut significant_digits = 0usize;
+        let mut seen_point = false;
+        for (i, c) in trimmed.char_indices() {
+            match c {
+                '+' | '-' if i == 0 => negative = c == '-',
+                '.' if !seen_point => seen_point = true,
+                '0'..='9' => {
+                    digits += 1;
+                    if mantissa != 0 || c != '0' {
+                        significant_digits += 1;
+                    }
+                    if significant_digits > MAX_DIGITS {
+                        return Err(DecimalParseError::TooManyDigits);
+                    }
+                    mantissa = mantissa * 10 + (c as u8 - b'0') as i128;
+                    if seen_point {
+                        scale += 1;
+                    }
+                }
+                c => {
+                    return Err(DecimalParseError::UnexpectedCharacter(
+                        leading_whitespace + i,
+                        c,
+                    ))
+                }
+            }
+        }
+        if digits == 0 {
+            return Err(DecimalParseError::Empty);
+        }
+
+        Ok(Decimal {
+            mantissa: if negative { -mantissa } else { mantissa },
+            scale,
+        })
+    }
+}
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        if self.is_negative() {
+            write!(f, "-")?;
+        }
+        if scale == 0 {
+            return write!(f, "{digits}");
+        }
+        if digits.len() > scale {
+            let (integer, fraction) = digits.split_at(digits.len() - scale);
+            write!(f, "{integer}.{fraction}")
+        } else {
+            write!(f, "0.{digits:0>scale$}")
+        }
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let a = self.normalized();
+        let b = other.normalized();
+        let (scaled, fixed, flipped) = match a.scale.cmp(&b.scale) {
+            Ordering::Equal => return a.mantissa.cmp(&b.mantissa),
+            Ordering::Less => (a, b, false),
+            Ordering::Greater => (b, a, true),
+        };
+        if scaled.mantissa == 0 {
+            let ordering = 0.cmp(&fixed.mantissa);
+            return if flipped {
+                ordering.reverse()
+            } else {
+                ordering
+            };
+        }
+        let ordering = match 10i128
+            .checked_pow(fixed.scale - scaled.scale)
+            .and_then(|factor| scaled.mantissa.checked_mul(factor))
+        {
+            Some(mantissa) => mantissa.cmp(&fixed.mantissa),
+            // the rescaled value doesn't fit, so it must be larger in magnitude
+            None if scaled.is_negative() => Ordering::Less,
+            None => Ordering::Greater,
+        };
+        if flipped {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl Hash for Decimal {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let value = self.normalized();
+        value.mantissa.hash(state);
+        value.scale.hash(state);
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Self {
+        Decimal::new(value as i128, 0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Decimal {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Decimal {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    #[test]
+    fn can_parse_decimals() {
+        let value: Decimal = "12.50".parse().unwrap();
+        assert_eq!((value.mantissa(), value.scale()), (1250, 2));
+
+        let value: Decimal = " +007. ".parse().unwrap();
+        assert_eq!((value.mantissa(), value.scale()), (7, 0));
+
+        let value: Decimal = "-.5".parse().unwrap();
+        assert_eq!((value.mantissa(), value.scale()), (-5, 1));
+
+        let value: Decimal = "0.000000000000000000000000000000000000000001"
+            .parse()
+            .unwrap();
+        assert_eq!((value.mantissa(), value.scale()), (1, 42));
+    }
+
+    #[test]
+    fn cant_parse_invalid_decimals() {
+        assert_eq!("".parse::<Decimal>(), Err(DecimalParseError::Empty));
+        assert_eq!(" + ".parse::<Decimal>(), Err(DecimalParseError::Empty));
+        assert_eq!(
+            " 1.2.3".parse::<Decimal>(),
+            Err(DecimalParseError::UnexpectedCharacter(4, '.'))
+        );
+        assert_eq!(
+            "1e5".parse::<Decimal>(),
+            Err(DecimalParseError::UnexpectedCharacter(1, 'e'))
+        );
+        assert_eq!(
+            "1234567890123456789012345678901234567890".parse::<Decimal>(),
+            Err(DecimalParseError::TooManyDigits)
+        );
+    }
+
+    #[test]
+    fn can_display_decimals() {
+        for s in ["12.50", "0.05", "-0.05", "-12", "0", "100.000"] {
+            assert_eq!(s.parse::<Decimal>().unwrap().to_string(), s);
+        }
+        assert_eq!("+.5".parse::<Decimal>().unwrap().to_string(), "0.5");
+        assert_eq!("007.".parse::<Decimal>().unwrap().to_string(), "7");
+    }
+
+    #[test]
+    fn can_compare_decimals() {
+        let d = |s: &str| s.parse::<Decimal>().unwrap();
+        assert_eq!(d("1.5"), d("1.500"));
+        assert!(d("1.49") < d("1.5"));
+        assert!(d("-1.49") > d("-1.5"));
+        assert!(d("0.1") > d("-10"));
+        assert!(d("99999999999999999999999999999999999999") > d("0.5"));
+        assert!(d("-99999999999999999999999999999999999999") < d("0.5"));
+        assert!(d("0") < d("0.000000000000000000000000000000000000000001"));
+        assert!(d("-0.000000000000000000000000000000000000000001") < d("0.0"));
+    }
+
+    #[test]
+    fn can_convert_decimals() {
+        let d = |s: &str| s.parse::<Decimal>().unwrap();
+        assert_eq!(d("12.00").to_i64(), Some(12));
+        assert_eq!(d("12.01").to_i64(), None);
+        assert_eq!(d("12.25").to_f64(), 12.25);
+        assert!(d("-3.0").is_integer());
+    }
+}
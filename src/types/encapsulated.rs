@@ -0,0 +1,775 @@
+use super::coded::component_text;
+use crate::{
+    builder::{ComponentBuilder, FieldBuilder, RepeatBuilder},
+    message::{Component, Escape, Field, Repeat, Separators},
+};
+use std::{collections::HashMap, fmt::Display, io, str::FromStr};
+
+/// Errors that can result from decoding embedded data
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum EncapsulatedDataError {
+    #[error("Embedded data is missing its encoding")]
+    MissingEncoding,
+    #[error("Unknown data encoding '{0}', expected 'A', 'Hex', or 'Base64'")]
+    UnknownEncoding(String),
+    #[error("Invalid {0} character '{2}' at position {1}")]
+    InvalidCharacter(DataEncoding, usize, char),
+    #[error("{0} data ends part way through a byte")]
+    Truncated(DataEncoding),
+    #[error("Embedded data ends part way through an escape sequence")]
+    UnterminatedEscape,
+    #[error("Embedded data contains the escape sequence '{0}', which doesn't stand for data")]
+    UnsupportedEscape(String),
+}
+
+/// The encoding of embedded data (`ED.4`), from HL7 table 0299
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DataEncoding {
+    /// `A`: printable ASCII characters, stored as-is
+    Ascii,
+    /// `Hex`: two hexadecimal digits per byte
+    Hex,
+    /// `Base64`: base 64 encoding as described in RFC 4648
+    Base64,
+}
+
+impl DataEncoding {
+    /// The code for the encoding, i.e. `A`, `Hex`, or `Base64`
+    pub fn code(&self) -> &'static str {
+        match self {
+            DataEncoding::Ascii => "A",
+            DataEncoding::Hex => "Hex",
+            DataEncoding::Base64 => "Base64",
+        }
+    }
+}
+
+/// Parse an encoding code, ignoring case and surrounding whitespace
+impl FromStr for DataEncoding {
+    type Err = EncapsulatedDataError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "A" => Ok(DataEncoding::Ascii),
+            "HEX" => Ok(DataEncoding::Hex),
+            "BASE64" => Ok(DataEncoding::Base64),
+            "" => Err(EncapsulatedDataError::MissingEncoding),
+            _ => Err(EncapsulatedDataError::UnknownEncoding(s.to_string())),
+        }
+    }
+}
+
+impl Display for DataEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+/// Data embedded in a message, as found in HL7 `ED` values, such as a PDF report in `OBX-5`.
+/// The data is kept as it appears in the message (with escape sequences decoded), in the
+/// encoding given by `encoding`. Use [`EncapsulatedData::decode`] to get the bytes, or
+/// [`EncapsulatedDataReader`] to decode large values straight from a message without copying
+/// them first.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::{builder::FieldBuilder, message::Separators, types::EncapsulatedData};
+///
+/// let repeat = hl7_parser::parser::parse_repeat("LAB^AP^PDF^Base64^JVBERi0xLjQ=").unwrap();
+/// let ed = EncapsulatedData::from_repeat(&repeat, &Separators::default());
+/// assert_eq!(ed.type_of_data.as_deref(), Some("AP"));
+/// assert_eq!(ed.mime_type().as_deref(), Some("application/pdf"));
+/// assert_eq!(ed.decode().unwrap(), b"%PDF-1.4");
+///
+/// let ed = EncapsulatedData::from_bytes("TEXT", "", b"Hi!");
+/// let field = FieldBuilder::from(&ed);
+/// assert_eq!(field.display(&Separators::default()).to_string(), "^TEXT^^Base64^SGkh");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EncapsulatedData {
+    /// The application the data came from (`ED.1`)
+    pub source_application: Option<String>,
+    /// The type of data, from HL7 table 0191, such as `AP` (other application data) or `IM`
+    /// (image) (`ED.2`)
+    pub type_of_data: Option<String>,
+    /// The subtype of the data, from HL7 table 0291, such as `PDF` or `JPEG` (`ED.3`)
+    pub data_subtype: Option<String>,
+    /// The encoding of the data, from HL7 table 0299, i.e. `A` (ASCII), `Hex`, or `Base64`
+    /// (`ED.4`)
+    pub encoding: Option<String>,
+    /// The encoded data (`ED.5`)
+    pub data: String,
+}
+
+impl EncapsulatedData {
+    /// Read embedded data from the components of a field repeat
+    pub fn from_repeat(repeat: &Repeat, separators: &Separators) -> EncapsulatedData {
+        let component = |n| component_text(repeat, n, separators);
+        EncapsulatedData {
+            source_application: component(1),
+            type_of_data: component(2),
+            data_subtype: component(3),
+            encoding: component(4),
+            data: component(5).unwrap_or_default(),
+        }
+    }
+
+    /// Embed bytes using base 64 encoding. Empty types are left out.
+    pub fn from_bytes<T: Into<String>, S: Into<String>>(
+        type_of_data: T,
+        data_subtype: S,
+        bytes: &[u8],
+    ) -> EncapsulatedData {
+        let non_empty = |s: String| Some(s).filter(|s| !s.is_empty());
+        EncapsulatedData {
+            source_application: None,
+            type_of_data: non_empty(type_of_data.into()),
+            data_subtype: non_empty(data_subtype.into()),
+            encoding: Some(DataEncoding::Base64.code().to_string()),
+            data: encode_base64(bytes),
+        }
+    }
+
+    /// The encoding of the data
+    pub fn data_encoding(&self) -> Result<DataEncoding, EncapsulatedDataError> {
+        self.encoding.as_deref().unwrap_or_default().parse()
+    }
+
+    /// The MIME type of the data, based on its type and subtype. See [`mime_type`].
+    pub fn mime_type(&self) -> Option<String> {
+        mime_type(self.type_of_data.as_deref(), self.data_subtype.as_deref())
+    }
+
+    /// A reader which decodes the data as it's read
+    pub fn reader(&self) -> Result<EncapsulatedDataReader<'_>, EncapsulatedDataError> {
+        Ok(EncapsulatedDataReader {
+            type_of_data: self.type_of_data.as_deref(),
+            data_subtype: self.data_subtype.as_deref(),
+            decoder: Decoder::new(self.data_encoding()?),
+            separators: None,
+            chunks: vec![self.data.as_str()],
+            chunk: 0,
+            offset: 0,
+            escape: None,
+            pending: Vec::new(),
+            done: false,
+        })
+    }
+
+    /// Decode the data into bytes
+    pub fn decode(&self) -> Result<Vec<u8>, EncapsulatedDataError> {
+        self.reader()?.into_bytes()
+    }
+}
+
+/// The MIME type for a type of data (HL7 table 0191) and subtype (HL7 table 0291), such as
+/// `application/pdf` for `AP^PDF`. Subtypes which are already MIME types, such as
+/// `image/png`, are returned as-is. Text without a known subtype is `text/plain`.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::types::mime_type;
+///
+/// assert_eq!(mime_type(Some("IM"), Some("JPEG")).as_deref(), Some("image/jpeg"));
+/// assert_eq!(mime_type(Some("TX"), None).as_deref(), Some("text/plain"));
+/// assert_eq!(mime_type(Some("IM"), Some("PICT")), None);
+/// ```
+pub fn mime_type(type_of_data: Option<&str>, data_subtype: Option<&str>) -> Option<String> {
+    let subtype = data_subtype.map(str::trim).filter(|s| !s.is_empty());
+    if let Some(subtype) = subtype.filter(|s| s.contains('/')) {
+        return Some(subtype.to_ascii_lowercase());
+    }
+    let known = match subtype.map(str::to_ascii_uppercase).as_deref() {
+        Some("PDF") => Some("application/pdf"),
+        Some("RTF") => Some("application/rtf"),
+        Some("POSTSCRIPT") => Some("application/postscript"),
+        Some("OCTET-STREAM") => Some("application/octet-stream"),
+        Some("DICOM") => Some("application/dicom"),
+        Some("XML") => Some("application/xml"),
+        Some("X-HL7-CDA-LEVEL-ONE") => Some("application/x-hl7-cda-level-one+xml"),
+        Some("HTML") => Some("text/html"),
+        Some("SGML") => Some("text/sgml"),
+        Some("JPEG" | "JPG") => Some("image/jpeg"),
+        Some("PNG") => Some("image/png"),
+        Some("GIF") => Some("image/gif"),
+        Some("TIFF" | "TIF") => Some("image/tiff"),
+        Some("BMP") => Some("image/bmp"),
+        Some("BASIC") => Some("audio/basic"),
+        Some("WAV") => Some("audio/wav"),
+        Some(_) => None,
+        None => match type_of_data
+            .map(|t| t.trim().to_ascii_uppercase())
+            .as_deref()
+        {
+            Some("TEXT" | "TX") => Some("text/plain"),
+            _ => None,
+        },
+    };
+    known.map(str::to_string)
+}
+
+/// A reader which decodes embedded data (`ED`) straight from a message as it's read, so large
+/// payloads such as PDF reports don't need to be copied into one big string first. Escape
+/// sequences are decoded, whitespace and line breaks within `Hex` and `Base64` data are
+/// skipped, and padding part way through base 64 data (as when each repeat was encoded
+/// separately) is accepted.
+///
+/// Data which is split across the repeats of a field is joined when reading from a field, and
+/// data continued in later segments can be added with
+/// [`EncapsulatedDataReader::with_continuation`]. [`Obx::encapsulated_data`] does this for the
+/// `ADD` segments following an observation.
+///
+/// [`Obx::encapsulated_data`]: crate::segments::Obx::encapsulated_data
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Read;
+///
+/// let message = hl7_parser::Message::parse(
+///     "MSH|^~\\&|||||||ORU^R01|1|P|2.5.1\rOBX|1|ED|PDF^Report||^AP^PDF^Base64^JVBE~Ri0x\rADD|Lj\\X0D0A\\Q=",
+/// )
+/// .unwrap();
+/// let obx = message.observations().next().unwrap();
+/// let mut reader = obx.encapsulated_data().unwrap();
+/// assert_eq!(reader.mime_type().as_deref(), Some("application/pdf"));
+///
+/// let mut pdf = Vec::new();
+/// reader.read_to_end(&mut pdf).unwrap();
+/// assert_eq!(pdf, b"%PDF-1.4");
+/// ```
+#[derive(Debug, Clone)]
+pub struct EncapsulatedDataReader<'m> {
+    type_of_data: Option<&'m str>,
+    data_subtype: Option<&'m str>,
+    decoder: Decoder,
+    /// The separators used to decode escape sequences, or `None` if the data is already decoded
+    separators: Option<Separators>,
+    chunks: Vec<&'m str>,
+    chunk: usize,
+    offset: usize,
+    /// The escape sequence being read, if in the middle of one
+    escape: Option<String>,
+    pending: Vec<u8>,
+    done: bool,
+}
+
+impl<'m> EncapsulatedDataReader<'m> {
+    /// Read the embedded data in the components of a field repeat
+    pub fn from_repeat(
+        repeat: &Repeat<'m>,
+        separators: &Separators,
+    ) -> Result<Self, EncapsulatedDataError> {
+        let component = |n| repeat.component(n).map(|c| c.raw_value());
+        Self::new(
+            [component(2), component(3), component(4), component(5)],
+            separators,
+        )
+    }
+
+    /// Read the embedded data in a field, joining the data of every repeat. Repeats after the
+    /// first may either be complete `ED` values, whose data is their fifth component, or just
+    /// the continued data, if they don't have any components.
+    pub fn from_field(
+        field: &Field<'m>,
+        separators: &Separators,
+    ) -> Result<Self, EncapsulatedDataError> {
+        let mut repeats = field.repeats();
+        let first = repeats
+            .next()
+            .ok_or(EncapsulatedDataError::MissingEncoding)?;
+        let reader = Self::from_repeat(first, separators)?;
+        Ok(repeats.fold(reader, |reader, repeat| {
+            let data = if repeat.components.len() > 1 {
+                repeat.component(5).map_or("", |c| c.raw_value())
+            } else {
+                repeat.raw_value()
+            };
+            reader.with_continuation(data)
+        }))
+    }
+
+    /// Read the embedded data in the subcomponents of a component
+    pub fn from_component(
+        component: &Component<'m>,
+        separators: &Separators,
+    ) -> Result<Self, EncapsulatedDataError> {
+        let subcomponent = |n| component.subcomponent(n).map(|s| s.raw_value());
+        Self::new(
+            [
+                subcomponent(2),
+                subcomponent(3),
+                subcomponent(4),
+                subcomponent(5),
+            ],
+            separators,
+        )
+    }
+
+    fn new(
+        [type_of_data, data_subtype, encoding, data]: [Option<&'m str>; 4],
+        separators: &Separators,
+    ) -> Result<Self, EncapsulatedDataError> {
+        let non_empty = |s: Option<&'m str>| s.filter(|s| !s.is_empty());
+        Ok(EncapsulatedDataReader {
+            type_of_data: non_empty(type_of_data),
+            data_subtype: non_empty(data_subtype),
+            decoder: Decoder::new(encoding.unwrap_or_default().parse()?),
+            separators: Some(*separators),
+            chunks: data.into_iter().collect(),
+            chunk: 0,
+            offset: 0,
+            escape: None,
+            pending: Vec::new(),
+            done: false,
+        })
+    }
+
+    /// Add more data to the end, as it appears in the message (i.e. with escape sequences), such
+    /// as the value of an `ADD` segment continuing the data
+    pub fn with_continuation(mut self, data: &'m str) -> Self {
+        self.chunks.push(data);
+        self.done = false;
+        self
+    }
+
+    /// The type of data (`ED.2`)
+    pub fn type_of_data(&self) -> Option<&'m str> {
+        self.type_of_data
+    }
+
+    /// The subtype of the data (`ED.3`)
+    pub fn data_subtype(&self) -> Option<&'m str> {
+        self.data_subtype
+    }
+
+    /// The encoding of the data (`ED.4`)
+    pub fn encoding(&self) -> DataEncoding {
+        self.decoder.encoding
+    }
+
+    /// The MIME type of the data, based on its type and subtype. See [`mime_type`].
+    pub fn mime_type(&self) -> Option<String> {
+        mime_type(self.type_of_data, self.data_subtype)
+    }
+
+    /// Decode all of the remaining data into bytes
+    pub fn into_bytes(mut self) -> Result<Vec<u8>, EncapsulatedDataError> {
+        self.fill(usize::MAX)?;
+        Ok(self.pending)
+    }
+
+    /// Decode data until there are at least `wanted` bytes pending or the data runs out
+    fn fill(&mut self, wanted: usize) -> Result<(), EncapsulatedDataError> {
+        while self.pending.len() < wanted && !self.done {
+            let Some(c) = self.next_char() else {
+                if self.escape.is_some() {
+                    return Err(EncapsulatedDataError::UnterminatedEscape);
+                }
+                self.decoder.finish(&mut self.pending)?;
+                self.done = true;
+                break;
+            };
+            let Some(separators) = self.separators else {
+                self.decoder.push(c, &mut self.pending)?;
+                continue;
+            };
+            if c == separators.escape {
+                match self.escape.take() {
+                    Some(sequence) => self.unescape(&sequence, &separators)?,
+                    None => self.escape = Some(String::new()),
+                }
+            } else if let Some(sequence) = &mut self.escape {
+                sequence.push(c);
+            } else {
+                self.decoder.push(c, &mut self.pending)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        while let Some(chunk) = self.chunks.get(self.chunk) {
+            match chunk[self.offset..].chars().next() {
+                Some(c) => {
+                    self.offset += c.len_utf8();
+                    return Some(c);
+                }
+                None => {
+                    self.chunk += 1;
+                    self.offset = 0;
+                }
+            }
+        }
+        None
+    }
+
+    /// Decode an escape sequence in the same way as [`Separators::decode`], except that hex
+    /// data is decoded as bytes. Escape sequences which don't stand for data, such as `\H\`,
+    /// are an error.
+    fn unescape(
+        &mut self,
+        sequence: &str,
+        separators: &Separators,
+    ) -> Result<(), EncapsulatedDataError> {
+        match separators.classify(sequence) {
+            Escape::Hex(hex) => {
+                let bytes = (0..hex.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+                    .collect::<Result<Vec<u8>, _>>()
+                    .expect("hex escape sequences hold pairs of hex digits");
+                self.decoder.push_bytes(&bytes, &mut self.pending)
+            }
+            Escape::Separator(_) | Escape::Formatting(".br") => {
+                let mut buf = [0; 4];
+                for c in separators.unescape(sequence, &mut buf).chars() {
+                    self.decoder.push(c, &mut self.pending)?;
+                }
+                Ok(())
+            }
+            _ => Err(EncapsulatedDataError::UnsupportedEscape(
+                sequence.to_string(),
+            )),
+        }
+    }
+}
+
+impl io::Read for EncapsulatedDataReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.fill(buf.len())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let n = buf.len().min(self.pending.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
+    }
+}
+
+/// An incremental decoder for `Hex` and `Base64` data
+#[derive(Debug, Clone)]
+struct Decoder {
+    encoding: DataEncoding,
+    bits: u32,
+    count: u8,
+    position: usize,
+}
+
+impl Decoder {
+    fn new(encoding: DataEncoding) -> Self {
+        Decoder {
+            encoding,
+            bits: 0,
+            count: 0,
+            position: 0,
+        }
+    }
+
+    fn push(&mut self, c: char, output: &mut Vec<u8>) -> Result<(), EncapsulatedDataError> {
+        let position = self.position;
+        self.position += 1;
+        let encoding = self.encoding;
+        let invalid = || EncapsulatedDataError::InvalidCharacter(encoding, position, c);
+        match self.encoding {
+            DataEncoding::Ascii => {
+                let mut buf = [0; 4];
+                output.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+            _ if c.is_whitespace() => {}
+            DataEncoding::Hex => {
+                let digit = c.to_digit(16).ok_or_else(invalid)?;
+                self.bits = (self.bits << 4) | digit;
+                self.count += 1;
+                if self.count == 2 {
+                    output.push(self.bits as u8);
+                    self.bits = 0;
+                    self.count = 0;
+                }
+            }
+            DataEncoding::Base64 if c == '=' => self.finish(output)?,
+            DataEncoding::Base64 => {
+                let value = match c {
+                    'A'..='Z' => c as u32 - 'A' as u32,
+                    'a'..='z' => c as u32 - 'a' as u32 + 26,
+                    '0'..='9' => c as u32 - '0' as u32 + 52,
+                    '+' | '-' => 62,
+                    '/' | '_' => 63,
+                    _ => return Err(invalid()),
+                };
+                self.bits = (self.bits << 6) | value;
+                self.count += 1;
+                if self.count == 4 {
+                    output.extend_from_slice(&self.bits.to_be_bytes()[1..]);
+                    self.bits = 0;
+                    self.count = 0;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Push the bytes of a hex escape sequence, which are kept as-is for ASCII data
+    fn push_bytes(
+        &mut self,
+        bytes: &[u8],
+        output: &mut Vec<u8>,
+    ) -> Result<(), EncapsulatedDataError> {
+        match self.encoding {
+            DataEncoding::Ascii => {
+                self.position += bytes.len();
+                output.extend_from_slice(bytes);
+                Ok(())
+            }
+            _ => bytes
+                .iter()
+                .try_for_each(|b| self.push(char::from(*b), output)),
+        }
+    }
+
+    /// Flush a partial group at the end of the data or at base 64 padding
+    fn finish(&mut self, output: &mut Vec<u8>) -> Result<(), EncapsulatedDataError> {
+        match (self.encoding, self.count) {
+            (_, 0) | (DataEncoding::Ascii, _) => {}
+            (DataEncoding::Base64, 2) => output.push((self.bits >> 4) as u8),
+            (DataEncoding::Base64, 3) => {
+                output.extend_from_slice(&((self.bits >> 2) as u16).to_be_bytes())
+            }
+            (encoding, _) => return Err(EncapsulatedDataError::Truncated(encoding)),
+        }
+        self.bits = 0;
+        self.count = 0;
+        Ok(())
+    }
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut output = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let bits = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |bits, (i, b)| bits | ((*b as u32) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(ALPHABET[((bits >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+impl EncapsulatedData {
+    fn parts(&self) -> HashMap<usize, String> {
+        [
+            self.source_application.clone(),
+            self.type_of_data.clone(),
+            self.data_subtype.clone(),
+            self.encoding.clone(),
+            Some(self.data.clone()),
+        ]
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, part)| part.map(|part| (i + 1, part)))
+        .collect()
+    }
+}
+
+impl From<&EncapsulatedData> for RepeatBuilder {
+    fn from(value: &EncapsulatedData) -> Self {
+        RepeatBuilder::from_component_map(value.parts())
+    }
+}
+
+impl From<EncapsulatedData> for RepeatBuilder {
+    fn from(value: EncapsulatedData) -> Self {
+        RepeatBuilder::from(&value)
+    }
+}
+
+impl From<&EncapsulatedData> for FieldBuilder {
+    fn from(value: &EncapsulatedData) -> Self {
+        FieldBuilder::with_repeats(vec![value.into()])
+    }
+}
+
+impl From<EncapsulatedData> for FieldBuilder {
+    fn from(value: EncapsulatedData) -> Self {
+        FieldBuilder::from(&value)
+    }
+}
+
+/// Convert into a component of a composite field, using subcomponents for the parts
+impl From<&EncapsulatedData> for ComponentBuilder {
+    fn from(value: &EncapsulatedData) -> Self {
+        ComponentBuilder::with_subcomponents(value.parts())
+    }
+}
+
+impl From<EncapsulatedData> for ComponentBuilder {
+    fn from(value: EncapsulatedData) -> Self {
+        ComponentBuilder::from(&value)
+    }
+}
+
+/// A reference to data stored outside of the message, as found in HL7 `RP` values
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::{message::Separators, types::ReferencePointer};
+///
+/// let repeat = hl7_parser::parser::parse_repeat("scan-1234^PACS&1.2.3&ISO^IM^JPEG").unwrap();
+/// let rp = ReferencePointer::from_repeat(&repeat, &Separators::default());
+/// assert_eq!(rp.pointer.as_deref(), Some("scan-1234"));
+/// assert_eq!(rp.application_id.as_deref(), Some("PACS"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReferencePointer {
+    /// The key or location of the data within the application (`RP.1`)
+    pub pointer: Option<String>,
+    /// The namespace ID of the application the data is stored in (`RP.2.1`)
+    pub application_id: Option<String>,
+    /// The type of data, from HL7 table 0191 (`RP.3`)
+    pub type_of_data: Option<String>,
+    /// The subtype of the data, from HL7 table 0291 (`RP.4`)
+    pub subtype: Option<String>,
+}
+
+impl ReferencePointer {
+    /// Read a reference pointer from the components of a field repeat
+    pub fn from_repeat(repeat: &Repeat, separators: &Separators) -> ReferencePointer {
+        let component = |n| component_text(repeat, n, separators);
+        ReferencePointer {
+            pointer: component(1),
+            application_id: component(2),
+            type_of_data: component(3),
+            subtype: component(4),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_field;
+    use pretty_assertions_sorted::assert_eq;
+    use std::io::Read;
+
+    #[test]
+    fn can_round_trip_base64() {
+        for len in 0..8 {
+            let bytes = (0..len).map(|i| (i * 37 + 200) as u8).collect::<Vec<u8>>();
+            let ed = EncapsulatedData::from_bytes("AP", "octet-stream", &bytes);
+            assert_eq!(ed.decode().unwrap(), bytes);
+        }
+        assert_eq!(encode_base64(b"foob"), "Zm9vYg==");
+    }
+
+    #[test]
+    fn can_decode_hex_and_ascii() {
+        let separators = Separators::default();
+        let field = parse_field("^TX^^Hex^48 65\\X0D\\6C6c~6F").unwrap();
+        let reader = EncapsulatedDataReader::from_field(&field, &separators).unwrap();
+        assert_eq!(reader.mime_type().as_deref(), Some("text/plain"));
+        assert_eq!(reader.into_bytes().unwrap(), b"Hello");
+
+        let field = parse_field("^TEXT^^A^a\\T\\b\\XC3A9\\").unwrap();
+        let reader = EncapsulatedDataReader::from_field(&field, &separators).unwrap();
+        assert_eq!(
+            String::from_utf8(reader.into_bytes().unwrap()).unwrap(),
+            "a&bé"
+        );
+    }
+
+    #[test]
+    fn reports_invalid_data() {
+        let separators = Separators::default();
+        let read = |source| {
+            let field = parse_field(source).unwrap();
+            EncapsulatedDataReader::from_field(&field, &separators)
+                .and_then(|reader| reader.into_bytes())
+        };
+        assert_eq!(
+            read("^AP^PDF^Base64^SGk!"),
+            Err(EncapsulatedDataError::InvalidCharacter(
+                DataEncoding::Base64,
+                3,
+                '!'
+            ))
+        );
+        assert_eq!(
+            read("^AP^^Hex^ABC"),
+            Err(EncapsulatedDataError::Truncated(DataEncoding::Hex))
+        );
+        assert_eq!(
+            read("^AP^PDF^^SGk="),
+            Err(EncapsulatedDataError::MissingEncoding)
+        );
+        assert!(matches!(
+            read("^AP^PDF^Base32^SGk="),
+            Err(EncapsulatedDataError::UnknownEncoding(_))
+        ));
+        assert_eq!(
+            read("^TX^^A^abc\\X0D"),
+            Err(EncapsulatedDataError::UnterminatedEscape)
+        );
+        assert_eq!(
+            read("^TX^^A^a\\H\\b\\N\\"),
+            Err(EncapsulatedDataError::UnsupportedEscape("H".to_string()))
+        );
+        assert_eq!(
+            read("^AP^^Base64^SGk\\Zxx\\"),
+            Err(EncapsulatedDataError::UnsupportedEscape("Zxx".to_string()))
+        );
+        assert_eq!(
+            read("^TX^^A^a\\T\\b\\X0D0A\\\\.br\\"),
+            Ok(b"a&b\r\n\r".to_vec())
+        );
+    }
+
+    #[test]
+    fn reads_continuation_repeats_consistently() {
+        let separators = Separators::default();
+        let field = parse_field("^AP^^Hex^48~^AP^Hex~^^^Hex^65~6C6C").unwrap();
+        let reader = EncapsulatedDataReader::from_field(&field, &separators).unwrap();
+        assert_eq!(reader.into_bytes().unwrap(), b"Hell");
+    }
+
+    #[test]
+    fn can_read_in_small_pieces() {
+        let separators = Separators::default();
+        let field = parse_field("^AP^^Base64^SGVs~bG8=~IHdvcmxk").unwrap();
+        let mut reader = EncapsulatedDataReader::from_field(&field, &separators).unwrap();
+        let mut output = Vec::new();
+        let mut buf = [0; 2];
+        loop {
+            let n = reader.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            output.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(output, b"Hello world");
+    }
+
+    #[test]
+    fn can_build_components() {
+        let ed = EncapsulatedData::from_bytes("IM", "PNG", &[0x89, b'P']);
+        let component = ComponentBuilder::from(ed);
+        let separators = Separators::default();
+        let value = component.display(&separators).to_string();
+        assert_eq!(value, "&IM&PNG&Base64&iVA=");
+
+        let component = crate::parser::parse_component(&value).unwrap();
+        let reader = EncapsulatedDataReader::from_component(&component, &separators).unwrap();
+        assert_eq!(reader.into_bytes().unwrap(), [0x89, b'P']);
+    }
+}
@@ -0,0 +1,10 @@
+mod coded;
+mod decimal;
+mod encapsulated;
+mod numeric;
+mod text;
+pub use coded::*;
+pub use decimal::*;
+pub use encapsulated::*;
+pub use numeric::*;
+pub use text::*;
@@ -0,0 +1,661 @@
+use super::{CodedElement, Decimal, DecimalParseError};
+use crate::{
+    builder::{ComponentBuilder, FieldBuilder, RepeatBuilder},
+    message::{Component, Field, Repeat, Separators},
+    parser::ParseError,
+};
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+/// Errors that can result from parsing numeric and quantity values
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum NumericParseError {
+    #[error("Invalid {0}: {1}")]
+    InvalidNumber(&'static str, #[source] DecimalParseError),
+    #[error("Invalid comparator '{0}'")]
+    InvalidComparator(String),
+    #[error("Invalid separator or suffix '{0}'")]
+    InvalidSeparator(String),
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+}
+
+/// The comparator of a structured numeric (`SN.1`)
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Comparator {
+    /// `>`
+    GreaterThan,
+    /// `<`
+    LessThan,
+    /// `>=`
+    GreaterThanOrEqual,
+    /// `<=`
+    LessThanOrEqual,
+    /// `=`
+    Equal,
+    /// `<>`
+    NotEqual,
+}
+
+impl Comparator {
+    /// The comparator as it appears in a message, i.e. `>=`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Comparator::GreaterThan => ">",
+            Comparator::LessThan => "<",
+            Comparator::GreaterThanOrEqual => ">=",
+            Comparator::LessThanOrEqual => "<=",
+            Comparator::Equal => "=",
+            Comparator::NotEqual => "<>",
+        }
+    }
+
+    /// Whether `value` compares to `reference` using the comparator, i.e. `value > reference`
+    pub fn compare(&self, value: &Decimal, reference: &Decimal) -> bool {
+        match self {
+            Comparator::GreaterThan => value > reference,
+            Comparator::LessThan => value < reference,
+            Comparator::GreaterThanOrEqual => value >= reference,
+            Comparator::LessThanOrEqual => value <= reference,
+            Comparator::Equal => value == reference,
+            Comparator::NotEqual => value != reference,
+        }
+    }
+}
+
+/// Parse a comparator, ignoring surrounding whitespace
+impl FromStr for Comparator {
+    type Err = NumericParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            ">" => Ok(Comparator::GreaterThan),
+            "<" => Ok(Comparator::LessThan),
+            ">=" => Ok(Comparator::GreaterThanOrEqual),
+            "<=" => Ok(Comparator::LessThanOrEqual),
+            "=" => Ok(Comparator::Equal),
+            "<>" => Ok(Comparator::NotEqual),
+            _ => Err(NumericParseError::InvalidComparator(s.to_string())),
+        }
+    }
+}
+
+impl Display for Comparator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The separator or suffix of a structured numeric (`SN.3`)
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NumericSeparator {
+    /// `-`, for a range such as `10-20`
+    Range,
+    /// `+`, as a suffix for a categorical value such as `2+`
+    Plus,
+    /// `/`, for a ratio or fraction such as `1/128`
+    Slash,
+    /// `:`, for a ratio such as `1:128`
+    Colon,
+    /// `.`, for a decimal split into whole and fractional parts
+    Point,
+}
+
+impl NumericSeparator {
+    /// The separator as it appears in a message
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NumericSeparator::Range => "-",
+            NumericSeparator::Plus => "+",
+            NumericSeparator::Slash => "/",
+            NumericSeparator::Colon => ":",
+            NumericSeparator::Point => ".",
+        }
+    }
+}
+
+/// Parse a separator or suffix, ignoring surrounding whitespace
+impl FromStr for NumericSeparator {
+    type Err = NumericParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "-" => Ok(NumericSeparator::Range),
+            "+" => Ok(NumericSeparator::Plus),
+            "/" => Ok(NumericSeparator::Slash),
+            ":" => Ok(NumericSeparator::Colon),
+            "." => Ok(NumericSeparator::Point),
+            _ => Err(NumericParseError::InvalidSeparator(s.to_string())),
+        }
+    }
+}
+
+impl Display for NumericSeparator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A structured numeric (`SN`), as used for lab results which aren't a simple number, such as
+/// `>^100`, `^1^:^128`, or `^10^-^20`
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::types::{Comparator, Decimal, StructuredNumeric};
+///
+/// let sn: StructuredNumeric = "<^0.50".parse().unwrap();
+/// assert_eq!(sn.comparator, Some(Comparator::LessThan));
+/// assert_eq!(sn.num1.unwrap().to_string(), "0.50");
+/// assert_eq!(sn.matches(&"0.3".parse().unwrap()), Some(true));
+///
+/// let sn: StructuredNumeric = "^1^:^128".parse().unwrap();
+/// assert_eq!(sn.ratio(), Some(("1".parse().unwrap(), "128".parse().unwrap())));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StructuredNumeric {
+    /// The comparator (`SN.1`)
+    pub comparator: Option<Comparator>,
+    /// The first number (`SN.2`)
+    pub num1: Option<Decimal>,
+    /// The separator or suffix (`SN.3`)
+    pub separator_suffix: Option<NumericSeparator>,
+    /// The second number (`SN.4`)
+    pub num2: Option<Decimal>,
+}
+
+impl StructuredNumeric {
+    /// The range, if the value is a range such as `^10^-^20`
+    pub fn range(&self) -> Option<NumericRange> {
+        match (self.num1, self.separator_suffix, self.num2) {
+            (Some(low), Some(NumericSeparator::Range), Some(high)) => Some(NumericRange {
+                low: Some(low),
+                high: Some(high),
+            }),
+            _ => None,
+        }
+    }
+
+    /// The two numbers, if the value is a ratio such as `^1^:^128` or `^1^/^2`
+    pub fn ratio(&self) -> Option<(Decimal, Decimal)> {
+        match (self.num1, self.separator_suffix, self.num2) {
+            (
+                Some(numerator),
+                Some(NumericSeparator::Colon | NumericSeparator::Slash),
+                Some(denominator),
+            ) => Some((numerator, denominator)),
+            _ => None,
+        }
+    }
+
+    /// Whether a number satisfies the value: for a comparison such as `>^100`, whether the
+    /// number compares to `num1`; for a range, whether it falls within the range; and for a
+    /// plain number, whether it is equal. Returns `None` for ratios, suffixes, and empty values,
+    /// which a single number can't be checked against.
+    pub fn matches(&self, value: &Decimal) -> Option<bool> {
+        if let Some(range) = self.range().filter(|_| self.comparator.is_none()) {
+            return Some(range.contains(value));
+        }
+        match (self.num1, self.separator_suffix, self.num2) {
+            (Some(num1), None, None) => Some(
+                self.comparator
+                    .unwrap_or(Comparator::Equal)
+                    .compare(value, &num1),
+            ),
+            _ => None,
+        }
+    }
+}
+
+impl Parts for StructuredNumeric {
+    fn from_parts(parts: &[String]) -> Result<Self, NumericParseError> {
+        Ok(StructuredNumeric {
+            comparator: optional(&parts[0]).map(str::parse).transpose()?,
+            num1: number("first number", &parts[1])?,
+            separator_suffix: optional(&parts[2]).map(str::parse).transpose()?,
+            num2: number("second number", &parts[3])?,
+        })
+    }
+
+    fn to_parts(&self) -> Vec<Option<String>> {
+        vec![
+            self.comparator.map(|c| c.to_string()),
+            self.num1.map(|n| n.to_string()),
+            self.separator_suffix.map(|s| s.to_string()),
+            self.num2.map(|n| n.to_string()),
+        ]
+    }
+}
+
+/// A quantity with units (`CQ`), such as `500^mg`. The units are a coded value, such as
+/// `mL&milliliter&UCUM`. When the quantity is itself a component of a composite value, only
+/// the identifier of the units can be given.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::types::CompositeQuantity;
+///
+/// let cq: CompositeQuantity = "+1.50^mL&milliliter&UCUM".parse().unwrap();
+/// assert_eq!(cq.quantity.unwrap().to_string(), "1.50");
+/// let units = cq.units.unwrap();
+/// assert_eq!(units.identifier.as_deref(), Some("mL"));
+/// assert_eq!(units.text.as_deref(), Some("milliliter"));
+/// assert_eq!(units.coding_system.as_deref(), Some("UCUM"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompositeQuantity {
+    /// The quantity (`CQ.1`)
+    pub quantity: Option<Decimal>,
+    /// The units (`CQ.2`)
+    pub units: Option<CodedElement>,
+}
+
+impl Parts for CompositeQuantity {
+    fn from_parts(parts: &[String]) -> Result<Self, NumericParseError> {
+        Ok(CompositeQuantity {
+            quantity: number("quantity", &parts[0])?,
+            units: optional(&parts[1]).map(|identifier| CodedElement {
+                identifier: Some(identifier.to_string()),
+                ..Default::default()
+            }),
+        })
+    }
+
+    fn from_repeat_parts(
+        repeat: &Repeat,
+        separators: &Separators,
+    ) -> Result<Self, NumericParseError> {
+        let units = repeat
+            .component(2)
+            .map(|c| CodedElement::from_component(c, separators))
+            .filter(|units| *units != CodedElement::default());
+        Ok(CompositeQuantity {
+            units,
+            ..Self::from_parts(&repeat_parts(repeat, separators))?
+        })
+    }
+
+    fn to_parts(&self) -> Vec<Option<String>> {
+        vec![
+            self.quantity.map(|q| q.to_string()),
+            self.units
+                .as_ref()
+                .and_then(|units| units.identifier.clone()),
+        ]
+    }
+
+    fn to_components(&self) -> HashMap<usize, ComponentBuilder> {
+        let mut components = part_map(self.to_parts())
+            .into_iter()
+            .map(|(n, part)| (n, ComponentBuilder::from(part)))
+            .collect::<HashMap<_, _>>();
+        if let Some(units) = &self.units {
+            components.insert(2, ComponentBuilder::with_subcomponents(units.parts()));
+        }
+        components
+    }
+}
+
+/// An amount of money (`MO`), such as `125.00^USD`. The denomination is an ISO 4217 currency
+/// code.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::types::Money;
+///
+/// let money: Money = "125.00^USD".parse().unwrap();
+/// assert_eq!(money.quantity.unwrap().to_string(), "125.00");
+/// assert_eq!(money.denomination.as_deref(), Some("USD"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Money {
+    /// The amount (`MO.1`)
+    pub quantity: Option<Decimal>,
+    /// The currency (`MO.2`)
+    pub denomination: Option<String>,
+}
+
+impl Parts for Money {
+    fn from_parts(parts: &[String]) -> Result<Self, NumericParseError> {
+        Ok(Money {
+            quantity: number("quantity", &parts[0])?,
+            denomination: optional(&parts[1]).map(str::to_string),
+        })
+    }
+
+    fn to_parts(&self) -> Vec<Option<String>> {
+        vec![
+            self.quantity.map(|q| q.to_string()),
+            self.denomination.clone(),
+        ]
+    }
+}
+
+/// A numeric range (`NR`), such as a reference range of `3.5^5.0`. Either end may be missing
+/// for ranges which are open-ended.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::types::NumericRange;
+///
+/// let range: NumericRange = "3.5^5.0".parse().unwrap();
+/// assert!(range.contains(&"5".parse().unwrap()));
+/// assert!(!range.contains(&"5.01".parse().unwrap()));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NumericRange {
+    /// The low end of the range (`NR.1`)
+    pub low: Option<Decimal>,
+    /// The high end of the range (`NR.2`)
+    pub high: Option<Decimal>,
+}
+
+impl NumericRange {
+    /// Whether the value falls within the range, including at either end
+    pub fn contains(&self, value: &Decimal) -> bool {
+        self.low.is_none_or(|low| *value >= low) && self.high.is_none_or(|high| *value <= high)
+    }
+}
+
+impl Parts for NumericRange {
+    fn from_parts(parts: &[String]) -> Result<Self, NumericParseError> {
+        Ok(NumericRange {
+            low: number("low value", &parts[0])?,
+            high: number("high value", &parts[1])?,
+        })
+    }
+
+    fn to_parts(&self) -> Vec<Option<String>> {
+        vec![
+            self.low.map(|n| n.to_string()),
+            self.high.map(|n| n.to_string()),
+        ]
+    }
+}
+
+/// A composite value made of parts, which are components when the value is a field or the
+/// subcomponents when the value is a component of another composite
+trait Parts: Sized {
+    /// Parse the value from its decoded parts. Missing parts are empty strings.
+    fn from_parts(parts: &[String]) -> Result<Self, NumericParseError>;
+
+    /// Parse the value from the components of a field repeat. By default, only the first
+    /// subcomponent of each component is used.
+    fn from_repeat_parts(
+        repeat: &Repeat,
+        separators: &Separators,
+    ) -> Result<Self, NumericParseError> {
+        Self::from_parts(&repeat_parts(repeat, separators))
+    }
+
+    /// The parts of the value, with `None` for missing parts
+    fn to_parts(&self) -> Vec<Option<String>>;
+
+    /// The components of the value as a field repeat
+    fn to_components(&self) -> HashMap<usize, ComponentBuilder> {
+        part_map(self.to_parts())
+            .into_iter()
+            .map(|(n, part)| (n, ComponentBuilder::from(part)))
+            .collect()
+    }
+}
+
+/// The decoded first subcomponents of the first four components of a repeat
+fn repeat_parts(repeat: &Repeat, separators: &Separators) -> Vec<String> {
+    (1..=4)
+        .map(|n| {
+            repeat
+                .component(n)
+                .and_then(|c| c.subcomponent(1))
+                .map(|s| separators.decode(s.raw_value()).to_string())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Treat empty and whitespace-only parts as missing
+fn optional(part: &str) -> Option<&str> {
+    Some(part).filter(|p| !p.trim().is_empty())
+}
+
+fn number(name: &'static str, part: &str) -> Result<Option<Decimal>, NumericParseError> {
+    optional(part)
+        .map(|p| {
+            p.parse()
+                .map_err(|e| NumericParseError::InvalidNumber(name, e))
+        })
+        .transpose()
+}
+
+fn part_map(parts: Vec<Option<String>>) -> HashMap<usize, String> {
+    parts
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, part)| part.map(|part| (i + 1, part)))
+        .collect()
+}
+
+macro_rules! impl_composite {
+    ($($t:ty),*) => {
+        $(
+            impl $t {
+                #[doc = concat!("Parse the first repeat of a field as a ", stringify!($t))]
+                pub fn from_field(field: &Field, separators: &Separators) -> Result<$t, NumericParseError> {
+                    match field.repeat(1) {
+                        Some(repeat) => Self::from_repeat(repeat, separators),
+                        None => Self::from_parts(&vec![String::new(); 4]),
+                    }
+                }
+
+                #[doc = concat!("Parse a field repeat as a ", stringify!($t), ", using its components")]
+                pub fn from_repeat(repeat: &Repeat, separators: &Separators) -> Result<$t, NumericParseError> {
+                    Self::from_repeat_parts(repeat, separators)
+                }
+
+                #[doc = concat!("Parse a component of a composite field as a ", stringify!($t), ", using its subcomponents")]
+                pub fn from_component(component: &Component, separators: &Separators) -> Result<$t, NumericParseError> {
+                    let parts = (1..=4)
+                        .map(|n| {
+                            component
+                                .subcomponent(n)
+                                .map(|s| separators.decode(s.raw_value()).to_string())
+                                .unwrap_or_default()
+                        })
+                        .collect::<Vec<_>>();
+                    Self::from_parts(&parts)
+                }
+            }
+
+            /// Parse a value using the default separators
+            impl FromStr for $t {
+                type Err = NumericParseError;
+
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    Self::from_repeat(&crate::parser::parse_repeat(s)?, &Separators::default())
+                }
+            }
+
+            impl From<&$t> for RepeatBuilder {
+                fn from(value: &$t) -> Self {
+                    RepeatBuilder::with_components(value.to_components())
+                }
+            }
+
+            impl From<$t> for RepeatBuilder {
+                fn from(value: $t) -> Self {
+                    RepeatBuilder::from(&value)
+                }
+            }
+
+            impl From<&$t> for FieldBuilder {
+                fn from(value: &$t) -> Self {
+                    FieldBuilder::with_repeats(vec![value.into()])
+                }
+            }
+
+            impl From<$t> for FieldBuilder {
+                fn from(value: $t) -> Self {
+                    FieldBuilder::from(&value)
+                }
+            }
+
+            /// Convert into a component of a composite field, using subcomponents for the parts
+            impl From<&$t> for ComponentBuilder {
+                fn from(value: &$t) -> Self {
+                    ComponentBuilder::with_subcomponents(part_map(value.to_parts()))
+                }
+            }
+
+            impl From<$t> for ComponentBuilder {
+                fn from(value: $t) -> Self {
+                    ComponentBuilder::from(&value)
+                }
+            }
+        )*
+    };
+}
+
+impl_composite!(StructuredNumeric, CompositeQuantity, Money, NumericRange);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{message::Separators, Message};
+    use pretty_assertions_sorted::assert_eq;
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn can_parse_structured_numerics() {
+        let sn: StructuredNumeric = ">^100".parse().unwrap();
+        assert_eq!(
+            sn,
+            StructuredNumeric {
+                comparator: Some(Comparator::GreaterThan),
+                num1: Some(d("100")),
+                ..Default::default()
+            }
+        );
+
+        let sn: StructuredNumeric = "^ +10. ^-^20.0".parse().unwrap();
+        assert_eq!(
+            sn.range(),
+            Some(NumericRange {
+                low: Some(d("10")),
+                high: Some(d("20"))
+            })
+        );
+        assert_eq!(sn.num2.unwrap().to_string(), "20.0");
+
+        let sn: StructuredNumeric = "^2^+".parse().unwrap();
+        assert_eq!(sn.separator_suffix, Some(NumericSeparator::Plus));
+        assert_eq!(sn.matches(&d("2")), None);
+
+        assert!(matches!(
+            "=>^1".parse::<StructuredNumeric>(),
+            Err(NumericParseError::InvalidComparator(_))
+        ));
+        assert!(matches!(
+            "^1^x^2".parse::<StructuredNumeric>(),
+            Err(NumericParseError::InvalidSeparator(_))
+        ));
+        assert!(matches!(
+            "^1.2.3".parse::<StructuredNumeric>(),
+            Err(NumericParseError::InvalidNumber("first number", _))
+        ));
+    }
+
+    #[test]
+    fn structured_numerics_match_values() {
+        let sn: StructuredNumeric = ">=^100".parse().unwrap();
+        assert_eq!(sn.matches(&d("100.0")), Some(true));
+        assert_eq!(sn.matches(&d("99.99")), Some(false));
+        let sn: StructuredNumeric = "^5".parse().unwrap();
+        assert_eq!(sn.matches(&d("5.00")), Some(true));
+        let sn: StructuredNumeric = "^1^-^2".parse().unwrap();
+        assert_eq!(sn.matches(&d("1.5")), Some(true));
+        assert_eq!(sn.matches(&d("2.5")), Some(false));
+        assert_eq!(StructuredNumeric::default().matches(&d("1")), None);
+    }
+
+    #[test]
+    fn can_parse_from_messages() {
+        let message = Message::parse(
+            "MSH|^~\\&|||||||ORU^R01|1|P|2.5.1\rOBX|1|SN|TITER||^1^:^128|||||F\rRXE|^^^^^^^^^^^^&mL||||2.50^mL&&UCUM|||||||||||12.00^USD",
+        )
+        .unwrap();
+        let obx = message.segment("OBX").unwrap();
+        let sn = StructuredNumeric::from_field(obx.field(5).unwrap(), &message.separators).unwrap();
+        assert_eq!(sn.ratio(), Some((d("1"), d("128"))));
+
+        let rxe = message.segment("RXE").unwrap();
+        let cq = CompositeQuantity::from_field(rxe.field(5).unwrap(), &message.separators).unwrap();
+        assert_eq!(cq.quantity.unwrap().to_string(), "2.50");
+        assert_eq!(
+            cq.units,
+            Some(CodedElement {
+                identifier: Some("mL".into()),
+                coding_system: Some("UCUM".into()),
+                ..Default::default()
+            })
+        );
+        let money = Money::from_field(rxe.field(16).unwrap(), &message.separators).unwrap();
+        assert_eq!(money.quantity.unwrap().to_string(), "12.00");
+
+        let component = rxe.field(1).unwrap().component(13).unwrap();
+        let cq = CompositeQuantity::from_component(component, &message.separators).unwrap();
+        assert_eq!(
+            cq,
+            CompositeQuantity {
+                quantity: None,
+                units: Some(CodedElement {
+                    identifier: Some("mL".into()),
+                    ..Default::default()
+                })
+            }
+        );
+    }
+
+    #[test]
+    fn can_convert_into_builders() {
+        let separators = Separators::default();
+        let sn: StructuredNumeric = "<^0.50".parse().unwrap();
+        let field = FieldBuilder::from(&sn);
+        assert_eq!(field.display(&separators).to_string(), "<^0.50");
+
+        let sn: StructuredNumeric = "^1^:^128".parse().unwrap();
+        let repeat = RepeatBuilder::from(sn);
+        assert_eq!(repeat.display(&separators).to_string(), "^1^:^128");
+
+        let range = NumericRange {
+            low: None,
+            high: Some(d("5.0")),
+        };
+        let component = ComponentBuilder::from(range);
+        assert_eq!(component.display(&separators).to_string(), "&5.0");
+
+        let money: Money = "+0012.50^USD".parse().unwrap();
+        let field = FieldBuilder::from(money);
+        assert_eq!(field.display(&separators).to_string(), "12.50^USD");
+
+        let source = r"2^mg\S\kg&milligram per kilogram&UCUM";
+        let cq: CompositeQuantity = source.parse().unwrap();
+        let units = cq.units.as_ref().unwrap();
+        assert_eq!(units.identifier.as_deref(), Some("mg^kg"));
+        assert_eq!(units.text.as_deref(), Some("milligram per kilogram"));
+        let field = FieldBuilder::from(&cq);
+        assert_eq!(field.display(&separators).to_string(), source);
+        let component = ComponentBuilder::from(&cq);
+        assert_eq!(component.display(&separators).to_string(), r"2&mg\S\kg");
+    }
+}
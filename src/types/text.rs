@@ -0,0 +1,225 @@
+use crate::message::{Escape, Separators};
+
+/// A piece of formatted text (`FT`)
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FormattedTextElement {
+    /// Text, with escaped separators decoded
+    Text(String),
+    /// `\.br\`: begin a new line
+    LineBreak,
+    /// `\.sp <n>\`: end the current line and skip `n` lines
+    Skip(u32),
+    /// `\.in <n>\`: indent following lines by `n` spaces
+    Indent(i32),
+    /// `\.ti <n>\`: indent the next line by `n` more spaces
+    TemporaryIndent(i32),
+    /// `\.sk <n>\`: skip `n` spaces to the right
+    SkipSpaces(u32),
+    /// `\.ce\`: end the current line and center the next
+    Center,
+    /// `\.fi\` (`true`) or `\.nf\` (`false`): turn word wrapping on or off
+    Fill(bool),
+    /// `\H\` (`true`) or `\N\` (`false`): start or end highlighting
+    Highlight(bool),
+}
+
+/// Formatted text (`FT`), such as a narrative lab report, with its formatting commands
+/// parsed. Use [`FormattedText::to_plain_text`] to render it for display.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::{message::Separators, types::FormattedText};
+///
+/// let text = FormattedText::parse(
+///     r"\H\Impression\N\:\.br\\.in 2\Normal \T\ unremarkable\.sp\Signed",
+///     &Separators::default(),
+/// );
+/// assert_eq!(
+///     text.to_plain_text(),
+///     "Impression:\n  Normal & unremarkable\n\n  Signed"
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FormattedText {
+    /// The text and formatting commands, in order
+    pub elements: Vec<FormattedTextElement>,
+}
+
+impl FormattedText {
+    /// Parse formatted text from its raw value in a message. As with
+    /// [`Separators::decode`], unknown escape sequences are kept as their contents and an
+    /// unterminated escape sequence is dropped.
+    pub fn parse(value: &str, separators: &Separators) -> FormattedText {
+        let mut elements = Vec::new();
+        let mut text = String::new();
+        let mut rest = value;
+        while let Some(start) = rest.find(separators.escape) {
+            text.push_str(&rest[..start]);
+            let after = &rest[start + separators.escape.len_utf8()..];
+            let Some(end) = after.find(separators.escape) else {
+                rest = "";
+                break;
+            };
+            let sequence = &after[..end];
+            rest = &after[end + separators.escape.len_utf8()..];
+
+            let element = match separators.classify(sequence) {
+                Escape::Separator(c) => {
+                    text.push(c);
+                    continue;
+                }
+                Escape::Hex(hex) => {
+                    match decode_hex(hex) {
+                        Some(decoded) => text.push_str(&decoded),
+                        None => text.push_str(sequence),
+                    }
+                    continue;
+                }
+                Escape::Formatting("H") => FormattedTextElement::Highlight(true),
+                Escape::Formatting("N") => FormattedTextElement::Highlight(false),
+                Escape::Formatting(formatting) => match command(formatting) {
+                    Some(element) => element,
+                    None => {
+                        text.push_str(sequence);
+                        continue;
+                    }
+                },
+                Escape::Other(_) | Escape::Unrecognised(_) => {
+                    text.push_str(sequence);
+                    continue;
+                }
+            };
+            if !text.is_empty() {
+                elements.push(FormattedTextElement::Text(std::mem::take(&mut text)));
+            }
+            elements.push(element);
+        }
+        text.push_str(rest);
+        if !text.is_empty() {
+            elements.push(FormattedTextElement::Text(text));
+        }
+        FormattedText { elements }
+    }
+
+    /// Render the text as plain text, with `\n` line endings and indents as spaces. Centering,
+    /// word wrapping, and highlighting are ignored.
+    pub fn to_plain_text(&self) -> String {
+        let mut output = String::new();
+        let mut at_line_start = true;
+        let mut indent = 0i32;
+        let mut temporary_indent = 0i32;
+        for element in &self.elements {
+            match element {
+                FormattedTextElement::Text(text) => {
+                    if at_line_start {
+                        let spaces = (indent + temporary_indent).max(0) as usize;
+                        output.extend(std::iter::repeat_n(' ', spaces));
+                        temporary_indent = 0;
+                        at_line_start = false;
+                    }
+                    output.push_str(text);
+                }
+                FormattedTextElement::LineBreak | FormattedTextElement::Center => {
+                    output.push('\n');
+                    at_line_start = true;
+                }
+                FormattedTextElement::Skip(lines) => {
+                    if !at_line_start {
+                        output.push('\n');
+                    }
+                    output.extend(std::iter::repeat_n('\n', *lines as usize));
+                    at_line_start = true;
+                }
+                FormattedTextElement::Indent(n) => indent = *n,
+                FormattedTextElement::TemporaryIndent(n) => temporary_indent = *n,
+                FormattedTextElement::SkipSpaces(n) => {
+                    output.extend(std::iter::repeat_n(' ', *n as usize));
+                    at_line_start = false;
+                }
+                FormattedTextElement::Fill(_) | FormattedTextElement::Highlight(_) => {}
+            }
+        }
+        output
+    }
+}
+
+/// Parse a formatting command such as `.sp 2`. The number defaults to 1 when it's missing.
+fn command(sequence: &str) -> Option<FormattedTextElement> {
+    let sequence = sequence.strip_prefix('.')?;
+    let split = sequence
+        .char_indices()
+        .nth(2)
+        .map_or(sequence.len(), |(i, _)| i);
+    let (name, argument) = sequence.split_at(split);
+    let argument = argument.trim();
+    let number = || -> Option<i32> {
+        if argument.is_empty() {
+            Some(1)
+        } else {
+            argument.strip_prefix('+').unwrap_or(argument).parse().ok()
+        }
+    };
+    let count = || number().and_then(|n| u32::try_from(n).ok());
+    match name {
+        "br" if argument.is_empty() => Some(FormattedTextElement::LineBreak),
+        "sp" => count().map(FormattedTextElement::Skip),
+        "in" => number().map(FormattedTextElement::Indent),
+        "ti" => number().map(FormattedTextElement::TemporaryIndent),
+        "sk" => count().map(FormattedTextElement::SkipSpaces),
+        "ce" if argument.is_empty() => Some(FormattedTextElement::Center),
+        "fi" if argument.is_empty() => Some(FormattedTextElement::Fill(true)),
+        "nf" if argument.is_empty() => Some(FormattedTextElement::Fill(false)),
+        _ => None,
+    }
+}
+
+/// Decode hexadecimal data such as the `0D0A` of `\X0D0A\` as UTF-8
+fn decode_hex(hex: &str) -> Option<String> {
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    #[test]
+    fn can_parse_formatted_text() {
+        let text = FormattedText::parse(
+            r"a\F\b\.sp 2\\.ti -1\c\X0D0A\\Zfoo\",
+            &Separators::default(),
+        );
+        assert_eq!(
+            text.elements,
+            vec![
+                FormattedTextElement::Text("a|b".into()),
+                FormattedTextElement::Skip(2),
+                FormattedTextElement::TemporaryIndent(-1),
+                FormattedTextElement::Text("c\r\nZfoo".into()),
+            ]
+        );
+        assert_eq!(
+            FormattedText::parse(
+                r"\.in 4\\.ti -2\1.\.sk 1\x\.br\y\.ce\z\",
+                &Separators::default()
+            )
+            .to_plain_text(),
+            "  1. x\n    y\n    z"
+        );
+        assert_eq!(
+            FormattedText::parse(r"a\.spx\\Xzz\\H\b", &Separators::default()).elements,
+            vec![
+                FormattedTextElement::Text("a.spxXzz".into()),
+                FormattedTextElement::Highlight(true),
+                FormattedTextElement::Text("b".into()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,693 @@
+//! # Converting messages between HL7 versions
+//!
+//! [`convert`] rewrites a [`MessageBuilder`] from the version in its `MSH.12` to another
+//! version, applying the structural changes between the two versions which are known to the
+//! [`dictionary`]:
+//!
+//! - `MSH.12` is updated to the new version
+//! - `MSH.9.3` (message structure) is added when converting to v2.3.1 or later, and removed
+//!   when converting to an earlier version
+//! - Identifiers in `PID.2` and `PID.4` are copied into `PID.3` when converting to v2.3.1 or
+//!   later, where those fields are only kept for backwards compatibility, and moved into
+//!   `PID.3` when converting to v2.7 or later
+//! - `OBX.2` value types are updated (i.e. `CE` becomes `CWE` and `TS` becomes `DTM` as of
+//!   v2.6), and `OBX.5` values are truncated to fit the new value type
+//! - Fields which don't exist in the new version are removed, fields which can't repeat in the
+//!   new version keep only their first repeat, and fields whose data type has fewer components
+//!   in the new version (such as `CWE` becoming `CE`) are truncated
+//!
+//! Each change is listed in a [`Report`], with changes that lose information marked as lossy.
+//! Segments which aren't in the dictionary (including `Z` segments) are left as they are.
+//!
+//! ## Examples
+//!
+//! ```
+//! use hl7_parser::{builder::MessageBuilder, dictionary::Version, Message};
+//!
+//! let message = Message::parse(
+//!     "MSH|^~\\&|LAB||EMR||20240102030405||ADT^A04|1|P|2.3\rPID|1|E123|M456^^^MRN||Doe^John",
+//! ).unwrap();
+//! let mut builder = MessageBuilder::from(&message);
+//!
+//! let report = builder.convert_version(Version::V2_7).unwrap();
+//! assert!(!report.is_lossy());
+//! assert_eq!(
+//!     builder.to_string(),
+//!     "MSH|^~\\&|LAB||EMR||20240102030405||ADT^A04^ADT_A01|1|P|2.7\rPID|1||M456^^^MRN~E123||Doe^John",
+//! );
+//!
+//! let report = builder.convert_version(Version::V2_3).unwrap();
+//! assert_eq!(
+//!     report.to_string(),
+//!     "MSH.9: message structure: \"ADT^A04^ADT_A01\" -> \"ADT^A04\"\nMSH.12: version: \"2.7\" -> \"2.3\"\n",
+//! );
+//! ```
+
+use crate::{
+    builder::{MessageBuilder, Patch, PatchError},
+    dictionary::{self, UnknownVersionError, Version},
+    message::{Field, Repeat, Segment},
+    query::LocationQuery,
+    Message,
+};
+use std::{collections::HashMap, fmt::Display};
+
+/// The changes made when converting a message from one version to another
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Report {
+    pub from: Version,
+    pub to: Version,
+    pub changes: Vec<Change>,
+}
+
+/// A change made to a value when converting a message between versions
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Change {
+    /// The location of the value that was changed
+    #[cfg_attr(feature = "serde", serde(with = "crate::query::serde_string"))]
+    pub query: LocationQuery,
+    pub reason: Reason,
+    /// The raw value before the change
+    pub old: String,
+    /// The raw value after the change, which is empty if the value was removed
+    pub new: String,
+    /// Whether information was lost by the change
+    pub lossy: bool,
+}
+
+/// Why a value was changed when converting a message between versions
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Reason {
+    /// The version in `MSH.12` was updated
+    Version,
+    /// The message structure in `MSH.9.3` was added or removed
+    MessageStructure,
+    /// Identifiers in `PID.2` or `PID.4` were copied or moved into `PID.3`
+    PatientIdentifiers,
+    /// The value type in `OBX.2` was updated
+    ValueType,
+    /// The data type of the value has fewer components in the new version
+    DataType { from: String, to: String },
+    /// The field can't repeat in the new version
+    NotRepeatable,
+    /// The field doesn't exist in the new version
+    FieldRemoved,
+}
+
+/// Errors that can occur when converting a message between versions
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The message doesn't have a version in `MSH.12`
+    #[error("The message doesn't have a version in MSH.12")]
+    MissingVersion,
+
+    /// The version in `MSH.12` isn't known
+    #[error(transparent)]
+    UnknownVersion(#[from] UnknownVersionError),
+
+    /// The builder doesn't render to a message which can be parsed, i.e. it doesn't start with
+    /// an `MSH` segment
+    #[error("The message can't be parsed: {0}")]
+    InvalidMessage(String),
+
+    /// The changes couldn't be applied to the builder
+    #[error(transparent)]
+    Patch(#[from] PatchError),
+}
+
+impl Report {
+    /// Whether any of the changes lost information
+    pub fn is_lossy(&self) -> bool {
+        self.changes.iter().any(|change| change.lossy)
+    }
+
+    /// An iterator over the changes which lost information
+    pub fn lossy(&self) -> impl Iterator<Item = &Change> {
+        self.changes.iter().filter(|change| change.lossy)
+    }
+}
+
+/// Display the report with one change per line
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Display the change as `query: reason: "old" -> "new"`, followed by `(lossy)` if information
+/// was lost
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {}: {:?} -> {:?}",
+            self.query, self.reason, self.old, self.new
+        )?;
+        if self.lossy {
+            write!(f, " (lossy)")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reason::Version => write!(f, "version"),
+            Reason::MessageStructure => write!(f, "message structure"),
+            Reason::PatientIdentifiers => write!(f, "patient identifiers"),
+            Reason::ValueType => write!(f, "value type"),
+            Reason::DataType { from, to } => write!(f, "data type {from} -> {to}"),
+            Reason::NotRepeatable => write!(f, "not repeatable"),
+            Reason::FieldRemoved => write!(f, "field removed"),
+        }
+    }
+}
+
+/// Convert a message builder from the version in its `MSH.12` to another version, returning a
+/// report of the changes that were made. If converting fails, the builder is left unchanged.
+pub fn convert(builder: &mut MessageBuilder, to: Version) -> Result<Report, ConversionError> {
+    let source = builder.to_string();
+    let message =
+        Message::parse(&source).map_err(|e| ConversionError::InvalidMessage(e.to_string()))?;
+    let version = message
+        .query("MSH.12.1")
+        .map(|version| version.raw_value())
+        .filter(|version| !version.is_empty())
+        .ok_or(ConversionError::MissingVersion)?;
+    let from = version.parse()?;
+    apply(builder, &message, from, to)
+}
+
+/// Convert a message builder from one version to another, regardless of the version in its
+/// `MSH.12`, returning a report of the changes that were made. If converting fails, the builder
+/// is left unchanged.
+pub fn convert_from(
+    builder: &mut MessageBuilder,
+    from: Version,
+    to: Version,
+) -> Result<Report, ConversionError> {
+    let source = builder.to_string();
+    let message =
+        Message::parse(&source).map_err(|e| ConversionError::InvalidMessage(e.to_string()))?;
+    apply(builder, &message, from, to)
+}
+
+impl MessageBuilder {
+    /// Convert the message from the version in its `MSH.12` to another version. See
+    /// [`convert`].
+    pub fn convert_version(&mut self, to: Version) -> Result<Report, ConversionError> {
+        convert(self, to)
+    }
+}
+
+/// The message structure (`MSH.9.3`) for a message type and trigger event, for versions 2.3.1
+/// and later. Trigger events which share a structure with another event (such as `ADT^A04`,
+/// which uses `ADT_A01`) are mapped to that structure, and other events use
+/// `{message type}_{trigger event}`.
+///
+/// # Examples
+///
+/// ```
+/// use hl7_parser::version::message_structure;
+/// assert_eq!(message_structure("ADT", "A08").as_deref(), Some("ADT_A01"));
+/// assert_eq!(message_structure("ORU", "R01").as_deref(), Some("ORU_R01"));
+/// assert_eq!(message_structure("ACK", "A01").as_deref(), Some("ACK"));
+/// ```
+pub fn message_structure(message_type: &str, trigger_event: &str) -> Option<String> {
+    if message_type == "ACK" {
+        return Some("ACK".to_string());
+    }
+    if message_type.is_empty() || trigger_event.is_empty() {
+        return None;
+    }
+    let event = match (message_type, trigger_event) {
+        ("ADT", "A04" | "A08" | "A13") => "A01",
+        ("ADT", "A14" | "A28" | "A31") => "A05",
+        ("ADT", "A07") => "A06",
+        ("ADT", "A10" | "A11") => "A09",
+        ("ADT", "A22" | "A23" | "A25" | "A26" | "A27" | "A29" | "A32" | "A33") => "A21",
+        ("ADT", "A34" | "A35" | "A36" | "A46" | "A47" | "A48" | "A49") => "A30",
+        ("ADT", "A40" | "A41" | "A42") => "A39",
+        ("ADT", "A44") => "A43",
+        ("ADT", "A51") => "A50",
+        ("ADT", "A53") => "A52",
+        ("ADT", "A55") => "A54",
+        ("ADT", "A62") => "A61",
+        (_, event) => event,
+    };
+    Some(format!("{message_type}_{event}"))
+}
+
+/// The `OBX.2` value type to use in a version
+fn value_type(value_type: &str, version: Version) -> &str {
+    match value_type {
+        "CE" if version >= Version::V2_6 => "CWE",
+        "TS" if version >= Version::V2_6 => "DTM",
+        "DTM" if version < Version::V2_5 => "TS",
+        "CWE" | "CNE" if version < Version::V2_3 => "CE",
+        value_type => value_type,
+    }
+}
+
+/// If a data type has fewer components in the new version, the names of the data types in each
+/// version and the number of components in the new version
+fn truncation(
+    from: Version,
+    source: &str,
+    to: Version,
+    target: &str,
+) -> Option<(&'static str, &'static str, usize)> {
+    let source = dictionary::data_type(from, source)?;
+    let target = dictionary::data_type(to, target)?;
+    let components = |components: usize| components.max(1);
+    let length = components(target.components_for(to).count());
+    (length < components(source.components_for(from).count())).then_some((
+        source.name,
+        target.name,
+        length,
+    ))
+}
+
+/// Converts a parsed message between versions, collecting the changes
+struct Converter<'m> {
+    message: &'m Message<'m>,
+    from: Version,
+    to: Version,
+    counts: HashMap<&'m str, usize>,
+    changes: Vec<Change>,
+}
+
+fn apply(
+    builder: &mut MessageBuilder,
+    message: &Message,
+    from: Version,
+    to: Version,
+) -> Result<Report, ConversionError> {
+    let mut counts = HashMap::new();
+    for segment in message.segments() {
+        *counts.entry(segment.name).or_default() += 1;
+    }
+    let mut converter = Converter {
+        message,
+        from,
+        to,
+        counts,
+        changes: Vec::new(),
+    };
+
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    for segment in message.segments() {
+        let index = seen.entry(segment.name).or_default();
+        *index += 1;
+        let index = *index;
+        let start = converter.changes.len();
+        match segment.name {
+            "MSH" => converter.header(segment),
+            "PID" => converter.patient_identifiers(segment, index),
+            "OBX" => converter.observation(segment, index),
+            _ => {}
+        }
+        converter.fields(segment, index);
+        converter.changes[start..].sort_by_key(|change| change.query.field);
+    }
+
+    let patch: Patch = converter
+        .changes
+        .iter()
+        .map(|change| match change.new.is_empty() {
+            true => crate::builder::Operation::Delete {
+                query: change.query.clone(),
+            },
+            false => crate::builder::Operation::Set {
+                query: change.query.clone(),
+                value: change.new.clone(),
+            },
+        })
+        .collect();
+    patch.apply(builder)?;
+
+    Ok(Report {
+        from,
+        to,
+        changes: converter.changes,
+    })
+}
+
+impl<'m> Converter<'m> {
+    /// A query for a field, including the segment index if there is more than one segment with
+    /// the same name
+    fn query(&self, segment: &str, index: usize, field: usize) -> LocationQuery {
+        LocationQuery {
+            segment: segment.to_string(),
+            segment_index: (self.counts.get(segment).copied().unwrap_or_default() > 1)
+                .then_some(index),
+            field: Some(field),
+            repeat: None,
+            component: None,
+            subcomponent: None,
+        }
+    }
+
+    fn change(
+        &mut self,
+        query: LocationQuery,
+        reason: Reason,
+        old: &str,
+        new: String,
+        lossy: bool,
+    ) {
+        self.changes.push(Change {
+            query,
+            reason,
+            old: old.to_string(),
+            new,
+            lossy,
+        });
+    }
+
+    /// Join the raw values of components with the component separator
+    fn join<'c>(&self, components: impl Iterator<Item = &'c str>) -> String {
+        let separator = self.message.separators.component.to_string();
+        components.collect::<Vec<_>>().join(&separator)
+    }
+
+    /// Update the version and message structure in `MSH`
+    fn header(&mut self, msh: &Segment) {
+        let old = msh.field(12).map(|f| f.raw_value()).unwrap_or_default();
+        let mut components = old.split(self.message.separators.component);
+        components.next();
+        let rest: Vec<&str> = components.collect();
+        let (new, lossy) = if self.to < Version::V2_3_1 {
+            // MSH.12 is a plain ID before v2.3.1
+            (
+                self.to.as_str().to_string(),
+                rest.iter().any(|c| !c.is_empty()),
+            )
+        } else {
+            let version = std::iter::once(self.to.as_str());
+            (self.join(version.chain(rest.iter().copied())), false)
+        };
+        if new != old {
+            let query = self.query("MSH", 1, 12);
+            self.change(query, Reason::Version, old, new, lossy);
+        }
+
+        let Some(field) = msh.field(9) else {
+            return;
+        };
+        let component = |n: usize| {
+            field
+                .component(n)
+                .map(|c| c.raw_value())
+                .unwrap_or_default()
+        };
+        let (message_type, trigger_event, structure) = (component(1), component(2), component(3));
+        let default = message_structure(message_type, trigger_event);
+        if self.to < Version::V2_3_1 && !structure.is_empty() {
+            let new = self.join([message_type, trigger_event].into_iter());
+            let lossy = default.as_deref() != Some(structure);
+            let query = self.query("MSH", 1, 9);
+            self.change(
+                query,
+                Reason::MessageStructure,
+                field.raw_value(),
+                new,
+                lossy,
+            );
+        } else if self.to >= Version::V2_3_1 && structure.is_empty() {
+            if let Some(structure) = default {
+                let new = self.join([message_type, trigger_event, &structure].into_iter());
+                let query = self.query("MSH", 1, 9);
+                self.change(
+                    query,
+                    Reason::MessageStructure,
+                    field.raw_value(),
+                    new,
+                    false,
+                );
+            }
+        }
+    }
+
+    /// Copy or move identifiers from `PID.2` and `PID.4` into `PID.3`
+    fn patient_identifiers(&mut self, pid: &Segment, index: usize) {
+        if self.from >= self.to || self.to < Version::V2_3_1 {
+            return;
+        }
+        let repeats = |n: usize| -> Vec<&str> {
+            pid.field(n)
+                .map(|f| f.repeats().map(|r| r.raw_value()).collect())
+                .unwrap_or_default()
+        };
+        let mut identifiers: Vec<&str> = repeats(3);
+        let old = pid.field(3).map(|f| f.raw_value()).unwrap_or_default();
+        let mut moved = Vec::new();
+        for field in [2, 4] {
+            let Some(value) = pid.field(field).filter(|f| !f.is_empty()) else {
+                continue;
+            };
+            for repeat in repeats(field) {
+                if !repeat.is_empty() && !identifiers.contains(&repeat) {
+                    identifiers.push(repeat);
+                }
+            }
+            moved.push((field, value.raw_value()));
+        }
+        if moved.is_empty() {
+            return;
+        }
+
+        let identifiers: Vec<&str> = identifiers.into_iter().filter(|i| !i.is_empty()).collect();
+        let new = identifiers.join(&self.message.separators.repetition.to_string());
+        if new != old {
+            let query = self.query("PID", index, 3);
+            self.change(query, Reason::PatientIdentifiers, old, new, false);
+        }
+        // PID.2 and PID.4 were withdrawn in v2.7
+        if self.to >= Version::V2_7 {
+            for (field, value) in moved {
+                let query = self.query("PID", index, field);
+                self.change(
+                    query,
+                    Reason::PatientIdentifiers,
+                    value,
+                    String::new(),
+                    false,
+                );
+            }
+        }
+    }
+
+    /// Update the value type in `OBX.2`, and truncate the values in `OBX.5` to fit it
+    fn observation(&mut self, obx: &Segment, index: usize) {
+        let source = obx.field(2).map(|f| f.raw_value()).unwrap_or_default();
+        let target = value_type(source, self.to);
+        if target != source {
+            let query = self.query("OBX", index, 2);
+            self.change(query, Reason::ValueType, source, target.to_string(), false);
+        }
+        if let Some(field) = obx.field(5) {
+            if let Some(truncation) = truncation(self.from, source, self.to, target) {
+                self.truncate(obx.name, index, 5, field, truncation);
+            }
+        }
+    }
+
+    /// Apply the changes to the fields of a segment in the dictionary
+    fn fields(&mut self, segment: &Segment, index: usize) {
+        let name = segment.name;
+        for (position, field) in segment.fields.iter().enumerate() {
+            let position = position + 1;
+            let handled = match name {
+                "MSH" => matches!(position, 1 | 2 | 9 | 12),
+                "OBX" => position == 5,
+                _ => false,
+            };
+            if handled || field.is_empty() {
+                continue;
+            }
+            let Some(source) = dictionary::field(self.from, name, position) else {
+                continue;
+            };
+            let Some(target) = dictionary::field(self.to, name, position) else {
+                let query = self.query(name, index, position);
+                self.change(
+                    query,
+                    Reason::FieldRemoved,
+                    field.raw_value(),
+                    String::new(),
+                    true,
+                );
+                continue;
+            };
+
+            if source.repeatable && !target.repeatable && field.repeats.len() > 1 {
+                let first = field.repeats[0].raw_value();
+                let lossy = field.repeats[1..].iter().any(|r| !r.is_empty());
+                let query = self.query(name, index, position);
+                let new = first.to_string();
+                self.change(query, Reason::NotRepeatable, field.raw_value(), new, lossy);
+            }
+            let truncation = truncation(self.from, source.data_type, self.to, target.data_type);
+            if let Some(truncation) = truncation {
+                self.truncate(name, index, position, field, truncation);
+            }
+        }
+    }
+
+    /// Truncate each repeat of a field to the number of components in its new data type
+    fn truncate(
+        &mut self,
+        segment: &str,
+        index: usize,
+        position: usize,
+        field: &Field,
+        (from, to, length): (&str, &str, usize),
+    ) {
+        let repeats: Vec<&Repeat> = match dictionary::field(self.to, segment, position) {
+            Some(definition) if !definition.repeatable => field.repeats.iter().take(1).collect(),
+            _ => field.repeats.iter().collect(),
+        };
+        let count = repeats.len();
+        for (n, repeat) in repeats.into_iter().enumerate() {
+            if repeat.components.iter().skip(length).all(|c| c.is_empty()) {
+                continue;
+            }
+            let mut query = self.query(segment, index, position);
+            if count > 1 || field.repeats.len() > 1 {
+                query.repeat = Some(n + 1);
+            }
+            let mut components: Vec<&str> = repeat
+                .components
+                .iter()
+                .take(length)
+                .map(|c| c.raw_value())
+                .collect();
+            while components.last().is_some_and(|c| c.is_empty()) {
+                components.pop();
+            }
+            let new = self.join(components.into_iter());
+            let reason = Reason::DataType {
+                from: from.to_string(),
+                to: to.to_string(),
+            };
+            self.change(query, reason, repeat.raw_value(), new, true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    fn convert(message: &str, to: Version) -> (String, Report) {
+        let message = Message::parse(message).unwrap();
+        let mut builder = MessageBuilder::from(&message);
+        let report = builder.convert_version(to).unwrap();
+        (
+            builder.render_with_segment_separators("\n").to_string(),
+            report,
+        )
+    }
+
+    #[test]
+    fn upgrades_messages() {
+        let (message, report) = convert(
+            "MSH|^~\\&|LAB||EMR||20240102030405||ORU^R01|1|P|2.3\r\
+             PID|1|E123|M456^^^MRN~E123||Doe^John\r\
+             OBX|1|CE|GLU^Glucose||H^High^L||||||F|||20240102^S\r\
+             OBX|2|TS|TIME^Time||20240102^S||||||F",
+            Version::V2_6,
+        );
+        assert_eq!(
+            message,
+            "MSH|^~\\&|LAB||EMR||20240102030405||ORU^R01^ORU_R01|1|P|2.6\n\
+             PID|1|E123|M456^^^MRN~E123||Doe^John\n\
+             OBX|1|CWE|GLU^Glucose||H^High^L||||||F|||20240102\n\
+             OBX|2|DTM|TIME^Time||20240102||||||F"
+        );
+        // PID.3 already has the identifier in PID.2, and only the TS precisions are lost
+        let lossy: Vec<String> = report.lossy().map(|c| c.to_string()).collect();
+        assert_eq!(
+            lossy,
+            vec![
+                "OBX[1].14: data type TS -> DTM: \"20240102^S\" -> \"20240102\" (lossy)",
+                "OBX[2].5: data type TS -> DTM: \"20240102^S\" -> \"20240102\" (lossy)",
+            ]
+        );
+        assert_eq!(report.from, Version::V2_3);
+        assert_eq!(report.changes.len(), 6);
+    }
+
+    #[test]
+    fn moves_patient_identifiers_when_withdrawn() {
+        let (message, report) = convert(
+            "MSH|^~\\&|||||||ADT^A01|1|P|2.5.1\rPID|1|E1|M1~M2||Doe||||||||||||||||||||||||\rPID|2||M3|A1~A2",
+            Version::V2_7,
+        );
+        assert_eq!(
+            message,
+            "MSH|^~\\&|||||||ADT^A01^ADT_A01|1|P|2.7\nPID|1||M1~M2~E1||Doe\nPID|2||M3~A1~A2"
+        );
+        assert!(!report.is_lossy());
+        assert_eq!(
+            report.changes[3].to_string(),
+            "PID[1].3: patient identifiers: \"M1~M2\" -> \"M1~M2~E1\""
+        );
+    }
+
+    #[test]
+    fn downgrades_messages() {
+        let (message, report) = convert(
+            "MSH|^~\\&|LAB||EMR||20240102030405||ORU^R01^ORU_R01|1|P|2.6^CAN|||||||||PROFILE\r\
+             PID|1||M1||Doe^John^^^^^L~Smith^John\r\
+             OBX|1|CWE|GLU^Glucose^LN^^^^2.1||H^High^L^^^^1||||||F|||||||EQUIP|20240102",
+            Version::V2_2,
+        );
+        assert_eq!(
+            message,
+            "MSH|^~\\&|LAB||EMR||20240102030405||ORU^R01|1|P|2.2\n\
+             PID|1||M1||Doe^John\n\
+             OBX|1|CE|GLU^Glucose^LN||H^High^L||||||F"
+        );
+        let lossy: Vec<String> = report.lossy().map(|c| c.query.to_string()).collect();
+        assert_eq!(
+            lossy,
+            vec!["MSH.12", "MSH.21", "PID.5", "PID.5[1]", "OBX.3", "OBX.5", "OBX.18", "OBX.19"]
+        );
+        // ORU_R01 is the default structure for ORU^R01
+        assert!(!report.changes[0].lossy);
+    }
+
+    #[test]
+    fn reports_errors_without_changing_the_builder() {
+        let message = Message::parse("MSH|^~\\&|||||||ADT^A01|1|P").unwrap();
+        let mut builder = MessageBuilder::from(&message);
+        assert_eq!(
+            builder.convert_version(Version::V2_5),
+            Err(ConversionError::MissingVersion)
+        );
+        let report = convert_from(&mut builder, Version::V2_3, Version::V2_5).unwrap();
+        assert_eq!(report.changes.len(), 2);
+        assert!(builder.to_string().ends_with("|ADT^A01^ADT_A01|1|P|2.5"));
+
+        let message = Message::parse("MSH|^~\\&|||||||ADT^A01|1|P|3.0").unwrap();
+        let mut builder = MessageBuilder::from(&message);
+        assert_eq!(
+            builder.convert_version(Version::V2_5),
+            Err(ConversionError::UnknownVersion(UnknownVersionError(
+                "3.0".to_string()
+            )))
+        );
+        assert_eq!(builder.to_string(), message.raw_value());
+    }
+}
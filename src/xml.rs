@@ -0,0 +1,673 @@
+//! # HL7 v2 XML encoding
+//!
+//! Converts messages to and from the HL7 v2.xml encoding, in which each segment, field,
+//! component, and subcomponent is an element:
+//!
+//! ```xml
+//! <ADT_A01 xmlns="urn:hl7-org:v2xml">
+//!   <PID>
+//!     <PID.5><XPN.1><FN.1>Doe</FN.1></XPN.1><XPN.2>John</XPN.2></PID.5>
+//!   </PID>
+//! </ADT_A01>
+//! ```
+//!
+//! Fields are named after their segment and position (`PID.5`), and each repeat of a field is a
+//! separate element. Components and subcomponents are named after the data type of the field or
+//! component that contains them, using the [`dictionary`](crate::dictionary) for the message's
+//! version (`XPN.1`, `FN.1`), and `OBX.5` uses the value type in `OBX.2`. Values whose data
+//! type isn't known, such as those in `Z` segments, are named after their position within the
+//! element that contains them (`ZPI.1.2`, `ZPI.1.2.1`).
+//!
+//! Escape sequences for separators (`\F\`, `\S\`, `\T\`, `\R\`, and `\E\`) are written as the
+//! characters they stand for, and other escape sequences (such as `\.br\` or `\H\`) are written
+//! as `<escape V=".br"/>` elements. An escape sequence which isn't terminated runs to the end
+//! of its value, and is written as an `<escape>` element like any other, so it's terminated
+//! when the message is read back.
+//!
+//! The root element is named after the message structure in `MSH.9.3`, or the structure for the
+//! message type and trigger event if there isn't one. Segments aren't grouped as they are in
+//! the v2.xml schemas, but groups are accepted (and ignored) when reading. Empty values are
+//! omitted, except that an empty last field, component, or subcomponent is written as an empty
+//! element so that trailing separators survive a round trip along with every other value.
+//!
+//! ## Examples
+//!
+//! ```
+//! use hl7_parser::{xml, Message};
+//!
+//! let message = Message::parse("MSH|^~\\&|||||||ADT^A01|1|P|2.5.1\rPID|1||||Doe^John\\T\\Jane").unwrap();
+//! let encoded = xml::to_xml(&message);
+//! assert!(encoded.contains("<PID.5><XPN.1><FN.1>Doe</FN.1></XPN.1><XPN.2>John&amp;Jane</XPN.2></PID.5>"));
+//!
+//! let builder = xml::from_xml(&encoded).unwrap();
+//! assert_eq!(builder.to_string(), message.raw_value());
+//! ```
+
+use crate::{
+    builder::MessageBuilder,
+    dictionary::{self, DataTypeDefinition, Version},
+    message::{Component, Escape, Repeat, Separators},
+    Message,
+};
+use quick_xml::{
+    escape::escape,
+    events::{BytesStart, Event},
+    Reader,
+};
+use std::{collections::BTreeMap, fmt::Write};
+
+/// The namespace of HL7 v2.xml documents
+pub const NAMESPACE: &str = "urn:hl7-org:v2xml";
+
+/// Errors that can occur when reading a message from XML
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+pub enum XmlError {
+    /// The document isn't well-formed XML
+    #[error("Invalid XML: {0}")]
+    Xml(String),
+
+    /// An element within a segment isn't named after its position (i.e. `PID.5` or `XPN.1`),
+    /// or is nested more deeply than a subcomponent
+    #[error("Unexpected element `{0}`")]
+    UnexpectedElement(String),
+
+    /// The values in the document don't make up a valid message, i.e. it doesn't start with an
+    /// `MSH` segment
+    #[error("Invalid message: {0}")]
+    InvalidMessage(String),
+}
+
+/// Encode a message as HL7 v2.xml
+pub fn to_xml(message: &Message) -> String {
+    let version = message.version().unwrap_or(Version::LATEST);
+    let mut xml = String::new();
+    let root = root_name(message);
+    let _ = writeln!(xml, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+    let _ = writeln!(xml, "<{root} xmlns=\"{NAMESPACE}\">");
+    for segment in message.segments() {
+        let name = segment.name;
+        let _ = writeln!(xml, "  <{name}>");
+        let last = segment.fields.len().saturating_sub(1);
+        for (i, field) in segment.fields.iter().enumerate() {
+            let element = format!("{name}.{}", i + 1);
+            if name == "MSH" && i < 2 {
+                let _ = writeln!(
+                    xml,
+                    "    <{element}>{}</{element}>",
+                    escape(field.raw_value())
+                );
+                continue;
+            }
+            if field.raw_value().is_empty() {
+                if i == last {
+                    let _ = writeln!(xml, "    <{element}/>");
+                }
+                continue;
+            }
+            let data_type = match (name, i + 1) {
+                ("OBX", 5) => segment.field(2).map(|f| f.raw_value()),
+                (_, position) => dictionary::field(version, name, position).map(|f| f.data_type),
+            };
+            let data_type = data_type.and_then(|t| dictionary::data_type(version, t));
+            for repeat in field.repeats() {
+                let _ = write!(xml, "    ");
+                write_repeat(
+                    &mut xml,
+                    &element,
+                    repeat,
+                    data_type,
+                    version,
+                    &message.separators,
+                );
+                let _ = writeln!(xml);
+            }
+        }
+        let _ = writeln!(xml, "  </{name}>");
+    }
+    let _ = writeln!(xml, "</{root}>");
+    xml
+}
+
+/// The name of the root element: the message structure, if it's a valid element name
+fn root_name(message: &Message) -> String {
+    let component = |n: usize| {
+        message
+            .query(format!("MSH.9.{n}").as_str())
+            .map(|c| c.raw_value())
+            .unwrap_or_default()
+    };
+    let structure = match component(3) {
+        "" => crate::version::message_structure(component(1), component(2)).unwrap_or_default(),
+        structure => structure.to_string(),
+    };
+    let valid = structure.starts_with(|c: char| c.is_ascii_alphabetic())
+        && structure
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-');
+    if valid {
+        structure
+    } else {
+        "HL7Message".to_string()
+    }
+}
+
+/// Write a repeat of a field as an element
+fn write_repeat(
+    xml: &mut String,
+    element: &str,
+    repeat: &Repeat,
+    data_type: Option<&DataTypeDefinition>,
+    version: Version,
+    separators: &Separators,
+) {
+    let composite = data_type.filter(|t| !t.is_primitive());
+    if repeat.raw_value().is_empty() {
+        let _ = write!(xml, "<{element}/>");
+        return;
+    }
+    let _ = write!(xml, "<{element}>");
+    // a lone component is written straight into the field, unless it has subcomponents, which
+    // need a component element around them to be read back as subcomponents
+    if composite.is_none()
+        && repeat.components.len() == 1
+        && repeat.components[0].subcomponents.len() == 1
+    {
+        write_component(
+            xml,
+            element,
+            &repeat.components[0],
+            None,
+            version,
+            separators,
+        );
+    } else {
+        let last = repeat.components.len().saturating_sub(1);
+        for (i, component) in repeat.components.iter().enumerate() {
+            if component.raw_value().is_empty() && i < last {
+                continue;
+            }
+            let definition = composite.and_then(|t| t.component(version, i + 1));
+            let (name, data_type) = match (composite, definition) {
+                (Some(composite), Some(definition)) => (
+                    format!("{}.{}", composite.name, i + 1),
+                    dictionary::data_type(version, definition.data_type),
+                ),
+                _ => (format!("{element}.{}", i + 1), None),
+            };
+            if component.raw_value().is_empty() {
+                let _ = write!(xml, "<{name}/>");
+                continue;
+            }
+            let _ = write!(xml, "<{name}>");
+            write_component(xml, &name, component, data_type, version, separators);
+            let _ = write!(xml, "</{name}>");
+        }
+    }
+    let _ = write!(xml, "</{element}>");
+}
+
+/// Write the contents of a component
+fn write_component(
+    xml: &mut String,
+    element: &str,
+    component: &Component,
+    data_type: Option<&DataTypeDefinition>,
+    version: Version,
+    separators: &Separators,
+) {
+    let composite = data_type.filter(|t| !t.is_primitive());
+    if composite.is_none() && component.subcomponents.len() == 1 {
+        write_text(xml, component.subcomponents[0].value, separators);
+        return;
+    }
+    let last = component.subcomponents.len().saturating_sub(1);
+    for (i, subcomponent) in component.subcomponents.iter().enumerate() {
+        if subcomponent.value.is_empty() && i < last {
+            continue;
+        }
+        let name = match composite {
+            Some(composite) if composite.component(version, i + 1).is_some() => {
+                format!("{}.{}", composite.name, i + 1)
+            }
+            _ => format!("{element}.{}", i + 1),
+        };
+        if subcomponent.value.is_empty() {
+            let _ = write!(xml, "<{name}/>");
+            continue;
+        }
+        let _ = write!(xml, "<{name}>");
+        write_text(xml, subcomponent.value, separators);
+        let _ = write!(xml, "</{name}>");
+    }
+}
+
+/// Write a raw value as text, replacing escape sequences for separators with the separators and
+/// other escape sequences with `<escape>` elements. An escape sequence which isn't terminated
+/// runs to the end of the value.
+fn write_text(xml: &mut String, value: &str, separators: &Separators) {
+    let mut rest = value;
+    while let Some(start) = rest.find(separators.escape) {
+        let after = &rest[start + separators.escape.len_utf8()..];
+        let (sequence, next) = match after.find(separators.escape) {
+            Some(end) => (&after[..end], &after[end + separators.escape.len_utf8()..]),
+            None => (after, ""),
+        };
+        xml.push_str(&escape(&rest[..start]));
+        match separators.classify(sequence) {
+            Escape::Separator(c) => xml.push_str(&escape(c.to_string())),
+            _ => {
+                let _ = write!(xml, "<escape V=\"{}\"/>", escape(sequence));
+            }
+        }
+        rest = next;
+    }
+    xml.push_str(&escape(rest));
+}
+
+/// A field repeat, component, or subcomponent read from XML, holding its raw (encoded) value
+#[derive(Debug, Default)]
+struct Node {
+    text: String,
+    children: BTreeMap<usize, Node>,
+}
+
+impl Node {
+    /// Render the node as a raw value, joining its children with the separators (in order)
+    fn render(&self, separators: &[char]) -> String {
+        let Some((separator, rest)) = separators.split_first() else {
+            return self.text.clone();
+        };
+        let Some(last) = self.children.keys().next_back() else {
+            return self.text.clone();
+        };
+        (1..=*last)
+            .map(|n| {
+                self.children
+                    .get(&n)
+                    .map(|child| child.render(rest))
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>()
+            .join(&separator.to_string())
+    }
+}
+
+/// A segment read from XML
+#[derive(Debug, Default)]
+struct SegmentNode {
+    name: String,
+    fields: BTreeMap<usize, Vec<Node>>,
+}
+
+/// The element being read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frame {
+    /// The root element, or a group of segments
+    Group,
+    Segment,
+    Field(usize),
+    Component(usize),
+    Subcomponent(usize),
+    /// An `<escape>` element, whose contents are ignored
+    Escape,
+}
+
+/// Reads segments from XML, building their raw values
+struct XmlReader {
+    frames: Vec<Frame>,
+    segment: Option<SegmentNode>,
+    segments: Vec<String>,
+    separators: Separators,
+}
+
+/// Read a message from HL7 v2.xml, returning a builder for it. Escape sequences other than those
+/// for separators are kept in the builder's values as they are, so rendering the builder gives
+/// back the raw message that was written.
+pub fn from_xml(xml: &str) -> Result<MessageBuilder, XmlError> {
+    let mut reader = Reader::from_str(xml);
+    let mut state = XmlReader {
+        frames: Vec::new(),
+        segment: None,
+        segments: Vec::new(),
+        separators: Separators::default(),
+    };
+    let error = |e: quick_xml::Error| XmlError::Xml(e.to_string());
+    loop {
+        match reader.read_event().map_err(error)? {
+            Event::Start(element) => state.start(&element)?,
+            Event::Empty(element) => {
+                state.start(&element)?;
+                state.end();
+            }
+            Event::End(_) => state.end(),
+            Event::Text(text) => state.text(&text.unescape().map_err(error)?),
+            Event::CData(data) => state.text(&String::from_utf8_lossy(&data)),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let source = state.segments.join("\r");
+    let message = Message::parse(&source).map_err(|e| XmlError::InvalidMessage(e.to_string()))?;
+    Ok(MessageBuilder::from(&message))
+}
+
+impl XmlReader {
+    fn start(&mut self, element: &BytesStart) -> Result<(), XmlError> {
+        let name = String::from_utf8_lossy(element.local_name().as_ref()).to_string();
+        let position = || {
+            name.rsplit_once('.')
+                .and_then(|(_, position)| position.parse::<usize>().ok())
+                .filter(|position| *position > 0)
+                .ok_or_else(|| XmlError::UnexpectedElement(name.clone()))
+        };
+        let frame = match self.frames.last().copied() {
+            None | Some(Frame::Group) if is_segment_name(&name) => {
+                self.segment = Some(SegmentNode {
+                    name: name.clone(),
+                    fields: BTreeMap::new(),
+                });
+                Frame::Segment
+            }
+            None | Some(Frame::Group) => Frame::Group,
+            Some(Frame::Escape) => Frame::Escape,
+            Some(_) if name == "escape" => {
+                let value = element
+                    .try_get_attribute("V")
+                    .map_err(|e| XmlError::Xml(e.to_string()))?
+                    .map(|v| v.unescape_value().map(|v| v.to_string()))
+                    .transpose()
+                    .map_err(|e| XmlError::Xml(e.to_string()))?
+                    .unwrap_or_default();
+                let escape = self.separators.escape;
+                if let Some(node) = self.node() {
+                    let _ = write!(node.text, "{escape}{value}{escape}");
+                }
+                Frame::Escape
+            }
+            Some(Frame::Segment) => {
+                let position = position()?;
+                if let Some(segment) = self.segment.as_mut() {
+                    segment
+                        .fields
+                        .entry(position)
+                        .or_default()
+                        .push(Node::default());
+                }
+                Frame::Field(position)
+            }
+            Some(Frame::Field(_)) => Frame::Component(position()?),
+            Some(Frame::Component(_)) => Frame::Subcomponent(position()?),
+            Some(Frame::Subcomponent(_)) => return Err(XmlError::UnexpectedElement(name)),
+        };
+        self.frames.push(frame);
+        // make sure the node exists, even if it's empty
+        self.node();
+        Ok(())
+    }
+
+    fn end(&mut self) {
+        match self.frames.pop() {
+            Some(Frame::Segment) => {
+                if let Some(segment) = self.segment.take() {
+                    self.segments.push(self.render(&segment));
+                }
+            }
+            Some(Frame::Field(position @ (1 | 2))) => self.update_separators(position),
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        let raw = match self.frames.last() {
+            Some(Frame::Field(1 | 2)) if self.is_header() => text.to_string(),
+            Some(Frame::Field(_) | Frame::Component(_) | Frame::Subcomponent(_)) => {
+                self.separators.encode(text).to_string()
+            }
+            // whitespace between elements, and the contents of escape elements
+            _ => return,
+        };
+        if let Some(node) = self.node() {
+            node.text.push_str(&raw);
+        }
+    }
+
+    fn is_header(&self) -> bool {
+        self.segment.as_ref().is_some_and(|s| s.name == "MSH")
+    }
+
+    /// Use the separators in `MSH.1` and `MSH.2` once they've been read
+    fn update_separators(&mut self, position: usize) {
+        if !self.is_header() {
+            return;
+        }
+        let Some(segment) = self.segment.as_ref() else {
+            return;
+        };
+        let value = segment
+            .fields
+            .get(&position)
+            .and_then(|repeats| repeats.first())
+            .map(|node| node.text.clone())
+            .unwrap_or_default();
+        let mut chars = value.chars();
+        if position == 1 {
+            if let Some(field) = chars.next() {
+                self.separators.field = field;
+            }
+            return;
+        }
+        let separators = &mut self.separators;
+        for separator in [
+            &mut separators.component,
+            &mut separators.repetition,
+            &mut separators.escape,
+            &mut separators.subcomponent,
+        ] {
+            if let Some(c) = chars.next() {
+                *separator = c;
+            }
+        }
+    }
+
+    /// The node for the innermost field, component, or subcomponent being read
+    fn node(&mut self) -> Option<&mut Node> {
+        let segment = self.segment.as_mut()?;
+        let mut frames = self
+            .frames
+            .iter()
+            .skip_while(|f| !matches!(f, Frame::Field(_)));
+        let Some(Frame::Field(position)) = frames.next() else {
+            return None;
+        };
+        let mut node = segment.fields.get_mut(position)?.last_mut()?;
+        for frame in frames {
+            if let Frame::Component(position) | Frame::Subcomponent(position) = frame {
+                node = node.children.entry(*position).or_default();
+            }
+        }
+        Some(node)
+    }
+
+    /// Render a segment as its raw value
+    fn render(&self, segment: &SegmentNode) -> String {
+        let separators = &self.separators;
+        let levels = [separators.component, separators.subcomponent];
+        let repetition = separators.repetition.to_string();
+        let field = |position: usize| {
+            segment
+                .fields
+                .get(&position)
+                .map(|repeats| {
+                    repeats
+                        .iter()
+                        .map(|repeat| repeat.render(&levels))
+                        .collect::<Vec<_>>()
+                        .join(&repetition)
+                })
+                .unwrap_or_default()
+        };
+
+        let mut raw = segment.name.clone();
+        let first = if segment.name == "MSH" {
+            raw.push(separators.field);
+            raw.push_str(&field(2));
+            3
+        } else {
+            1
+        };
+        let last = segment
+            .fields
+            .keys()
+            .next_back()
+            .copied()
+            .unwrap_or_default()
+            // segments without any fields still need a field separator to be parsed
+            .max(first);
+        for position in first..=last {
+            raw.push(separators.field);
+            raw.push_str(&field(position));
+        }
+        raw
+    }
+}
+
+/// Whether an element name is a segment name, such as `PID` or `ZPI`, rather than a group
+fn is_segment_name(name: &str) -> bool {
+    name.len() == 3
+        && name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions_sorted::assert_eq;
+
+    #[test]
+    fn writes_elements_named_by_data_type() {
+        let message = Message::parse(
+            "MSH|^~\\&|APP||||20240102||ORU^R01^ORU_R01|1|P|2.5.1\r\
+             PID|1||123^^^MRN~456||Doe^John||||||||||||||||||||||||||||||||||||\r\
+             OBX|1|CE|GLU^Glucose||H^High||||||F\r\
+             ZPI|a^b&c|plain",
+        )
+        .unwrap();
+        assert_eq!(
+            to_xml(&message),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<ORU_R01 xmlns="urn:hl7-org:v2xml">
+  <MSH>
+    <MSH.1>|</MSH.1>
+    <MSH.2>^~\&amp;</MSH.2>
+    <MSH.3><HD.1>APP</HD.1></MSH.3>
+    <MSH.7><TS.1>20240102</TS.1></MSH.7>
+    <MSH.9><MSG.1>ORU</MSG.1><MSG.2>R01</MSG.2><MSG.3>ORU_R01</MSG.3></MSH.9>
+    <MSH.10>1</MSH.10>
+    <MSH.11><PT.1>P</PT.1></MSH.11>
+    <MSH.12><VID.1>2.5.1</VID.1></MSH.12>
+  </MSH>
+  <PID>
+    <PID.1>1</PID.1>
+    <PID.3><CX.1>123</CX.1><CX.4><HD.1>MRN</HD.1></CX.4></PID.3>
+    <PID.3><CX.1>456</CX.1></PID.3>
+    <PID.5><XPN.1><FN.1>Doe</FN.1></XPN.1><XPN.2>John</XPN.2></PID.5>
+    <PID.41/>
+  </PID>
+  <OBX>
+    <OBX.1>1</OBX.1>
+    <OBX.2>CE</OBX.2>
+    <OBX.3><CE.1>GLU</CE.1><CE.2>Glucose</CE.2></OBX.3>
+    <OBX.5><CE.1>H</CE.1><CE.2>High</CE.2></OBX.5>
+    <OBX.11>F</OBX.11>
+  </OBX>
+  <ZPI>
+    <ZPI.1><ZPI.1.1>a</ZPI.1.1><ZPI.1.2><ZPI.1.2.1>b</ZPI.1.2.1><ZPI.1.2.2>c</ZPI.1.2.2></ZPI.1.2></ZPI.1>
+    <ZPI.2>plain</ZPI.2>
+  </ZPI>
+</ORU_R01>
+"#
+        );
+    }
+
+    #[test]
+    fn terminates_unterminated_escape_sequences() {
+        let message = Message::parse("MSH|^~\\&|\rNTE|1||a\\X0D\rNTE|2||b\\F").unwrap();
+        let xml = to_xml(&message);
+        assert!(xml.contains("<NTE.3>a<escape V=\"X0D\"/></NTE.3>"), "{xml}");
+        assert!(xml.contains("<NTE.3>b|</NTE.3>"), "{xml}");
+
+        let builder = from_xml(&xml).unwrap();
+        let rendered = builder.to_string();
+        assert_eq!(rendered, "MSH|^~\\&|\rNTE|1||a\\X0D\\\rNTE|2||b\\F\\");
+        let round_tripped = Message::parse(&rendered).unwrap();
+        assert_eq!(to_xml(&round_tripped), xml);
+    }
+
+    #[test]
+    fn maps_escape_sequences() {
+        let message =
+            Message::parse("MSH|^~\\&|\rNTE|1||a\\F\\b\\S\\c\\E\\d<e>\\.br\\f\\H\\g\\X0D\\")
+                .unwrap();
+        let xml = to_xml(&message);
+        assert!(xml.contains(
+            "<NTE.3>a|b^c\\d&lt;e&gt;<escape V=\".br\"/>f<escape V=\"H\"/>g<escape V=\"X0D\"/></NTE.3>"
+        ));
+        // escape sequences other than separators, such as `\H\`, are kept in builder values
+        let builder = from_xml(&xml).unwrap();
+        assert_eq!(
+            builder
+                .segment_named("NTE")
+                .unwrap()
+                .field(3)
+                .unwrap()
+                .value(),
+            Some(&"a|b^c\\d<e>\\.br\\f\\H\\g\\X0D\\".to_string())
+        );
+        assert_eq!(builder.to_string(), message.raw_value());
+    }
+
+    #[test]
+    fn reads_groups_and_custom_separators() {
+        let xml = r#"<?xml version="1.0"?>
+            <ORU_R01 xmlns="urn:hl7-org:v2xml">
+              <MSH><MSH.1>#</MSH.1><MSH.2>*~\&amp;</MSH.2><MSH.9><MSG.1>ORU</MSG.1><MSG.2>R01</MSG.2></MSH.9></MSH>
+              <ORU_R01.PATIENT_RESULT>
+                <ORU_R01.PATIENT>
+                  <PID>
+                    <PID.3/>
+                    <PID.3><CX.1>2</CX.1></PID.3>
+                    <PID.5><XPN.1><FN.1>O'Brien</FN.1><FN.3>Smith</FN.3></XPN.1><XPN.2> Pat * Lee </XPN.2></PID.5>
+                  </PID>
+                </ORU_R01.PATIENT>
+                <ORU_R01.ORDER_OBSERVATION>
+                  <OBX><OBX.5><![CDATA[<b>bold</b>]]></OBX.5></OBX>
+                </ORU_R01.ORDER_OBSERVATION>
+              </ORU_R01.PATIENT_RESULT>
+            </ORU_R01>"#;
+        assert_eq!(
+            from_xml(xml).unwrap().to_string(),
+            "MSH#*~\\&#######ORU*R01\rPID###~2##O'Brien&&Smith* Pat \\S\\ Lee \rOBX#####<b>bold</b>"
+        );
+    }
+
+    #[test]
+    fn reports_invalid_documents() {
+        assert!(matches!(
+            from_xml("<ADT_A01><PID>"),
+            Err(XmlError::Xml(_)) | Err(XmlError::InvalidMessage(_))
+        ));
+        assert_eq!(
+            from_xml(
+                "<ADT_A01><MSH><MSH.1>|</MSH.1><MSH.2>^~\\&amp;</MSH.2><Foo/></MSH></ADT_A01>"
+            ),
+            Err(XmlError::UnexpectedElement("Foo".to_string()))
+        );
+        assert!(matches!(
+            from_xml("<ADT_A01><PID><PID.1>1</PID.1></PID></ADT_A01>"),
+            Err(XmlError::InvalidMessage(_))
+        ));
+    }
+}
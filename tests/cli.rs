@@ -0,0 +1,273 @@
+#![cfg(feature = "cli")]
+
+use pretty_assertions_sorted::assert_eq;
+use std::{
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+const A01: &str = "test_assets/sample_adt_a01.hl7";
+const A02: &str = "test_assets/sample_adt_a02.hl7";
+const A03: &str = "test_assets/sample_adt_a03.hl7";
+
+/// The result of running the `hl7` binary
+struct Output {
+    code: i32,
+    stdout: String,
+    stderr: String,
+}
+
+fn hl7(args: &[&str], stdin: Option<&str>) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_hl7"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Can run the tool");
+    let mut child_stdin = child.stdin.take().expect("Tool has stdin");
+    if let Some(stdin) = stdin {
+        child_stdin
+            .write_all(stdin.as_bytes())
+            .expect("Can write to the tool");
+    }
+    drop(child_stdin);
+    let output = child.wait_with_output().expect("Tool runs to completion");
+    Output {
+        code: output.status.code().expect("Tool exits normally"),
+        stdout: String::from_utf8(output.stdout).expect("Output is UTF-8"),
+        stderr: String::from_utf8(output.stderr).expect("Output is UTF-8"),
+    }
+}
+
+/// A fresh temporary directory for a test
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("hl7-cli-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Can create a temporary directory");
+    dir
+}
+
+#[test]
+fn query_prints_decoded_values() {
+    let output = hl7(&["query", "PID.PatientName.FamilyName", A01], None);
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "DUCK\n");
+
+    let output = hl7(&["query", "MSH.10", A01, A02], None);
+    assert_eq!(
+        output.stdout,
+        format!("{A01}:1: 599102\n{A02}:1: 59910287\n")
+    );
+
+    let message = "MSH|^~\\&|||||||ADT^A01|1|P|2.5.1\rNTE|1||a\\F\\b";
+    assert_eq!(hl7(&["query", "NTE.3"], Some(message)).stdout, "a|b\n");
+    assert_eq!(
+        hl7(&["query", "--raw", "NTE.3"], Some(message)).stdout,
+        "a\\F\\b\n"
+    );
+}
+
+#[test]
+fn query_fails_when_nothing_is_found() {
+    let output = hl7(&["query", "ZZZ.1", A01], None);
+    assert_eq!(output.code, 1);
+    assert_eq!(output.stdout, "");
+
+    let output = hl7(&["query", "PID.NotAField", A01], None);
+    assert_eq!(output.code, 2);
+}
+
+#[test]
+fn splits_batches_into_messages() {
+    let a02 = std::fs::read_to_string(A02).unwrap();
+    let a03 = std::fs::read_to_string(A03).unwrap();
+    let batch = format!("FHS|^~\\&\nBHS|^~\\&\n{a02}\n{a03}BTS|2\nFTS|1\n");
+    let dir = temp_dir("split");
+    let input = dir.join("batch.hl7");
+    std::fs::write(&input, &batch).unwrap();
+
+    let output = hl7(&["query", "MSH.10", input.to_str().unwrap()], None);
+    let name = input.display();
+    assert_eq!(
+        output.stdout,
+        format!("{name}:3: 59910287\n{name}:8: 59912415\n")
+    );
+
+    let out_dir = dir.join("out");
+    let output = hl7(
+        &[
+            "split",
+            "--out-dir",
+            out_dir.to_str().unwrap(),
+            input.to_str().unwrap(),
+        ],
+        None,
+    );
+    assert_eq!(output.code, 0);
+    let first = out_dir.join("batch-0001.hl7");
+    let second = out_dir.join("batch-0002.hl7");
+    assert_eq!(
+        output.stdout,
+        format!("{}\n{}\n", first.display(), second.display())
+    );
+    assert_eq!(std::fs::read_to_string(&first).unwrap(), a02.trim_end());
+    assert_eq!(std::fs::read_to_string(&second).unwrap(), a03.trim_end());
+
+    // existing files aren't overwritten
+    let output = hl7(
+        &[
+            "split",
+            "--out-dir",
+            out_dir.to_str().unwrap(),
+            input.to_str().unwrap(),
+        ],
+        None,
+    );
+    assert_eq!(output.code, 2);
+    assert_eq!(output.stdout, "");
+    assert_eq!(std::fs::read_to_string(&first).unwrap(), a02.trim_end());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn splits_inputs_with_the_same_name_into_different_files() {
+    let a02 = std::fs::read_to_string(A02).unwrap();
+    let a03 = std::fs::read_to_string(A03).unwrap();
+    let dir = temp_dir("split-same-name");
+    for (subdir, message) in [("a", &a02), ("b", &a03)] {
+        std::fs::create_dir_all(dir.join(subdir)).unwrap();
+        std::fs::write(dir.join(subdir).join("message.hl7"), message).unwrap();
+    }
+
+    let out_dir = dir.join("out");
+    let output = hl7(
+        &[
+            "split",
+            "--out-dir",
+            out_dir.to_str().unwrap(),
+            dir.join("a/message.hl7").to_str().unwrap(),
+            dir.join("b/message.hl7").to_str().unwrap(),
+        ],
+        None,
+    );
+    assert_eq!(output.code, 0);
+    let first = out_dir.join("message-0001.hl7");
+    let second = out_dir.join("message-2-0001.hl7");
+    assert_eq!(
+        output.stdout,
+        format!("{}\n{}\n", first.display(), second.display())
+    );
+    assert_eq!(std::fs::read_to_string(first).unwrap(), a02.trim_end());
+    assert_eq!(std::fs::read_to_string(second).unwrap(), a03.trim_end());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn validate_reports_problems_with_positions() {
+    let output = hl7(&["validate", A01], None);
+    assert_eq!(output.stdout, "");
+    assert_eq!(output.code, 0);
+
+    let message = "MSH|^~\\&|||||yesterday||ADT^A01|1|P|2.5.1\nPID|1||123||DOE^JANE\nnot a segment";
+    let output = hl7(&["validate", "-"], Some(message));
+    assert_eq!(output.code, 1);
+    let lines = output.stdout.lines().collect::<Vec<_>>();
+    assert!(
+        lines[0].starts_with("<stdin>:1:14: warning: MSH.7 (Date/Time of Message) is not a valid")
+    );
+    assert_eq!(
+        lines[1],
+        "<stdin>:3:1: error: Expected a segment: a three character segment name followed by the field separator"
+    );
+}
+
+#[test]
+fn json_round_trips() {
+    let original = std::fs::read_to_string(A01).unwrap();
+    let json = hl7(&["to-json", A01], None);
+    assert_eq!(json.code, 0);
+    assert_eq!(json.stdout.lines().count(), 1);
+
+    let output = hl7(&["from-json"], Some(&json.stdout));
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, original);
+}
+
+#[test]
+fn diff_compares_two_messages() {
+    let output = hl7(&["diff", A02, A02], None);
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "");
+
+    let output = hl7(&["diff", A02, A03], None);
+    assert_eq!(output.code, 1);
+    assert!(output
+        .stdout
+        .lines()
+        .any(|line| line == "~ MSH.9.2: \"A02\" -> \"A03\""));
+}
+
+#[test]
+fn anonymize_is_deterministic() {
+    let first = hl7(&["anonymize", "--salt", "secret", A01], None);
+    assert_eq!(first.code, 0, "{}", first.stderr);
+    assert!(!first.stdout.contains("DONALD"));
+    let second = hl7(&["anonymize", A01], None);
+    assert_eq!(second.code, 2);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_hl7"))
+        .args(["anonymize", A01])
+        .env("HL7_ANONYMIZE_SALT", "secret")
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), first.stdout);
+}
+
+#[test]
+fn acknowledges_messages() {
+    let output = hl7(
+        &[
+            "ack",
+            "--code",
+            "AE",
+            "--text",
+            "Bad|value",
+            "--control-id",
+            "ACK1",
+            A01,
+        ],
+        None,
+    );
+    assert_eq!(output.code, 0);
+    let lines = output.stdout.lines().collect::<Vec<_>>();
+    let msh = lines[0].split('|').collect::<Vec<_>>();
+    assert_eq!(&msh[..6], &["MSH", "^~\\&", "", "", "AccMgr", "1"]);
+    assert_eq!(&msh[7..], &["", "ACK^A01", "ACK1", "P", "2.3"]);
+    assert_eq!(lines[1], "MSA|AE|599102|Bad\\F\\value");
+}
+
+#[test]
+fn pretty_prints_a_tree() {
+    let output = hl7(&["pretty", "--names", A01], None);
+    assert_eq!(output.code, 0);
+    let lines = output
+        .stdout
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>();
+    assert!(lines.contains(&"PID (Patient Identification)".to_string()));
+    assert!(lines.contains(&"PID.5 (Patient Name) DUCK^DONALD^D".to_string()));
+    assert!(lines.contains(&"PID.5.1 (Family Name) DUCK".to_string()));
+}
+
+#[test]
+fn pretty_prints_a_table() {
+    let output = hl7(&["pretty", "--table", "--width", "16", A01], None);
+    assert_eq!(output.code, 0);
+    let lines = output.stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "PATH         VA…");
+    assert!(lines.contains(&"PID.5        DU…"));
+    assert!(lines.contains(&"PID.8        M"));
+}
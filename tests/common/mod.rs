@@ -0,0 +1,37 @@
+//! Helpers shared by the integration tests
+
+/// The sample messages in `test_assets`, by name
+pub static ASSETS: &[(&str, &str)] = &[
+    (
+        "sample_adt_a01",
+        include_str!("../../test_assets/sample_adt_a01.hl7"),
+    ),
+    (
+        "sample_adt_a01_encoded",
+        include_str!("../../test_assets/sample_adt_a01_encoded.hl7"),
+    ),
+    (
+        "sample_adt_a02",
+        include_str!("../../test_assets/sample_adt_a02.hl7"),
+    ),
+    (
+        "sample_adt_a03",
+        include_str!("../../test_assets/sample_adt_a03.hl7"),
+    ),
+    (
+        "sample_adt_a04",
+        include_str!("../../test_assets/sample_adt_a04.hl7"),
+    ),
+    (
+        "sample_adt_a08",
+        include_str!("../../test_assets/sample_adt_a08.hl7"),
+    ),
+    (
+        "sample_oru_r01_generic",
+        include_str!("../../test_assets/sample_oru_r01_generic.hl7"),
+    ),
+    (
+        "sample_oru_r01_lab",
+        include_str!("../../test_assets/sample_oru_r01_lab.hl7"),
+    ),
+];
@@ -14,3 +14,57 @@ fn locate_the_cursor() {
     assert_eq!(format!("{cursor}"), "PID.5.1");
     assert_eq!(cursor.component.unwrap().1.raw_value(), "DUCK");
 }
+
+#[test]
+fn locate_the_cursor_by_line_and_column() {
+    use hl7_parser::{
+        locate::{LineCol, Offset},
+        Message,
+    };
+
+    for newline in ["\r", "\n", "\r\n"] {
+        let source = [
+            "MSH|^~\\&|SENDER|FACILITY",
+            "PID|1||123^^^MRN||Gonçalves^Zoë||19800101|F",
+            "NK1|1|Ängström^Björn😀^^^|SPO",
+        ]
+        .join(newline);
+        let message =
+            Message::parse_with_lenient_newlines(&source, true).expect("Can parse message");
+
+        let cursor = message
+            .locate_cursor_at(LineCol::new(1, 29))
+            .expect("Can locate cursor");
+        assert_eq!(format!("{cursor}"), "PID.5.2");
+        assert_eq!(cursor.component.unwrap().1.raw_value(), "Zoë");
+
+        let range = cursor.range().expect("Cursor has a range").clone();
+        assert_eq!(
+            message.range_to_line_col(range),
+            Some(LineCol::new(1, 28)..LineCol::new(1, 31))
+        );
+
+        // '😀' is two UTF-16 code units, so the columns after it differ
+        let range = message.query("NK1.2").expect("Can query NK1.2").range();
+        let positions = message.positions();
+        assert_eq!(
+            positions.range_to_line_col(range.clone()),
+            Some(LineCol::new(2, 6)..LineCol::new(2, 24))
+        );
+        assert_eq!(
+            positions.range_to_line_col_utf16(range),
+            Some(LineCol::new(2, 6)..LineCol::new(2, 25))
+        );
+        let cursor = message
+            .locate_cursor_at(Offset::LineColUtf16(LineCol::new(2, 23)))
+            .expect("Can locate cursor");
+        assert_eq!(format!("{cursor}"), "NK1.2.3");
+        let cursor = message
+            .locate_cursor_at(LineCol::new(2, 23))
+            .expect("Can locate cursor");
+        assert_eq!(format!("{cursor}"), "NK1.2.4");
+        assert!(message
+            .locate_cursor_at(Offset::LineColUtf16(LineCol::new(2, 21)))
+            .is_none());
+    }
+}
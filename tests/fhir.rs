@@ -0,0 +1,30 @@
+#![cfg(feature = "fhir")]
+//! Compares the FHIR bundles for each message in `test_assets` with the golden files in
+//! `test_assets/fhir`. Run with `HL7_UPDATE_GOLDEN=1` to update the golden files after changing
+//! the mappings, and review the differences.
+
+mod common;
+
+use hl7_parser::{fhir, parse_message_with_lenient_newlines};
+use std::path::PathBuf;
+
+#[test]
+fn test_assets_match_golden_bundles() {
+    let update = std::env::var_os("HL7_UPDATE_GOLDEN").is_some();
+    for (name, source) in common::ASSETS {
+        let message = parse_message_with_lenient_newlines(source).expect("can parse asset");
+        let bundle = fhir::to_bundle(&message);
+
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("test_assets/fhir")
+            .join(format!("{name}.json"));
+        if update {
+            let json = serde_json::to_string_pretty(&bundle).expect("can serialize bundle");
+            std::fs::write(&path, json + "\n").expect("can write golden file");
+            continue;
+        }
+        let golden = std::fs::read_to_string(&path).expect("can read golden file");
+        let golden: serde_json::Value = serde_json::from_str(&golden).expect("can parse golden");
+        assert_eq!(bundle, golden, "{name} doesn't match {}", path.display());
+    }
+}
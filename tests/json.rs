@@ -0,0 +1,20 @@
+#![cfg(feature = "json")]
+
+mod common;
+
+use hl7_parser::{json, parse_message_with_lenient_newlines};
+
+#[test]
+fn test_assets_round_trip_through_json() {
+    for (name, source) in common::ASSETS {
+        let message = parse_message_with_lenient_newlines(source).expect("can parse asset");
+        let value = json::to_json(&message);
+
+        let text = serde_json::to_string(&value).expect("can serialize JSON");
+        let value: serde_json::Value = serde_json::from_str(&text).expect("can deserialize JSON");
+        let round_tripped =
+            json::from_json(&value).unwrap_or_else(|e| panic!("can convert JSON for {name}: {e}"));
+
+        assert_eq!(round_tripped, message.raw_value(), "{name}");
+    }
+}
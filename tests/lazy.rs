@@ -0,0 +1,77 @@
+mod common;
+
+use hl7_parser::{LazyMessage, Message};
+
+/// Every query that can be made against the message, down to the subcomponent level
+fn all_queries(message: &Message) -> Vec<String> {
+    let mut queries = Vec::new();
+    let mut seen = std::collections::HashMap::new();
+    for segment in message.segments() {
+        let n = seen.entry(segment.name).or_insert(0);
+        *n += 1;
+        let segment_query = format!("{}[{}]", segment.name, n);
+        queries.push(segment_query.clone());
+        for (f, field) in segment.fields().enumerate() {
+            queries.push(format!("{segment_query}.{}", f + 1));
+            for (r, repeat) in field.repeats().enumerate() {
+                queries.push(format!("{segment_query}.{}[{}]", f + 1, r + 1));
+                for (c, component) in repeat.components().enumerate() {
+                    queries.push(format!("{segment_query}.{}[{}].{}", f + 1, r + 1, c + 1));
+                    for s in 0..component.subcomponents().count() {
+                        queries.push(format!(
+                            "{segment_query}.{}[{}].{}.{}",
+                            f + 1,
+                            r + 1,
+                            c + 1,
+                            s + 1
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    queries
+}
+
+#[test]
+fn lazy_messages_match_eager_messages() {
+    for (_, source) in common::ASSETS {
+        let eager = Message::parse_with_lenient_newlines(source, true).expect("can parse");
+        let lazy = LazyMessage::parse_with_lenient_newlines(source, true).expect("can parse");
+
+        assert_eq!(lazy.raw_value(), eager.raw_value());
+        assert_eq!(
+            lazy.segment_names().collect::<Vec<_>>(),
+            eager.segments().map(|s| s.name).collect::<Vec<_>>()
+        );
+
+        // query the deepest locations first, so that they're split out of segments which
+        // haven't been parsed in full yet
+        for query in all_queries(&eager).iter().rev() {
+            let expected = eager.query(query.as_str()).expect("query exists");
+            let actual = lazy
+                .query(query.as_str())
+                .expect("segment can be parsed")
+                .expect("query exists");
+            assert_eq!(actual, expected, "{query}");
+        }
+
+        assert_eq!(lazy.to_message().unwrap(), eager);
+    }
+}
+
+#[test]
+fn lazy_messages_locate_cursors_like_eager_messages() {
+    for (_, source) in common::ASSETS {
+        let eager = Message::parse_with_lenient_newlines(source, true).expect("can parse");
+        let lazy = LazyMessage::parse_with_lenient_newlines(source, true).expect("can parse");
+
+        for offset in 0..=source.len() {
+            assert_eq!(
+                lazy.locate_cursor(offset).map(|c| c.to_string()),
+                eager.locate_cursor(offset).map(|c| c.to_string()),
+                "offset {offset}"
+            );
+        }
+    }
+}
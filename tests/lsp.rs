@@ -0,0 +1,266 @@
+#![cfg(feature = "lsp")]
+
+use serde_json::{json, Value};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+/// A minimal LSP client which talks to the `hl7-lsp` binary over stdio
+struct Client {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: i64,
+}
+
+impl Client {
+    fn start() -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_hl7-lsp"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Can start the language server");
+        let stdin = child.stdin.take().expect("Server has stdin");
+        let stdout = BufReader::new(child.stdout.take().expect("Server has stdout"));
+        let mut client = Client {
+            child,
+            stdin,
+            stdout,
+            next_id: 1,
+        };
+        let result = client.request("initialize", json!({ "capabilities": {} }));
+        assert_eq!(result["serverInfo"]["name"], "hl7-lsp");
+        client.notify("initialized", json!({}));
+        client
+    }
+
+    fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{body}", body.len())
+            .expect("Can write to the server");
+        self.stdin.flush().expect("Can flush to the server");
+    }
+
+    fn receive(&mut self) -> Value {
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            self.stdout
+                .read_line(&mut header)
+                .expect("Can read from the server");
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length: ") {
+                length = value.parse().expect("Content length is a number");
+            }
+        }
+        let mut body = vec![0; length];
+        self.stdout
+            .read_exact(&mut body)
+            .expect("Can read from the server");
+        serde_json::from_slice(&body).expect("Server sends JSON")
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+        loop {
+            let message = self.receive();
+            if message["id"] == id {
+                assert!(message.get("error").is_none(), "{message}");
+                return message["result"].clone();
+            }
+        }
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    fn notification(&mut self, method: &str) -> Value {
+        loop {
+            let message = self.receive();
+            if message["method"] == method {
+                return message["params"].clone();
+            }
+        }
+    }
+
+    fn shutdown(mut self) {
+        self.request("shutdown", Value::Null);
+        self.notify("exit", Value::Null);
+        let status = self.child.wait().expect("Server exits");
+        assert!(status.success());
+    }
+}
+
+const URI: &str = "file:///messages.hl7";
+
+fn document() -> String {
+    [
+        "MSH|^~\\&|SENDER|FAC|RECEIVER|FAC|20240102030405||ADT^A01|MSG00001|P|2.5.1",
+        "EVN|A01|20240102030405",
+        "PID|1||12345^^^MRN||Zoë^Ängström||notadate|F",
+        "MSH|^~\\&|RECEIVER|FAC|SENDER|FAC|20240102030406||ACK^A01|ACK00001|P|2.5.1",
+        "MSA|AA|MSG00001",
+        "",
+        "not a segment",
+    ]
+    .join("\r\n")
+}
+
+fn open() -> Client {
+    let mut client = Client::start();
+    client.notify(
+        "textDocument/didOpen",
+        json!({
+            "textDocument": { "uri": URI, "languageId": "hl7", "version": 1, "text": document() }
+        }),
+    );
+    client
+}
+
+fn position(line: u32, character: u32) -> Value {
+    json!({ "textDocument": { "uri": URI }, "position": { "line": line, "character": character } })
+}
+
+#[test]
+fn publishes_diagnostics() {
+    let mut client = open();
+    let params = client.notification("textDocument/publishDiagnostics");
+    assert_eq!(params["uri"], URI);
+    assert_eq!(params["version"], 1);
+
+    let diagnostics = params["diagnostics"].as_array().expect("Has diagnostics");
+    let messages: Vec<&str> = diagnostics
+        .iter()
+        .map(|d| d["message"].as_str().expect("Has a message"))
+        .collect();
+    assert!(
+        messages
+            .iter()
+            .any(|m| m.starts_with("PID.7 (Date/Time of Birth) is not a valid TS")),
+        "{messages:?}"
+    );
+    assert!(
+        messages.iter().any(|m| m.starts_with("Expected a segment")),
+        "{messages:?}"
+    );
+    let error = diagnostics
+        .iter()
+        .find(|d| d["severity"] == 1)
+        .expect("Has an error");
+    assert_eq!(
+        error["range"]["start"],
+        json!({ "line": 6, "character": 0 })
+    );
+    assert_eq!(error["range"]["end"], json!({ "line": 6, "character": 13 }));
+
+    client.shutdown();
+}
+
+#[test]
+fn hovers_over_accented_names() {
+    let mut client = open();
+    // 'Ä' is the 25th character of the PID line
+    let hover = client.request("textDocument/hover", position(2, 26));
+    let contents = hover["contents"]["value"].as_str().expect("Has contents");
+    assert!(
+        contents.starts_with("**PID.5.2** — Patient Name / Given Name"),
+        "{contents}"
+    );
+    assert!(contents.contains("Ängström"), "{contents}");
+    assert_eq!(
+        hover["range"],
+        json!({ "start": { "line": 2, "character": 24 }, "end": { "line": 2, "character": 32 } })
+    );
+    client.shutdown();
+}
+
+#[test]
+fn goes_from_the_acknowledgement_to_the_control_id() {
+    let mut client = open();
+    let location = client.request("textDocument/definition", position(4, 10));
+    assert_eq!(location["uri"], URI);
+    assert_eq!(
+        location["range"],
+        json!({ "start": { "line": 0, "character": 57 }, "end": { "line": 0, "character": 65 } })
+    );
+    client.shutdown();
+}
+
+#[test]
+fn lists_symbols_hints_and_tokens() {
+    let mut client = open();
+
+    let symbols = client.request(
+        "textDocument/documentSymbol",
+        json!({ "textDocument": { "uri": URI } }),
+    );
+    let names: Vec<&Value> = symbols
+        .as_array()
+        .expect("Has symbols")
+        .iter()
+        .map(|s| &s["name"])
+        .collect();
+    assert_eq!(names, vec!["ADT^A01", "ACK^A01"]);
+    let segments: Vec<&Value> = symbols[0]["children"]
+        .as_array()
+        .expect("Has segments")
+        .iter()
+        .map(|s| &s["name"])
+        .collect();
+    assert_eq!(segments, vec!["MSH", "EVN", "PID"]);
+
+    let hints = client.request(
+        "textDocument/inlayHint",
+        json!({
+            "textDocument": { "uri": URI },
+            "range": { "start": { "line": 4, "character": 0 }, "end": { "line": 4, "character": 15 } }
+        }),
+    );
+    assert_eq!(
+        hints,
+        json!([
+            {
+                "position": { "line": 4, "character": 4 },
+                "label": "1:",
+                "kind": 2,
+                "tooltip": "MSA.1: Acknowledgment Code"
+            },
+            {
+                "position": { "line": 4, "character": 7 },
+                "label": "2:",
+                "kind": 2,
+                "tooltip": "MSA.2: Message Control ID"
+            }
+        ])
+    );
+
+    let tokens = client.request(
+        "textDocument/semanticTokens/full",
+        json!({ "textDocument": { "uri": URI } }),
+    );
+    let data = tokens["data"].as_array().expect("Has token data");
+    // the first tokens are the MSH segment name, followed by the five encoding characters
+    assert_eq!(
+        data[..30]
+            .iter()
+            .map(|v| v.as_u64().unwrap())
+            .collect::<Vec<_>>(),
+        vec![
+            0, 0, 3, 0, 0, //
+            0, 3, 1, 1, 0, //
+            0, 1, 1, 1, 0, //
+            0, 1, 1, 1, 0, //
+            0, 1, 1, 1, 0, //
+            0, 1, 1, 1, 0, //
+        ]
+    );
+
+    client.shutdown();
+}
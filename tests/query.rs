@@ -11,3 +11,18 @@ fn query_a_message() {
     let result = message.query(query).expect("Can query message");
     assert_eq!(result.raw_value(), "DONALD");
 }
+
+#[test]
+fn query_a_message_by_name() {
+    let message = parse_message_with_lenient_newlines(ADT_SRC).expect("Can parse message");
+
+    let result = message
+        .query("PID.PatientName.GivenName")
+        .expect("Can query message");
+    assert_eq!(result.raw_value(), "DONALD");
+
+    let result = message
+        .query("MSH-MessageType.TriggerEvent")
+        .expect("Can query message");
+    assert_eq!(result.raw_value(), "A08");
+}
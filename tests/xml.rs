@@ -0,0 +1,43 @@
+#![cfg(feature = "xml")]
+
+mod common;
+
+use hl7_parser::{diff::diff, parse_message_with_lenient_newlines, xml};
+
+#[test]
+fn test_assets_round_trip_through_xml() {
+    for (name, source) in common::ASSETS {
+        let message = parse_message_with_lenient_newlines(source).expect("can parse asset");
+        let encoded = xml::to_xml(&message);
+        let builder = xml::from_xml(&encoded)
+            .unwrap_or_else(|e| panic!("can read XML for {name}: {e}\n{encoded}"));
+        let rendered = builder.to_string();
+        let round_tripped = hl7_parser::parse_message(&rendered).expect("can parse round trip");
+
+        let differences = diff(&message, &round_tripped);
+        assert!(
+            differences.is_empty(),
+            "{name} didn't round trip:\n{differences}"
+        );
+        assert_eq!(xml::to_xml(&round_tripped), encoded, "{name}");
+        let raw = message
+            .segments()
+            .map(|segment| segment.raw_value())
+            .collect::<Vec<_>>()
+            .join("\r");
+        assert_eq!(round_tripped.raw_value(), raw, "{name}");
+    }
+}
+
+#[test]
+fn subcomponents_of_lone_components_round_trip() {
+    for source in [
+        "MSH|^~\\&|||||||ADT^A01|1|P|2.5.1\rNTE|1||a&b",
+        "MSH|^~\\&|||||||ADT^A01|1|P|2.5.1\rZPI|x&y",
+    ] {
+        let message = hl7_parser::parse_message(source).expect("can parse message");
+        let encoded = xml::to_xml(&message);
+        let builder = xml::from_xml(&encoded).expect("can read XML");
+        assert_eq!(builder.to_string(), source, "{encoded}");
+    }
+}